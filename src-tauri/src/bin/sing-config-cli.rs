@@ -0,0 +1,10 @@
+//! 命令行入口，命令和退出码见 `sing_config_lib::cli`
+
+fn main() {
+    let code = sing_config_lib::cli::run(
+        std::env::args().skip(1),
+        &mut std::io::stdout(),
+        &mut std::io::stderr(),
+    );
+    std::process::exit(code);
+}
@@ -0,0 +1,366 @@
+//! Clash 配置 → 完整 sing-box 配置：代理、代理组、规则、rule-providers、DNS 与入站端口
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::config::{
+    ClashApi, Config, DomainResolver, Experimental, Inbound, InboundType, Listable, Log, Outbound,
+    OutboundType, Route, RouteAction, RouteRule, RuleItems, RuleSet, RuleSetType,
+};
+use crate::migration::{GEOIP_URL, GEOSITE_URL};
+
+use super::{convert_proxies, dns, rule, Fields, Unsupported};
+
+/// Clash 的 DIRECT 对应的出站
+pub(super) const DIRECT_TAG: &str = "direct";
+
+/// 转换结果和无法转换的条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConvertResult {
+    pub config: Config,
+    pub unsupported: Vec<Unsupported>,
+}
+
+/// 转换过程中共享的状态
+#[derive(Default)]
+pub(super) struct Context {
+    /// 已转换的代理和代理组名称
+    pub tags: HashSet<String>,
+    pub rule_sets: Vec<RuleSet>,
+    /// 已转换的 rule-provider，值为是否含 IP 规则
+    pub providers: HashMap<String, bool>,
+    pub unsupported: Vec<Unsupported>,
+}
+
+impl Context {
+    pub fn report(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.unsupported.push(Unsupported {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Clash 代理名 → 出站标签，DIRECT 为 direct 出站
+    pub fn outbound(&self, name: &str) -> Option<String> {
+        if name == "DIRECT" {
+            return Some(DIRECT_TAG.to_string());
+        }
+        self.tags.contains(name).then(|| name.to_string())
+    }
+
+    /// 引用官方 geoip / geosite 规则集，返回其标签
+    pub fn geo_rule_set(&mut self, kind: &str, code: &str) -> String {
+        let base_url = if kind == "geoip" {
+            GEOIP_URL
+        } else {
+            GEOSITE_URL
+        };
+        let tag = format!("{kind}-{}", code.to_ascii_lowercase());
+        if !self
+            .rule_sets
+            .iter()
+            .any(|rule_set| rule_set.tag.as_deref() == Some(tag.as_str()))
+        {
+            self.rule_sets.push(RuleSet {
+                kind: Some(RuleSetType::Remote),
+                url: Some(format!("{base_url}/{tag}.srs")),
+                tag: Some(tag.clone()),
+                format: Some("binary".to_string()),
+                ..Default::default()
+            });
+        }
+        tag
+    }
+}
+
+/// 转换单个代理组
+fn group(index: usize, group: &Fields, proxies: &[String], ctx: &mut Context) -> Option<Outbound> {
+    let path = format!("proxy-groups[{index}]");
+    let name = group.str("name")?;
+    let kind = group.str("type").unwrap_or_default();
+    let outbound_type = match kind.as_str() {
+        "select" => OutboundType::Selector,
+        "url-test" => OutboundType::UrlTest,
+        "fallback" => {
+            ctx.report(
+                path.clone(),
+                "fallback is converted to urltest, which picks the fastest member instead of the first available one",
+            );
+            OutboundType::UrlTest
+        }
+        "load-balance" => {
+            ctx.report(path.clone(), "load-balance is converted to urltest");
+            OutboundType::UrlTest
+        }
+        "relay" => {
+            ctx.report(
+                path,
+                "relay groups are not supported; chain proxies with dialer-proxy instead",
+            );
+            return None;
+        }
+        other => {
+            ctx.report(path, format!("proxy group type {other} is not supported"));
+            return None;
+        }
+    };
+
+    let mut members: Vec<String> = Vec::new();
+    for member in group.strings("proxies") {
+        match member.as_str() {
+            "REJECT" | "REJECT-DROP" | "PASS" => ctx.report(
+                path.clone(),
+                format!("member {member} is not supported in sing-box groups"),
+            ),
+            _ => match ctx.outbound(&member) {
+                Some(tag) if !members.contains(&tag) => members.push(tag),
+                Some(_) => {}
+                None => ctx.report(path.clone(), format!("member {member} was not converted")),
+            },
+        }
+    }
+    if group.bool("include-all") == Some(true) || group.bool("include-all-proxies") == Some(true) {
+        for tag in proxies {
+            if !members.contains(tag) {
+                members.push(tag.clone());
+            }
+        }
+    }
+    let providers = group.strings("use");
+    if !providers.is_empty() {
+        ctx.report(
+            path.clone(),
+            format!(
+                "proxy providers {} are not converted; add them as subscriptions",
+                providers.join(", ")
+            ),
+        );
+    }
+    for key in ["filter", "exclude-filter", "exclude-type"] {
+        if group.get(key).is_some() {
+            ctx.report(path.clone(), format!("{key} is ignored"));
+        }
+    }
+    if members.is_empty() {
+        ctx.report(path, "group has no members left, direct is used instead");
+        members.push(DIRECT_TAG.to_string());
+    }
+
+    let mut outbound = Outbound::new(outbound_type.clone(), name);
+    if outbound_type == OutboundType::UrlTest {
+        outbound.url = group.str("url");
+        outbound.interval = group.u64("interval").map(|seconds| format!("{seconds}s"));
+        outbound.tolerance = group.u64("tolerance").and_then(|ms| u32::try_from(ms).ok());
+    }
+    outbound.outbounds = Some(members);
+    Some(outbound)
+}
+
+/// 转换 proxy-groups，先登记所有组名以便组之间互相引用
+fn groups(fields: &Fields, proxies: &[String], ctx: &mut Context) -> Vec<Outbound> {
+    let list: Vec<Fields> = fields
+        .get("proxy-groups")
+        .and_then(Value::as_array)
+        .map(|groups| {
+            groups
+                .iter()
+                .filter_map(Value::as_object)
+                .map(Fields)
+                .collect()
+        })
+        .unwrap_or_default();
+    for group in &list {
+        let convertible = matches!(
+            group.str("type").as_deref(),
+            Some("select" | "url-test" | "fallback" | "load-balance")
+        );
+        if let Some(name) = group.str("name").filter(|_| convertible) {
+            ctx.tags.insert(name);
+        }
+    }
+    list.iter()
+        .enumerate()
+        .filter_map(|(index, fields)| group(index, fields, proxies, ctx))
+        .collect()
+}
+
+/// mixed-port / port / socks-port / redir-port / tproxy-port 与 tun → 入站
+fn inbounds(fields: &Fields) -> Vec<Inbound> {
+    let listen = if fields.bool("allow-lan") == Some(true) {
+        match fields.str("bind-address").as_deref() {
+            None | Some("*") => "0.0.0.0".to_string(),
+            Some(address) => address.to_string(),
+        }
+    } else {
+        "127.0.0.1".to_string()
+    };
+    let mut inbounds = Vec::new();
+    for (key, kind, tag) in [
+        ("mixed-port", InboundType::Mixed, "mixed-in"),
+        ("port", InboundType::Http, "http-in"),
+        ("socks-port", InboundType::Socks, "socks-in"),
+        ("redir-port", InboundType::Redirect, "redirect-in"),
+        ("tproxy-port", InboundType::TProxy, "tproxy-in"),
+    ] {
+        let Some(port) = fields
+            .u64(key)
+            .and_then(|port| u16::try_from(port).ok())
+            .filter(|port| *port > 0)
+        else {
+            continue;
+        };
+        inbounds.push(Inbound {
+            kind,
+            tag: Some(tag.to_string()),
+            listen: Some(listen.clone()),
+            listen_port: Some(port),
+            ..Default::default()
+        });
+    }
+    if let Some(tun) = fields
+        .object("tun")
+        .filter(|tun| tun.bool("enable") == Some(true))
+    {
+        let mut address = vec!["172.19.0.1/30".to_string()];
+        if fields.bool("ipv6") != Some(false) {
+            address.push("fdfe:dcba:9876::1/126".to_string());
+        }
+        let mut extra = Map::new();
+        extra.insert(
+            "auto_route".to_string(),
+            json!(tun.bool("auto-route").unwrap_or(true)),
+        );
+        if let Some(strict) = tun.bool("strict-route") {
+            extra.insert("strict_route".to_string(), json!(strict));
+        }
+        if let Some(stack) = tun.str("stack") {
+            extra.insert("stack".to_string(), json!(stack.to_ascii_lowercase()));
+        }
+        if let Some(mtu) = tun.u64("mtu") {
+            extra.insert("mtu".to_string(), json!(mtu));
+        }
+        inbounds.push(Inbound {
+            kind: InboundType::Tun,
+            tag: Some("tun-in".to_string()),
+            address: Some(Listable::Many(address)),
+            extra,
+            ..Default::default()
+        });
+    }
+    inbounds
+}
+
+/// log-level → log.level
+fn log(fields: &Fields) -> Option<Log> {
+    let level = fields.str("log-level")?;
+    let mut log = Log::default();
+    match level.as_str() {
+        "silent" => log.disabled = Some(true),
+        "warning" => log.level = Some("warn".to_string()),
+        other => log.level = Some(other.to_string()),
+    }
+    Some(log)
+}
+
+/// external-controller → Clash API
+fn experimental(fields: &Fields) -> Option<Experimental> {
+    let controller = fields.str("external-controller")?;
+    Some(Experimental {
+        clash_api: Some(ClashApi {
+            external_controller: Some(controller),
+            secret: fields.str("secret"),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+}
+
+/// 把 Clash 配置转换为完整的 sing-box 配置
+pub fn convert_profile(profile: &Value) -> ConvertResult {
+    let empty = Map::new();
+    let fields = Fields(profile.as_object().unwrap_or(&empty));
+    let mut ctx = Context::default();
+
+    let (proxies, unsupported) = convert_proxies(profile);
+    ctx.unsupported = unsupported;
+    let proxy_tags: Vec<String> = proxies
+        .iter()
+        .filter_map(|proxy| proxy.tag.clone())
+        .collect();
+    ctx.tags.extend(proxy_tags.iter().cloned());
+    if let Some(providers) = fields.object("proxy-providers") {
+        for name in providers.0.keys() {
+            ctx.report(
+                format!("proxy-providers.{name}"),
+                "proxy providers are not converted; add them as subscriptions",
+            );
+        }
+    }
+
+    let groups = groups(&fields, &proxy_tags, &mut ctx);
+    rule::providers(&fields, &mut ctx);
+    let rules = rule::rules(&fields, &mut ctx);
+    let dns = dns::convert(&fields, &mut ctx);
+    let inbounds = inbounds(&fields);
+
+    match fields.str("mode").as_deref() {
+        None | Some("rule") => {}
+        Some(mode) => ctx.report(
+            "mode",
+            format!("mode {mode} is ignored; sing-box always routes by rules"),
+        ),
+    }
+
+    let mut route_rules = Vec::new();
+    if !inbounds.is_empty() {
+        route_rules.push(RouteRule {
+            action: Some(RouteAction::Sniff),
+            ..Default::default()
+        });
+        if dns.is_some() {
+            route_rules.push(RouteRule {
+                items: RuleItems {
+                    protocol: Some(Listable::One("dns".to_string())),
+                    ..Default::default()
+                },
+                action: Some(RouteAction::HijackDns),
+                ..Default::default()
+            });
+        }
+    }
+    route_rules.extend(rules.rules);
+
+    let mut outbounds = groups;
+    outbounds.extend(proxies);
+    outbounds.push(Outbound::new(OutboundType::Direct, DIRECT_TAG));
+
+    let tun = inbounds
+        .iter()
+        .any(|inbound| inbound.kind == InboundType::Tun);
+    let route = Route {
+        rules: (!route_rules.is_empty()).then_some(route_rules),
+        rule_set: (!ctx.rule_sets.is_empty()).then(|| std::mem::take(&mut ctx.rule_sets)),
+        final_outbound: rules.final_outbound,
+        auto_detect_interface: tun.then_some(true),
+        default_domain_resolver: dns
+            .as_ref()
+            .map(|dns| DomainResolver::Tag(dns.resolver.clone())),
+        ..Default::default()
+    };
+
+    let config = Config {
+        log: log(&fields),
+        dns: dns.map(|dns| dns.dns),
+        inbounds: (!inbounds.is_empty()).then_some(inbounds),
+        outbounds: Some(outbounds),
+        route: Some(route),
+        experimental: experimental(&fields),
+        ..Default::default()
+    };
+    ConvertResult {
+        config,
+        unsupported: ctx.unsupported,
+    }
+}
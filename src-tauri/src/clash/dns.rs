@@ -0,0 +1,347 @@
+//! Clash `dns` / `hosts` → DNS 服务器与 DNS 规则（1.12 新格式）
+
+use std::net::IpAddr;
+
+use serde_json::Map;
+
+use crate::config::{
+    Dns, DnsRule, DnsServer, DnsServerType, DomainResolver, Listable, QueryType, RuleItems,
+};
+
+use super::convert::{Context, DIRECT_TAG};
+use super::rule::Domains;
+use super::{listable, strings, Fields};
+
+const FAKEIP_TAG: &str = "fakeip";
+const HOSTS_TAG: &str = "hosts";
+const LOCAL_TAG: &str = "local";
+
+/// 转换后的 DNS 配置，resolver 用作 route.default_domain_resolver
+pub(super) struct DnsResult {
+    pub dns: Dns,
+    pub resolver: String,
+}
+
+fn is_ip(host: &str) -> bool {
+    host.parse::<IpAddr>().is_ok()
+}
+
+/// "host:port"、"[v6]:port" 或 "host"
+fn host_port(authority: &str) -> Result<(String, Option<u16>), String> {
+    let parse_port = |port: &str| {
+        port.parse::<u16>()
+            .map(Some)
+            .map_err(|_| format!("invalid port: {port}"))
+    };
+    if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest
+            .split_once(']')
+            .ok_or_else(|| format!("invalid address: {authority}"))?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => parse_port(port)?,
+            None => None,
+        };
+        return Ok((host.to_string(), port));
+    }
+    if is_ip(authority) {
+        return Ok((authority.to_string(), None));
+    }
+    match authority.rsplit_once(':') {
+        Some((host, port)) => Ok((host.to_string(), parse_port(port)?)),
+        None => Ok((authority.to_string(), None)),
+    }
+}
+
+/// 转换一个 Clash 名称服务器地址，如 "tls://1.1.1.1#PROXY"、"https://dns.google/dns-query#h3"
+fn server(address: &str, tag: &str, ctx: &mut Context) -> Result<DnsServer, String> {
+    let (address, fragment) = address
+        .trim()
+        .split_once('#')
+        .unwrap_or((address.trim(), ""));
+    let mut server = DnsServer {
+        tag: Some(tag.to_string()),
+        ..Default::default()
+    };
+    let mut h3 = false;
+    for option in fragment.split('&').filter(|option| !option.is_empty()) {
+        if option == "h3" {
+            h3 = true;
+        } else if option.contains('=') {
+            ctx.report(format!("dns.{tag}"), format!("option {option} is ignored"));
+        } else if option != "DIRECT" {
+            server.dial.detour = Some(
+                ctx.outbound(option)
+                    .filter(|detour| detour != DIRECT_TAG)
+                    .ok_or_else(|| format!("proxy {option} was not converted"))?,
+            );
+        }
+    }
+
+    let (scheme, rest) = address.split_once("://").unwrap_or(("udp", address));
+    let kind = match scheme {
+        "system" => DnsServerType::Local,
+        "dhcp" => DnsServerType::Dhcp,
+        "udp" => DnsServerType::Udp,
+        "tcp" => DnsServerType::Tcp,
+        "tls" => DnsServerType::Tls,
+        "https" if h3 => DnsServerType::H3,
+        "https" => DnsServerType::Https,
+        "quic" => DnsServerType::Quic,
+        other => return Err(format!("DNS scheme {other} is not supported")),
+    };
+    if address == "system" {
+        server.kind = Some(DnsServerType::Local);
+        return Ok(server);
+    }
+    match kind {
+        DnsServerType::Local => {}
+        DnsServerType::Dhcp => {
+            if !rest.is_empty() && rest != "system" {
+                server.interface = Some(rest.to_string());
+            }
+        }
+        _ => {
+            let (authority, path) = match rest.find('/') {
+                Some(index) => (&rest[..index], Some(&rest[index..])),
+                None => (rest, None),
+            };
+            let (host, port) = host_port(authority)?;
+            server.server = Some(host);
+            server.server_port = port;
+            if let Some(path) = path.filter(|path| *path != "/dns-query") {
+                server.path = Some(Listable::One(path.to_string()));
+            }
+        }
+    }
+    server.kind = Some(kind);
+    Ok(server)
+}
+
+/// 转换地址列表，tag 为 "{prefix}-{序号}"
+fn servers(dns: &Fields, key: &str, prefix: &str, ctx: &mut Context) -> Vec<DnsServer> {
+    let mut servers = Vec::new();
+    for (index, address) in dns.strings(key).iter().enumerate() {
+        let tag = format!("{prefix}-{}", servers.len() + 1);
+        match server(address, &tag, ctx) {
+            Ok(server) => servers.push(server),
+            Err(message) => ctx.report(format!("dns.{key}[{index}]"), message),
+        }
+    }
+    servers
+}
+
+/// nameserver-policy 的匹配条件："geosite:cn,private"、"rule-set:a,b" 或 "+.a.com,b.com"
+fn policy_items(pattern: &str, ctx: &mut Context) -> Result<RuleItems, String> {
+    let mut items = RuleItems::default();
+    if let Some(codes) = pattern.strip_prefix("geosite:") {
+        let tags = codes
+            .split(',')
+            .map(|code| ctx.geo_rule_set("geosite", code.trim()))
+            .collect();
+        items.rule_set = listable(tags);
+    } else if let Some(names) = pattern.strip_prefix("rule-set:") {
+        let names: Vec<String> = names
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .collect();
+        if let Some(name) = names.iter().find(|name| !ctx.providers.contains_key(*name)) {
+            return Err(format!("rule-set {name} was not converted"));
+        }
+        items.rule_set = listable(names);
+    } else {
+        let mut domains = Domains::default();
+        pattern.split(',').for_each(|entry| domains.push(entry));
+        domains.apply(&mut items);
+    }
+    Ok(items)
+}
+
+/// hosts 映射 → hosts 服务器的 predefined，只支持精确域名
+fn hosts(fields: &Fields, ctx: &mut Context) -> Option<DnsServer> {
+    let hosts = fields.object("hosts")?;
+    let mut predefined = Map::new();
+    for (domain, value) in hosts.0 {
+        if domain.contains(['*', '+']) {
+            ctx.report(
+                format!("hosts.{domain}"),
+                "wildcard hosts are not supported",
+            );
+            continue;
+        }
+        predefined.insert(domain.clone(), value.clone());
+    }
+    if predefined.is_empty() {
+        return None;
+    }
+    Some(DnsServer {
+        kind: Some(DnsServerType::Hosts),
+        tag: Some(HOSTS_TAG.to_string()),
+        predefined: Some(predefined),
+        ..Default::default()
+    })
+}
+
+/// 转换 dns 段，未启用时返回 None
+pub(super) fn convert(fields: &Fields, ctx: &mut Context) -> Option<DnsResult> {
+    let dns = fields
+        .object("dns")
+        .filter(|dns| dns.bool("enable") == Some(true))?;
+    let mut result = Dns::default();
+    let mut rules = Vec::new();
+
+    let bootstrap = servers(&dns, "default-nameserver", "bootstrap", ctx);
+    let proxy_servers = servers(&dns, "proxy-server-nameserver", "proxy-server", ctx);
+    let mut nameservers = servers(&dns, "nameserver", "nameserver", ctx);
+    if nameservers.is_empty() {
+        nameservers.push(DnsServer {
+            kind: Some(DnsServerType::Local),
+            tag: Some(LOCAL_TAG.to_string()),
+            ..Default::default()
+        });
+    }
+    if !dns.strings("fallback").is_empty() {
+        ctx.report(
+            "dns.fallback",
+            "fallback nameservers are not supported; use DNS rules to pick servers",
+        );
+    }
+    let final_server = nameservers[0].tag.clone().unwrap_or_default();
+    let mut all: Vec<DnsServer> = Vec::new();
+
+    if dns.bool("use-hosts") != Some(false) {
+        if let Some(hosts) = hosts(fields, ctx) {
+            all.push(hosts);
+            rules.push(DnsRule {
+                ip_accept_any: Some(true),
+                server: Some(Listable::One(HOSTS_TAG.to_string())),
+                ..Default::default()
+            });
+        }
+    }
+
+    if let Some(policy) = dns.object("nameserver-policy") {
+        for (index, (pattern, value)) in policy.0.iter().enumerate() {
+            let path = format!("dns.nameserver-policy.{pattern}");
+            let addresses = strings(value);
+            let Some(address) = addresses.first() else {
+                continue;
+            };
+            if addresses.len() > 1 {
+                ctx.report(path.clone(), "only the first server is used");
+            }
+            let tag = format!("policy-{}", index + 1);
+            let converted = policy_items(pattern, ctx)
+                .and_then(|items| server(address, &tag, ctx).map(|server| (items, server)));
+            match converted {
+                Ok((items, server)) => {
+                    all.push(server);
+                    rules.push(DnsRule {
+                        items,
+                        server: Some(Listable::One(tag)),
+                        ..Default::default()
+                    });
+                }
+                Err(message) => ctx.report(path, message),
+            }
+        }
+    }
+
+    if dns.str("enhanced-mode").as_deref() == Some("fake-ip") {
+        let mut filter = Domains::default();
+        for entry in dns.strings("fake-ip-filter") {
+            if entry.starts_with("geosite:") || entry.starts_with("rule-set:") {
+                ctx.report("dns.fake-ip-filter", format!("{entry} is not supported"));
+            } else {
+                filter.push(&entry);
+            }
+        }
+        if dns.str("fake-ip-filter-mode").as_deref() == Some("whitelist") {
+            ctx.report(
+                "dns.fake-ip-filter-mode",
+                "whitelist mode is not supported; the filter is used as a blacklist",
+            );
+        }
+        if !filter.is_empty() {
+            let mut items = RuleItems::default();
+            filter.apply(&mut items);
+            rules.push(DnsRule {
+                items,
+                server: Some(Listable::One(final_server.clone())),
+                ..Default::default()
+            });
+        }
+        let ipv6 = dns.bool("ipv6") != Some(false);
+        all.push(DnsServer {
+            kind: Some(DnsServerType::FakeIp),
+            tag: Some(FAKEIP_TAG.to_string()),
+            inet4_range: Some(
+                dns.str("fake-ip-range")
+                    .unwrap_or_else(|| "198.18.0.1/16".to_string()),
+            ),
+            inet6_range: ipv6.then(|| "fc00::/18".to_string()),
+            ..Default::default()
+        });
+        rules.push(DnsRule {
+            items: RuleItems {
+                query_type: Some(Listable::Many(vec![
+                    QueryType::Name("A".to_string()),
+                    QueryType::Name("AAAA".to_string()),
+                ])),
+                ..Default::default()
+            },
+            server: Some(Listable::One(FAKEIP_TAG.to_string())),
+            ..Default::default()
+        });
+    }
+
+    // 域名形式的服务器需要先用 default-nameserver（没有时用系统 DNS）解析
+    let resolver = bootstrap
+        .first()
+        .and_then(|server| server.tag.clone())
+        .unwrap_or_else(|| LOCAL_TAG.to_string());
+    let mut servers: Vec<DnsServer> = bootstrap
+        .into_iter()
+        .take(1)
+        .chain(proxy_servers.iter().cloned())
+        .chain(nameservers)
+        .chain(all)
+        .collect();
+    let mut needs_local = false;
+    for server in &mut servers {
+        let domain = server.server.as_deref().is_some_and(|host| !is_ip(host));
+        if domain && server.tag.as_deref() != Some(resolver.as_str()) {
+            server.dial.domain_resolver = Some(DomainResolver::Tag(resolver.clone()));
+            needs_local |= resolver == LOCAL_TAG;
+        }
+    }
+    if needs_local
+        && !servers
+            .iter()
+            .any(|server| server.tag.as_deref() == Some(LOCAL_TAG))
+    {
+        servers.insert(
+            0,
+            DnsServer {
+                kind: Some(DnsServerType::Local),
+                tag: Some(LOCAL_TAG.to_string()),
+                ..Default::default()
+            },
+        );
+    }
+
+    result.servers = Some(servers);
+    result.rules = (!rules.is_empty()).then_some(rules);
+    result.final_server = Some(final_server.clone());
+    if dns.bool("ipv6") == Some(false) {
+        result.strategy = Some("ipv4_only".to_string());
+    }
+    // 代理服务器的域名不能解析成 FakeIP，优先使用 proxy-server-nameserver
+    let resolver = proxy_servers
+        .first()
+        .and_then(|server| server.tag.clone())
+        .unwrap_or(final_server);
+    Some(DnsResult {
+        dns: result,
+        resolver,
+    })
+}
@@ -0,0 +1,1170 @@
+//! sing-box 配置 → Clash Meta（mihomo）YAML
+//!
+//! 导出出站、selector/urltest 分组、路由规则和规则集，无法等价表达的部分记入警告。
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::config::{
+    Config, HeadlessRule, InboundType, Listable, LogicalMode, Outbound, OutboundType, RouteAction,
+    RouteRule, RuleItems, RuleSet, RuleSetType, Tls, Transport,
+};
+use crate::migration::{GEOIP_URL, GEOSITE_URL};
+
+use super::Unsupported;
+
+/// 导出结果：Clash YAML 和有损转换的警告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportResult {
+    pub yaml: String,
+    pub warnings: Vec<Unsupported>,
+}
+
+/// sing-box 缺省的 urltest 测试地址和间隔
+const DEFAULT_TEST_URL: &str = "https://www.gstatic.com/generate_204";
+const DEFAULT_TEST_INTERVAL: u64 = 180;
+
+#[derive(Default)]
+struct Exporter {
+    /// 出站标签 → Clash 中引用的名称（direct 为 DIRECT，block 为 REJECT）
+    names: HashMap<String, String>,
+    /// 可以导出为 rule-provider 的规则集标签 → Clash 中的写法
+    rule_sets: HashMap<String, RuleSetRef>,
+    warnings: Vec<Unsupported>,
+}
+
+/// 规则集在 Clash 中的写法
+#[derive(Clone)]
+enum RuleSetRef {
+    Geosite(String),
+    Geoip(String),
+    Provider,
+}
+
+impl Exporter {
+    fn warn(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.warnings.push(Unsupported {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+}
+
+/// "1h30m"、"300ms"、"10s" → 毫秒
+fn duration_ms(text: &str) -> Option<u64> {
+    let mut total = 0u64;
+    let mut rest = text.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let value: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let factor = match &rest[..unit] {
+            "ms" => 1,
+            "s" | "" => 1000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            "d" => 86_400_000,
+            _ => return None,
+        };
+        total += value * factor;
+        rest = &rest[unit..];
+    }
+    Some(total)
+}
+
+fn strings(values: &Option<Listable<String>>) -> Vec<String> {
+    values
+        .as_ref()
+        .map(|values| values.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// "a=1;b;c=2" 形式的插件参数
+fn plugin_opts(opts: &str) -> Vec<(&str, &str)> {
+    opts.split(';')
+        .map(str::trim)
+        .filter(|opt| !opt.is_empty())
+        .map(|opt| opt.split_once('=').unwrap_or((opt, "")))
+        .collect()
+}
+
+fn shadowsocks_plugin(outbound: &Outbound, proxy: &mut Map<String, Value>) -> Result<(), String> {
+    let Some(plugin) = outbound.plugin.as_deref() else {
+        return Ok(());
+    };
+    let opts = plugin_opts(outbound.plugin_opts.as_deref().unwrap_or_default());
+    let mut clash_opts = Map::new();
+    match plugin {
+        "obfs-local" => {
+            for (key, value) in opts {
+                match key {
+                    "obfs" => clash_opts.insert("mode".into(), json!(value)),
+                    "obfs-host" => clash_opts.insert("host".into(), json!(value)),
+                    _ => None,
+                };
+            }
+            proxy.insert("plugin".into(), json!("obfs"));
+        }
+        "v2ray-plugin" => {
+            for (key, value) in opts {
+                match key {
+                    "mode" | "host" | "path" => clash_opts.insert(key.into(), json!(value)),
+                    "tls" => clash_opts.insert("tls".into(), json!(true)),
+                    "mux" => clash_opts.insert("mux".into(), json!(value != "0")),
+                    _ => None,
+                };
+            }
+            proxy.insert("plugin".into(), json!("v2ray-plugin"));
+        }
+        other => {
+            return Err(format!(
+                "shadowsocks plugin {other} is not supported by mihomo"
+            ))
+        }
+    }
+    proxy.insert("plugin-opts".into(), Value::Object(clash_opts));
+    Ok(())
+}
+
+/// TLS 字段；trojan、hysteria2 等总是使用 TLS 的协议用 sni，其余用 tls + servername
+fn tls(tls: &Tls, kind: &OutboundType, proxy: &mut Map<String, Value>, lossy: &mut Vec<&str>) {
+    if !tls.is_enabled() {
+        return;
+    }
+    let sni_key = match kind {
+        OutboundType::Vmess | OutboundType::Vless => {
+            proxy.insert("tls".into(), json!(true));
+            "servername"
+        }
+        OutboundType::Http | OutboundType::Socks => {
+            proxy.insert("tls".into(), json!(true));
+            "sni"
+        }
+        _ => "sni",
+    };
+    if let Some(server_name) = &tls.server_name {
+        proxy.insert(sni_key.into(), json!(server_name));
+    }
+    if tls.insecure == Some(true) {
+        proxy.insert("skip-cert-verify".into(), json!(true));
+    }
+    let alpn = strings(&tls.alpn);
+    if !alpn.is_empty() {
+        proxy.insert("alpn".into(), json!(alpn));
+    }
+    if tls.disable_sni == Some(true) {
+        proxy.insert("disable-sni".into(), json!(true));
+    }
+    if let Some(fingerprint) = tls.utls.as_ref().and_then(|utls| utls.fingerprint.as_ref()) {
+        proxy.insert("client-fingerprint".into(), json!(fingerprint));
+    }
+    if let Some(reality) = tls
+        .reality
+        .as_ref()
+        .filter(|reality| reality.enabled == Some(true))
+    {
+        let mut opts = Map::new();
+        if let Some(public_key) = &reality.public_key {
+            opts.insert("public-key".into(), json!(public_key));
+        }
+        if let Some(short_id) = reality.short_id.as_ref().and_then(Listable::first) {
+            opts.insert("short-id".into(), json!(short_id));
+        }
+        proxy.insert("reality-opts".into(), Value::Object(opts));
+    }
+    if tls.ech.is_some() {
+        lossy.push("tls.ech");
+    }
+    if tls.certificate.is_some() || tls.certificate_path.is_some() {
+        lossy.push("tls.certificate");
+    }
+    if tls.min_version.is_some() || tls.max_version.is_some() || tls.cipher_suites.is_some() {
+        lossy.push("tls versions and cipher suites");
+    }
+}
+
+fn transport(
+    transport: &Transport,
+    tls_enabled: bool,
+    proxy: &mut Map<String, Value>,
+) -> Result<(), String> {
+    let host = strings(&transport.host);
+    match transport.kind.as_deref().unwrap_or_default() {
+        "ws" => {
+            let mut opts = Map::new();
+            if let Some(path) = &transport.path {
+                opts.insert("path".into(), json!(path));
+            }
+            if let Some(headers) = &transport.headers {
+                opts.insert("headers".into(), Value::Object(headers.clone()));
+            }
+            if let Some(max_early_data) = transport.max_early_data {
+                opts.insert("max-early-data".into(), json!(max_early_data));
+            }
+            if let Some(name) = &transport.early_data_header_name {
+                opts.insert("early-data-header-name".into(), json!(name));
+            }
+            proxy.insert("network".into(), json!("ws"));
+            proxy.insert("ws-opts".into(), Value::Object(opts));
+        }
+        "httpupgrade" => {
+            let mut opts = Map::new();
+            if let Some(path) = &transport.path {
+                opts.insert("path".into(), json!(path));
+            }
+            if let Some(host) = host.first() {
+                opts.insert("headers".into(), json!({ "Host": host }));
+            }
+            opts.insert("v2ray-http-upgrade".into(), json!(true));
+            proxy.insert("network".into(), json!("ws"));
+            proxy.insert("ws-opts".into(), Value::Object(opts));
+        }
+        "grpc" => {
+            let mut opts = Map::new();
+            if let Some(service_name) = &transport.service_name {
+                opts.insert("grpc-service-name".into(), json!(service_name));
+            }
+            proxy.insert("network".into(), json!("grpc"));
+            proxy.insert("grpc-opts".into(), Value::Object(opts));
+        }
+        // 带 TLS 的 HTTP 传输即 HTTP/2
+        "http" if tls_enabled => {
+            let mut opts = Map::new();
+            if !host.is_empty() {
+                opts.insert("host".into(), json!(host));
+            }
+            if let Some(path) = &transport.path {
+                opts.insert("path".into(), json!(path));
+            }
+            proxy.insert("network".into(), json!("h2"));
+            proxy.insert("h2-opts".into(), Value::Object(opts));
+        }
+        "http" => {
+            let mut opts = Map::new();
+            if let Some(method) = &transport.method {
+                opts.insert("method".into(), json!(method));
+            }
+            if let Some(path) = &transport.path {
+                opts.insert("path".into(), json!([path]));
+            }
+            let mut headers = transport.headers.clone().unwrap_or_default();
+            if !host.is_empty() {
+                headers.insert("Host".into(), json!(host));
+            }
+            if !headers.is_empty() {
+                opts.insert("headers".into(), Value::Object(headers));
+            }
+            proxy.insert("network".into(), json!("http"));
+            proxy.insert("http-opts".into(), Value::Object(opts));
+        }
+        other => return Err(format!("transport {other} is not supported by mihomo")),
+    }
+    Ok(())
+}
+
+/// 取出 extra 中的字段，记录已处理的键
+fn take_extra<'a>(
+    outbound: &'a Outbound,
+    key: &'a str,
+    used: &mut Vec<&'a str>,
+) -> Option<&'a Value> {
+    used.push(key);
+    outbound.extra.get(key)
+}
+
+impl Exporter {
+    /// 出站 → Clash 代理条目
+    fn proxy(&mut self, outbound: &Outbound, path: &str) -> Result<Map<String, Value>, String> {
+        let tag = outbound.tag.clone().unwrap_or_default();
+        let kind = match outbound.kind {
+            OutboundType::Shadowsocks => "ss",
+            OutboundType::Vmess => "vmess",
+            OutboundType::Vless => "vless",
+            OutboundType::Trojan => "trojan",
+            OutboundType::Hysteria => "hysteria",
+            OutboundType::Hysteria2 => "hysteria2",
+            OutboundType::Tuic => "tuic",
+            OutboundType::AnyTls => "anytls",
+            OutboundType::Socks => "socks5",
+            OutboundType::Http => "http",
+            OutboundType::Ssh => "ssh",
+            ref other => return Err(format!("outbound type {other} is not supported by mihomo")),
+        };
+        let mut proxy = Map::new();
+        proxy.insert("name".into(), json!(tag));
+        proxy.insert("type".into(), json!(kind));
+        proxy.insert(
+            "server".into(),
+            json!(outbound.server.as_deref().ok_or("outbound has no server")?),
+        );
+        if let Some(port) = outbound.server_port {
+            proxy.insert("port".into(), json!(port));
+        }
+
+        let mut used = Vec::new();
+        let mut lossy = Vec::new();
+        let insert = |proxy: &mut Map<String, Value>, key: &str, value: &Option<String>| {
+            if let Some(value) = value {
+                proxy.insert(key.into(), json!(value));
+            }
+        };
+        match outbound.kind {
+            OutboundType::Shadowsocks => {
+                insert(&mut proxy, "cipher", &outbound.method);
+                insert(&mut proxy, "password", &outbound.password);
+                if take_extra(outbound, "udp_over_tcp", &mut used)
+                    .is_some_and(|v| v != &json!(false))
+                {
+                    proxy.insert("udp-over-tcp".into(), json!(true));
+                }
+                shadowsocks_plugin(outbound, &mut proxy)?;
+            }
+            OutboundType::Vmess => {
+                insert(&mut proxy, "uuid", &outbound.uuid);
+                proxy.insert("alterId".into(), json!(outbound.alter_id.unwrap_or(0)));
+                proxy.insert(
+                    "cipher".into(),
+                    json!(outbound.security.as_deref().unwrap_or("auto")),
+                );
+                insert(&mut proxy, "packet-encoding", &outbound.packet_encoding);
+            }
+            OutboundType::Vless => {
+                insert(&mut proxy, "uuid", &outbound.uuid);
+                insert(&mut proxy, "flow", &outbound.flow);
+                insert(&mut proxy, "packet-encoding", &outbound.packet_encoding);
+            }
+            OutboundType::Trojan | OutboundType::AnyTls => {
+                insert(&mut proxy, "password", &outbound.password);
+            }
+            OutboundType::Hysteria | OutboundType::Hysteria2 => {
+                insert(&mut proxy, "password", &outbound.password);
+                for (key, target) in [("up_mbps", "up"), ("down_mbps", "down")] {
+                    if let Some(mbps) = take_extra(outbound, key, &mut used).and_then(Value::as_u64)
+                    {
+                        proxy.insert(target.into(), json!(format!("{mbps} Mbps")));
+                    }
+                }
+                if let Some(auth) = take_extra(outbound, "auth_str", &mut used) {
+                    proxy.insert("auth-str".into(), auth.clone());
+                }
+                match take_extra(outbound, "obfs", &mut used) {
+                    Some(Value::String(obfs)) => {
+                        proxy.insert("obfs".into(), json!(obfs));
+                    }
+                    Some(Value::Object(obfs)) => {
+                        if let Some(kind) = obfs.get("type") {
+                            proxy.insert("obfs".into(), kind.clone());
+                        }
+                        if let Some(password) = obfs.get("password") {
+                            proxy.insert("obfs-password".into(), password.clone());
+                        }
+                    }
+                    _ => {}
+                }
+                let ports: Vec<String> = strings(&outbound.server_ports)
+                    .iter()
+                    .map(|range| match range.split_once(':') {
+                        Some((start, end)) if start == end => start.to_string(),
+                        Some((start, end)) => format!("{start}-{end}"),
+                        None => range.clone(),
+                    })
+                    .collect();
+                if !ports.is_empty() {
+                    proxy.insert("ports".into(), json!(ports.join(",")));
+                }
+            }
+            OutboundType::Tuic => {
+                insert(&mut proxy, "uuid", &outbound.uuid);
+                insert(&mut proxy, "password", &outbound.password);
+                for (key, target) in [
+                    ("congestion_control", "congestion-controller"),
+                    ("udp_relay_mode", "udp-relay-mode"),
+                ] {
+                    if let Some(value) = take_extra(outbound, key, &mut used) {
+                        proxy.insert(target.into(), value.clone());
+                    }
+                }
+                if take_extra(outbound, "zero_rtt_handshake", &mut used) == Some(&json!(true)) {
+                    proxy.insert("reduce-rtt".into(), json!(true));
+                }
+                if let Some(ms) = take_extra(outbound, "heartbeat", &mut used)
+                    .and_then(Value::as_str)
+                    .and_then(duration_ms)
+                {
+                    proxy.insert("heartbeat-interval".into(), json!(ms));
+                }
+            }
+            OutboundType::Socks | OutboundType::Http => {
+                insert(&mut proxy, "username", &outbound.username);
+                insert(&mut proxy, "password", &outbound.password);
+                used.push("version");
+            }
+            OutboundType::Ssh => {
+                proxy.insert(
+                    "username".into(),
+                    json!(outbound.username.as_deref().unwrap_or("root")),
+                );
+                insert(&mut proxy, "password", &outbound.password);
+                if let Some(key) = take_extra(outbound, "private_key", &mut used) {
+                    proxy.insert("private-key".into(), key.clone());
+                }
+            }
+            _ => {}
+        }
+
+        let only_tcp = outbound
+            .network
+            .as_ref()
+            .is_some_and(|network| network.iter().all(|network| network == "tcp"));
+        proxy.insert("udp".into(), json!(!only_tcp));
+
+        if let Some(tls_options) = &outbound.tls {
+            tls(tls_options, &outbound.kind, &mut proxy, &mut lossy);
+        }
+        if let Some(options) = &outbound.transport {
+            let tls_enabled = outbound.tls.as_ref().is_some_and(Tls::is_enabled);
+            transport(options, tls_enabled, &mut proxy)?;
+        }
+        if let Some(multiplex) = outbound.multiplex.as_ref().filter(|mux| mux.is_enabled()) {
+            let mut smux = Map::new();
+            smux.insert("enabled".into(), json!(true));
+            if let Some(protocol) = &multiplex.protocol {
+                smux.insert("protocol".into(), json!(protocol));
+            }
+            for (key, value) in [
+                ("max-connections", multiplex.max_connections),
+                ("min-streams", multiplex.min_streams),
+                ("max-streams", multiplex.max_streams),
+            ] {
+                if let Some(value) = value {
+                    smux.insert(key.into(), json!(value));
+                }
+            }
+            if multiplex.padding == Some(true) {
+                smux.insert("padding".into(), json!(true));
+            }
+            if multiplex.brutal.is_some() {
+                lossy.push("multiplex.brutal");
+            }
+            proxy.insert("smux".into(), Value::Object(smux));
+        }
+
+        let dial = &outbound.dial;
+        if let Some(detour) = &dial.detour {
+            match self.names.get(detour) {
+                Some(name) => {
+                    proxy.insert("dialer-proxy".into(), json!(name));
+                }
+                None => lossy.push("detour"),
+            }
+        }
+        if let Some(interface) = &dial.bind_interface {
+            proxy.insert("interface-name".into(), json!(interface));
+        }
+        if dial.tcp_fast_open == Some(true) {
+            proxy.insert("tfo".into(), json!(true));
+        }
+        if dial.tcp_multi_path == Some(true) {
+            proxy.insert("mptcp".into(), json!(true));
+        }
+        if dial.domain_resolver.is_some() || dial.domain_strategy.is_some() {
+            lossy.push("domain_resolver");
+        }
+
+        let mut ignored: Vec<&str> = outbound
+            .extra
+            .keys()
+            .map(String::as_str)
+            .filter(|key| !used.contains(key))
+            .collect();
+        ignored.extend(lossy);
+        if !ignored.is_empty() {
+            self.warn(path, format!("{} not exported", ignored.join(", ")));
+        }
+        Ok(proxy)
+    }
+}
+
+/// Clash 规则条件，逻辑规则写成 "AND,((A),(B))"
+enum Cond {
+    Leaf(String),
+    And(Vec<Cond>),
+    Or(Vec<Cond>),
+    Not(Box<Cond>),
+}
+
+impl Cond {
+    fn any(mut conds: Vec<Cond>) -> Option<Cond> {
+        match conds.len() {
+            0 => None,
+            1 => conds.pop(),
+            _ => Some(Cond::Or(conds)),
+        }
+    }
+
+    fn all(mut conds: Vec<Cond>) -> Option<Cond> {
+        match conds.len() {
+            0 => None,
+            1 => conds.pop(),
+            _ => Some(Cond::And(conds)),
+        }
+    }
+
+    fn render(&self) -> String {
+        let join = |conds: &[Cond]| {
+            conds
+                .iter()
+                .map(|cond| format!("({})", cond.render()))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        match self {
+            Cond::Leaf(rule) => rule.clone(),
+            Cond::And(conds) => format!("AND,({})", join(conds)),
+            Cond::Or(conds) => format!("OR,({})", join(conds)),
+            Cond::Not(cond) => format!("NOT,(({}))", cond.render()),
+        }
+    }
+
+    /// 规则行（不含目标），顶层的 OR 拆成多行
+    fn lines(self) -> Vec<String> {
+        match self {
+            Cond::Or(conds) => conds.iter().map(Cond::render).collect(),
+            cond => vec![cond.render()],
+        }
+    }
+}
+
+fn leaves(kind: &str, values: impl IntoIterator<Item = String>) -> Vec<Cond> {
+    values
+        .into_iter()
+        .map(|value| Cond::Leaf(format!("{kind},{value}")))
+        .collect()
+}
+
+fn ip_cidr(kind: &str, values: &Option<Listable<String>>) -> Vec<Cond> {
+    strings(values)
+        .into_iter()
+        .map(|cidr| {
+            let v6 = cidr.contains(':');
+            let cidr = match (cidr.contains('/'), v6) {
+                (true, _) => cidr,
+                (false, true) => format!("{cidr}/128"),
+                (false, false) => format!("{cidr}/32"),
+            };
+            if kind == "IP-CIDR" && v6 {
+                Cond::Leaf(format!("IP-CIDR6,{cidr}"))
+            } else {
+                Cond::Leaf(format!("{kind},{cidr}"))
+            }
+        })
+        .collect()
+}
+
+/// port 与 "a:b" 形式的 port_range → "a-b"
+fn ports(
+    kind: &str,
+    ports: &Option<Listable<u16>>,
+    ranges: &Option<Listable<String>>,
+) -> Vec<Cond> {
+    let mut values: Vec<String> = ports.iter().flatten().map(u16::to_string).collect();
+    for range in strings(ranges) {
+        let (start, end) = range.split_once(':').unwrap_or((&range, &range));
+        let start = if start.is_empty() { "0" } else { start };
+        let end = if end.is_empty() { "65535" } else { end };
+        values.push(format!("{start}-{end}"));
+    }
+    leaves(kind, values)
+}
+
+impl Exporter {
+    /// 默认规则的匹配字段 → 条件；同类字段之间为“或”，不同类之间为“且”
+    fn items(&self, items: &RuleItems) -> Result<Option<Cond>, String> {
+        let unsupported = [
+            ("inbound", items.inbound.is_some()),
+            ("ip_version", items.ip_version.is_some()),
+            ("query_type", items.query_type.is_some()),
+            ("protocol", items.protocol.is_some()),
+            ("client", items.client.is_some()),
+            ("user", items.user.is_some()),
+            ("clash_mode", items.clash_mode.is_some()),
+            ("network_type", items.network_type.is_some()),
+            ("network_is_expensive", items.network_is_expensive.is_some()),
+            (
+                "network_is_constrained",
+                items.network_is_constrained.is_some(),
+            ),
+            ("wifi_ssid", items.wifi_ssid.is_some()),
+            ("wifi_bssid", items.wifi_bssid.is_some()),
+            (
+                "rule_set_ip_cidr_match_source",
+                items.rule_set_ip_cidr_match_source == Some(true)
+                    || items.rule_set_ipcidr_match_source == Some(true),
+            ),
+        ];
+        let fields: Vec<&str> = unsupported
+            .iter()
+            .filter(|(_, present)| *present)
+            .map(|(field, _)| *field)
+            .collect();
+        if !fields.is_empty() {
+            return Err(format!(
+                "{} cannot be expressed in mihomo",
+                fields.join(", ")
+            ));
+        }
+
+        let mut destination = Vec::new();
+        destination.extend(leaves("DOMAIN", strings(&items.domain)));
+        destination.extend(leaves(
+            "DOMAIN-SUFFIX",
+            strings(&items.domain_suffix)
+                .into_iter()
+                .map(|suffix| suffix.trim_start_matches('.').to_string()),
+        ));
+        destination.extend(leaves("DOMAIN-KEYWORD", strings(&items.domain_keyword)));
+        let regex = strings(&items.domain_regex);
+        if regex.iter().any(|regex| regex.contains(',')) {
+            return Err("domain_regex with commas cannot be expressed in mihomo".to_string());
+        }
+        destination.extend(leaves("DOMAIN-REGEX", regex));
+        destination.extend(leaves("GEOSITE", strings(&items.geosite)));
+        destination.extend(leaves("GEOIP", strings(&items.geoip)));
+        destination.extend(ip_cidr("IP-CIDR", &items.ip_cidr));
+        if items.ip_is_private == Some(true) {
+            destination.push(Cond::Leaf("GEOIP,LAN".to_string()));
+        }
+        for tag in strings(&items.rule_set) {
+            let leaf = match self.rule_sets.get(&tag) {
+                Some(RuleSetRef::Geosite(code)) => format!("GEOSITE,{code}"),
+                Some(RuleSetRef::Geoip(code)) => format!("GEOIP,{code}"),
+                Some(RuleSetRef::Provider) => format!("RULE-SET,{tag}"),
+                None => return Err(format!("rule-set {tag} was not exported")),
+            };
+            destination.push(Cond::Leaf(leaf));
+        }
+
+        let mut source = leaves("SRC-GEOIP", strings(&items.source_geoip));
+        source.extend(ip_cidr("SRC-IP-CIDR", &items.source_ip_cidr));
+        if items.source_ip_is_private == Some(true) {
+            source.push(Cond::Leaf("SRC-GEOIP,LAN".to_string()));
+        }
+
+        let groups = [
+            destination,
+            source,
+            ports("DST-PORT", &items.port, &items.port_range),
+            ports("SRC-PORT", &items.source_port, &items.source_port_range),
+            leaves(
+                "NETWORK",
+                strings(&items.network)
+                    .into_iter()
+                    .map(|network| network.to_ascii_uppercase()),
+            ),
+            leaves("IN-USER", strings(&items.auth_user)),
+            leaves(
+                "PROCESS-NAME",
+                strings(&items.process_name)
+                    .into_iter()
+                    .chain(strings(&items.package_name)),
+            ),
+            leaves("PROCESS-PATH", strings(&items.process_path)),
+            leaves("PROCESS-PATH-REGEX", strings(&items.process_path_regex)),
+            leaves("UID", items.user_id.iter().flatten().map(u32::to_string)),
+        ];
+        Ok(Cond::all(
+            groups.into_iter().filter_map(Cond::any).collect(),
+        ))
+    }
+
+    /// 合并逻辑规则的子条件
+    fn logical(
+        mode: &Option<LogicalMode>,
+        subs: Vec<Option<Cond>>,
+    ) -> Result<Option<Cond>, String> {
+        let subs: Vec<Cond> = subs
+            .into_iter()
+            .collect::<Option<_>>()
+            .ok_or("logical rule has an empty sub-rule")?;
+        Ok(match mode {
+            Some(LogicalMode::Or) => Cond::any(subs),
+            _ => Cond::all(subs),
+        })
+    }
+
+    fn invert(cond: Option<Cond>, invert: Option<bool>) -> Result<Option<Cond>, String> {
+        match (cond, invert) {
+            (Some(cond), Some(true)) => Ok(Some(Cond::Not(Box::new(cond)))),
+            (None, Some(true)) => Err("inverted empty rule never matches".to_string()),
+            (cond, _) => Ok(cond),
+        }
+    }
+
+    fn route_cond(&self, rule: &RouteRule) -> Result<Option<Cond>, String> {
+        if let Some(key) = rule.extra.keys().next() {
+            return Err(format!("{key} cannot be expressed in mihomo"));
+        }
+        let cond = if rule.is_logical() {
+            let subs = rule
+                .rules
+                .iter()
+                .flatten()
+                .map(|rule| self.route_cond(rule))
+                .collect::<Result<_, _>>()?;
+            Self::logical(&rule.mode, subs)?
+        } else {
+            self.items(&rule.items)?
+        };
+        Self::invert(cond, rule.invert)
+    }
+
+    fn headless_cond(&self, rule: &HeadlessRule) -> Result<Option<Cond>, String> {
+        if let Some(key) = rule.extra.keys().next() {
+            return Err(format!("{key} cannot be expressed in mihomo"));
+        }
+        let cond = if rule.is_logical() {
+            let subs = rule
+                .rules
+                .iter()
+                .flatten()
+                .map(|rule| self.headless_cond(rule))
+                .collect::<Result<_, _>>()?;
+            Self::logical(&rule.mode, subs)?
+        } else {
+            self.items(&rule.items)?
+        };
+        Self::invert(cond, rule.invert)
+    }
+
+    /// 路由规则 → rules；resolve 动作之前的 IP 规则加 no-resolve，与 sing-box 不解析域名的行为一致
+    fn rules(&mut self, rules: &[RouteRule]) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut resolved = false;
+        for (index, rule) in rules.iter().enumerate() {
+            let path = format!("route.rules[{index}]");
+            let target = match rule.action() {
+                RouteAction::Route => {
+                    let outbound = rule.outbound.clone().unwrap_or_default();
+                    match self.names.get(&outbound) {
+                        Some(name) => name.clone(),
+                        None => {
+                            self.warn(path, format!("outbound {outbound} was not exported"));
+                            continue;
+                        }
+                    }
+                }
+                RouteAction::Reject if rule.method.as_deref() == Some("drop") => {
+                    "REJECT-DROP".to_string()
+                }
+                RouteAction::Reject => "REJECT".to_string(),
+                RouteAction::Resolve => {
+                    resolved = true;
+                    continue;
+                }
+                // mihomo 通过 sniffer 和 dns-hijack 配置实现
+                RouteAction::Sniff | RouteAction::HijackDns => continue,
+                other => {
+                    self.warn(path, format!("action {other} is not supported by mihomo"));
+                    continue;
+                }
+            };
+            if rule.items.clash_mode.is_some() {
+                self.warn(
+                    path,
+                    "clash_mode rules are skipped; mihomo switches modes itself",
+                );
+                continue;
+            }
+            let cond = match self.route_cond(rule) {
+                Ok(cond) => cond,
+                Err(message) => {
+                    self.warn(path, message);
+                    continue;
+                }
+            };
+            let Some(cond) = cond else {
+                lines.push(format!("MATCH,{target}"));
+                continue;
+            };
+            for line in cond.lines() {
+                let ip = ["IP-CIDR,", "IP-CIDR6,", "GEOIP,"]
+                    .iter()
+                    .any(|prefix| line.starts_with(prefix));
+                if ip && !resolved {
+                    lines.push(format!("{line},{target},no-resolve"));
+                } else {
+                    lines.push(format!("{line},{target}"));
+                }
+            }
+        }
+        lines
+    }
+}
+
+impl Exporter {
+    /// 规则集 → rule-provider；官方 geoip / geosite 规则集直接写成 GEOIP / GEOSITE 规则
+    fn rule_set(&mut self, rule_set: &RuleSet, path: &str) -> Option<Value> {
+        let tag = rule_set.tag.clone()?;
+        let provider = match rule_set.kind.clone().unwrap_or(RuleSetType::Inline) {
+            RuleSetType::Remote => {
+                let url = rule_set.url.as_deref().unwrap_or_default();
+                for (base, kind) in [(GEOSITE_URL, "geosite"), (GEOIP_URL, "geoip")] {
+                    let code = url
+                        .strip_prefix(base)
+                        .and_then(|rest| rest.strip_prefix(&format!("/{kind}-")))
+                        .and_then(|rest| rest.strip_suffix(".srs"));
+                    if let Some(code) = code {
+                        let reference = if kind == "geosite" {
+                            RuleSetRef::Geosite(code.to_string())
+                        } else {
+                            RuleSetRef::Geoip(code.to_string())
+                        };
+                        self.rule_sets.insert(tag, reference);
+                        return None;
+                    }
+                }
+                // MetaCubeX meta-rules-dat 同时提供两种格式，换成同仓库的 mrs 文件
+                let behavior = if url.contains("/geoip/") {
+                    "ipcidr"
+                } else {
+                    "domain"
+                };
+                let meta = url.contains("meta-rules-dat")
+                    && (url.contains("/sing/geo") || url.contains("@sing/geo"))
+                    && url.ends_with(".srs");
+                if !meta {
+                    self.warn(
+                        path,
+                        format!("rule-set {tag} uses a sing-box format mihomo cannot read"),
+                    );
+                    return None;
+                }
+                let url =
+                    url.replacen("/sing/geo", "/meta/geo", 1)
+                        .replacen("@sing/geo", "@meta/geo", 1);
+                let mut provider = Map::new();
+                provider.insert("type".into(), json!("http"));
+                provider.insert("behavior".into(), json!(behavior));
+                provider.insert("format".into(), json!("mrs"));
+                provider.insert(
+                    "url".into(),
+                    json!(format!("{}.mrs", url.trim_end_matches(".srs"))),
+                );
+                let interval = rule_set
+                    .update_interval
+                    .as_deref()
+                    .and_then(duration_ms)
+                    .map_or(86_400, |ms| ms / 1000);
+                provider.insert("interval".into(), json!(interval));
+                if let Some(detour) = &rule_set.download_detour {
+                    match self.names.get(detour) {
+                        Some(name) => {
+                            provider.insert("proxy".into(), json!(name));
+                        }
+                        None => self.warn(path, format!("download_detour {detour} is dropped")),
+                    }
+                }
+                provider
+            }
+            RuleSetType::Inline => {
+                let mut payload = Vec::new();
+                for rule in rule_set.rules.iter().flatten() {
+                    match self.headless_cond(rule) {
+                        Ok(Some(cond)) => payload.extend(cond.lines()),
+                        Ok(None) => {}
+                        Err(message) => {
+                            self.warn(path, format!("rule-set {tag}: {message}"));
+                            return None;
+                        }
+                    }
+                }
+                let mut provider = Map::new();
+                provider.insert("type".into(), json!("inline"));
+                provider.insert("behavior".into(), json!("classical"));
+                provider.insert("payload".into(), json!(payload));
+                provider
+            }
+            RuleSetType::Local => {
+                self.warn(
+                    path,
+                    format!("local rule-set {tag} is not exported; make it inline or remote"),
+                );
+                return None;
+            }
+            other => {
+                self.warn(path, format!("rule-set type {other} is not supported"));
+                return None;
+            }
+        };
+        self.rule_sets.insert(tag, RuleSetRef::Provider);
+        Some(Value::Object(provider))
+    }
+
+    /// selector / urltest → select / url-test
+    fn group(&mut self, outbound: &Outbound, path: &str) -> Value {
+        let mut members = Vec::new();
+        for member in outbound.members() {
+            match self.names.get(member) {
+                Some(name) if !members.contains(name) => members.push(name.clone()),
+                Some(_) => {}
+                None => self.warn(path, format!("member {member} was not exported")),
+            }
+        }
+        // mihomo 的 select 默认选中第一个成员
+        if let Some(default) = outbound
+            .default
+            .as_ref()
+            .and_then(|tag| self.names.get(tag))
+        {
+            if let Some(index) = members.iter().position(|member| member == default) {
+                let default = members.remove(index);
+                members.insert(0, default);
+            }
+        }
+        if members.is_empty() {
+            self.warn(path, "group has no members left, DIRECT is used instead");
+            members.push("DIRECT".to_string());
+        }
+
+        let mut group = Map::new();
+        group.insert("name".into(), json!(outbound.tag));
+        if outbound.kind == OutboundType::Selector {
+            group.insert("type".into(), json!("select"));
+            group.insert("proxies".into(), json!(members));
+        } else {
+            group.insert("type".into(), json!("url-test"));
+            group.insert("proxies".into(), json!(members));
+            group.insert(
+                "url".into(),
+                json!(outbound.url.as_deref().unwrap_or(DEFAULT_TEST_URL)),
+            );
+            let interval = outbound
+                .interval
+                .as_deref()
+                .and_then(duration_ms)
+                .map_or(DEFAULT_TEST_INTERVAL, |ms| ms / 1000);
+            group.insert("interval".into(), json!(interval));
+            if let Some(tolerance) = outbound.tolerance {
+                group.insert("tolerance".into(), json!(tolerance));
+            }
+        }
+        Value::Object(group)
+    }
+
+    /// 本地入站 → 端口设置和 tun
+    fn inbounds(&mut self, config: &Config, hijack_dns: bool, profile: &mut Map<String, Value>) {
+        for (index, inbound) in config.inbounds().iter().enumerate() {
+            let path = format!("inbounds[{index}]");
+            let key = match inbound.kind {
+                InboundType::Mixed => "mixed-port",
+                InboundType::Http => "port",
+                InboundType::Socks => "socks-port",
+                InboundType::Redirect => "redir-port",
+                InboundType::TProxy => "tproxy-port",
+                InboundType::Tun => {
+                    let mut tun = Map::new();
+                    tun.insert("enable".into(), json!(true));
+                    for (key, target) in [
+                        ("stack", "stack"),
+                        ("auto_route", "auto-route"),
+                        ("strict_route", "strict-route"),
+                        ("mtu", "mtu"),
+                    ] {
+                        if let Some(value) = inbound.extra.get(key) {
+                            tun.insert(target.into(), value.clone());
+                        }
+                    }
+                    tun.insert("auto-detect-interface".into(), json!(true));
+                    if hijack_dns {
+                        tun.insert("dns-hijack".into(), json!(["any:53"]));
+                    }
+                    profile.insert("tun".into(), Value::Object(tun));
+                    continue;
+                }
+                ref other => {
+                    self.warn(path, format!("inbound type {other} is not exported"));
+                    continue;
+                }
+            };
+            let Some(port) = inbound.listen_port else {
+                continue;
+            };
+            if profile.contains_key(key) {
+                self.warn(
+                    path,
+                    format!("only the first {} inbound is exported", inbound.kind),
+                );
+                continue;
+            }
+            profile.insert(key.into(), json!(port));
+            if matches!(inbound.listen.as_deref(), Some("0.0.0.0" | "::")) {
+                profile.insert("allow-lan".into(), json!(true));
+            }
+        }
+    }
+}
+
+/// sing-box 日志级别 → log-level
+fn log_level(config: &Config) -> Option<&'static str> {
+    let log = config.log.as_ref()?;
+    if log.disabled == Some(true) {
+        return Some("silent");
+    }
+    Some(match log.level.as_deref()? {
+        "trace" | "debug" => "debug",
+        "info" => "info",
+        "warn" => "warning",
+        _ => "error",
+    })
+}
+
+/// 把 sing-box 配置导出为 Clash Meta 配置
+pub fn export_profile(config: &Config) -> Result<ExportResult, String> {
+    let mut exporter = Exporter::default();
+    let outbounds = config.outbounds();
+
+    // 先登记所有名称，代理和分组之间可以互相引用
+    for outbound in outbounds {
+        let Some(tag) = outbound.tag.clone() else {
+            continue;
+        };
+        let name = match outbound.kind {
+            OutboundType::Direct => "DIRECT".to_string(),
+            OutboundType::Block => "REJECT".to_string(),
+            OutboundType::Dns
+            | OutboundType::WireGuard
+            | OutboundType::ShadowTls
+            | OutboundType::Tor => {
+                continue;
+            }
+            _ => tag.clone(),
+        };
+        exporter.names.insert(tag, name);
+    }
+
+    let mut proxies = Vec::new();
+    for (index, outbound) in outbounds.iter().enumerate() {
+        let path = format!("outbounds[{index}]");
+        match outbound.kind {
+            OutboundType::Direct | OutboundType::Block | OutboundType::Dns => continue,
+            ref kind if kind.is_group() => continue,
+            _ => {}
+        }
+        match exporter.proxy(outbound, &path) {
+            Ok(proxy) => proxies.push(Value::Object(proxy)),
+            Err(message) => {
+                if let Some(tag) = &outbound.tag {
+                    exporter.names.remove(tag);
+                }
+                exporter.warn(path, message);
+            }
+        }
+    }
+    for (index, endpoint) in config.endpoints().iter().enumerate() {
+        exporter.warn(
+            format!("endpoints[{index}]"),
+            format!(
+                "endpoint {} is not exported",
+                endpoint.tag.as_deref().unwrap_or_default()
+            ),
+        );
+    }
+    let groups: Vec<Value> = outbounds
+        .iter()
+        .enumerate()
+        .filter(|(_, outbound)| outbound.kind.is_group())
+        .map(|(index, outbound)| exporter.group(outbound, &format!("outbounds[{index}]")))
+        .collect();
+
+    let route = config.route.clone().unwrap_or_default();
+    let mut providers = Vec::new();
+    for (index, rule_set) in route.rule_sets().iter().enumerate() {
+        if let Some(provider) = exporter.rule_set(rule_set, &format!("route.rule_set[{index}]")) {
+            providers.push((rule_set.tag.clone().unwrap_or_default(), provider));
+        }
+    }
+    let mut rules = exporter.rules(route.rules());
+    let final_name = match &route.final_outbound {
+        Some(tag) => exporter.names.get(tag).cloned().or_else(|| {
+            exporter.warn("route.final", format!("outbound {tag} was not exported"));
+            None
+        }),
+        // sing-box 缺省使用第一个出站
+        None => outbounds
+            .first()
+            .and_then(|outbound| outbound.tag.as_ref())
+            .and_then(|tag| exporter.names.get(tag))
+            .cloned(),
+    };
+    rules.push(format!(
+        "MATCH,{}",
+        final_name.as_deref().unwrap_or("DIRECT")
+    ));
+    // 只保留被规则引用的 rule-provider
+    providers.retain(|(tag, _)| {
+        rules.iter().any(|rule| {
+            rule.contains(&format!("RULE-SET,{tag},")) || rule.contains(&format!("RULE-SET,{tag})"))
+        })
+    });
+
+    let mut profile = Map::new();
+    let hijack_dns = route
+        .rules()
+        .iter()
+        .any(|rule| rule.action() == RouteAction::HijackDns);
+    let mut inbound_settings = Map::new();
+    exporter.inbounds(config, hijack_dns, &mut inbound_settings);
+    let tun = inbound_settings.remove("tun");
+    profile.extend(inbound_settings);
+    profile.insert("mode".into(), json!("rule"));
+    if let Some(level) = log_level(config) {
+        profile.insert("log-level".into(), json!(level));
+    }
+    if let Some(clash_api) = config
+        .experimental
+        .as_ref()
+        .and_then(|experimental| experimental.clash_api.as_ref())
+    {
+        if let Some(controller) = &clash_api.external_controller {
+            profile.insert("external-controller".into(), json!(controller));
+        }
+        if let Some(secret) = &clash_api.secret {
+            profile.insert("secret".into(), json!(secret));
+        }
+    }
+    if let Some(tun) = tun {
+        profile.insert("tun".into(), tun);
+    }
+    if config.dns.is_some() {
+        exporter.warn(
+            "dns",
+            "DNS settings are not exported; configure mihomo's dns section separately",
+        );
+    }
+    profile.insert("proxies".into(), json!(proxies));
+    profile.insert("proxy-groups".into(), json!(groups));
+    if !providers.is_empty() {
+        profile.insert(
+            "rule-providers".into(),
+            Value::Object(providers.into_iter().collect()),
+        );
+    }
+    profile.insert("rules".into(), json!(rules));
+
+    let yaml = serde_yaml::to_string(&Value::Object(profile))
+        .map_err(|e| format!("failed to write YAML: {e}"))?;
+    Ok(ExportResult {
+        yaml,
+        warnings: exporter.warnings,
+    })
+}
@@ -0,0 +1,132 @@
+//! Clash Meta（mihomo）配置转换
+
+mod convert;
+mod dns;
+mod export;
+mod proxy;
+mod rule;
+
+pub use convert::{convert_profile, ConvertResult};
+pub use export::{export_profile, ExportResult};
+pub use proxy::proxy_to_outbound;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::config::{Listable, Outbound};
+
+/// 无法转换的条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Unsupported {
+    /// 条目位置，如 "proxies[3]"
+    pub path: String,
+    pub message: String,
+}
+
+/// 解析 Clash YAML
+pub fn parse_yaml(text: &str) -> Result<Value, String> {
+    serde_yaml::from_str(text).map_err(|e| format!("invalid YAML: {e}"))
+}
+
+/// 是否像 Clash 配置（含 proxies 或 proxy-providers）
+pub fn is_profile(profile: &Value) -> bool {
+    profile.get("proxies").is_some() || profile.get("proxy-providers").is_some()
+}
+
+/// 解析 Clash YAML 并转换为完整的 sing-box 配置
+pub fn convert_yaml(text: &str) -> Result<ConvertResult, String> {
+    let profile = parse_yaml(text)?;
+    if !profile.is_object() {
+        return Err("Clash profile must be a mapping".to_string());
+    }
+    Ok(convert_profile(&profile))
+}
+
+/// 转换 proxies 列表，无法转换的条目计入 Unsupported
+pub fn convert_proxies(profile: &Value) -> (Vec<Outbound>, Vec<Unsupported>) {
+    let mut outbounds = Vec::new();
+    let mut unsupported = Vec::new();
+    let proxies = profile
+        .get("proxies")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for (index, proxy) in proxies.iter().enumerate() {
+        match proxy_to_outbound(proxy) {
+            Ok(outbound) => outbounds.push(outbound),
+            Err(message) => unsupported.push(Unsupported {
+                path: format!("proxies[{index}]"),
+                message,
+            }),
+        }
+    }
+    (outbounds, unsupported)
+}
+
+/// Clash 条目的字段读取，端口等数字可能写成字符串
+struct Fields<'a>(&'a Map<String, Value>);
+
+impl<'a> Fields<'a> {
+    fn get(&self, key: &str) -> Option<&'a Value> {
+        self.0.get(key).filter(|value| !value.is_null())
+    }
+
+    fn str(&self, key: &str) -> Option<String> {
+        match self.get(key)? {
+            Value::String(value) if !value.is_empty() => Some(value.clone()),
+            Value::Number(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    fn bool(&self, key: &str) -> Option<bool> {
+        match self.get(key)? {
+            Value::Bool(value) => Some(*value),
+            Value::String(value) => Some(value == "true"),
+            _ => None,
+        }
+    }
+
+    fn u64(&self, key: &str) -> Option<u64> {
+        match self.get(key)? {
+            Value::Number(value) => value.as_u64(),
+            Value::String(value) => value.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn object(&self, key: &str) -> Option<Fields<'a>> {
+        self.get(key)?.as_object().map(Fields)
+    }
+
+    /// 单个字符串（逗号分隔）或字符串列表
+    fn strings(&self, key: &str) -> Vec<String> {
+        self.get(key).map(strings).unwrap_or_default()
+    }
+}
+
+/// 单个字符串（逗号分隔）或字符串列表
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Value::Array(values) => values
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn listable(values: Vec<String>) -> Option<Listable<String>> {
+    match values.len() {
+        0 => None,
+        1 => values.into_iter().next().map(Listable::One),
+        _ => Some(Listable::Many(values)),
+    }
+}
@@ -0,0 +1,381 @@
+//! Clash Meta `proxies` 条目 → 出站，参考 mihomo 文档中的 proxies 配置
+
+use serde_json::{json, Value};
+
+use crate::config::shared::{Reality, Utls};
+use crate::config::{Listable, Multiplex, Outbound, OutboundType, Tls, Transport};
+
+use super::{listable, Fields};
+
+/// 把 "100 Mbps"、"50" 等带宽写法转为 Mbps
+fn mbps(value: &str) -> Option<u64> {
+    let digits: String = value.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// 转换单个代理条目
+pub fn proxy_to_outbound(proxy: &Value) -> Result<Outbound, String> {
+    let object = proxy
+        .as_object()
+        .ok_or_else(|| "proxy must be a mapping".to_string())?;
+    let fields = Fields(object);
+    let name = fields
+        .str("name")
+        .ok_or_else(|| "proxy has no name".to_string())?;
+    let kind = fields.str("type").unwrap_or_default();
+    let kind = match kind.as_str() {
+        "ss" => OutboundType::Shadowsocks,
+        "vmess" => OutboundType::Vmess,
+        "vless" => OutboundType::Vless,
+        "trojan" => OutboundType::Trojan,
+        "hysteria" => OutboundType::Hysteria,
+        "hysteria2" => OutboundType::Hysteria2,
+        "tuic" => OutboundType::Tuic,
+        "anytls" => OutboundType::AnyTls,
+        "socks5" => OutboundType::Socks,
+        "http" => OutboundType::Http,
+        "ssh" => OutboundType::Ssh,
+        other => return Err(format!("proxy type {other} is not supported")),
+    };
+
+    let mut outbound = Outbound::new(kind.clone(), name);
+    outbound.server = Some(
+        fields
+            .str("server")
+            .ok_or_else(|| "proxy has no server".to_string())?,
+    );
+    outbound.server_port = fields
+        .u64("port")
+        .map(|port| u16::try_from(port).map_err(|_| format!("invalid port: {port}")))
+        .transpose()?;
+    if fields.bool("udp") == Some(false) && kind != OutboundType::Shadowsocks {
+        outbound.network = Some(Listable::One("tcp".to_string()));
+    }
+    if fields.bool("tfo") == Some(true) {
+        outbound.dial.tcp_fast_open = Some(true);
+    }
+    if fields.bool("mptcp") == Some(true) {
+        outbound.dial.tcp_multi_path = Some(true);
+    }
+    outbound.dial.detour = fields.str("dialer-proxy");
+
+    match kind {
+        OutboundType::Shadowsocks => {
+            outbound.method = fields.str("cipher");
+            outbound.password = fields.str("password");
+            if fields.bool("udp-over-tcp") == Some(true) {
+                outbound
+                    .extra
+                    .insert("udp_over_tcp".to_string(), json!(true));
+            }
+            shadowsocks_plugin(&fields, &mut outbound)?;
+        }
+        OutboundType::Vmess => {
+            outbound.uuid = fields.str("uuid");
+            outbound.alter_id = fields
+                .u64("alterId")
+                .and_then(|aid| u32::try_from(aid).ok());
+            outbound.alter_id = outbound.alter_id.filter(|aid| *aid > 0);
+            outbound.security = fields.str("cipher");
+            outbound.packet_encoding = packet_encoding(&fields);
+        }
+        OutboundType::Vless => {
+            outbound.uuid = fields.str("uuid");
+            outbound.flow = fields.str("flow");
+            outbound.packet_encoding = packet_encoding(&fields);
+        }
+        OutboundType::Trojan => outbound.password = fields.str("password"),
+        OutboundType::Hysteria => {
+            for (key, target) in [("up", "up_mbps"), ("down", "down_mbps")] {
+                if let Some(value) = fields.str(key).as_deref().and_then(mbps) {
+                    outbound.extra.insert(target.to_string(), json!(value));
+                }
+            }
+            if let Some(auth) = fields.str("auth-str") {
+                outbound.extra.insert("auth_str".to_string(), json!(auth));
+            }
+            if let Some(obfs) = fields.str("obfs") {
+                outbound.extra.insert("obfs".to_string(), json!(obfs));
+            }
+            hop_ports(&fields, &mut outbound);
+        }
+        OutboundType::Hysteria2 => {
+            outbound.password = fields.str("password");
+            for (key, target) in [("up", "up_mbps"), ("down", "down_mbps")] {
+                if let Some(value) = fields.str(key).as_deref().and_then(mbps) {
+                    outbound.extra.insert(target.to_string(), json!(value));
+                }
+            }
+            if let Some(obfs) = fields.str("obfs") {
+                let password = fields.str("obfs-password").unwrap_or_default();
+                outbound.extra.insert(
+                    "obfs".to_string(),
+                    json!({ "type": obfs, "password": password }),
+                );
+            }
+            hop_ports(&fields, &mut outbound);
+        }
+        OutboundType::Tuic => {
+            outbound.uuid = fields.str("uuid");
+            outbound.password = fields.str("password");
+            for (key, target) in [
+                ("congestion-controller", "congestion_control"),
+                ("udp-relay-mode", "udp_relay_mode"),
+            ] {
+                if let Some(value) = fields.str(key) {
+                    outbound.extra.insert(target.to_string(), json!(value));
+                }
+            }
+            if fields.bool("reduce-rtt") == Some(true) {
+                outbound
+                    .extra
+                    .insert("zero_rtt_handshake".to_string(), json!(true));
+            }
+            if let Some(ms) = fields.u64("heartbeat-interval") {
+                outbound
+                    .extra
+                    .insert("heartbeat".to_string(), json!(format!("{ms}ms")));
+            }
+        }
+        OutboundType::AnyTls => outbound.password = fields.str("password"),
+        OutboundType::Socks | OutboundType::Http => {
+            outbound.username = fields.str("username");
+            outbound.password = fields.str("password");
+        }
+        OutboundType::Ssh => {
+            outbound.extra.insert(
+                "user".to_string(),
+                json!(fields.str("username").unwrap_or_else(|| "root".to_string())),
+            );
+            outbound.password = fields.str("password");
+            if let Some(key) = fields.str("private-key") {
+                outbound.extra.insert("private_key".to_string(), json!(key));
+            }
+        }
+        _ => {}
+    }
+
+    outbound.tls = tls(&fields, &kind)?;
+    outbound.transport = transport(&fields)?;
+    outbound.multiplex = multiplex(&fields);
+    Ok(outbound)
+}
+
+fn packet_encoding(fields: &Fields) -> Option<String> {
+    if let Some(encoding) = fields.str("packet-encoding") {
+        return Some(encoding);
+    }
+    if fields.bool("xudp") == Some(true) {
+        return Some("xudp".to_string());
+    }
+    if fields.bool("packet-addr") == Some(true) {
+        return Some("packetaddr".to_string());
+    }
+    None
+}
+
+/// ports: "1000-2000,3000" → server_ports ["1000:2000", "3000:3000"]
+fn hop_ports(fields: &Fields, outbound: &mut Outbound) {
+    let Some(ports) = fields.str("ports") else {
+        return;
+    };
+    let ranges = ports
+        .split(',')
+        .map(str::trim)
+        .filter(|range| !range.is_empty())
+        .map(|range| match range.split_once('-') {
+            Some((start, end)) => format!("{}:{}", start.trim(), end.trim()),
+            None => format!("{range}:{range}"),
+        })
+        .collect();
+    outbound.server_ports = listable(ranges);
+}
+
+/// obfs / v2ray-plugin / shadow-tls 以外的插件不支持
+fn shadowsocks_plugin(fields: &Fields, outbound: &mut Outbound) -> Result<(), String> {
+    let Some(plugin) = fields.str("plugin") else {
+        return Ok(());
+    };
+    let opts = fields.object("plugin-opts");
+    let opt = |key: &str| opts.as_ref().and_then(|opts| opts.str(key));
+    match plugin.as_str() {
+        "obfs" => {
+            let mut parts = vec![format!(
+                "obfs={}",
+                opt("mode").unwrap_or_else(|| "http".into())
+            )];
+            if let Some(host) = opt("host") {
+                parts.push(format!("obfs-host={host}"));
+            }
+            outbound.plugin = Some("obfs-local".to_string());
+            outbound.plugin_opts = Some(parts.join(";"));
+        }
+        "v2ray-plugin" => {
+            let mut parts = vec![format!(
+                "mode={}",
+                opt("mode").unwrap_or_else(|| "websocket".into())
+            )];
+            if opts.as_ref().and_then(|opts| opts.bool("tls")) == Some(true) {
+                parts.push("tls".to_string());
+            }
+            if let Some(host) = opt("host") {
+                parts.push(format!("host={host}"));
+            }
+            if let Some(path) = opt("path") {
+                parts.push(format!("path={path}"));
+            }
+            if opts.as_ref().and_then(|opts| opts.bool("mux")) == Some(true) {
+                parts.push("mux=1".to_string());
+            }
+            outbound.plugin = Some("v2ray-plugin".to_string());
+            outbound.plugin_opts = Some(parts.join(";"));
+        }
+        other => return Err(format!("shadowsocks plugin {other} is not supported")),
+    }
+    Ok(())
+}
+
+fn tls(fields: &Fields, kind: &OutboundType) -> Result<Option<Tls>, String> {
+    // 这些协议总是使用 TLS
+    let always = matches!(
+        kind,
+        OutboundType::Trojan
+            | OutboundType::Hysteria
+            | OutboundType::Hysteria2
+            | OutboundType::Tuic
+            | OutboundType::AnyTls
+    );
+    if !always && fields.bool("tls") != Some(true) {
+        return Ok(None);
+    }
+    let reality = match fields.object("reality-opts") {
+        Some(opts) => Some(Reality {
+            enabled: Some(true),
+            public_key: Some(
+                opts.str("public-key")
+                    .ok_or_else(|| "reality-opts has no public-key".to_string())?,
+            ),
+            short_id: opts.str("short-id").map(Listable::One),
+            ..Default::default()
+        }),
+        None => None,
+    };
+    // reality 客户端必须启用 uTLS
+    let fingerprint = fields
+        .str("client-fingerprint")
+        .or(reality.as_ref().map(|_| "chrome".to_string()));
+    Ok(Some(Tls {
+        enabled: Some(true),
+        server_name: fields.str("sni").or(fields.str("servername")),
+        insecure: fields.bool("skip-cert-verify").filter(|insecure| *insecure),
+        alpn: listable(fields.strings("alpn")),
+        disable_sni: fields.bool("disable-sni").filter(|disable| *disable),
+        utls: fingerprint.map(|fingerprint| Utls {
+            enabled: Some(true),
+            fingerprint: Some(fingerprint),
+            ..Default::default()
+        }),
+        reality,
+        ..Default::default()
+    }))
+}
+
+fn transport(fields: &Fields) -> Result<Option<Transport>, String> {
+    let network = fields.str("network").unwrap_or_default();
+    let transport = match network.as_str() {
+        "" | "tcp" => return Ok(None),
+        "ws" => {
+            let opts = fields.object("ws-opts");
+            let opt = |key: &str| opts.as_ref().and_then(|opts| opts.str(key));
+            let headers = opts
+                .as_ref()
+                .and_then(|opts| opts.get("headers"))
+                .and_then(Value::as_object)
+                .cloned();
+            let max_early_data = opts
+                .as_ref()
+                .and_then(|opts| opts.u64("max-early-data"))
+                .and_then(|ed| u32::try_from(ed).ok());
+            let upgrade = opts
+                .as_ref()
+                .and_then(|opts| opts.bool("v2ray-http-upgrade"))
+                == Some(true);
+            if upgrade {
+                let host = headers
+                    .as_ref()
+                    .and_then(|headers| headers.get("Host"))
+                    .and_then(Value::as_str)
+                    .map(|host| Listable::One(host.to_string()));
+                Transport {
+                    kind: Some("httpupgrade".to_string()),
+                    host,
+                    path: opt("path"),
+                    ..Default::default()
+                }
+            } else {
+                Transport {
+                    kind: Some("ws".to_string()),
+                    path: opt("path"),
+                    headers,
+                    max_early_data,
+                    early_data_header_name: opt("early-data-header-name")
+                        .or(max_early_data.map(|_| "Sec-WebSocket-Protocol".to_string())),
+                    ..Default::default()
+                }
+            }
+        }
+        "grpc" => Transport {
+            kind: Some("grpc".to_string()),
+            service_name: fields
+                .object("grpc-opts")
+                .and_then(|opts| opts.str("grpc-service-name")),
+            ..Default::default()
+        },
+        "h2" => {
+            let opts = fields.object("h2-opts");
+            Transport {
+                kind: Some("http".to_string()),
+                host: opts
+                    .as_ref()
+                    .and_then(|opts| listable(opts.strings("host"))),
+                path: opts.as_ref().and_then(|opts| opts.str("path")),
+                ..Default::default()
+            }
+        }
+        "http" => {
+            let opts = fields.object("http-opts");
+            Transport {
+                kind: Some("http".to_string()),
+                method: opts.as_ref().and_then(|opts| opts.str("method")),
+                path: opts
+                    .as_ref()
+                    .and_then(|opts| opts.strings("path").into_iter().next()),
+                headers: opts
+                    .as_ref()
+                    .and_then(|opts| opts.get("headers"))
+                    .and_then(Value::as_object)
+                    .cloned(),
+                ..Default::default()
+            }
+        }
+        other => return Err(format!("network {other} is not supported")),
+    };
+    Ok(Some(transport))
+}
+
+fn multiplex(fields: &Fields) -> Option<Multiplex> {
+    let smux = fields.object("smux")?;
+    if smux.bool("enabled") != Some(true) {
+        return None;
+    }
+    let number = |key: &str| smux.u64(key).and_then(|value| u32::try_from(value).ok());
+    Some(Multiplex {
+        enabled: Some(true),
+        protocol: smux.str("protocol"),
+        max_connections: number("max-connections"),
+        min_streams: number("min-streams"),
+        max_streams: number("max-streams"),
+        padding: smux.bool("padding").filter(|padding| *padding),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,555 @@
+//! Clash `rules` / `rule-providers` → 路由规则与规则集
+
+use serde_json::Value;
+
+use crate::config::{
+    HeadlessRule, Listable, LogicalMode, RouteAction, RouteRule, RuleItems, RuleSet, RuleSetType,
+    RuleType,
+};
+
+use super::convert::Context;
+use super::{listable, Fields};
+
+/// 规则的匹配部分，逻辑规则可以嵌套
+#[derive(Default)]
+pub(super) struct Matcher {
+    mode: Option<LogicalMode>,
+    rules: Vec<Matcher>,
+    items: RuleItems,
+    invert: bool,
+    /// 是否按 IP 匹配（Clash 会先解析域名）
+    ip: bool,
+}
+
+impl Matcher {
+    fn items(items: RuleItems) -> Self {
+        Self {
+            items,
+            ..Default::default()
+        }
+    }
+
+    fn ip(items: RuleItems) -> Self {
+        Self {
+            items,
+            ip: true,
+            ..Default::default()
+        }
+    }
+
+    pub(super) fn into_route_rule(self) -> RouteRule {
+        RouteRule {
+            kind: self.mode.is_some().then_some(RuleType::Logical),
+            mode: self.mode,
+            rules: (!self.rules.is_empty())
+                .then(|| self.rules.into_iter().map(Self::into_route_rule).collect()),
+            items: self.items,
+            invert: self.invert.then_some(true),
+            ..Default::default()
+        }
+    }
+
+    fn into_headless_rule(self) -> HeadlessRule {
+        HeadlessRule {
+            kind: self.mode.is_some().then_some(RuleType::Logical),
+            mode: self.mode,
+            rules: (!self.rules.is_empty()).then(|| {
+                self.rules
+                    .into_iter()
+                    .map(Self::into_headless_rule)
+                    .collect()
+            }),
+            items: self.items,
+            invert: self.invert.then_some(true),
+            ..Default::default()
+        }
+    }
+}
+
+/// 拆开的一行规则："TYPE,payload,target,options..."
+struct RuleLine<'a> {
+    kind: String,
+    payload: &'a str,
+    /// payload 之后的部分（目标与选项）
+    rest: Vec<&'a str>,
+}
+
+fn split_rule(line: &str) -> Result<RuleLine<'_>, String> {
+    let line = line.trim();
+    let (kind, rest) = line.split_once(',').unwrap_or((line, ""));
+    let kind = kind.trim().to_ascii_uppercase();
+    let (payload, rest) = match kind.as_str() {
+        "MATCH" | "FINAL" => ("", rest),
+        "AND" | "OR" | "NOT" => {
+            let rest = rest.trim_start();
+            let end =
+                closing_paren(rest).ok_or_else(|| format!("unbalanced parentheses in {line}"))?;
+            (&rest[..end], rest[end..].trim_start_matches(','))
+        }
+        _ => rest.split_once(',').unwrap_or((rest, "")),
+    };
+    let rest = rest
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect();
+    Ok(RuleLine {
+        kind,
+        payload: payload.trim(),
+        rest,
+    })
+}
+
+/// 以 "(" 开头的文本中与之配对的 ")" 之后的位置
+fn closing_paren(text: &str) -> Option<usize> {
+    if !text.starts_with('(') {
+        return None;
+    }
+    let mut depth = 0usize;
+    for (index, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 逻辑规则的子规则："((DOMAIN,a),(NETWORK,UDP))" → ["DOMAIN,a", "NETWORK,UDP"]
+fn sub_rules(payload: &str) -> Result<Vec<&str>, String> {
+    let inner = payload
+        .strip_prefix('(')
+        .and_then(|inner| inner.strip_suffix(')'))
+        .ok_or_else(|| format!("invalid logical payload: {payload}"))?;
+    let mut rules = Vec::new();
+    let mut rest = inner.trim();
+    while !rest.is_empty() {
+        let end =
+            closing_paren(rest).ok_or_else(|| format!("invalid logical payload: {payload}"))?;
+        rules.push(rest[1..end - 1].trim());
+        rest = rest[end..]
+            .trim_start()
+            .trim_start_matches(',')
+            .trim_start();
+    }
+    Ok(rules)
+}
+
+/// Clash 通配符域名转为正则：* 匹配一级标签，? 匹配单个字符
+fn wildcard_regex(pattern: &str) -> String {
+    let mut regex = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str("[^.]+"),
+            '?' => regex.push_str("[^.]"),
+            c if c.is_ascii_alphanumeric() || c == '-' || c == '_' => regex.push(c),
+            c => {
+                regex.push('\\');
+                regex.push(c);
+            }
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+/// domain 类型 rule-provider 和 fake-ip-filter 中的域名写法
+#[derive(Default)]
+pub(super) struct Domains {
+    pub domain: Vec<String>,
+    pub suffix: Vec<String>,
+    pub regex: Vec<String>,
+}
+
+impl Domains {
+    /// "+.a.com" 匹配自身和子域名，".a.com" 只匹配子域名，含 * 的按通配符处理
+    pub(super) fn push(&mut self, entry: &str) {
+        let entry = entry.trim();
+        if let Some(suffix) = entry.strip_prefix("+.") {
+            self.suffix.push(suffix.to_string());
+        } else if entry.starts_with('.') {
+            self.suffix.push(entry.to_string());
+        } else if entry.contains(['*', '?']) {
+            self.regex.push(wildcard_regex(entry));
+        } else if !entry.is_empty() {
+            self.domain.push(entry.to_string());
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.domain.is_empty() && self.suffix.is_empty() && self.regex.is_empty()
+    }
+
+    pub(super) fn apply(self, items: &mut RuleItems) {
+        items.domain = listable(self.domain);
+        items.domain_suffix = listable(self.suffix);
+        items.domain_regex = listable(self.regex);
+    }
+}
+
+/// "80/443/1000-2000" → (port, port_range)
+fn ports(payload: &str) -> Result<(Vec<u16>, Vec<String>), String> {
+    let mut ports = Vec::new();
+    let mut ranges = Vec::new();
+    for part in payload.split('/').map(str::trim) {
+        if let Some((start, end)) = part.split_once('-') {
+            ranges.push(format!("{}:{}", start.trim(), end.trim()));
+        } else {
+            ports.push(part.parse().map_err(|_| format!("invalid port: {part}"))?);
+        }
+    }
+    Ok((ports, ranges))
+}
+
+fn listable_ports(ports: Vec<u16>) -> Option<Listable<u16>> {
+    match ports.len() {
+        0 => None,
+        1 => Some(Listable::One(ports[0])),
+        _ => Some(Listable::Many(ports)),
+    }
+}
+
+/// 转换一条规则的匹配部分
+pub(super) fn matcher(kind: &str, payload: &str, ctx: &mut Context) -> Result<Matcher, String> {
+    let one = || Some(Listable::One(payload.to_string()));
+    let mut items = RuleItems::default();
+    match kind {
+        "AND" | "OR" => {
+            let mut rules = sub_rules(payload)?
+                .into_iter()
+                .map(|rule| sub_matcher(rule, ctx))
+                .collect::<Result<Vec<_>, _>>()?;
+            if rules.len() == 1 {
+                return Ok(rules.remove(0));
+            }
+            return Ok(Matcher {
+                mode: Some(if kind == "AND" {
+                    LogicalMode::And
+                } else {
+                    LogicalMode::Or
+                }),
+                ip: rules.iter().any(|rule| rule.ip),
+                rules,
+                ..Default::default()
+            });
+        }
+        "NOT" => {
+            let rules = sub_rules(payload)?;
+            let [rule] = rules.as_slice() else {
+                return Err("NOT takes exactly one rule".to_string());
+            };
+            let mut rule = sub_matcher(rule, ctx)?;
+            rule.invert = !rule.invert;
+            return Ok(rule);
+        }
+        "DOMAIN" => items.domain = one(),
+        "DOMAIN-SUFFIX" => items.domain_suffix = one(),
+        "DOMAIN-KEYWORD" => items.domain_keyword = one(),
+        "DOMAIN-REGEX" => items.domain_regex = one(),
+        "DOMAIN-WILDCARD" => items.domain_regex = Some(Listable::One(wildcard_regex(payload))),
+        "GEOSITE" => items.rule_set = Some(Listable::One(ctx.geo_rule_set("geosite", payload))),
+        "GEOIP" => {
+            if payload.eq_ignore_ascii_case("lan") || payload.eq_ignore_ascii_case("private") {
+                items.ip_is_private = Some(true);
+            } else {
+                items.rule_set = Some(Listable::One(ctx.geo_rule_set("geoip", payload)));
+            }
+            return Ok(Matcher::ip(items));
+        }
+        "SRC-GEOIP" => {
+            if payload.eq_ignore_ascii_case("lan") || payload.eq_ignore_ascii_case("private") {
+                items.source_ip_is_private = Some(true);
+            } else {
+                items.rule_set = Some(Listable::One(ctx.geo_rule_set("geoip", payload)));
+                items.rule_set_ip_cidr_match_source = Some(true);
+            }
+        }
+        "IP-CIDR" | "IP-CIDR6" => {
+            items.ip_cidr = one();
+            return Ok(Matcher::ip(items));
+        }
+        "SRC-IP-CIDR" => items.source_ip_cidr = one(),
+        "DST-PORT" => {
+            let (ports, ranges) = ports(payload)?;
+            items.port = listable_ports(ports);
+            items.port_range = listable(ranges);
+        }
+        "SRC-PORT" => {
+            let (ports, ranges) = ports(payload)?;
+            items.source_port = listable_ports(ports);
+            items.source_port_range = listable(ranges);
+        }
+        "PROCESS-NAME" => items.process_name = one(),
+        "PROCESS-PATH" => items.process_path = one(),
+        "PROCESS-PATH-REGEX" => items.process_path_regex = one(),
+        "UID" => {
+            let uid = payload
+                .parse()
+                .map_err(|_| format!("invalid UID: {payload}"))?;
+            items.user_id = Some(Listable::One(uid));
+        }
+        "NETWORK" => items.network = Some(Listable::One(payload.to_ascii_lowercase())),
+        "IN-USER" => items.auth_user = one(),
+        "IN-NAME" => items.inbound = one(),
+        "RULE-SET" => {
+            let ip = *ctx
+                .providers
+                .get(payload)
+                .ok_or_else(|| format!("rule-set {payload} was not converted"))?;
+            items.rule_set = one();
+            return Ok(Matcher {
+                ip,
+                ..Matcher::items(items)
+            });
+        }
+        other => return Err(format!("rule type {other} is not supported")),
+    }
+    Ok(Matcher::items(items))
+}
+
+/// 逻辑规则中的子规则，如 "DOMAIN,a.com" 或嵌套的 "OR,((...),(...))"
+fn sub_matcher(rule: &str, ctx: &mut Context) -> Result<Matcher, String> {
+    let line = split_rule(rule)?;
+    matcher(&line.kind, line.payload, ctx)
+}
+
+/// 规则目标：出站或拒绝
+enum Target {
+    Outbound(String),
+    Reject(Option<&'static str>),
+}
+
+fn target(name: &str, ctx: &mut Context) -> Result<Target, String> {
+    match name {
+        "REJECT" | "REJECT-TINYGIF" => Ok(Target::Reject(None)),
+        "REJECT-DROP" => Ok(Target::Reject(Some("drop"))),
+        "PASS" => Err("PASS is not supported".to_string()),
+        name => ctx
+            .outbound(name)
+            .map(Target::Outbound)
+            .ok_or_else(|| format!("target {name} was not converted")),
+    }
+}
+
+fn set_target(rule: &mut RouteRule, target: Target) {
+    match target {
+        Target::Outbound(tag) => rule.outbound = Some(tag),
+        Target::Reject(method) => {
+            rule.action = Some(RouteAction::Reject);
+            rule.method = method.map(str::to_string);
+        }
+    }
+}
+
+/// 转换后的路由规则
+#[derive(Default)]
+pub(super) struct Rules {
+    pub rules: Vec<RouteRule>,
+    pub final_outbound: Option<String>,
+}
+
+/// 转换 rules 列表，MATCH 成为 route.final
+///
+/// Clash 遇到 IP 规则会先解析域名（除非带 no-resolve），
+/// 因此在第一条这样的规则前插入 resolve 动作。
+pub(super) fn rules(fields: &Fields, ctx: &mut Context) -> Rules {
+    let mut result = Rules::default();
+    let mut resolved = false;
+    let list = fields
+        .get("rules")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for (index, line) in list.iter().enumerate() {
+        let path = format!("rules[{index}]");
+        let Some(line) = line.as_str() else {
+            ctx.report(path, "rule must be a string");
+            continue;
+        };
+        let converted = split_rule(line).and_then(|line| {
+            let target_name = line
+                .rest
+                .first()
+                .ok_or_else(|| "rule has no target".to_string())?;
+            let target = target(target_name, ctx)?;
+            if matches!(line.kind.as_str(), "MATCH" | "FINAL") {
+                return Ok((None, target));
+            }
+            let matcher = matcher(&line.kind, line.payload, ctx)?;
+            let no_resolve = line.rest[1..].contains(&"no-resolve");
+            Ok((Some((matcher, no_resolve)), target))
+        });
+        match converted {
+            Ok((Some((matcher, no_resolve)), target)) => {
+                if matcher.ip && !no_resolve && !resolved {
+                    result.rules.push(RouteRule {
+                        action: Some(RouteAction::Resolve),
+                        ..Default::default()
+                    });
+                    resolved = true;
+                }
+                let mut rule = matcher.into_route_rule();
+                set_target(&mut rule, target);
+                result.rules.push(rule);
+            }
+            Ok((None, Target::Outbound(tag))) => {
+                result.final_outbound = Some(tag);
+                break;
+            }
+            Ok((None, target)) => {
+                let mut rule = RouteRule::default();
+                set_target(&mut rule, target);
+                result.rules.push(rule);
+                break;
+            }
+            Err(message) => ctx.report(path, message),
+        }
+    }
+    result
+}
+
+/// 规则集地址对应的格式；MetaCubeX meta-rules-dat 的 mihomo 格式换成同仓库的 sing-box 格式
+fn rule_set_source(location: &str) -> Option<(String, &'static str)> {
+    for (meta, sing) in [("/meta/geo/", "/sing/geo/"), ("@meta/geo/", "@sing/geo/")] {
+        if location.contains("meta-rules-dat") && location.contains(meta) {
+            let location = location.replacen(meta, sing, 1);
+            let stem = match location.rsplit_once('.') {
+                Some((stem, extension)) if !extension.contains('/') => stem.to_string(),
+                _ => location,
+            };
+            return Some((format!("{stem}.srs"), "binary"));
+        }
+    }
+    if location.ends_with(".srs") {
+        Some((location.to_string(), "binary"))
+    } else if location.ends_with(".json") {
+        Some((location.to_string(), "source"))
+    } else {
+        None
+    }
+}
+
+/// 内联 rule-provider 的 payload → 无头规则，返回规则和是否含 IP 规则
+fn inline_rules(
+    behavior: &str,
+    payload: &[String],
+    path: &str,
+    ctx: &mut Context,
+) -> Result<(Vec<HeadlessRule>, bool), String> {
+    let mut items = RuleItems::default();
+    match behavior {
+        "domain" => {
+            let mut domains = Domains::default();
+            payload.iter().for_each(|entry| domains.push(entry));
+            domains.apply(&mut items);
+            Ok((
+                vec![HeadlessRule {
+                    items,
+                    ..Default::default()
+                }],
+                false,
+            ))
+        }
+        "ipcidr" => {
+            items.ip_cidr = listable(payload.to_vec());
+            Ok((
+                vec![HeadlessRule {
+                    items,
+                    ..Default::default()
+                }],
+                true,
+            ))
+        }
+        "classical" => {
+            let mut rules = Vec::new();
+            let mut ip = false;
+            for (index, line) in payload.iter().enumerate() {
+                match split_rule(line).and_then(|line| matcher(&line.kind, line.payload, ctx)) {
+                    Ok(matcher) => {
+                        ip |= matcher.ip;
+                        rules.push(matcher.into_headless_rule());
+                    }
+                    Err(message) => ctx.report(format!("{path}.payload[{index}]"), message),
+                }
+            }
+            Ok((rules, ip))
+        }
+        other => Err(format!("rule provider behavior {other} is not supported")),
+    }
+}
+
+/// 转换 rule-providers，记录到 Context 供 RULE-SET 规则引用
+pub(super) fn providers(fields: &Fields, ctx: &mut Context) {
+    let Some(providers) = fields.object("rule-providers") else {
+        return;
+    };
+    for (name, provider) in providers.0 {
+        let path = format!("rule-providers.{name}");
+        let Some(provider) = provider.as_object().map(Fields) else {
+            ctx.report(path, "rule provider must be a mapping");
+            continue;
+        };
+        let behavior = provider
+            .str("behavior")
+            .unwrap_or_else(|| "classical".to_string());
+        let format = provider.str("format").unwrap_or_else(|| "yaml".to_string());
+        let mut rule_set = RuleSet {
+            tag: Some(name.clone()),
+            ..Default::default()
+        };
+        let ip = match provider.str("type").as_deref() {
+            Some("inline") => {
+                let payload = provider.strings("payload");
+                match inline_rules(&behavior, &payload, &path, ctx) {
+                    Ok((rules, ip)) => {
+                        rule_set.kind = Some(RuleSetType::Inline);
+                        rule_set.rules = Some(rules);
+                        ip
+                    }
+                    Err(message) => {
+                        ctx.report(path, message);
+                        continue;
+                    }
+                }
+            }
+            Some(kind @ ("http" | "file")) => {
+                let key = if kind == "http" { "url" } else { "path" };
+                let location = provider.str(key).unwrap_or_default();
+                let Some((location, source_format)) = rule_set_source(&location) else {
+                    ctx.report(
+                        path,
+                        format!("{format} rule provider cannot be read by sing-box; convert it to a .srs or .json rule-set"),
+                    );
+                    continue;
+                };
+                rule_set.format = Some(source_format.to_string());
+                if kind == "http" {
+                    rule_set.kind = Some(RuleSetType::Remote);
+                    rule_set.url = Some(location);
+                    rule_set.update_interval = provider
+                        .u64("interval")
+                        .map(|seconds| format!("{seconds}s"));
+                    rule_set.download_detour =
+                        provider.str("proxy").and_then(|proxy| ctx.outbound(&proxy));
+                } else {
+                    rule_set.kind = Some(RuleSetType::Local);
+                    rule_set.path = Some(location);
+                }
+                behavior == "ipcidr"
+            }
+            other => {
+                let other = other.unwrap_or_default();
+                ctx.report(path, format!("rule provider type {other} is not supported"));
+                continue;
+            }
+        };
+        ctx.providers.insert(name.clone(), ip);
+        ctx.rule_sets.push(rule_set);
+    }
+}
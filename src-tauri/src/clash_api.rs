@@ -0,0 +1,408 @@
+//! sing-box Clash API 客户端，参考 docs/configuration/experimental/clash-api.md
+//!
+//! 覆盖出站列表、selector 切换、延迟测试、连接管理，以及 traffic/memory 的
+//! WebSocket 推送。
+
+use std::collections::BTreeMap;
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tungstenite::client::IntoClientRequest;
+use tungstenite::http::HeaderValue;
+
+use crate::config::Config;
+
+/// 延迟测试默认使用的地址
+pub const DEFAULT_TEST_URL: &str = "https://www.gstatic.com/generate_204";
+
+/// 延迟测试默认超时（毫秒）
+pub const DEFAULT_TEST_TIMEOUT: u32 = 5000;
+
+/// 普通请求的超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// WebSocket 读超时，用于及时响应停止请求
+const STREAM_READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 推送断开后重连前的等待时间
+const STREAM_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// 出站或出站组
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Proxy {
+    pub name: String,
+    /// Clash 风格的类型名，如 Selector、URLTest、Shadowsocks
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// 出站组当前选中的成员
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub now: Option<String>,
+    /// 出站组的全部成员
+    #[serde(default)]
+    pub all: Vec<String>,
+    #[serde(default)]
+    pub history: Vec<DelayHistory>,
+    #[serde(default)]
+    pub udp: bool,
+}
+
+impl Proxy {
+    pub fn is_selector(&self) -> bool {
+        self.kind == "Selector"
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DelayHistory {
+    pub time: String,
+    pub delay: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Connections {
+    pub download_total: u64,
+    pub upload_total: u64,
+    /// 没有连接时 sing-box 返回 null
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub connections: Vec<Connection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Connection {
+    pub id: String,
+    /// network、type、sourceIP、destinationIP、host、processPath 等
+    pub metadata: Map<String, Value>,
+    pub upload: u64,
+    pub download: u64,
+    pub start: String,
+    #[serde(default)]
+    pub chains: Vec<String>,
+    #[serde(default)]
+    pub rule: String,
+    #[serde(default)]
+    pub rule_payload: String,
+}
+
+/// /traffic 推送，单位为字节每秒
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Traffic {
+    pub up: u64,
+    pub down: u64,
+}
+
+/// /memory 推送，单位为字节
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Memory {
+    pub inuse: u64,
+    pub oslimit: u64,
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Clash API 客户端
+#[derive(Debug, Clone)]
+pub struct ClashClient {
+    /// 如 127.0.0.1:9090
+    host: String,
+    secret: Option<String>,
+    agent: ureq::Agent,
+}
+
+impl ClashClient {
+    /// controller 为 `external_controller` 的值，监听所有地址时连接本机
+    pub fn new(controller: &str, secret: Option<&str>) -> Self {
+        let host = match controller.rsplit_once(':') {
+            Some(("" | "0.0.0.0", port)) => format!("127.0.0.1:{}", port),
+            Some(("[::]", port)) => format!("[::1]:{}", port),
+            _ => controller.to_string(),
+        };
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            .http_status_as_error(false)
+            .build()
+            .into();
+        Self {
+            host,
+            secret: secret.filter(|s| !s.is_empty()).map(str::to_string),
+            agent,
+        }
+    }
+
+    /// 从配置的 `experimental.clash_api` 创建，未启用时返回 None
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let api = config.experimental.as_ref()?.clash_api.as_ref()?;
+        let controller = api.external_controller.as_deref().filter(|c| !c.is_empty())?;
+        Some(Self::new(controller, api.secret.as_deref()))
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.host, path)
+    }
+
+    fn authorization(&self) -> Option<String> {
+        self.secret.as_ref().map(|secret| format!("Bearer {}", secret))
+    }
+
+    /// 检查响应状态，出错时读取 `{"message": ...}`
+    fn check(response: ureq::http::Response<ureq::Body>) -> Result<ureq::Body, String> {
+        let status = response.status();
+        let mut body = response.into_body();
+        if status.is_success() {
+            return Ok(body);
+        }
+        let text = body.read_to_string().unwrap_or_default();
+        let message = serde_json::from_str::<Value>(&text)
+            .ok()
+            .and_then(|v| v.get("message").and_then(Value::as_str).map(str::to_string))
+            .unwrap_or(text);
+        Err(format!("Clash API returned {}: {}", status, message))
+    }
+
+    fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T, String> {
+        let mut request = self.agent.get(self.url(path));
+        if let Some(auth) = self.authorization() {
+            request = request.header("Authorization", auth);
+        }
+        for (key, value) in query {
+            request = request.query(*key, value);
+        }
+        let response = request.call().map_err(|e| e.to_string())?;
+        Self::check(response)?
+            .read_json()
+            .map_err(|e| e.to_string())
+    }
+
+    /// sing-box 版本信息
+    pub fn version(&self) -> Result<Value, String> {
+        self.get("/version", &[])
+    }
+
+    /// 全部出站和出站组，保持 API 返回的顺序
+    pub fn proxies(&self) -> Result<Vec<Proxy>, String> {
+        #[derive(Deserialize)]
+        struct Response {
+            proxies: Map<String, Value>,
+        }
+        let response: Response = self.get("/proxies", &[])?;
+        response
+            .proxies
+            .into_iter()
+            .map(|(_, proxy)| serde_json::from_value(proxy).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    /// 全部 selector 出站
+    pub fn selectors(&self) -> Result<Vec<Proxy>, String> {
+        Ok(self
+            .proxies()?
+            .into_iter()
+            .filter(Proxy::is_selector)
+            .collect())
+    }
+
+    /// 切换 selector 选中的成员
+    pub fn select(&self, selector: &str, member: &str) -> Result<(), String> {
+        let mut request = self
+            .agent
+            .put(self.url(&format!("/proxies/{}", encode_segment(selector))));
+        if let Some(auth) = self.authorization() {
+            request = request.header("Authorization", auth);
+        }
+        let response = request
+            .send_json(json!({ "name": member }))
+            .map_err(|e| e.to_string())?;
+        Self::check(response).map(|_| ())
+    }
+
+    /// 测试单个出站的延迟（毫秒）
+    pub fn proxy_delay(&self, name: &str, url: &str, timeout: u32) -> Result<u32, String> {
+        #[derive(Deserialize)]
+        struct Response {
+            delay: u32,
+        }
+        let path = format!("/proxies/{}/delay", encode_segment(name));
+        let response: Response = self.get(&path, &delay_query(url, timeout))?;
+        Ok(response.delay)
+    }
+
+    /// 测试出站组（如 urltest）全部成员的延迟，未返回的成员表示超时
+    pub fn group_delay(
+        &self,
+        group: &str,
+        url: &str,
+        timeout: u32,
+    ) -> Result<BTreeMap<String, u32>, String> {
+        let path = format!("/group/{}/delay", encode_segment(group));
+        self.get(&path, &delay_query(url, timeout))
+    }
+
+    pub fn connections(&self) -> Result<Connections, String> {
+        self.get("/connections", &[])
+    }
+
+    /// 关闭指定连接，id 为 None 时关闭全部连接
+    pub fn close_connections(&self, id: Option<&str>) -> Result<(), String> {
+        let path = match id {
+            Some(id) => format!("/connections/{}", encode_segment(id)),
+            None => "/connections".to_string(),
+        };
+        let mut request = self.agent.delete(self.url(&path));
+        if let Some(auth) = self.authorization() {
+            request = request.header("Authorization", auth);
+        }
+        let response = request.call().map_err(|e| e.to_string())?;
+        Self::check(response).map(|_| ())
+    }
+
+    /// 连接 WebSocket 推送（如 /traffic、/memory）
+    fn connect_stream(
+        &self,
+        path: &str,
+    ) -> Result<tungstenite::WebSocket<TcpStream>, String> {
+        let mut request = format!("ws://{}{}", self.host, path)
+            .into_client_request()
+            .map_err(|e| e.to_string())?;
+        if let Some(auth) = self.authorization() {
+            let value = HeaderValue::from_str(&auth).map_err(|e| e.to_string())?;
+            request.headers_mut().insert("Authorization", value);
+        }
+        let stream = self.connect_tcp()?;
+        stream
+            .set_read_timeout(Some(STREAM_READ_TIMEOUT))
+            .map_err(|e| e.to_string())?;
+        let (socket, _) = tungstenite::client(request, stream).map_err(|e| e.to_string())?;
+        Ok(socket)
+    }
+
+    /// 连接控制器，与 REST 请求使用同样的超时，控制器不可达时不会阻塞到系统的连接超时
+    fn connect_tcp(&self) -> Result<TcpStream, String> {
+        let addrs = self
+            .host
+            .to_socket_addrs()
+            .map_err(|e| format!("{}: {e}", self.host))?;
+        let mut last_error = format!("{}: no address", self.host);
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, REQUEST_TIMEOUT) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = format!("{addr}: {e}"),
+            }
+        }
+        Err(last_error)
+    }
+
+    /// 在后台线程中订阅推送，断开后自动重连，直到返回的句柄被停止或释放
+    pub fn subscribe<T>(&self, path: &str, on_message: impl Fn(T) + Send + 'static) -> Subscription
+    where
+        T: DeserializeOwned,
+    {
+        let client = self.clone();
+        let path = path.to_string();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let thread = thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                if let Err(e) = client.read_stream(&path, &stopped, &on_message) {
+                    eprintln!("Clash API stream {} closed: {}", path, e);
+                }
+                // 等待后重连，期间仍响应停止
+                let mut waited = Duration::ZERO;
+                while waited < STREAM_RETRY_DELAY && !stopped.load(Ordering::Relaxed) {
+                    thread::sleep(Duration::from_millis(100));
+                    waited += Duration::from_millis(100);
+                }
+            }
+        });
+        Subscription {
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    fn read_stream<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        stop: &AtomicBool,
+        on_message: &impl Fn(T),
+    ) -> Result<(), String> {
+        let mut socket = self.connect_stream(path)?;
+        while !stop.load(Ordering::Relaxed) {
+            match socket.read() {
+                Ok(tungstenite::Message::Text(text)) => match serde_json::from_str(&text) {
+                    Ok(message) => on_message(message),
+                    Err(e) => eprintln!("Unexpected message from {}: {}", path, e),
+                },
+                Ok(tungstenite::Message::Close(_)) => return Err("closed by server".into()),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e))
+                    if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        let _ = socket.close(None);
+        Ok(())
+    }
+}
+
+/// 后台推送订阅，释放时停止
+pub struct Subscription {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Subscription {
+    /// 停止订阅并等待后台线程结束
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn delay_query(url: &str, timeout: u32) -> [(&'static str, String); 2] {
+    [("url", url.to_string()), ("timeout", timeout.to_string())]
+}
+
+/// 对路径片段做百分号编码，出站名可能包含空格、斜杠或 emoji
+fn encode_segment(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
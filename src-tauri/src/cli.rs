@@ -0,0 +1,350 @@
+//! 命令行工具：不启动 Tauri，直接调用后端的校验、预检、迁移、转换和格式化
+//!
+//! 结果以 JSON 写到标准输出，错误以 `{"error": ...}` 写到标准错误。
+//! 退出码：0 成功，1 检查未通过（校验失败、预检有错误、未格式化、有差异、合并有冲突），2 参数或读写错误。
+
+use std::io::{Read, Write};
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::preflight::{IssueLevel, Preflight};
+use crate::{clash, diff, migration, save, validation};
+
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_FAILED: i32 = 1;
+pub const EXIT_ERROR: i32 = 2;
+
+pub const USAGE: &str = "\
+Usage: sing-config-cli <command> [options]
+
+Commands:
+  validate <file>                      Check the config against the bundled JSON schema
+  lint <file> [--disable <id>]... [--locale <en|zh>] [--strict]
+                                       Run preflight checks; --strict also fails on warnings
+  migrate <file> [--to <version>] [--output <path> | --write]
+                                       Migrate the config to a sing-box version (default: latest)
+  import <clash.yaml> [--output <path>]
+                                       Convert a Clash Meta profile to a sing-box config
+  fmt <file> [--check | --write]       Pretty-print the config with two-space indentation
+  diff <old> <new>                     List changed fields between two configs
+  merge <base> <ours> <theirs> [--output <path> | --write]
+                                       Three-way merge; --write updates <ours> (git merge driver)
+
+Use - as <file> to read from standard input.";
+
+/// 执行一条命令，返回退出码
+pub fn run<I>(args: I, stdout: &mut dyn Write, stderr: &mut dyn Write) -> i32
+where
+    I: IntoIterator<Item = String>,
+{
+    let args: Vec<String> = args.into_iter().collect();
+    match execute(&args) {
+        Ok(Outcome::Json(value, code)) => {
+            let text = serde_json::to_string_pretty(&value).unwrap_or_default();
+            let _ = writeln!(stdout, "{text}");
+            code
+        }
+        Ok(Outcome::Text(text)) => {
+            let _ = writeln!(stdout, "{text}");
+            EXIT_OK
+        }
+        Err(Error::Usage(message)) => {
+            let _ = writeln!(stderr, "{message}\n\n{USAGE}");
+            EXIT_ERROR
+        }
+        Err(Error::Failed(message)) => {
+            let _ = writeln!(stderr, "{}", json!({ "error": message }));
+            EXIT_ERROR
+        }
+    }
+}
+
+enum Outcome {
+    Json(Value, i32),
+    Text(String),
+}
+
+enum Error {
+    /// 命令行参数有误，同时打印用法
+    Usage(String),
+    Failed(String),
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Failed(message)
+    }
+}
+
+fn execute(args: &[String]) -> Result<Outcome, Error> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(Error::Usage("missing command".to_string()));
+    };
+    match command.as_str() {
+        "validate" => validate(&Args::parse(rest, &[], &[])?),
+        "lint" => lint(&Args::parse(
+            rest,
+            &["--disable", "--locale"],
+            &["--strict"],
+        )?),
+        "migrate" => migrate(&Args::parse(rest, &["--to", "--output"], &["--write"])?),
+        "import" => import(&Args::parse(rest, &["--output"], &[])?),
+        "fmt" => fmt(&Args::parse(rest, &[], &["--check", "--write"])?),
+        "diff" => compare(&Args::parse(rest, &[], &[])?),
+        "merge" => merge(&Args::parse(rest, &["--output"], &["--write"])?),
+        "help" | "--help" | "-h" => Ok(Outcome::Text(USAGE.to_string())),
+        "--version" | "-V" => Ok(Outcome::Text(env!("CARGO_PKG_VERSION").to_string())),
+        _ => Err(Error::Usage(format!("unknown command: {command}"))),
+    }
+}
+
+/// 解析后的参数，选项可写作 `--to 1.12` 或 `--to=1.12`
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+    flags: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: &[String], options: &[&str], flags: &[&str]) -> Result<Self, Error> {
+        let mut parsed = Args {
+            positional: Vec::new(),
+            options: Vec::new(),
+            flags: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "-" || !arg.starts_with('-') {
+                parsed.positional.push(arg.clone());
+                continue;
+            }
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            if options.contains(&name) {
+                let value = inline
+                    .or_else(|| args.next().cloned())
+                    .ok_or_else(|| Error::Usage(format!("{name} requires a value")))?;
+                parsed.options.push((name.to_string(), value));
+            } else if flags.contains(&name) && inline.is_none() {
+                parsed.flags.push(name.to_string());
+            } else {
+                return Err(Error::Usage(format!("unknown option: {arg}")));
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// 恰好 N 个位置参数
+    fn files<const N: usize>(&self) -> Result<[&str; N], Error> {
+        let files: Vec<&str> = self.positional.iter().map(String::as_str).collect();
+        files.try_into().map_err(|files: Vec<&str>| {
+            Error::Usage(format!(
+                "expected {N} file argument(s), got {}",
+                files.len()
+            ))
+        })
+    }
+
+    fn file(&self) -> Result<&str, Error> {
+        self.files::<1>().map(|[path]| path)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.options
+            .iter()
+            .filter(move |(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    /// `--output <path>` 或 `--write`（写回 path）指定的输出文件，都没有时为空
+    fn output<'a>(&'a self, path: &'a str) -> Result<Option<&'a str>, Error> {
+        match (self.option("--output"), self.flag("--write")) {
+            (Some(_), true) => Err(Error::Usage(
+                "--output and --write cannot be used together".to_string(),
+            )),
+            (Some(output), false) => Ok(Some(output)),
+            (None, true) if path == "-" => {
+                Err(Error::Usage("--write needs a file, not stdin".to_string()))
+            }
+            (None, true) => Ok(Some(path)),
+            (None, false) => Ok(None),
+        }
+    }
+}
+
+/// 读取文件，"-" 为标准输入
+fn read(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("stdin: {e}"))?;
+        return Ok(text);
+    }
+    std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))
+}
+
+fn read_json(path: &str) -> Result<Value, String> {
+    serde_json::from_str(&read(path)?).map_err(|e| format!("{path}: invalid JSON: {e}"))
+}
+
+/// 与编辑器“格式化保存”一致的两空格缩进，末尾加换行
+fn pretty(value: &impl Serialize) -> Result<String, String> {
+    serde_json::to_string_pretty(value)
+        .map(|text| text + "\n")
+        .map_err(|e| e.to_string())
+}
+
+/// 原子写入，不保留备份（流水线中的文件由版本库管理）
+fn write(path: &str, text: &str) -> Result<(), String> {
+    save::write_atomic(Path::new(path), text.as_bytes(), 0)
+        .map(|_| ())
+        .map_err(|e| format!("{path}: {e}"))
+}
+
+fn to_json(value: &impl Serialize) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+fn validate(args: &Args) -> Result<Outcome, Error> {
+    let path = args.file()?;
+    let result = validation::validate_text(&read(path)?);
+    let code = if result.valid { EXIT_OK } else { EXIT_FAILED };
+    Ok(Outcome::Json(to_json(&result)?, code))
+}
+
+fn lint(args: &Args) -> Result<Outcome, Error> {
+    let path = args.file()?;
+    let config = read_json(path)?;
+    let mut engine = Preflight::new();
+    for id in args.all("--disable") {
+        if !engine.rule_ids().contains(&id) {
+            return Err(Error::Usage(format!("unknown preflight rule: {id}")));
+        }
+        engine.disable(id);
+    }
+    let issues = engine
+        .run_value(config, args.option("--locale").unwrap_or("en"))
+        .map_err(|e| format!("{path}: {e}"))?;
+    let failed = issues.iter().any(|issue| {
+        issue.level == IssueLevel::Error
+            || (args.flag("--strict") && issue.level == IssueLevel::Warning)
+    });
+    let code = if failed { EXIT_FAILED } else { EXIT_OK };
+    Ok(Outcome::Json(to_json(&issues)?, code))
+}
+
+fn migrate(args: &Args) -> Result<Outcome, Error> {
+    let path = args.file()?;
+    let output = args.output(path)?;
+    let target = args
+        .option("--to")
+        .map(str::to_string)
+        .unwrap_or_else(|| migration::LATEST.to_string());
+    let result = migration::migrate_value(read_json(path)?, &target)?;
+    let mut report = json!({ "changed": result.changed(), "steps": result.steps });
+    match output {
+        Some(output) => write(output, &pretty(&result.config)?)?,
+        None => report["config"] = result.config,
+    }
+    Ok(Outcome::Json(report, EXIT_OK))
+}
+
+fn import(args: &Args) -> Result<Outcome, Error> {
+    let path = args.file()?;
+    let result = clash::convert_yaml(&read(path)?)?;
+    let mut report = json!({ "unsupported": result.unsupported });
+    match args.option("--output") {
+        Some(output) => write(output, &pretty(&result.config)?)?,
+        None => report["config"] = to_json(&result.config)?,
+    }
+    Ok(Outcome::Json(report, EXIT_OK))
+}
+
+fn fmt(args: &Args) -> Result<Outcome, Error> {
+    let path = args.file()?;
+    let text = read(path)?;
+    let value: Value =
+        serde_json::from_str(&text).map_err(|e| format!("{path}: invalid JSON: {e}"))?;
+    let formatted = pretty(&value)?;
+    // 编辑器保存的文件末尾没有换行，同样视为已格式化
+    let unchanged = text == formatted || text == formatted.trim_end_matches('\n');
+    match (args.flag("--check"), args.flag("--write")) {
+        (true, true) => Err(Error::Usage(
+            "--check and --write cannot be used together".to_string(),
+        )),
+        (true, false) => {
+            let code = if unchanged { EXIT_OK } else { EXIT_FAILED };
+            Ok(Outcome::Json(json!({ "formatted": unchanged }), code))
+        }
+        (false, true) if path == "-" => {
+            Err(Error::Usage("--write needs a file, not stdin".to_string()))
+        }
+        (false, true) => {
+            if !unchanged {
+                write(path, &formatted)?;
+            }
+            Ok(Outcome::Json(json!({ "changed": !unchanged }), EXIT_OK))
+        }
+        (false, false) => Ok(Outcome::Json(value, EXIT_OK)),
+    }
+}
+
+fn compare(args: &Args) -> Result<Outcome, Error> {
+    let [old, new] = args.files()?;
+    if old == "-" && new == "-" {
+        return Err(Error::Usage(
+            "only one file can be read from stdin".to_string(),
+        ));
+    }
+    let changes = diff::diff(&read_json(old)?, &read_json(new)?);
+    let code = if changes.is_empty() {
+        EXIT_OK
+    } else {
+        EXIT_FAILED
+    };
+    Ok(Outcome::Json(to_json(&changes)?, code))
+}
+
+fn merge(args: &Args) -> Result<Outcome, Error> {
+    let [base, ours, theirs] = args.files()?;
+    if [base, ours, theirs]
+        .iter()
+        .filter(|path| **path == "-")
+        .count()
+        > 1
+    {
+        return Err(Error::Usage(
+            "only one file can be read from stdin".to_string(),
+        ));
+    }
+    let output = args.output(ours)?;
+    let result = diff::merge(&read_json(base)?, &read_json(ours)?, &read_json(theirs)?);
+    let code = if result.conflicts.is_empty() {
+        EXIT_OK
+    } else {
+        EXIT_FAILED
+    };
+    let mut report = json!({ "conflicts": result.conflicts });
+    match output {
+        Some(output) => write(output, &pretty(&result.merged)?)?,
+        None => report["config"] = result.merged,
+    }
+    Ok(Outcome::Json(report, code))
+}
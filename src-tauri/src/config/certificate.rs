@@ -0,0 +1,21 @@
+//! 证书配置，参考 docs/configuration/certificate/
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::shared::Listable;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Certificate {
+    /// system / mozilla / none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate_path: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate_directory_path: Option<Listable<String>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
@@ -0,0 +1,191 @@
+//! DNS 配置，参考 docs/configuration/dns/
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::rule::{LogicalMode, RuleItems, RuleType};
+use super::shared::{DialOptions, Listable, Tls};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Dns {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servers: Option<Vec<DnsServer>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<Vec<DnsRule>>,
+    #[serde(rename = "final", skip_serializing_if = "Option::is_none")]
+    pub final_server: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_cache: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_expire: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub independent_cache: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_capacity: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reverse_mapping: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_subnet: Option<String>,
+    /// 旧版 FakeIP 设置，1.12 起改为 fakeip 类型的服务器
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fakeip: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Dns {
+    pub fn servers(&self) -> &[DnsServer] {
+        self.servers.as_deref().unwrap_or_default()
+    }
+
+    pub fn rules(&self) -> &[DnsRule] {
+        self.rules.as_deref().unwrap_or_default()
+    }
+
+    /// 按标签查找 DNS 服务器
+    pub fn server(&self, tag: &str) -> Option<&DnsServer> {
+        self.servers().iter().find(|server| server.tag.as_deref() == Some(tag))
+    }
+}
+
+string_enum! {
+    /// DNS 服务器类型（1.12 新格式）
+    pub enum DnsServerType {
+        Local => "local",
+        Hosts => "hosts",
+        Tcp => "tcp",
+        Udp => "udp",
+        Tls => "tls",
+        Quic => "quic",
+        Https => "https",
+        H3 => "h3",
+        Dhcp => "dhcp",
+        FakeIp => "fakeip",
+        Tailscale => "tailscale",
+        Resolved => "resolved",
+    }
+}
+
+/// DNS 服务器
+///
+/// 没有 `type` 字段时为旧格式，使用 `address` 描述服务器。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DnsServer {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<DnsServerType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_port: Option<u16>,
+    /// https/h3 为请求路径，hosts 为文件路径列表
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<Map<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<Tls>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predefined: Option<Map<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inet4_range: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inet6_range: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// 旧格式：服务器地址（如 tls://1.1.1.1）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// 旧格式：解析服务器域名使用的 DNS 服务器
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_resolver: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_strategy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    #[serde(flatten)]
+    pub dial: DialOptions,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl DnsServer {
+    /// 是否为旧格式服务器
+    pub fn is_legacy(&self) -> bool {
+        self.kind.is_none() && self.address.is_some()
+    }
+}
+
+string_enum! {
+    /// DNS 规则动作，参考 docs/configuration/dns/rule_action.md
+    pub enum DnsAction {
+        Route => "route",
+        RouteOptions => "route-options",
+        Reject => "reject",
+        Predefined => "predefined",
+    }
+}
+
+/// DNS 规则（默认规则或逻辑规则）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DnsRule {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<RuleType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<LogicalMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<Vec<DnsRule>>,
+    #[serde(flatten)]
+    pub items: RuleItems,
+    /// 匹配发起查询的出站
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outbound: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_accept_any: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_set_ip_cidr_accept_empty: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invert: Option<bool>,
+    /// 缺省为 route
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<DnsAction>,
+    /// 旧版配置可能写成数组，原样保留；实际路由到第一个，见 [`DnsRule::server_tag`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_cache: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewrite_ttl: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_subnet: Option<String>,
+    /// reject 动作的拒绝方式
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rcode: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl DnsRule {
+    pub fn is_logical(&self) -> bool {
+        self.kind == Some(RuleType::Logical)
+    }
+
+    /// 实际动作（未填写时为 route）
+    pub fn action(&self) -> DnsAction {
+        self.action.clone().unwrap_or(DnsAction::Route)
+    }
+
+    /// 路由到的服务器标签，写成数组时取第一个
+    pub fn server_tag(&self) -> Option<&str> {
+        self.server.as_ref().and_then(Listable::first).map(String::as_str)
+    }
+}
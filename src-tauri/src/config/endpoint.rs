@@ -0,0 +1,73 @@
+//! 端点配置，参考 docs/configuration/endpoint/
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::shared::{DialOptions, Listable};
+
+string_enum! {
+    /// 端点类型
+    #[derive(Default)]
+    pub enum EndpointType {
+        #[default]
+        WireGuard => "wireguard",
+        Tailscale => "tailscale",
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Endpoint {
+    #[serde(rename = "type")]
+    pub kind: EndpointType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mtu: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen_port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peers: Option<Vec<WireGuardPeer>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub udp_timeout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workers: Option<u32>,
+    #[serde(flatten)]
+    pub dial: DialOptions,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Endpoint {
+    pub fn peers(&self) -> &[WireGuardPeer] {
+        self.peers.as_deref().unwrap_or_default()
+    }
+}
+
+/// WireGuard 对端
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WireGuardPeer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_shared_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_ips: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persistent_keepalive_interval: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reserved: Option<Vec<u8>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
@@ -0,0 +1,59 @@
+//! 实验性配置，参考 docs/configuration/experimental/
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::shared::Listable;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Experimental {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_file: Option<CacheFile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clash_api: Option<ClashApi>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub v2ray_api: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store_fakeip: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store_rdrc: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rdrc_timeout: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Clash API，参考 docs/configuration/experimental/clash-api.md
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClashApi {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_controller: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_ui: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_ui_download_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_ui_download_detour: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_control_allow_origin: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_control_allow_private_network: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
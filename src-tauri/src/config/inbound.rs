@@ -0,0 +1,91 @@
+//! 入站配置，参考 docs/configuration/inbound/
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::shared::{Listable, Multiplex, Tls, Transport};
+
+string_enum! {
+    /// 入站类型
+    #[derive(Default)]
+    pub enum InboundType {
+        Direct => "direct",
+        #[default]
+        Mixed => "mixed",
+        Socks => "socks",
+        Http => "http",
+        Shadowsocks => "shadowsocks",
+        Vmess => "vmess",
+        Trojan => "trojan",
+        Naive => "naive",
+        Hysteria => "hysteria",
+        ShadowTls => "shadowtls",
+        Tuic => "tuic",
+        Hysteria2 => "hysteria2",
+        Vless => "vless",
+        AnyTls => "anytls",
+        Tun => "tun",
+        Redirect => "redirect",
+        TProxy => "tproxy",
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Inbound {
+    #[serde(rename = "type")]
+    pub kind: InboundType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen_port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub users: Option<Vec<InboundUser>>,
+    /// shadowsocks 加密方法
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<Tls>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiplex: Option<Multiplex>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transport: Option<Transport>,
+    /// tun 接口地址
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detour: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Inbound {
+    pub fn users(&self) -> &[InboundUser] {
+        self.users.as_deref().unwrap_or_default()
+    }
+}
+
+/// 入站用户，各协议使用的字段不同
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InboundUser {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// socks/http/mixed/naive 使用
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flow: Option<String>,
+    #[serde(rename = "alterId", skip_serializing_if = "Option::is_none")]
+    pub alter_id: Option<u32>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
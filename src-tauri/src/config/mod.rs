@@ -0,0 +1,381 @@
+//! sing-box 配置的类型化模型
+//!
+//! 每个结构体只对常用字段做强类型定义，其余字段通过 `extra` 原样保留，
+//! 保证未知字段在反序列化/序列化往返中不丢失。类型化字段按结构体顺序输出，
+//! 需要保持用户文件原样时使用 [`Config::to_value_with_layout`]。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// 定义以字符串序列化的枚举，未知取值保存在 `Other` 中以便无损往返
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$vmeta:meta])* $variant:ident => $value:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$vmeta])* $variant,)*
+            /// 未识别的取值
+            Other(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)*
+                    Self::Other(value) => value,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($value => Self::$variant,)*
+                    other => Self::Other(other.to_string()),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                Ok(Self::from(value.as_str()))
+            }
+        }
+    };
+}
+
+pub mod certificate;
+pub mod dns;
+pub mod endpoint;
+pub mod experimental;
+pub mod inbound;
+pub mod log;
+pub mod ntp;
+pub mod outbound;
+pub mod route;
+pub mod rule;
+pub mod service;
+pub mod shared;
+
+pub use certificate::Certificate;
+pub use dns::{Dns, DnsAction, DnsRule, DnsServer, DnsServerType};
+pub use endpoint::{Endpoint, EndpointType, WireGuardPeer};
+pub use experimental::{CacheFile, ClashApi, Experimental};
+pub use inbound::{Inbound, InboundType, InboundUser};
+pub use log::Log;
+pub use ntp::Ntp;
+pub use outbound::{Outbound, OutboundType};
+pub use route::{Route, RouteAction, RouteRule, RuleSet, RuleSetType};
+pub use rule::{HeadlessRule, LogicalMode, QueryType, RuleItems, RuleType};
+pub use service::{Service, ServiceType};
+pub use shared::{DialOptions, DomainResolver, Listable, Multiplex, Tls, Transport};
+
+/// sing-box 配置根对象
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log: Option<Log>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns: Option<Dns>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ntp: Option<Ntp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<Certificate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoints: Option<Vec<Endpoint>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inbounds: Option<Vec<Inbound>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outbounds: Option<Vec<Outbound>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<Route>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<Service>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub experimental: Option<Experimental>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 宽松解析时跳过的字段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedField {
+    /// JSON Pointer，如 "/dns/rules/0/server"
+    pub path: String,
+    pub message: String,
+}
+
+impl Config {
+    /// 从 JSON 文本解析配置
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(text)
+    }
+
+    /// 从 JSON 值解析配置
+    pub fn from_value(value: Value) -> serde_json::Result<Self> {
+        serde_json::from_value(value)
+    }
+
+    /// 宽松解析：移除无法解析的字段并记录位置，其余部分照常解析
+    ///
+    /// 只用于预检等只读场景；保存和迁移使用 [`Config::from_value`]，不丢弃内容。
+    /// 字段位于 flatten 的规则项中时无法定位到字段，跳过整个元素。
+    pub fn from_value_lenient(mut value: Value) -> serde_json::Result<(Self, Vec<SkippedField>)> {
+        let mut skipped = Vec::new();
+        loop {
+            let error = match serde_path_to_error::deserialize(&value) {
+                Ok(config) => return Ok((config, skipped)),
+                Err(error) => error,
+            };
+            let path = known_segments(error.path());
+            let error = error.into_inner();
+            if !skip_at(&mut value, &path) {
+                return Err(error);
+            }
+            let path: String = path
+                .iter()
+                .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+                .collect();
+            // 数组元素替换为空对象后仍无法解析时会再次报同一位置
+            if skipped.last().map(|field: &SkippedField| &field.path) != Some(&path) {
+                skipped.push(SkippedField {
+                    path,
+                    message: error.to_string(),
+                });
+            }
+        }
+    }
+
+    /// 转换为 JSON 值
+    pub fn to_value(&self) -> serde_json::Result<Value> {
+        serde_json::to_value(self)
+    }
+
+    /// 转换为 JSON 值，键顺序和显式的 null 按原文档恢复
+    ///
+    /// 数组元素有 tag 时按 tag 对应到原文档，没有 tag 时按内容对应；新增的键排在后面。
+    /// 只补回类型化模型仍在使用的字段的 null，已被移除的字段和已废弃的字段不补回。
+    pub fn to_value_with_layout(&self, original: &Value) -> serde_json::Result<Value> {
+        // 原文档直接往返的结果，用来区分被模型省略的 null 和被修改移除的字段
+        let baseline = Self::from_value(original.clone())
+            .and_then(|config| config.to_value())
+            .ok();
+        Ok(restore_layout(original, baseline.as_ref(), self.to_value()?))
+    }
+
+    /// 序列化为带缩进的 JSON 文本
+    pub fn to_json_pretty(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn endpoints(&self) -> &[Endpoint] {
+        self.endpoints.as_deref().unwrap_or_default()
+    }
+
+    pub fn inbounds(&self) -> &[Inbound] {
+        self.inbounds.as_deref().unwrap_or_default()
+    }
+
+    pub fn outbounds(&self) -> &[Outbound] {
+        self.outbounds.as_deref().unwrap_or_default()
+    }
+
+    pub fn services(&self) -> &[Service] {
+        self.services.as_deref().unwrap_or_default()
+    }
+
+    /// 按标签查找出站
+    pub fn outbound(&self, tag: &str) -> Option<&Outbound> {
+        self.outbounds().iter().find(|outbound| outbound.tag.as_deref() == Some(tag))
+    }
+
+    /// 所有出站与端点的标签（路由规则可以引用二者）
+    pub fn outbound_tags(&self) -> Vec<&str> {
+        self.outbounds()
+            .iter()
+            .filter_map(|outbound| outbound.tag.as_deref())
+            .chain(self.endpoints().iter().filter_map(|endpoint| endpoint.tag.as_deref()))
+            .collect()
+    }
+}
+
+/// 错误位置中可以定位的部分，遇到无法定位的段（如 flatten 字段）时截断
+fn known_segments(path: &serde_path_to_error::Path) -> Vec<String> {
+    use serde_path_to_error::Segment;
+
+    path.iter()
+        .map_while(|segment| match segment {
+            Segment::Seq { index } => Some(index.to_string()),
+            Segment::Map { key } => Some(key.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 移除 path 指向的字段；数组元素先替换为空对象，保持后续元素的下标不变，
+/// 空对象仍无法解析时再移除
+fn skip_at(value: &mut Value, path: &[String]) -> bool {
+    let Some((last, parents)) = path.split_last() else {
+        return false;
+    };
+    let mut current = value;
+    for segment in parents {
+        let next = match current {
+            Value::Object(map) => map.get_mut(segment),
+            Value::Array(items) => segment
+                .parse()
+                .ok()
+                .and_then(|index: usize| items.get_mut(index)),
+            _ => None,
+        };
+        let Some(next) = next else {
+            return false;
+        };
+        current = next;
+    }
+    match current {
+        Value::Object(map) => map.shift_remove(last).is_some(),
+        Value::Array(items) => {
+            let Some(index) = last
+                .parse::<usize>()
+                .ok()
+                .filter(|index| *index < items.len())
+            else {
+                return false;
+            };
+            if items[index] == Value::Object(Map::new()) {
+                items.remove(index);
+            } else {
+                items[index] = Value::Object(Map::new());
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// 已废弃的类型化字段，迁移会移除它们，按原文档恢复时不补回 null
+///
+/// 只按键名判断，同名的现行字段也不补回 null；null 与缺省等价，少补回不改变配置含义。
+const DEPRECATED_FIELDS: &[&str] = &[
+    "geoip",
+    "geosite",
+    "source_geoip",
+    "rule_set_ipcidr_match_source",
+    "domain_strategy",
+    "fakeip",
+    "address",
+    "address_resolver",
+    "address_strategy",
+    "strategy",
+];
+
+/// 按 original 的键顺序重排 output，并补回 original 中值为 null 的键
+///
+/// baseline 为 original 直接往返的结果：某个 null 键在 baseline 中也不存在，
+/// 说明是类型化字段的 null 被省略，而不是被修改移除，只有这种情况才补回。
+fn restore_layout(original: &Value, baseline: Option<&Value>, output: Value) -> Value {
+    match (original, output) {
+        (Value::Object(original), Value::Object(mut output)) => {
+            let mut restored = Map::new();
+            for (key, value) in original {
+                let base = baseline.and_then(|baseline| baseline.get(key));
+                match output.shift_remove(key) {
+                    Some(output) => {
+                        restored.insert(key.clone(), restore_layout(value, base, output));
+                    }
+                    None if value.is_null()
+                        && baseline.is_some()
+                        && base.is_none()
+                        && !DEPRECATED_FIELDS.contains(&key.as_str()) =>
+                    {
+                        restored.insert(key.clone(), Value::Null);
+                    }
+                    None => {}
+                }
+            }
+            restored.extend(output);
+            Value::Object(restored)
+        }
+        (Value::Array(original), Value::Array(output)) => {
+            let mut used = vec![false; original.len()];
+            let items = output
+                .into_iter()
+                .map(|output| {
+                    let index = match_element(original, baseline, &used, &output);
+                    match index {
+                        Some(index) => {
+                            used[index] = true;
+                            let base = baseline.and_then(|baseline| baseline.get(index));
+                            restore_layout(&original[index], base, output)
+                        }
+                        None => output,
+                    }
+                })
+                .collect();
+            Value::Array(items)
+        }
+        (_, output) => output,
+    }
+}
+
+/// 找到 output 在原数组中对应的元素
+///
+/// 有 tag 时按 tag 查找；否则先找往返结果完全相同的元素，再找相同键值最多的元素，
+/// 插入或删除元素后其余元素仍能对应到原来的位置。
+fn match_element(
+    original: &[Value],
+    baseline: Option<&Value>,
+    used: &[bool],
+    output: &Value,
+) -> Option<usize> {
+    let unused = || (0..original.len()).filter(|index| !used[*index]);
+    if let Some(tag) = output.get("tag").and_then(Value::as_str) {
+        return unused()
+            .find(|index| original[*index].get("tag").and_then(Value::as_str) == Some(tag));
+    }
+    let base = |index: usize| {
+        baseline
+            .and_then(|baseline| baseline.get(index))
+            .unwrap_or(&original[index])
+    };
+    if let Some(index) = unused().find(|index| base(*index) == output) {
+        return Some(index);
+    }
+    let Value::Object(output) = output else {
+        return None;
+    };
+    let shared = |index: usize| match base(index) {
+        Value::Object(base) => output
+            .iter()
+            .filter(|(key, value)| base.get(*key) == Some(*value))
+            .count(),
+        _ => 0,
+    };
+    unused()
+        .filter(|index| original[*index].get("tag").is_none())
+        .map(|index| (shared(index), index))
+        .filter(|(shared, _)| *shared > 0)
+        .max_by_key(|(shared, index)| (*shared, std::cmp::Reverse(*index)))
+        .map(|(_, index)| index)
+}
@@ -0,0 +1,125 @@
+//! 出站配置，参考 docs/configuration/outbound/
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::shared::{DialOptions, Listable, Multiplex, Tls, Transport};
+
+string_enum! {
+    /// 出站类型
+    #[derive(Default)]
+    pub enum OutboundType {
+        #[default]
+        Direct => "direct",
+        /// 已在 1.13 移除，由 reject 规则动作取代
+        Block => "block",
+        Socks => "socks",
+        Http => "http",
+        Shadowsocks => "shadowsocks",
+        Vmess => "vmess",
+        Trojan => "trojan",
+        /// 已迁移为 WireGuard 端点
+        WireGuard => "wireguard",
+        Hysteria => "hysteria",
+        Vless => "vless",
+        ShadowTls => "shadowtls",
+        Tuic => "tuic",
+        Hysteria2 => "hysteria2",
+        AnyTls => "anytls",
+        Tor => "tor",
+        Ssh => "ssh",
+        /// 已在 1.13 移除，由 hijack-dns 规则动作取代
+        Dns => "dns",
+        Selector => "selector",
+        UrlTest => "urltest",
+    }
+}
+
+impl OutboundType {
+    /// 是否为分组出站（selector / urltest）
+    pub fn is_group(&self) -> bool {
+        matches!(self, Self::Selector | Self::UrlTest)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Outbound {
+    #[serde(rename = "type")]
+    pub kind: OutboundType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_port: Option<u16>,
+    /// hysteria/hysteria2 端口跳跃范围
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_ports: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// shadowsocks 加密方法
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugin: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugin_opts: Option<String>,
+    /// vless 流控
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flow: Option<String>,
+    /// vmess 加密方式
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alter_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packet_encoding: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<Tls>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiplex: Option<Multiplex>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transport: Option<Transport>,
+    /// selector/urltest 的成员
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outbounds: Option<Vec<String>>,
+    /// selector 默认成员
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// urltest 测试地址
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tolerance: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_timeout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interrupt_exist_connections: Option<bool>,
+    #[serde(flatten)]
+    pub dial: DialOptions,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Outbound {
+    pub fn new(kind: OutboundType, tag: impl Into<String>) -> Self {
+        Self {
+            kind,
+            tag: Some(tag.into()),
+            ..Default::default()
+        }
+    }
+
+    /// 分组出站的成员标签
+    pub fn members(&self) -> &[String] {
+        self.outbounds.as_deref().unwrap_or_default()
+    }
+}
@@ -0,0 +1,141 @@
+//! 路由配置，参考 docs/configuration/route/
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::rule::{HeadlessRule, LogicalMode, RuleItems, RuleType};
+use super::shared::{DomainResolver, Listable};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Route {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<Vec<RouteRule>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_set: Option<Vec<RuleSet>>,
+    #[serde(rename = "final", skip_serializing_if = "Option::is_none")]
+    pub final_outbound: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub find_process: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_detect_interface: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_android_vpn: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_interface: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_domain_resolver: Option<DomainResolver>,
+    /// 已在 1.12 移除
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geoip: Option<Value>,
+    /// 已在 1.12 移除
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geosite: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Route {
+    pub fn rules(&self) -> &[RouteRule] {
+        self.rules.as_deref().unwrap_or_default()
+    }
+
+    pub fn rule_sets(&self) -> &[RuleSet] {
+        self.rule_set.as_deref().unwrap_or_default()
+    }
+}
+
+string_enum! {
+    /// 路由规则动作，参考 docs/configuration/route/rule_action.md
+    pub enum RouteAction {
+        Route => "route",
+        RouteOptions => "route-options",
+        Reject => "reject",
+        HijackDns => "hijack-dns",
+        Sniff => "sniff",
+        Resolve => "resolve",
+    }
+}
+
+/// 路由规则（默认规则或逻辑规则）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RouteRule {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<RuleType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<LogicalMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<Vec<RouteRule>>,
+    #[serde(flatten)]
+    pub items: RuleItems,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invert: Option<bool>,
+    /// 缺省为 route
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<RouteAction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outbound: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_port: Option<u16>,
+    /// reject 动作的拒绝方式
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_drop: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sniffer: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+    /// resolve 动作使用的 DNS 服务器
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl RouteRule {
+    pub fn is_logical(&self) -> bool {
+        self.kind == Some(RuleType::Logical)
+    }
+
+    /// 实际动作（未填写时为 route）
+    pub fn action(&self) -> RouteAction {
+        self.action.clone().unwrap_or(RouteAction::Route)
+    }
+}
+
+string_enum! {
+    /// 规则集类型
+    pub enum RuleSetType {
+        Inline => "inline",
+        Local => "local",
+        Remote => "remote",
+    }
+}
+
+/// 规则集，参考 docs/configuration/rule-set/index.md
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleSet {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<RuleSetType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// source 或 binary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_detour: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_interval: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<Vec<HeadlessRule>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
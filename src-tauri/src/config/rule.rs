@@ -0,0 +1,138 @@
+//! 路由规则、DNS 规则与规则集共用的匹配字段
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::shared::Listable;
+
+string_enum! {
+    /// 规则类型
+    pub enum RuleType {
+        Default => "default",
+        Logical => "logical",
+    }
+}
+
+string_enum! {
+    /// 逻辑规则模式
+    pub enum LogicalMode {
+        And => "and",
+        Or => "or",
+    }
+}
+
+/// DNS 查询类型，可以写成名称（"AAAA"）或数字（28）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum QueryType {
+    Code(u16),
+    Name(String),
+}
+
+/// 默认规则的匹配字段
+///
+/// 路由规则、DNS 规则和无头规则各自只使用其中一部分。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleItems {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inbound: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_version: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_type: Option<Listable<QueryType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_user: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain_suffix: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain_keyword: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain_regex: Option<Listable<String>>,
+    /// 已在 1.12 移除，迁移为规则集
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geosite: Option<Listable<String>>,
+    /// 已在 1.12 移除，迁移为规则集
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_geoip: Option<Listable<String>>,
+    /// 已在 1.12 移除，迁移为规则集
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geoip: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_ip_cidr: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_ip_is_private: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_cidr: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_is_private: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_port: Option<Listable<u16>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_port_range: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<Listable<u16>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port_range: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_name: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_path: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_path_regex: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package_name: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<Listable<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clash_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_type: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_is_expensive: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_is_constrained: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wifi_ssid: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wifi_bssid: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_set: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_set_ip_cidr_match_source: Option<bool>,
+    /// 已废弃，由 rule_set_ip_cidr_match_source 取代
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_set_ipcidr_match_source: Option<bool>,
+}
+
+/// 无头规则（规则集中的规则），参考 docs/configuration/rule-set/headless-rule.md
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HeadlessRule {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<RuleType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<LogicalMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<Vec<HeadlessRule>>,
+    #[serde(flatten)]
+    pub items: RuleItems,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invert: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl HeadlessRule {
+    pub fn is_logical(&self) -> bool {
+        self.kind == Some(RuleType::Logical)
+    }
+}
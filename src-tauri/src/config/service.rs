@@ -0,0 +1,33 @@
+//! 服务配置，参考 docs/configuration/service/
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::shared::Tls;
+
+string_enum! {
+    /// 服务类型
+    #[derive(Default)]
+    pub enum ServiceType {
+        #[default]
+        Derp => "derp",
+        Resolved => "resolved",
+        SsmApi => "ssm-api",
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Service {
+    #[serde(rename = "type")]
+    pub kind: ServiceType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen_port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<Tls>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
@@ -0,0 +1,278 @@
+//! 多个配置段共用的字段：可列表值、拨号字段、TLS、V2Ray 传输层、多路复用
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// sing-box 中"单个值或数组"皆可的字段，保留原始写法
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Listable<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> Listable<T> {
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            Self::One(value) => std::slice::from_ref(value),
+            Self::Many(values) => values,
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.as_slice().first()
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::One(value) => vec![value],
+            Self::Many(values) => values,
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Listable<T> {
+    fn from(values: Vec<T>) -> Self {
+        Self::Many(values)
+    }
+}
+
+impl<T> From<T> for Listable<T> {
+    fn from(value: T) -> Self {
+        Self::One(value)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Listable<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// 域名解析器：可以是 DNS 服务器标签，也可以是完整选项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DomainResolver {
+    Tag(String),
+    Options(DomainResolverOptions),
+}
+
+impl DomainResolver {
+    /// 引用的 DNS 服务器标签
+    pub fn server(&self) -> Option<&str> {
+        match self {
+            Self::Tag(tag) => Some(tag),
+            Self::Options(options) => options.server.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DomainResolverOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_cache: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewrite_ttl: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_subnet: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 拨号字段，参考 docs/configuration/shared/dial.md
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DialOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detour: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bind_interface: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inet4_bind_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inet6_bind_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reuse_addr: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub netns: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp_fast_open: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp_multi_path: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub udp_fragment: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain_resolver: Option<DomainResolver>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_strategy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_type: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_network_type: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_delay: Option<String>,
+    /// 已废弃，由 domain_resolver 取代
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain_strategy: Option<String>,
+}
+
+/// TLS 字段（入站与出站共用），参考 docs/configuration/shared/tls.md
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tls {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_sni: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insecure: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alpn: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cipher_suites: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utls: Option<Utls>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reality: Option<Reality>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ech: Option<Ech>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Tls {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Utls {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Reality {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// 出站使用
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    /// 入站使用
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+    /// 出站为单个值，入站为列表
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_id: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handshake: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_time_difference: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ech {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// V2Ray 传输层，参考 docs/configuration/shared/v2ray-transport.md
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transport {
+    /// http / ws / quic / grpc / httpupgrade
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<Listable<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<Map<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_early_data: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub early_data_header_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_timeout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ping_timeout: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 多路复用，参考 docs/configuration/shared/multiplex.md
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Multiplex {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_streams: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_streams: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brutal: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Multiplex {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+}
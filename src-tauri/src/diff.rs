@@ -0,0 +1,434 @@
+//! 配置差异与三方合并
+//!
+//! inbounds、outbounds、endpoints、dns.servers 和 route.rule_set 的元素按 tag 对应，
+//! 路径写作 `outbounds["proxy"].server`，调整顺序记为移动而不是逐项修改；
+//! 其他数组按内容对齐（最长公共子序列），路径写作 `route.rules[2]`。
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// 按 tag 对应元素的数组
+pub const KEYED_ARRAYS: [&str; 5] = [
+    "inbounds",
+    "outbounds",
+    "endpoints",
+    "dns.servers",
+    "route.rule_set",
+];
+
+/// 超过该规模的数组不做内容对齐，按下标对应
+const ALIGN_LIMIT: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+    /// 元素调整了顺序，old_value 和 new_value 为前后的下标
+    Moved,
+}
+
+/// 一处差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    /// 差异位置，如 "outbounds[\"proxy\"].server"、"route.rules[1]"；
+    /// 按内容对齐的数组中，删除的元素使用旧下标，其余使用新下标
+    pub path: String,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_value: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_value: Option<Value>,
+}
+
+/// 三方合并中双方改动不一致的位置，合并结果保留 ours 的值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conflict {
+    /// 路径写法同 `Change.path`
+    pub path: String,
+    /// 为空表示该方没有这个字段；双方都调整了顺序时为各方的 tag 列表
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ours: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theirs: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Merge {
+    pub merged: Value,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// 比较两份配置
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    compare(old, new, String::new(), &mut changes);
+    changes
+}
+
+/// 以 base 为共同祖先合并 ours 和 theirs 的改动
+pub fn merge(base: &Value, ours: &Value, theirs: &Value) -> Merge {
+    let mut conflicts = Vec::new();
+    let merged = merge_value(Some(base), Some(ours), Some(theirs), "", &mut conflicts)
+        .unwrap_or(Value::Null);
+    Merge { merged, conflicts }
+}
+
+fn compare(old: &Value, new: &Value, path: String, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                let path = child(&path, key);
+                match new.get(key) {
+                    Some(new_value) => compare(old_value, new_value, path, changes),
+                    None => changes.push(removed(path, old_value)),
+                }
+            }
+            for (key, new_value) in new {
+                if !old.contains_key(key) {
+                    changes.push(added(child(&path, key), new_value));
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            let tagged = if keyed(&path) {
+                tags(old).zip(tags(new))
+            } else {
+                None
+            };
+            match tagged {
+                Some((old_tags, new_tags)) => {
+                    compare_keyed(old, new, &old_tags, &new_tags, &path, changes)
+                }
+                None => compare_sequence(old, new, &path, changes),
+            }
+        }
+        _ if old != new => changes.push(Change {
+            path,
+            kind: ChangeKind::Modified,
+            old_value: Some(old.clone()),
+            new_value: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn compare_keyed(
+    old: &[Value],
+    new: &[Value],
+    old_tags: &[&str],
+    new_tags: &[&str],
+    path: &str,
+    changes: &mut Vec<Change>,
+) {
+    for (item, tag) in old.iter().zip(old_tags) {
+        if !new_tags.contains(tag) {
+            changes.push(removed(element(path, tag), item));
+        }
+    }
+    let positions: Vec<Option<usize>> = new_tags
+        .iter()
+        .map(|tag| old_tags.iter().position(|old_tag| old_tag == tag))
+        .collect();
+    // 相对顺序不变的最长一组元素保持原位，其余的记为移动
+    let stable = increasing(&positions.iter().flatten().copied().collect::<Vec<_>>());
+    let mut common = 0;
+    for (index, (item, tag)) in new.iter().zip(new_tags).enumerate() {
+        let path = element(path, tag);
+        let Some(old_index) = positions[index] else {
+            changes.push(added(path, item));
+            continue;
+        };
+        if !stable[common] {
+            changes.push(Change {
+                path: path.clone(),
+                kind: ChangeKind::Moved,
+                old_value: Some(json!(old_index)),
+                new_value: Some(json!(index)),
+            });
+        }
+        common += 1;
+        compare(&old[old_index], item, path, changes);
+    }
+}
+
+fn compare_sequence(old: &[Value], new: &[Value], path: &str, changes: &mut Vec<Change>) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    // 相同元素之间的空档按位置配对，多出的记为新增或删除
+    let mut anchors = common_subsequence(old, new);
+    anchors.push((old.len(), new.len()));
+    let (mut i, mut j) = (0, 0);
+    for (a, b) in anchors {
+        let paired = (a - i).min(b - j);
+        for k in 0..paired {
+            compare(
+                &old[i + k],
+                &new[j + k],
+                format!("{path}[{}]", prefix + j + k),
+                changes,
+            );
+        }
+        for (k, item) in old.iter().enumerate().take(a).skip(i + paired) {
+            changes.push(removed(format!("{path}[{}]", prefix + k), item));
+        }
+        for (k, item) in new.iter().enumerate().take(b).skip(j + paired) {
+            changes.push(added(format!("{path}[{}]", prefix + k), item));
+        }
+        (i, j) = (a + 1, b + 1);
+    }
+}
+
+/// 最长公共子序列中各元素的下标对
+fn common_subsequence(old: &[Value], new: &[Value]) -> Vec<(usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    if n == 0 || m == 0 || n.saturating_mul(m) > ALIGN_LIMIT {
+        return Vec::new();
+    }
+    // lengths[i][j] 为 old[i..] 与 new[j..] 的最长公共子序列长度
+    let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// 标记最长递增子序列中的元素
+fn increasing(values: &[usize]) -> Vec<bool> {
+    let mut lengths = vec![1usize; values.len()];
+    let mut previous = vec![None; values.len()];
+    for i in 0..values.len() {
+        for j in 0..i {
+            if values[j] < values[i] && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+    let mut stable = vec![false; values.len()];
+    let mut current = (0..values.len()).max_by_key(|&i| lengths[i]);
+    while let Some(i) = current {
+        stable[i] = true;
+        current = previous[i];
+    }
+    stable
+}
+
+fn merge_value(
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    path: &str,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Value> {
+    if ours == theirs || base == theirs {
+        return ours.cloned();
+    }
+    if base == ours {
+        return theirs.cloned();
+    }
+    match (base, ours, theirs) {
+        // 双方新增的同名对象同样逐字段合并
+        (None | Some(Value::Object(_)), Some(Value::Object(ours)), Some(Value::Object(theirs))) => {
+            let empty = Map::new();
+            let base = base.and_then(Value::as_object).unwrap_or(&empty);
+            let mut merged = Map::new();
+            let keys = ours
+                .keys()
+                .chain(theirs.keys().filter(|key| !ours.contains_key(*key)));
+            for key in keys {
+                let path = child(path, key);
+                if let Some(value) = merge_value(
+                    base.get(key),
+                    ours.get(key),
+                    theirs.get(key),
+                    &path,
+                    conflicts,
+                ) {
+                    merged.insert(key.clone(), value);
+                }
+            }
+            Some(Value::Object(merged))
+        }
+        (None | Some(Value::Array(_)), Some(Value::Array(ours)), Some(Value::Array(theirs)))
+            if keyed(path) =>
+        {
+            let items = base
+                .and_then(Value::as_array)
+                .map_or(&[][..], Vec::as_slice);
+            merge_keyed(items, ours, theirs, path, conflicts).or_else(|| {
+                conflict(
+                    path,
+                    base,
+                    Some(&Value::Array(ours.clone())),
+                    Some(&Value::Array(theirs.clone())),
+                    conflicts,
+                )
+            })
+        }
+        _ => conflict(path, base, ours, theirs, conflicts),
+    }
+}
+
+/// 按 tag 合并数组；有元素缺少 tag 或 tag 重复时返回 None
+fn merge_keyed(
+    base: &[Value],
+    ours: &[Value],
+    theirs: &[Value],
+    path: &str,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Value> {
+    let (base_tags, our_tags, their_tags) = (tags(base)?, tags(ours)?, tags(theirs)?);
+
+    // 只有一方调整了顺序时采用该方的顺序，双方的调整不一致时保留 ours 的顺序
+    let ours_moved = reordered(&base_tags, &our_tags);
+    let theirs_moved = reordered(&base_tags, &their_tags);
+    if ours_moved && theirs_moved && reordered(&our_tags, &their_tags) {
+        conflicts.push(Conflict {
+            path: path.to_string(),
+            base: Some(json!(base_tags)),
+            ours: Some(json!(our_tags)),
+            theirs: Some(json!(their_tags)),
+        });
+    }
+    let (primary, secondary) = if theirs_moved && !ours_moved {
+        (&their_tags, &our_tags)
+    } else {
+        (&our_tags, &their_tags)
+    };
+    // 另一方的元素插到它在该方中前一个元素之后
+    let mut order = primary.clone();
+    for (index, tag) in secondary.iter().enumerate() {
+        if order.contains(tag) {
+            continue;
+        }
+        let at = secondary[..index]
+            .iter()
+            .rev()
+            .find_map(|previous| order.iter().position(|tag| tag == previous))
+            .map_or(0, |position| position + 1);
+        order.insert(at, tag);
+    }
+
+    let merged = order
+        .into_iter()
+        .filter_map(|tag| {
+            merge_value(
+                find(base, &base_tags, tag),
+                find(ours, &our_tags, tag),
+                find(theirs, &their_tags, tag),
+                &element(path, tag),
+                conflicts,
+            )
+        })
+        .collect();
+    Some(Value::Array(merged))
+}
+
+fn conflict(
+    path: &str,
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Value> {
+    conflicts.push(Conflict {
+        path: path.to_string(),
+        base: base.cloned(),
+        ours: ours.cloned(),
+        theirs: theirs.cloned(),
+    });
+    ours.cloned()
+}
+
+fn find<'a>(items: &'a [Value], tags: &[&str], tag: &str) -> Option<&'a Value> {
+    tags.iter()
+        .position(|t| *t == tag)
+        .map(|index| &items[index])
+}
+
+/// 两边共有的 tag 相对顺序是否不同
+fn reordered(a: &[&str], b: &[&str]) -> bool {
+    let a_common = a.iter().filter(|tag| b.contains(tag));
+    let b_common = b.iter().filter(|tag| a.contains(tag));
+    !a_common.eq(b_common)
+}
+
+fn keyed(path: &str) -> bool {
+    KEYED_ARRAYS.contains(&path)
+}
+
+/// 每个元素的 tag；有元素缺少 tag 或 tag 重复时返回 None
+fn tags(items: &[Value]) -> Option<Vec<&str>> {
+    let mut seen = HashSet::new();
+    items
+        .iter()
+        .map(|item| {
+            item.get("tag")
+                .and_then(Value::as_str)
+                .filter(|tag| seen.insert(*tag))
+        })
+        .collect()
+}
+
+fn element(path: &str, tag: &str) -> String {
+    format!("{path}[{}]", Value::from(tag))
+}
+
+fn child(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn added(path: String, value: &Value) -> Change {
+    Change {
+        path,
+        kind: ChangeKind::Added,
+        old_value: None,
+        new_value: Some(value.clone()),
+    }
+}
+
+fn removed(path: String, value: &Value) -> Change {
+    Change {
+        path,
+        kind: ChangeKind::Removed,
+        old_value: Some(value.clone()),
+        new_value: None,
+    }
+}
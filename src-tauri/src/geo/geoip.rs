@@ -0,0 +1,325 @@
+//! geoip.db（MaxMind DB 格式）读取
+//!
+//! sing-geoip 的记录是国家代码字符串，GeoLite2-Country 的记录是 `country.iso_code`，
+//! 两种都支持。遍历整棵搜索树得到每个代码的网段，IPv6 树中映射到 IPv4 子树的别名
+//! （::ffff:0:0/96、2002::/16 等）会被跳过。
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use serde_json::{Map, Number, Value};
+
+pub(super) const METADATA_MARKER: &[u8] = b"\xAB\xCD\xEFMaxMind.com";
+/// 搜索树与数据区之间的 16 字节分隔
+const DATA_SEPARATOR: usize = 16;
+/// 数据嵌套深度上限，防止损坏的文件造成无限递归
+const MAX_DEPTH: usize = 32;
+
+/// 按国家代码分组的网段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeoIp {
+    pub database_type: String,
+    networks: BTreeMap<String, Vec<String>>,
+}
+
+impl GeoIp {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        let start = data
+            .windows(METADATA_MARKER.len())
+            .rposition(|window| window == METADATA_MARKER)
+            .ok_or("not a MaxMind DB file: metadata not found")?
+            + METADATA_MARKER.len();
+        let metadata = Decoder {
+            data: &data[start..],
+        }
+        .decode(0, 0)?
+        .0;
+        let field = |key: &str| {
+            metadata
+                .get(key)
+                .and_then(Value::as_u64)
+                .ok_or_else(|| format!("invalid MaxMind DB metadata: missing {key}"))
+        };
+        let node_count = field("node_count")?;
+        let record_size = field("record_size")?;
+        let ip_version = field("ip_version")?;
+        if !matches!(record_size, 24 | 28 | 32) {
+            return Err(format!("unsupported record size {record_size}"));
+        }
+        if !matches!(ip_version, 4 | 6) {
+            return Err(format!("unsupported IP version {ip_version}"));
+        }
+        let node_size = record_size as usize / 4;
+        let tree_size = usize::try_from(node_count)
+            .ok()
+            .and_then(|count| count.checked_mul(node_size))
+            .filter(|size| size + DATA_SEPARATOR <= start)
+            .ok_or("invalid MaxMind DB: search tree exceeds file size")?;
+        let tree = Tree {
+            data: &data[..tree_size],
+            node_count: node_count as u32,
+            node_size,
+            record_size,
+        };
+        let decoder = Decoder {
+            data: &data[tree_size + DATA_SEPARATOR..start - METADATA_MARKER.len()],
+        };
+        Ok(Self {
+            database_type: metadata
+                .get("database_type")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            networks: tree.networks(ip_version == 6, &decoder)?,
+        })
+    }
+
+    /// 所有国家代码（小写，升序）
+    pub fn codes(&self) -> Vec<String> {
+        self.networks.keys().cloned().collect()
+    }
+
+    /// 某个代码的全部网段
+    pub fn networks(&self, code: &str) -> Option<&[String]> {
+        self.networks.get(&code.to_lowercase()).map(Vec::as_slice)
+    }
+}
+
+struct Tree<'a> {
+    data: &'a [u8],
+    node_count: u32,
+    node_size: usize,
+    record_size: u64,
+}
+
+impl Tree<'_> {
+    fn record(&self, node: u32, right: bool) -> u32 {
+        let bytes = &self.data[node as usize * self.node_size..][..self.node_size];
+        let be = |bytes: &[u8]| bytes.iter().fold(0u32, |n, b| n << 8 | *b as u32);
+        match (self.record_size, right) {
+            (24, false) => be(&bytes[..3]),
+            (24, true) => be(&bytes[3..]),
+            (28, false) => (bytes[3] as u32 >> 4) << 24 | be(&bytes[..3]),
+            (28, true) => (bytes[3] as u32 & 0x0F) << 24 | be(&bytes[4..]),
+            (_, false) => be(&bytes[..4]),
+            (_, true) => be(&bytes[4..]),
+        }
+    }
+
+    /// 深度优先遍历，按地址升序收集每个代码的网段
+    fn networks(
+        &self,
+        v6: bool,
+        decoder: &Decoder,
+    ) -> Result<BTreeMap<String, Vec<String>>, String> {
+        let bits = if v6 { 128 } else { 32 };
+        // IPv4 地址在 IPv6 树中位于 ::/96
+        let mut ipv4_start = 0;
+        if v6 {
+            for _ in 0..96 {
+                if ipv4_start >= self.node_count {
+                    break;
+                }
+                ipv4_start = self.record(ipv4_start, false);
+            }
+        }
+
+        let mut networks: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut codes: HashMap<u32, Option<String>> = HashMap::new();
+        let mut stack = vec![(0u32, 0u128, 0u32)];
+        // 正常的搜索树中每个节点只会经过一次，别名节点到达后立即跳过
+        let mut budget = self.node_count as u64 * 2 + 1024;
+        while let Some((node, prefix, depth)) = stack.pop() {
+            if node == self.node_count {
+                continue;
+            }
+            if node > self.node_count {
+                let code = match codes.get(&node) {
+                    Some(code) => code.clone(),
+                    None => {
+                        let offset = (node - self.node_count) as usize;
+                        let offset = offset
+                            .checked_sub(DATA_SEPARATOR)
+                            .ok_or("invalid MaxMind DB: bad data pointer")?;
+                        let code = country_code(&decoder.decode(offset, 0)?.0);
+                        codes.insert(node, code.clone());
+                        code
+                    }
+                };
+                if let Some(code) = code {
+                    networks
+                        .entry(code)
+                        .or_default()
+                        .push(cidr(v6, prefix, depth));
+                }
+                continue;
+            }
+            if depth >= bits {
+                return Err("invalid MaxMind DB: search tree is too deep".to_string());
+            }
+            if v6 && node == ipv4_start && depth > 0 && prefix >> 32 != 0 {
+                continue;
+            }
+            budget = budget
+                .checked_sub(1)
+                .ok_or("invalid MaxMind DB: search tree contains cycles")?;
+            let bit = 1u128 << (bits - 1 - depth);
+            // 先压右子树，保证左子树（较小的地址）先出栈
+            stack.push((self.record(node, true), prefix | bit, depth + 1));
+            stack.push((self.record(node, false), prefix, depth + 1));
+        }
+        Ok(networks)
+    }
+}
+
+fn cidr(v6: bool, prefix: u128, depth: u32) -> String {
+    if !v6 {
+        return format!("{}/{}", Ipv4Addr::from(prefix as u32), depth);
+    }
+    if depth >= 96 && prefix >> 32 == 0 {
+        return format!("{}/{}", Ipv4Addr::from(prefix as u32), depth - 96);
+    }
+    format!("{}/{}", Ipv6Addr::from(prefix), depth)
+}
+
+/// sing-geoip 的记录是代码本身，GeoLite2 的记录是 {country: {iso_code}}
+fn country_code(record: &Value) -> Option<String> {
+    match record {
+        Value::String(code) => Some(code.to_lowercase()),
+        Value::Object(map) => ["country", "registered_country"].iter().find_map(|key| {
+            map.get(*key)?
+                .get("iso_code")?
+                .as_str()
+                .map(str::to_lowercase)
+        }),
+        _ => None,
+    }
+}
+
+/// MaxMind DB 数据区解码，指针相对于 data 起点
+struct Decoder<'a> {
+    data: &'a [u8],
+}
+
+impl Decoder<'_> {
+    fn bytes(&self, offset: usize, len: usize) -> Result<&[u8], String> {
+        self.data
+            .get(offset..offset.checked_add(len).ok_or("invalid MaxMind DB data")?)
+            .ok_or_else(|| "invalid MaxMind DB: unexpected end of data".to_string())
+    }
+
+    fn uint(&self, offset: usize, len: usize) -> Result<u64, String> {
+        if len > 8 {
+            return Err("invalid MaxMind DB: integer too large".to_string());
+        }
+        Ok(self
+            .bytes(offset, len)?
+            .iter()
+            .fold(0u64, |n, b| n << 8 | *b as u64))
+    }
+
+    /// 解码 offset 处的字段，返回值和下一个字段的位置
+    fn decode(&self, offset: usize, depth: usize) -> Result<(Value, usize), String> {
+        if depth > MAX_DEPTH {
+            return Err("invalid MaxMind DB: data nested too deeply".to_string());
+        }
+        let control = self.bytes(offset, 1)?[0];
+        let mut offset = offset + 1;
+        let mut kind = control >> 5;
+        if kind == 1 {
+            // 指针：大小字段另有编码，指向的值不能再是指针
+            let size = (control >> 3) & 0x3;
+            let high = (control & 0x7) as u64;
+            let len = size as usize + 1;
+            let low = self.uint(offset, len)?;
+            let pointer = match size {
+                0 => high << 8 | low,
+                1 => (high << 16 | low) + 2048,
+                2 => (high << 24 | low) + 526_336,
+                _ => low,
+            };
+            let (value, _) = self.decode(pointer as usize, depth + 1)?;
+            return Ok((value, offset + len));
+        }
+        if kind == 0 {
+            kind = 7 + self.bytes(offset, 1)?[0];
+            offset += 1;
+        }
+        let mut size = (control & 0x1F) as usize;
+        if size >= 29 {
+            let extra = size - 28;
+            let n = self.uint(offset, extra)? as usize;
+            offset += extra;
+            size = match extra {
+                1 => 29 + n,
+                2 => 285 + n,
+                _ => 65_821 + n,
+            };
+        }
+        match kind {
+            2 => {
+                let text = std::str::from_utf8(self.bytes(offset, size)?)
+                    .map_err(|_| "invalid MaxMind DB: bad UTF-8 string")?;
+                Ok((Value::String(text.to_string()), offset + size))
+            }
+            3 | 15 => {
+                let value = if kind == 3 {
+                    f64::from_be_bytes(self.bytes(offset, 8)?.try_into().unwrap_or_default())
+                } else {
+                    f32::from_be_bytes(self.bytes(offset, 4)?.try_into().unwrap_or_default()) as f64
+                };
+                let len = if kind == 3 { 8 } else { 4 };
+                Ok((
+                    Number::from_f64(value).map_or(Value::Null, Value::Number),
+                    offset + len,
+                ))
+            }
+            4 => Ok((
+                Value::Array(
+                    self.bytes(offset, size)?
+                        .iter()
+                        .map(|b| Value::from(*b))
+                        .collect(),
+                ),
+                offset + size,
+            )),
+            5 | 6 | 9 => Ok((Value::from(self.uint(offset, size)?), offset + size)),
+            8 => Ok((
+                Value::from(self.uint(offset, size)? as u32 as i32),
+                offset + size,
+            )),
+            10 => {
+                let bytes = self.bytes(offset, size)?;
+                if size > 16 {
+                    return Err("invalid MaxMind DB: integer too large".to_string());
+                }
+                let value = bytes.iter().fold(0u128, |n, b| n << 8 | *b as u128);
+                Ok((Value::String(value.to_string()), offset + size))
+            }
+            7 => {
+                let mut map = Map::new();
+                for _ in 0..size {
+                    let (key, next) = self.decode(offset, depth + 1)?;
+                    let Value::String(key) = key else {
+                        return Err("invalid MaxMind DB: map key is not a string".to_string());
+                    };
+                    let (value, next) = self.decode(next, depth + 1)?;
+                    map.insert(key, value);
+                    offset = next;
+                }
+                Ok((Value::Object(map), offset))
+            }
+            11 => {
+                let mut values = Vec::new();
+                for _ in 0..size {
+                    let (value, next) = self.decode(offset, depth + 1)?;
+                    values.push(value);
+                    offset = next;
+                }
+                Ok((Value::Array(values), offset))
+            }
+            14 => Ok((Value::Bool(size != 0), offset)),
+            other => Err(format!("invalid MaxMind DB: unknown data type {other}")),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! geosite.db（sing-geosite 格式）读取
+//!
+//! 文件头是版本号 0 和代码索引（代码、条目起始偏移、条目数），其后是所有条目：
+//! 一字节类型（0 domain、1 domain_suffix、2 domain_keyword、3 domain_regex）和一个字符串。
+
+use std::collections::BTreeMap;
+
+use crate::config::RuleItems;
+
+/// 按代码分组的域名规则
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoSite {
+    codes: BTreeMap<String, RuleItems>,
+}
+
+impl GeoSite {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { data, offset: 0 };
+        let version = reader.byte()?;
+        if version != 0 {
+            return Err(format!("unsupported geosite version {version}"));
+        }
+        let count = reader.uvarint()?;
+        let mut index = Vec::new();
+        for _ in 0..count {
+            let code = reader.string()?;
+            let start = reader.uvarint()? as usize;
+            let length = reader.uvarint()?;
+            index.push((code, start, length));
+        }
+
+        let content = reader.offset;
+        let mut codes = BTreeMap::new();
+        for (code, start, length) in index {
+            let mut reader = Reader {
+                data,
+                offset: content
+                    .checked_add(start)
+                    .filter(|offset| *offset <= data.len())
+                    .ok_or_else(|| format!("invalid geosite: code {code} is out of range"))?,
+            };
+            let mut lists: [Vec<String>; 4] = Default::default();
+            for _ in 0..length {
+                let kind = reader.byte()?;
+                let value = reader.string()?;
+                lists
+                    .get_mut(kind as usize)
+                    .ok_or_else(|| format!("invalid geosite: unknown item type {kind}"))?
+                    .push(value);
+            }
+            let [domain, domain_suffix, domain_keyword, domain_regex] =
+                lists.map(|values| (!values.is_empty()).then(|| values.into()));
+            let items = RuleItems {
+                domain,
+                domain_suffix,
+                domain_keyword,
+                domain_regex,
+                ..Default::default()
+            };
+            codes.insert(code, items);
+        }
+        Ok(Self { codes })
+    }
+
+    /// 所有代码（升序）
+    pub fn codes(&self) -> Vec<String> {
+        self.codes.keys().cloned().collect()
+    }
+
+    /// 某个代码的规则，代码不区分大小写
+    pub fn items(&self, code: &str) -> Option<&RuleItems> {
+        self.codes.get(code).or_else(|| {
+            self.codes
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(code))
+                .map(|(_, items)| items)
+        })
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .data
+            .get(self.offset)
+            .ok_or("invalid geosite: unexpected end of file")?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn uvarint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte < 0x80 {
+                return Ok(value);
+            }
+        }
+        Err("invalid geosite: bad varint".to_string())
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.uvarint()? as usize;
+        let bytes = self
+            .offset
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.offset..end))
+            .ok_or("invalid geosite: unexpected end of file")?;
+        self.offset += len;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| "invalid geosite: bad UTF-8 string".to_string())
+    }
+}
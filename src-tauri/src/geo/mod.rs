@@ -0,0 +1,242 @@
+//! 旧版 geoip.db / geosite.db 转换为规则集
+//!
+//! 1.8.0 起 GeoIP/Geosite 改为规则集（docs/migration.md）。迁移步骤默认引用官方的远程规则集，
+//! 这里从用户已有的数据库中按代码导出规则集，并把配置改为引用导出的本地文件。
+
+pub mod geoip;
+pub mod geosite;
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub use geoip::GeoIp;
+pub use geosite::GeoSite;
+
+use crate::config::{Config, HeadlessRule, RuleItems, RuleSet, RuleSetType};
+use crate::migration::{self, StepReport};
+use crate::rule_set::{self, RuleSetSource, VERSION_1, VERSION_2};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GeoKind {
+    GeoIp,
+    GeoSite,
+}
+
+impl GeoKind {
+    /// 规则集标签前缀，与迁移生成的 geoip-<code> / geosite-<code> 一致
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::GeoIp => "geoip",
+            Self::GeoSite => "geosite",
+        }
+    }
+
+    pub fn tag(self, code: &str) -> String {
+        format!("{}-{}", self.prefix(), code)
+    }
+}
+
+/// 已读取的数据库
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoDatabase {
+    GeoIp(GeoIp),
+    GeoSite(GeoSite),
+}
+
+/// 数据库类型和其中的代码
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeoInfo {
+    pub kind: GeoKind,
+    pub codes: Vec<String>,
+}
+
+impl GeoDatabase {
+    /// 带 MaxMind 元数据的按 geoip.db 读取，否则按 geosite.db 读取
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        if data
+            .windows(geoip::METADATA_MARKER.len())
+            .any(|window| window == geoip::METADATA_MARKER)
+        {
+            GeoIp::from_bytes(data).map(Self::GeoIp)
+        } else {
+            GeoSite::from_bytes(data).map(Self::GeoSite)
+        }
+    }
+
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Self::from_bytes(&data).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn kind(&self) -> GeoKind {
+        match self {
+            Self::GeoIp(_) => GeoKind::GeoIp,
+            Self::GeoSite(_) => GeoKind::GeoSite,
+        }
+    }
+
+    pub fn codes(&self) -> Vec<String> {
+        match self {
+            Self::GeoIp(db) => db.codes(),
+            Self::GeoSite(db) => db.codes(),
+        }
+    }
+
+    pub fn info(&self) -> GeoInfo {
+        GeoInfo {
+            kind: self.kind(),
+            codes: self.codes(),
+        }
+    }
+
+    /// 某个代码对应的规则集源文件；geoip 只含 ip_cidr，用版本 1 即可
+    pub fn source(&self, code: &str) -> Option<RuleSetSource> {
+        let (version, items) = match self {
+            Self::GeoIp(db) => (
+                VERSION_1,
+                RuleItems {
+                    ip_cidr: Some(db.networks(code)?.to_vec().into()),
+                    ..Default::default()
+                },
+            ),
+            Self::GeoSite(db) => (VERSION_2, db.items(code)?.clone()),
+        };
+        Some(RuleSetSource {
+            version,
+            rules: vec![HeadlessRule {
+                items,
+                ..Default::default()
+            }],
+        })
+    }
+}
+
+/// 把若干代码导出到 dir，binary 为真时写 .srs，否则写源文件 .json；返回写入的文件
+pub fn export(
+    db: &GeoDatabase,
+    codes: &[String],
+    dir: &Path,
+    binary: bool,
+) -> Result<Vec<PathBuf>, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    let kind = db.kind();
+    codes
+        .iter()
+        .map(|code| {
+            let source = db
+                .source(code)
+                .ok_or_else(|| format!("{} has no code {code}", kind.prefix()))?;
+            let extension = if binary { "srs" } else { "json" };
+            let path = dir.join(format!("{}.{extension}", kind.tag(code)));
+            let data = if binary {
+                rule_set::compile(&source, None)?
+            } else {
+                source.to_json_pretty()?.into_bytes()
+            };
+            std::fs::write(&path, data).map_err(|e| format!("{}: {e}", path.display()))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// 本地化结果：迁移步骤报告、写入的文件和警告
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalizeReport {
+    pub steps: Vec<StepReport>,
+    /// 写入的 .srs 文件
+    pub files: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// 执行 geoip/geosite 迁移步骤，再把对应的远程规则集替换为从 databases 导出到 dir 的本地规则集
+///
+/// 数据库中没有的代码保留远程规则集并给出警告。
+pub fn localize(
+    config: &mut Config,
+    databases: &[GeoDatabase],
+    dir: &Path,
+) -> Result<LocalizeReport, String> {
+    let mut report = LocalizeReport {
+        steps: migration::steps()
+            .iter()
+            .filter(|step| matches!(step.id, "geoip-to-rule-set" | "geosite-to-rule-set"))
+            .map(|step| step.run(config))
+            .collect(),
+        ..Default::default()
+    };
+    let Some(rule_sets) = config
+        .route
+        .as_mut()
+        .and_then(|route| route.rule_set.as_mut())
+    else {
+        return Ok(report);
+    };
+    for rule_set in rule_sets {
+        if rule_set.kind != Some(RuleSetType::Remote) {
+            continue;
+        }
+        let Some(tag) = rule_set.tag.clone() else {
+            continue;
+        };
+        let Some((db, code)) = databases.iter().find_map(|db| {
+            let code = tag.strip_prefix(db.kind().prefix())?.strip_prefix('-')?;
+            Some((db, code))
+        }) else {
+            continue;
+        };
+        let Some(source) = db.source(code) else {
+            report.warnings.push(format!(
+                "{}.db has no code {code}, rule-set {tag} is still remote",
+                db.kind().prefix()
+            ));
+            continue;
+        };
+        if report.files.is_empty() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        }
+        let path = dir.join(format!("{tag}.srs"));
+        std::fs::write(&path, rule_set::compile(&source, None)?)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        let path = path.display().to_string();
+        *rule_set = RuleSet {
+            kind: Some(RuleSetType::Local),
+            tag: Some(tag),
+            format: Some("binary".to_string()),
+            path: Some(path.clone()),
+            ..Default::default()
+        };
+        report.files.push(path);
+    }
+    Ok(report)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalizeResult {
+    pub config: Value,
+    #[serde(flatten)]
+    pub report: LocalizeReport,
+}
+
+/// 对 JSON 配置执行 [`localize`]，databases 为 geoip.db / geosite.db 的路径
+pub fn localize_value(
+    config: Value,
+    databases: &[PathBuf],
+    dir: &Path,
+) -> Result<LocalizeResult, String> {
+    let databases = databases
+        .iter()
+        .map(|path| GeoDatabase::open(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    let original = config;
+    let mut config = Config::from_value(original.clone()).map_err(|e| e.to_string())?;
+    let report = localize(&mut config, &databases, dir)?;
+    Ok(LocalizeResult {
+        config: config
+            .to_value_with_layout(&original)
+            .map_err(|e| e.to_string())?,
+        report,
+    })
+}
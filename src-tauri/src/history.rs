@@ -0,0 +1,213 @@
+//! 配置文件的修订历史，保存在应用数据目录的 history 下
+//!
+//! 每个配置文件一个子目录（按绝对路径的哈希命名），index.json 记录修订列表，
+//! 修订内容保存为 `<id>.json`。id 为毫秒时间戳，按时间递增。
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{diff, save, validation};
+
+/// history 目录名
+pub const DIR_NAME: &str = "history";
+
+/// 每个文件保留的修订数，超出时删除最旧的
+pub const MAX_REVISIONS: usize = 200;
+
+const INDEX_FILE: &str = "index.json";
+
+/// 一次修订
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Revision {
+    pub id: String,
+    /// Unix 时间（秒）
+    pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// 保存时是否通过 Schema 校验
+    pub valid: bool,
+    /// 校验错误数
+    #[serde(default)]
+    pub errors: usize,
+    /// 内容字节数
+    pub size: usize,
+}
+
+/// 恢复修订的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestoreResult {
+    /// 写回的内容
+    pub text: String,
+    /// 修订是否通过 Schema 校验，强制恢复无效修订时为 false
+    pub valid: bool,
+    /// 校验错误数
+    pub errors: usize,
+    /// 不影响恢复的问题，如无法保留原文件的属主
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Index {
+    path: String,
+    revisions: Vec<Revision>,
+}
+
+/// 单个配置文件的修订历史
+pub struct History {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl History {
+    /// root 为 history 目录，file 为配置文件路径
+    pub fn open(root: &Path, file: &Path) -> Self {
+        let path = absolute(file);
+        let key = fnv1a(path.to_string_lossy().as_bytes());
+        Self {
+            dir: root.join(format!("{key:016x}")),
+            path,
+        }
+    }
+
+    /// 修订列表，最新的在前
+    pub fn list(&self) -> Vec<Revision> {
+        let mut revisions = self.load().revisions;
+        revisions.reverse();
+        revisions
+    }
+
+    /// 记录一次修订
+    pub fn record(&self, text: &str, note: Option<&str>) -> io::Result<Revision> {
+        let mut index = self.load();
+        let result = validation::validate_text(text);
+        let mut id = now_millis();
+        if let Some(last) = index.revisions.last() {
+            // 同一毫秒内多次保存时顺延，保证 id 递增
+            let last: u64 = last.id.parse().unwrap_or_default();
+            id = id.max(last + 1);
+        }
+        let revision = Revision {
+            id: id.to_string(),
+            timestamp: id / 1000,
+            note: note
+                .map(str::trim)
+                .filter(|note| !note.is_empty())
+                .map(str::to_string),
+            valid: result.valid,
+            errors: result.errors.len(),
+            size: text.len(),
+        };
+
+        fs::create_dir_all(&self.dir)?;
+        save::write_atomic(&self.snapshot(&revision.id), text.as_bytes(), 0)?;
+        index.path = self.path.to_string_lossy().into_owned();
+        index.revisions.push(revision.clone());
+        let excess = index.revisions.len().saturating_sub(MAX_REVISIONS);
+        for old in index.revisions.drain(..excess) {
+            let _ = fs::remove_file(self.snapshot(&old.id));
+        }
+        let data = serde_json::to_vec_pretty(&index).map_err(io::Error::other)?;
+        save::write_atomic(&self.dir.join(INDEX_FILE), &data, 0)?;
+        Ok(revision)
+    }
+
+    /// 读取修订内容
+    pub fn read(&self, id: &str) -> Result<String, String> {
+        if !self
+            .load()
+            .revisions
+            .iter()
+            .any(|revision| revision.id == id)
+        {
+            return Err(format!("revision {id} not found"));
+        }
+        fs::read_to_string(self.snapshot(id)).map_err(|e| format!("revision {id}: {e}"))
+    }
+
+    /// 比较修订与当前配置，current 为空时读取磁盘上的文件
+    pub fn diff(&self, id: &str, current: Option<&Value>) -> Result<Vec<diff::Change>, String> {
+        let old = parse(&self.read(id)?).map_err(|e| format!("revision {id}: {e}"))?;
+        let new = match current {
+            Some(current) => current.clone(),
+            None => {
+                let text = fs::read_to_string(&self.path)
+                    .map_err(|e| format!("{}: {e}", self.path.display()))?;
+                parse(&text).map_err(|e| format!("{}: {e}", self.path.display()))?
+            }
+        };
+        Ok(diff::diff(&old, &new))
+    }
+
+    /// 把修订写回配置文件，并记录为一次新的修订
+    ///
+    /// 与保存一样先校验，未通过校验的修订只有 force 为 true 时才写回
+    pub fn restore(&self, id: &str, backups: usize, force: bool) -> Result<RestoreResult, String> {
+        let text = self.read(id)?;
+        let result = validation::validate_text(&text);
+        let saved = if force {
+            save::write_atomic(&self.path, text.as_bytes(), backups)
+                .map_err(|e| format!("{}: {e}", self.path.display()))?
+        } else {
+            save::save_config(&self.path, &text, backups)?
+        };
+        self.record(&text, Some(&format!("Restored revision {id}")))
+            .map_err(|e| e.to_string())?;
+        Ok(RestoreResult {
+            text,
+            valid: result.valid,
+            errors: result.errors.len(),
+            warnings: saved.warnings,
+        })
+    }
+
+    fn load(&self) -> Index {
+        fs::read_to_string(self.dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    fn snapshot(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+}
+
+fn parse(text: &str) -> Result<Value, String> {
+    serde_json::from_str(text).map_err(|e| format!("invalid JSON: {e}"))
+}
+
+/// 同一文件的不同写法（相对路径、符号链接）对应同一份历史
+///
+/// 文件还不存在时规范化所在目录再拼上文件名，与文件创建后的结果一致
+fn absolute(path: &Path) -> PathBuf {
+    if let Ok(path) = fs::canonicalize(path) {
+        return path;
+    }
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    match (fs::canonicalize(parent), path.file_name()) {
+        (Ok(parent), Some(name)) => parent.join(name),
+        _ => std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+    }
+}
+
+/// 目录名使用稳定的哈希，不随 Rust 版本变化
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
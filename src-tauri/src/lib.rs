@@ -1,44 +1,908 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+pub mod clash_api;
+pub mod clash;
+pub mod cli;
+pub mod config;
+pub mod diff;
+pub mod geo;
+pub mod history;
 #[cfg(desktop)]
 mod menu;
-#[cfg(desktop)]
-mod menu_i18n;
+pub mod menu_i18n;
+pub mod migration;
+pub mod preflight;
+pub mod recent;
+pub mod rule_set;
+pub mod save;
+pub mod settings;
+pub mod share_link;
+pub mod simulator;
+pub mod singbox;
+pub mod subscription;
 #[cfg(desktop)]
 mod tray;
+pub mod validation;
+pub mod wireguard;
 
 #[cfg(desktop)]
-use menu::build_menu;
+use menu::{build_menu, RECENT_ITEM_PREFIX};
+#[cfg(desktop)]
+use tray::{build_tray, handle_window_close, update_tray_menu, TraySelectors};
+use singbox::supervisor;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Emitter, Runtime, State};
 #[cfg(desktop)]
-use tray::{build_tray, handle_window_close};
-use tauri::{AppHandle, Manager, Emitter};
+use tauri_plugin_fs::FsExt;
 
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-/// 获取当前语言（默认中文，后续可从存储中读取）
+/// 应用配置目录下的文件路径
+fn config_file<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(name))
+        .map_err(|e| e.to_string())
+}
+
+/// 应用配置目录下的设置文件路径
+fn settings_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    config_file(app, settings::FILE_NAME)
+}
+
+/// 读取应用设置，无法定位配置目录时使用默认值
+fn load_settings<R: Runtime>(app: &AppHandle<R>) -> settings::AppSettings {
+    settings_path(app)
+        .map(|path| settings::AppSettings::load(&path))
+        .unwrap_or_default()
+}
+
+/// 获取当前语言（已保存的选择，否则跟随系统语言）
 #[tauri::command]
-fn get_current_locale() -> String {
-    "zh".to_string() // TODO: 从存储或前端获取
+fn get_current_locale(app: AppHandle) -> String {
+    load_settings(&app).locale()
 }
 
-/// 更新菜单语言（仅桌面平台）
+/// 保存语言选择
+fn save_locale(app: &AppHandle, locale: &str) -> Result<(), String> {
+    let path = settings_path(app)?;
+    let mut settings = settings::AppSettings::load(&path);
+    settings.locale = Some(locale.to_string());
+    settings.save(&path).map_err(|e| e.to_string())
+}
+
+/// 更新菜单与托盘语言并保存（仅桌面平台）
 #[tauri::command]
 #[cfg(desktop)]
 async fn update_menu_locale(app: AppHandle, locale: String) -> Result<(), String> {
-    let menu = build_menu(&app, &locale).map_err(|e| e.to_string())?;
-    app.set_menu(menu).map_err(|e| e.to_string())?;
+    let locale = settings::normalize_locale(&locale);
+    save_locale(&app, &locale)?;
+    refresh_menu(&app)?;
+    update_tray_menu(&app, &locale).map_err(|e| e.to_string())?;
     Ok(())
 }
 
-/// 更新菜单语言（移动平台占位）
+/// 保存语言选择（移动平台没有菜单）
 #[tauri::command]
 #[cfg(not(desktop))]
-async fn update_menu_locale(_app: AppHandle, _locale: String) -> Result<(), String> {
+async fn update_menu_locale(app: AppHandle, locale: String) -> Result<(), String> {
+    save_locale(&app, &settings::normalize_locale(&locale))
+}
+
+/// 按保存的语言和最近文件重建应用菜单
+#[cfg(desktop)]
+fn refresh_menu<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let locale = load_settings(app).locale();
+    let recent = update_recent_files(app, |_| {})?;
+    let menu = build_menu(app, &locale, &recent).map_err(|e| e.to_string())?;
+    app.set_menu(menu).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 移动平台没有应用菜单
+#[cfg(not(desktop))]
+fn refresh_menu<R: Runtime>(_app: &AppHandle<R>) -> Result<(), String> {
+    Ok(())
+}
+
+/// 读取并修改最近文件列表，已不存在的文件会被移除
+///
+/// 多个命令可能同时修改列表，读写期间加锁避免互相覆盖。
+fn update_recent_files<R: Runtime>(
+    app: &AppHandle<R>,
+    f: impl FnOnce(&mut recent::RecentFiles),
+) -> Result<Vec<PathBuf>, String> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = config_file(app, recent::FILE_NAME)?;
+    let mut recent = recent::RecentFiles::load(&path);
+    let pruned = recent.prune();
+    let before = recent.clone();
+    f(&mut recent);
+    if pruned || recent != before {
+        recent.save(&path).map_err(|e| e.to_string())?;
+    }
+    Ok(recent.files().to_vec())
+}
+
+/// 获取最近打开的文件（最近的在前）
+#[tauri::command]
+fn list_recent_files(app: AppHandle) -> Result<Vec<String>, String> {
+    let files = update_recent_files(&app, |_| {})?;
+    Ok(files.iter().map(|file| file.display().to_string()).collect())
+}
+
+/// 记录打开或保存过的文件，并刷新“打开最近”菜单
+#[tauri::command]
+fn add_recent_file(app: AppHandle, path: String) -> Result<(), String> {
+    update_recent_files(&app, |recent| recent.add(&path))?;
+    refresh_menu(&app)
+}
+
+/// 清空最近文件列表
+#[tauri::command]
+fn clear_recent_files(app: AppHandle) -> Result<(), String> {
+    update_recent_files(&app, recent::RecentFiles::clear)?;
+    refresh_menu(&app)
+}
+
+/// 打开“打开最近”菜单中的第 index 项，文件已不存在时从列表中移除
+#[cfg(desktop)]
+fn open_recent_file<R: Runtime>(app: &AppHandle<R>, index: usize) -> Result<(), String> {
+    let path = config_file(app, recent::FILE_NAME)?;
+    let Some(file) = recent::RecentFiles::load(&path).get(index).map(PathBuf::from) else {
+        return Ok(());
+    };
+    if !file.is_file() {
+        eprintln!("Recent file no longer exists: {}", file.display());
+        return refresh_menu(app);
+    }
+    // 重启后通过菜单打开的文件不在对话框授予的范围内，需要重新授权
+    if let Some(scope) = app.try_fs_scope() {
+        scope.allow_file(&file).map_err(|e| e.to_string())?;
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        window
+            .emit("open-file", file.display().to_string())
+            .map_err(|e| e.to_string())?;
+    }
     Ok(())
 }
 
+/// 查找 sing-box 内核：设置中的路径、PATH、应用数据目录
+fn locate_core<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    let configured = load_settings(app).core_path.map(PathBuf::from);
+    let data_dir = app.path().app_data_dir().ok();
+    singbox::locate(
+        configured.as_deref(),
+        std::env::var_os("PATH").as_deref(),
+        data_dir.as_deref(),
+    )
+}
+
+/// 获取本地 sing-box 内核的路径和版本，未找到时返回 null
+#[tauri::command]
+async fn get_core_info(app: AppHandle) -> Result<Option<singbox::CoreInfo>, String> {
+    locate_core(&app)
+        .map(|binary| singbox::CoreInfo::detect(&binary).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+/// 设置 sing-box 内核路径（传 null 恢复自动查找），保存前先确认可以读取版本
+#[tauri::command]
+async fn set_core_path(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<Option<singbox::CoreInfo>, String> {
+    if let Some(path) = &path {
+        singbox::locate(Some(PathBuf::from(path).as_path()), None, None)
+            .ok_or_else(|| format!("sing-box binary not found at {}", path))
+            .and_then(|binary| singbox::CoreInfo::detect(&binary).map_err(|e| e.to_string()))?;
+    }
+    let settings_file = settings_path(&app)?;
+    let mut settings = settings::AppSettings::load(&settings_file);
+    settings.core_path = path;
+    settings.save(&settings_file).map_err(|e| e.to_string())?;
+    get_core_info(app).await
+}
+
+/// 文档所在目录（配置中相对路径的解析目录）和文件名
+fn document_location(document: Option<&Path>) -> (Option<PathBuf>, String) {
+    let dir = document
+        .and_then(|path| path.parent())
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map(Path::to_path_buf);
+    let file_name = document
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "config.json".to_string());
+    (dir, file_name)
+}
+
+/// 用本地 sing-box 内核运行 `sing-box check`
+///
+/// path 为当前文档的路径，配置中的相对路径按其所在目录解析。
+#[tauri::command]
+async fn run_core_check(
+    app: AppHandle,
+    config: serde_json::Value,
+    path: Option<String>,
+) -> Result<singbox::CheckResult, String> {
+    let binary = locate_core(&app).ok_or("sing-box binary not found")?;
+    let document = path.map(PathBuf::from);
+    let (work_dir, file_name) = document_location(document.as_deref());
+    singbox::check(&binary, &config, work_dir.as_deref(), &file_name).map_err(|e| e.to_string())
+}
+
+/// 把内核状态和日志转发给前端，状态变化时刷新托盘菜单
+fn core_event_sink<R: Runtime>(app: &AppHandle<R>) -> supervisor::EventSink {
+    let app = app.clone();
+    Arc::new(move |event| {
+        let window = app.get_webview_window("main");
+        match event {
+            supervisor::SupervisorEvent::Log(line) => {
+                if let Some(window) = window {
+                    let _ = window.emit("core-log", line);
+                }
+            }
+            supervisor::SupervisorEvent::Status(status) => {
+                let running = matches!(status, singbox::CoreStatus::Running { .. });
+                if let Some(window) = window {
+                    let _ = window.emit("core-status", status);
+                }
+                // 内核不再运行时停止 Clash API 推送，释放时会等待后台线程，不在守护线程中进行
+                if !running {
+                    let subscriptions = std::mem::take(&mut *app.state::<ClashStreams>().lock());
+                    if !subscriptions.is_empty() {
+                        std::thread::spawn(move || drop(subscriptions));
+                    }
+                }
+                #[cfg(desktop)]
+                {
+                    update_tray(&app);
+                    refresh_tray_selectors(&app);
+                }
+            }
+        }
+    })
+}
+
+/// 在主线程上按保存的语言重建托盘菜单
+///
+/// 状态事件来自守护线程，直接更新托盘会与等待守护线程的 stop 互相阻塞。
+#[cfg(desktop)]
+fn update_tray<R: Runtime>(app: &AppHandle<R>) {
+    let handle = app.clone();
+    let _ = app.run_on_main_thread(move || {
+        let locale = load_settings(&handle).locale();
+        if let Err(e) = update_tray_menu(&handle, &locale) {
+            eprintln!("Failed to update tray menu: {}", e);
+        }
+    });
+}
+
+/// 在后台读取运行中内核的 selector 出站，更新托盘中的出站选择子菜单
+#[cfg(desktop)]
+fn refresh_tray_selectors<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut selectors = Vec::new();
+        if let Ok(client) = clash_client(&app) {
+            // 内核刚启动时 Clash API 可能还没有就绪
+            for _ in 0..10 {
+                match client.selectors() {
+                    Ok(list) => {
+                        selectors = list;
+                        break;
+                    }
+                    Err(_) => std::thread::sleep(std::time::Duration::from_millis(500)),
+                }
+            }
+        }
+        *app.state::<TraySelectors>().lock() = selectors;
+        update_tray(&app);
+    });
+}
+
+/// 检查配置文件后用它启动内核，已在运行时先停止
+fn start_core_with<R: Runtime>(
+    app: &AppHandle<R>,
+    config: PathBuf,
+) -> Result<singbox::CoreStatus, String> {
+    let binary = locate_core(app).ok_or("sing-box binary not found")?;
+    let text = std::fs::read_to_string(&config).map_err(|e| e.to_string())?;
+    let value: serde_json::Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    let (work_dir, file_name) = document_location(Some(&config));
+    let result = singbox::check(&binary, &value, work_dir.as_deref(), &file_name)
+        .map_err(|e| e.to_string())?;
+    if !result.valid {
+        let messages: Vec<_> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.level == preflight::IssueLevel::Error)
+            .map(|d| d.message.as_str())
+            .collect();
+        return Err(messages.join("\n"));
+    }
+
+    let supervisor = app.state::<singbox::Supervisor>();
+    let launch = singbox::Launch {
+        binary,
+        config,
+        work_dir,
+    };
+    supervisor
+        .start(launch, core_event_sink(app))
+        .map_err(|e| e.to_string())?;
+    Ok(supervisor.status())
+}
+
+/// 停止内核，等待进程退出
+fn stop_core_with<R: Runtime>(app: &AppHandle<R>) -> singbox::CoreStatus {
+    let supervisor = app.state::<singbox::Supervisor>();
+    supervisor.stop();
+    supervisor.status()
+}
+
+/// 重新检查并启动最近一次运行的配置
+fn restart_core_with<R: Runtime>(app: &AppHandle<R>) -> Result<singbox::CoreStatus, String> {
+    let launch = app
+        .state::<singbox::Supervisor>()
+        .launch()
+        .ok_or("sing-box has not been started")?;
+    start_core_with(app, launch.config)
+}
+
+/// 托盘“启动”使用的配置：最近一次运行的配置，否则为最近打开的文件
+#[cfg(desktop)]
+fn tray_core_config<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    app.state::<singbox::Supervisor>()
+        .launch()
+        .map(|launch| launch.config)
+        .or_else(|| update_recent_files(app, |_| {}).ok()?.into_iter().next())
+}
+
+/// 获取内核运行状态
+#[tauri::command]
+fn get_core_status(supervisor: State<'_, singbox::Supervisor>) -> singbox::CoreStatus {
+    supervisor.status()
+}
+
+/// 用保存的配置文件启动 sing-box，先运行 `sing-box check`，不通过时返回错误
+#[tauri::command]
+async fn start_core(app: AppHandle, path: String) -> Result<singbox::CoreStatus, String> {
+    start_core_with(&app, PathBuf::from(path))
+}
+
+/// 停止 sing-box
+#[tauri::command]
+async fn stop_core(app: AppHandle) -> singbox::CoreStatus {
+    stop_core_with(&app)
+}
+
+/// 重启 sing-box，重新读取并检查配置文件
+#[tauri::command]
+async fn restart_core(app: AppHandle) -> Result<singbox::CoreStatus, String> {
+    restart_core_with(&app)
+}
+
+/// 正在推送的 Clash API 订阅（traffic、memory）
+#[derive(Default)]
+struct ClashStreams(Mutex<Vec<clash_api::Subscription>>);
+
+impl ClashStreams {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<clash_api::Subscription>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 运行中内核的 Clash API 客户端，地址和密钥来自正在运行的配置文件
+fn clash_client<R: Runtime>(app: &AppHandle<R>) -> Result<clash_api::ClashClient, String> {
+    let supervisor = app.state::<singbox::Supervisor>();
+    let launch = supervisor
+        .launch()
+        .filter(|_| matches!(supervisor.status(), singbox::CoreStatus::Running { .. }))
+        .ok_or("sing-box is not running")?;
+    let text = std::fs::read_to_string(&launch.config).map_err(|e| e.to_string())?;
+    let config = config::Config::from_json(&text).map_err(|e| e.to_string())?;
+    clash_api::ClashClient::from_config(&config)
+        .ok_or_else(|| "experimental.clash_api is not enabled".to_string())
+}
+
+/// 获取全部出站和出站组
+#[tauri::command]
+async fn clash_proxies(app: AppHandle) -> Result<Vec<clash_api::Proxy>, String> {
+    clash_client(&app)?.proxies()
+}
+
+/// 切换 selector 出站选中的成员
+#[tauri::command]
+async fn clash_select(app: AppHandle, selector: String, member: String) -> Result<(), String> {
+    clash_client(&app)?.select(&selector, &member)?;
+    #[cfg(desktop)]
+    refresh_tray_selectors(&app);
+    Ok(())
+}
+
+/// 测试单个出站的延迟（毫秒）
+#[tauri::command]
+async fn clash_proxy_delay(
+    app: AppHandle,
+    name: String,
+    url: Option<String>,
+    timeout: Option<u32>,
+) -> Result<u32, String> {
+    clash_client(&app)?.proxy_delay(
+        &name,
+        url.as_deref().unwrap_or(clash_api::DEFAULT_TEST_URL),
+        timeout.unwrap_or(clash_api::DEFAULT_TEST_TIMEOUT),
+    )
+}
+
+/// 测试出站组（如 urltest）全部成员的延迟
+#[tauri::command]
+async fn clash_group_delay(
+    app: AppHandle,
+    group: String,
+    url: Option<String>,
+    timeout: Option<u32>,
+) -> Result<std::collections::BTreeMap<String, u32>, String> {
+    clash_client(&app)?.group_delay(
+        &group,
+        url.as_deref().unwrap_or(clash_api::DEFAULT_TEST_URL),
+        timeout.unwrap_or(clash_api::DEFAULT_TEST_TIMEOUT),
+    )
+}
+
+/// 获取当前连接
+#[tauri::command]
+async fn clash_connections(app: AppHandle) -> Result<clash_api::Connections, String> {
+    clash_client(&app)?.connections()
+}
+
+/// 关闭指定连接，不传 id 时关闭全部连接
+#[tauri::command]
+async fn clash_close_connections(app: AppHandle, id: Option<String>) -> Result<(), String> {
+    clash_client(&app)?.close_connections(id.as_deref())
+}
+
+/// 订阅 traffic 和 memory 推送，分别以 clash-traffic、clash-memory 事件发给前端
+#[tauri::command]
+async fn clash_subscribe(app: AppHandle) -> Result<(), String> {
+    let client = clash_client(&app)?;
+    let emitter = |event: &'static str| {
+        let app = app.clone();
+        move |payload: serde_json::Value| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.emit(event, payload);
+            }
+        }
+    };
+    let subscriptions = vec![
+        client.subscribe("/traffic", emitter("clash-traffic")),
+        client.subscribe("/memory", emitter("clash-memory")),
+    ];
+    let previous = std::mem::replace(&mut *app.state::<ClashStreams>().lock(), subscriptions);
+    drop(previous);
+    Ok(())
+}
+
+/// 停止 traffic 和 memory 推送
+#[tauri::command]
+async fn clash_unsubscribe(app: AppHandle) {
+    let subscriptions = std::mem::take(&mut *app.state::<ClashStreams>().lock());
+    drop(subscriptions);
+}
+
+/// 使用内置 schema.json 校验配置
+#[tauri::command]
+fn validate_config(config: serde_json::Value) -> validation::ValidationResult {
+    validation::validate(&config)
+}
+
+/// 校验后原子写入配置文件，保留 backups 份轮转备份（默认 3 份），并记录一次修订
+#[tauri::command]
+async fn save_config(
+    app: AppHandle,
+    path: String,
+    text: String,
+    backups: Option<usize>,
+    note: Option<String>,
+) -> Result<save::SaveResult, String> {
+    let history = file_history(&app, &path)?;
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    // 第一次在编辑器中保存时，先把磁盘上的原文件记为一次修订
+    let previous = history
+        .list()
+        .is_empty()
+        .then(|| std::fs::read_to_string(&path).ok())
+        .flatten();
+    let mut result = save::save_config(
+        Path::new(&path),
+        &text,
+        backups.unwrap_or(save::DEFAULT_BACKUPS),
+    )?;
+    let recorded = previous
+        .map_or(Ok(()), |previous| {
+            history.record(&previous, Some("Before first save")).map(drop)
+        })
+        .and_then(|()| history.record(&text, note.as_deref()).map(drop));
+    if let Err(e) = recorded {
+        result
+            .warnings
+            .push(format!("revision history is not updated: {e}"));
+    }
+    Ok(result)
+}
+
+/// 修订历史的读写加锁，避免保存和恢复同时修改索引
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// 配置文件的修订历史，保存在应用数据目录
+fn file_history<R: Runtime>(app: &AppHandle<R>, path: &str) -> Result<history::History, String> {
+    let root = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(history::DIR_NAME);
+    Ok(history::History::open(&root, Path::new(path)))
+}
+
+/// 列出配置文件的修订，最新的在前
+#[tauri::command]
+fn list_revisions(app: AppHandle, path: String) -> Result<Vec<history::Revision>, String> {
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(file_history(&app, &path)?.list())
+}
+
+/// 读取一次修订的内容
+#[tauri::command]
+fn get_revision(app: AppHandle, path: String, id: String) -> Result<String, String> {
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    file_history(&app, &path)?.read(&id)
+}
+
+/// 比较修订与当前配置；current 为空时与磁盘上的文件比较
+#[tauri::command]
+fn diff_revision(
+    app: AppHandle,
+    path: String,
+    id: String,
+    current: Option<serde_json::Value>,
+) -> Result<Vec<diff::Change>, String> {
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    file_history(&app, &path)?.diff(&id, current.as_ref())
+}
+
+/// 把修订写回配置文件；未通过校验的修订需要 force 为 true 才会写回
+#[tauri::command]
+fn restore_revision(
+    app: AppHandle,
+    path: String,
+    id: String,
+    backups: Option<usize>,
+    force: Option<bool>,
+) -> Result<history::RestoreResult, String> {
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    file_history(&app, &path)?.restore(
+        &id,
+        backups.unwrap_or(save::DEFAULT_BACKUPS),
+        force.unwrap_or(false),
+    )
+}
+
+/// 比较两份配置，编辑器的差异面板使用
+#[tauri::command]
+fn diff_configs(old: serde_json::Value, new: serde_json::Value) -> Vec<diff::Change> {
+    diff::diff(&old, &new)
+}
+
+/// 三方合并配置，冲突处保留 ours 的值
+#[tauri::command]
+fn merge_configs(
+    base: serde_json::Value,
+    ours: serde_json::Value,
+    theirs: serde_json::Value,
+) -> diff::Merge {
+    diff::merge(&base, &ours, &theirs)
+}
+
+/// 运行配置预检，可按规则 id 禁用部分检查
+#[tauri::command]
+fn run_preflight(
+    config: serde_json::Value,
+    locale: Option<String>,
+    disabled_rules: Option<Vec<String>>,
+) -> Result<Vec<preflight::PreflightIssue>, String> {
+    let mut engine = preflight::Preflight::new();
+    for id in disabled_rules.unwrap_or_default() {
+        engine.disable(&id);
+    }
+    engine
+        .run_value(config, locale.as_deref().unwrap_or("en"))
+        .map_err(|e| e.to_string())
+}
+
+/// 列出可用的预检规则 id
+#[tauri::command]
+fn list_preflight_rules() -> Vec<&'static str> {
+    preflight::Preflight::new().rule_ids()
+}
+
+/// 把配置迁移到目标版本（如 "1.12"），返回迁移后的配置及每个步骤的报告
+#[tauri::command]
+fn migrate_config(
+    config: serde_json::Value,
+    target_version: String,
+) -> Result<migration::MigrationResult, String> {
+    migration::migrate_value(config, &target_version)
+}
+
+/// 导入分享链接，返回出站及无法解析的行；existing_tags 用于避免标签重复
+#[tauri::command]
+fn import_share_links(
+    text: String,
+    existing_tags: Option<Vec<String>>,
+) -> share_link::ImportResult {
+    share_link::import_links(&text, &existing_tags.unwrap_or_default())
+}
+
+/// 把 Clash Meta YAML 转换为完整的 sing-box 配置，并列出无法转换的条目
+#[tauri::command]
+fn convert_clash_profile(text: String) -> Result<clash::ConvertResult, String> {
+    clash::convert_yaml(&text)
+}
+
+/// 把当前配置导出为 Clash Meta YAML，并列出有损转换的条目
+#[tauri::command]
+fn export_clash_profile(config: serde_json::Value) -> Result<clash::ExportResult, String> {
+    let config = config::Config::from_value(config).map_err(|e| e.to_string())?;
+    clash::export_profile(&config)
+}
+
+/// 导入 wg-quick 配置文件，返回 WireGuard 端点和经由它的 DNS 服务器
+#[tauri::command]
+fn import_wireguard_config(
+    text: String,
+    tag: String,
+    existing_tags: Option<Vec<String>>,
+) -> Result<wireguard::ConfImport, String> {
+    wireguard::import_conf(&text, &tag, &existing_tags.unwrap_or_default())
+}
+
+/// 生成出站的分享链接
+#[tauri::command]
+fn export_outbound_link(outbound: serde_json::Value) -> Result<String, String> {
+    let outbound: config::Outbound = serde_json::from_value(outbound).map_err(|e| e.to_string())?;
+    share_link::outbound_link(&outbound)
+}
+
+/// 由服务端入站生成每个用户的客户端链接，server 为客户端连接的地址
+#[tauri::command]
+fn export_inbound_links(
+    inbound: serde_json::Value,
+    server: String,
+) -> Result<Vec<share_link::ClientLink>, String> {
+    let inbound: config::Inbound = serde_json::from_value(inbound).map_err(|e| e.to_string())?;
+    share_link::inbound_links(&inbound, &server)
+}
+
+/// 渲染二维码：PNG 返回 data URL，SVG 返回 SVG 文本
+#[tauri::command]
+fn render_qr_code(text: String, format: share_link::QrFormat) -> Result<String, String> {
+    use base64::Engine;
+    match format {
+        share_link::QrFormat::Png => {
+            let png = share_link::qr_png(&text, 8)?;
+            let data = base64::engine::general_purpose::STANDARD.encode(png);
+            Ok(format!("data:image/png;base64,{}", data))
+        }
+        share_link::QrFormat::Svg => share_link::qr_svg(&text),
+    }
+}
+
+/// 把二维码保存为 PNG 或 SVG（按扩展名）
+#[tauri::command]
+async fn save_qr_code(text: String, path: String) -> Result<(), String> {
+    let path = PathBuf::from(path);
+    let format = share_link::QrFormat::from_path(&path)
+        .ok_or_else(|| "QR code file must end with .png or .svg".to_string())?;
+    let bytes = match format {
+        share_link::QrFormat::Png => share_link::qr_png(&text, 8)?,
+        share_link::QrFormat::Svg => share_link::qr_svg(&text)?.into_bytes(),
+    };
+    std::fs::write(&path, bytes).map_err(|e| e.to_string())
+}
+
+/// 把规则集源文件（JSON 文本）编译为 .srs，version 为空时使用源文件中的版本
+#[tauri::command]
+async fn compile_rule_set(source: String, output: String, version: Option<u8>) -> Result<(), String> {
+    let source = rule_set::RuleSetSource::from_json(&source)?;
+    let data = rule_set::compile(&source, version)?;
+    std::fs::write(&output, data).map_err(|e| format!("{}: {}", output, e))
+}
+
+/// 反编译 .srs 文件，返回规则集源格式
+#[tauri::command]
+async fn decompile_rule_set(path: String) -> Result<rule_set::RuleSetSource, String> {
+    rule_set::decompile_file(Path::new(&path))
+}
+
+/// 把 AdGuard DNS 过滤列表转换为规则集源文件，并统计无法转换的行
+#[tauri::command]
+fn convert_adguard_filter(text: String) -> rule_set::adguard::AdGuardConversion {
+    rule_set::adguard::convert(&text)
+}
+
+/// 读取 geoip.db / geosite.db，返回类型和其中的代码
+#[tauri::command]
+async fn inspect_geo_database(path: String) -> Result<geo::GeoInfo, String> {
+    geo::GeoDatabase::open(Path::new(&path)).map(|db| db.info())
+}
+
+/// 把数据库中的若干代码导出为规则集（binary 为真时为 .srs，否则为源文件），返回写入的文件
+#[tauri::command]
+async fn export_geo_rule_sets(
+    path: String,
+    codes: Vec<String>,
+    output_dir: String,
+    binary: bool,
+) -> Result<Vec<String>, String> {
+    let db = geo::GeoDatabase::open(Path::new(&path))?;
+    let files = geo::export(&db, &codes, Path::new(&output_dir), binary)?;
+    Ok(files.iter().map(|file| file.display().to_string()).collect())
+}
+
+/// 把配置中的 geoip / geosite 规则项改为引用从本地数据库导出的规则集
+#[tauri::command]
+async fn localize_geo_rule_sets(
+    config: serde_json::Value,
+    databases: Vec<String>,
+    output_dir: String,
+) -> Result<geo::LocalizeResult, String> {
+    let databases: Vec<PathBuf> = databases.into_iter().map(PathBuf::from).collect();
+    geo::localize_value(config, &databases, Path::new(&output_dir))
+}
+
+/// 模拟一条连接经过路由规则后的去向；config_path 用于定位相对路径的本地规则集
+#[tauri::command]
+fn simulate_route(
+    config: serde_json::Value,
+    connection: simulator::Connection,
+    config_path: Option<String>,
+) -> Result<simulator::route::RouteDecision, String> {
+    let config = config::Config::from_value(config).map_err(|e| e.to_string())?;
+    let base_dir = config_path
+        .as_deref()
+        .and_then(|path| Path::new(path).parent());
+    Ok(simulator::route::simulate(&config, &connection, base_dir))
+}
+
+/// 模拟一次 DNS 查询经过 dns.rules 的过程
+#[tauri::command]
+fn simulate_dns(
+    config: serde_json::Value,
+    query: simulator::dns::DnsQuery,
+    config_path: Option<String>,
+) -> Result<simulator::dns::DnsDecision, String> {
+    let config = config::Config::from_value(config).map_err(|e| e.to_string())?;
+    let base_dir = config_path
+        .as_deref()
+        .and_then(|path| Path::new(path).parent());
+    Ok(simulator::dns::simulate(&config, &query, base_dir))
+}
+
+/// 订阅列表文件，读写都要持有锁，避免定时刷新和前端修改互相覆盖
+#[derive(Default)]
+struct SubscriptionLock(Mutex<()>);
+
+fn subscriptions_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    config_file(app, subscription::FILE_NAME)
+}
+
+/// 读取订阅列表，修改后写回
+fn update_subscriptions<R: Runtime, T>(
+    app: &AppHandle<R>,
+    update: impl FnOnce(&mut subscription::Subscriptions) -> Result<T, String>,
+) -> Result<T, String> {
+    let lock = app.state::<SubscriptionLock>();
+    let _guard = lock.0.lock().unwrap_or_else(|e| e.into_inner());
+    let path = subscriptions_path(app)?;
+    let mut subscriptions = subscription::Subscriptions::load(&path);
+    let value = update(&mut subscriptions)?;
+    subscriptions.save(&path).map_err(|e| e.to_string())?;
+    Ok(value)
+}
+
+/// 刷新所有启用的订阅并通知前端合并到当前配置
+fn refresh_subscriptions_with<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<Vec<subscription::RefreshResult>, String> {
+    // 拉取可能很慢，只在读取和写回时持有锁，不阻塞对订阅列表的其他修改
+    let mut snapshot = {
+        let lock = app.state::<SubscriptionLock>();
+        let _guard = lock.0.lock().unwrap_or_else(|e| e.into_inner());
+        subscription::Subscriptions::load(&subscriptions_path(app)?)
+    };
+    let results = snapshot.refresh();
+    update_subscriptions(app, |subscriptions| {
+        subscriptions.merge_refreshed(snapshot.subscriptions);
+        Ok(())
+    })?;
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("subscriptions-updated", &results);
+    }
+    Ok(results)
+}
+
+/// 读取订阅列表和托管分组设置
+#[tauri::command]
+fn list_subscriptions(app: AppHandle) -> Result<subscription::Subscriptions, String> {
+    Ok(subscription::Subscriptions::load(&subscriptions_path(&app)?))
+}
+
+/// 添加或按名称替换订阅
+#[tauri::command]
+fn save_subscription(
+    app: AppHandle,
+    subscription: subscription::Subscription,
+) -> Result<subscription::Subscriptions, String> {
+    update_subscriptions(&app, |subscriptions| {
+        subscriptions.upsert(subscription)?;
+        Ok(subscriptions.clone())
+    })
+}
+
+/// 删除订阅，下次合并时会移除它的节点
+#[tauri::command]
+fn remove_subscription(app: AppHandle, name: String) -> Result<subscription::Subscriptions, String> {
+    update_subscriptions(&app, |subscriptions| {
+        subscriptions.remove(&name);
+        Ok(subscriptions.clone())
+    })
+}
+
+/// 设置托管分组和自动刷新间隔（分钟，0 为关闭）
+#[tauri::command]
+fn set_subscription_options(
+    app: AppHandle,
+    group: subscription::ManagedGroup,
+    interval_minutes: u64,
+) -> Result<subscription::Subscriptions, String> {
+    update_subscriptions(&app, |subscriptions| {
+        subscriptions.group = group;
+        subscriptions.interval_minutes = interval_minutes;
+        Ok(subscriptions.clone())
+    })
+}
+
+/// 立即刷新所有启用的订阅
+#[tauri::command]
+async fn refresh_subscriptions(app: AppHandle) -> Result<Vec<subscription::RefreshResult>, String> {
+    refresh_subscriptions_with(&app)
+}
+
+/// 把订阅节点合并到配置，替换上次合并的节点和托管分组
+#[tauri::command]
+fn apply_subscriptions(
+    app: AppHandle,
+    config: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let original = config;
+    let mut config = config::Config::from_value(original.clone()).map_err(|e| e.to_string())?;
+    // 记录本次生成的节点，下次合并时只替换这些节点
+    update_subscriptions(&app, |subscriptions| {
+        subscriptions.apply(&mut config);
+        Ok(())
+    })?;
+    config
+        .to_value_with_layout(&original)
+        .map_err(|e| e.to_string())
+}
+
 /// 退出应用（供前端快捷键调用）
 #[tauri::command]
 fn exit_app(app: AppHandle) {
@@ -67,25 +931,47 @@ pub fn run() {
     let mut builder = tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_fs::init());
+        .plugin(tauri_plugin_fs::init())
+        .manage(singbox::Supervisor::default())
+        .manage(ClashStreams::default())
+        .manage(SubscriptionLock::default());
     
     // 桌面平台特定功能
     #[cfg(desktop)]
     {
         builder = builder
             .setup(|app| {
-                // 获取初始语言（默认中文）
-                let locale = "zh";
+                app.manage(TraySelectors::default());
+                
+                // 每分钟检查一次订阅是否到了自动刷新时间
+                let handle = app.handle().clone();
+                app.manage(subscription::Scheduler::start(
+                    std::time::Duration::from_secs(60),
+                    move || {
+                        let due = subscriptions_path(&handle)
+                            .map(|path| subscription::Subscriptions::load(&path).is_due(subscription::now()))
+                            .unwrap_or(false);
+                        if due {
+                            if let Err(e) = refresh_subscriptions_with(&handle) {
+                                eprintln!("Failed to refresh subscriptions: {}", e);
+                            }
+                        }
+                    },
+                ));
+                
+                // 获取初始语言（已保存的选择或系统语言）
+                let locale = load_settings(app.handle()).locale();
                 
                 // 构建并设置应用菜单
-                let menu = build_menu(app.handle(), locale).map_err(|e| {
+                let recent = update_recent_files(app.handle(), |_| {}).unwrap_or_default();
+                let menu = build_menu(app.handle(), &locale, &recent).map_err(|e| {
                     eprintln!("Failed to build menu: {}", e);
                     e
                 })?;
                 app.set_menu(menu)?;
                 
                 // 构建并设置系统托盘
-                let _tray = build_tray(app.handle()).map_err(|e| {
+                let _tray = build_tray(app.handle(), &locale).map_err(|e| {
                     eprintln!("Failed to build system tray: {}", e);
                     e
                 })?;
@@ -113,6 +999,23 @@ pub fn run() {
                     return;
                 }
                 
+                // 最近文件列表由后端维护
+                if menu_id == "file_clear_recent" {
+                    if let Err(e) = clear_recent_files(app.clone()) {
+                        eprintln!("Failed to clear recent files: {}", e);
+                    }
+                    return;
+                }
+                if let Some(index) = menu_id
+                    .strip_prefix(RECENT_ITEM_PREFIX)
+                    .and_then(|index| index.parse().ok())
+                {
+                    if let Err(e) = open_recent_file(app, index) {
+                        eprintln!("Failed to open recent file: {}", e);
+                    }
+                    return;
+                }
+                
                 // 托盘菜单事件已在 TrayIconBuilder 的 on_menu_event 回调中处理
                 // 这里不再需要单独处理
                 
@@ -129,10 +1032,67 @@ pub fn run() {
         get_current_locale,
         update_menu_locale,
         exit_app,
-        set_window_title
+        set_window_title,
+        validate_config,
+        save_config,
+        list_revisions,
+        get_revision,
+        diff_revision,
+        restore_revision,
+        diff_configs,
+        merge_configs,
+        run_preflight,
+        list_preflight_rules,
+        migrate_config,
+        list_recent_files,
+        add_recent_file,
+        clear_recent_files,
+        get_core_info,
+        set_core_path,
+        run_core_check,
+        get_core_status,
+        start_core,
+        stop_core,
+        restart_core,
+        clash_proxies,
+        clash_select,
+        clash_proxy_delay,
+        clash_group_delay,
+        clash_connections,
+        clash_close_connections,
+        clash_subscribe,
+        clash_unsubscribe,
+        import_share_links,
+        convert_clash_profile,
+        export_clash_profile,
+        import_wireguard_config,
+        export_outbound_link,
+        export_inbound_links,
+        render_qr_code,
+        save_qr_code,
+        compile_rule_set,
+        decompile_rule_set,
+        convert_adguard_filter,
+        inspect_geo_database,
+        export_geo_rule_sets,
+        localize_geo_rule_sets,
+        simulate_route,
+        simulate_dns,
+        list_subscriptions,
+        save_subscription,
+        remove_subscription,
+        set_subscription_options,
+        refresh_subscriptions,
+        apply_subscriptions
     ]);
     
     builder
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // 退出前停止内核，让 sing-box 清理路由和系统代理
+            if let tauri::RunEvent::Exit = event {
+                app.state::<singbox::Supervisor>().stop();
+            }
+        });
 }
@@ -1,13 +1,17 @@
+use std::path::PathBuf;
 use tauri::AppHandle;
 use tauri::menu::{MenuBuilder, MenuItem, SubmenuBuilder, CheckMenuItemBuilder};
-use crate::menu_i18n::MenuI18n;
+use crate::menu_i18n::{available_locales, MenuI18n};
+
+/// 最近文件菜单项 id 前缀
+pub const RECENT_ITEM_PREFIX: &str = "file_recent_";
 
 /// 构建应用菜单（根据 Tauri 2 API）
-pub fn build_menu<R: tauri::Runtime>(app: &AppHandle<R>, locale: &str) -> Result<tauri::menu::Menu<R>, Box<dyn std::error::Error>> {
+pub fn build_menu<R: tauri::Runtime>(app: &AppHandle<R>, locale: &str, recent: &[PathBuf]) -> Result<tauri::menu::Menu<R>, Box<dyn std::error::Error>> {
     let i18n = MenuI18n::new(locale);
     
     // macOS 使用应用菜单，Windows/Linux 使用文件菜单
-    let file_menu = build_file_menu(app, &i18n)?;
+    let file_menu = build_file_menu(app, &i18n, recent)?;
     let edit_menu = build_edit_menu(app, &i18n)?;
     let view_menu = build_view_menu(app, &i18n)?;
     let tools_menu = build_tools_menu(app, &i18n)?;
@@ -58,7 +62,7 @@ fn build_app_menu<R: tauri::Runtime>(app: &AppHandle<R>, i18n: &MenuI18n) -> Res
 }
 
 /// 构建文件菜单（Windows/Linux）
-fn build_file_menu<R: tauri::Runtime>(app: &AppHandle<R>, i18n: &MenuI18n) -> Result<tauri::menu::Submenu<R>, Box<dyn std::error::Error>> {
+fn build_file_menu<R: tauri::Runtime>(app: &AppHandle<R>, i18n: &MenuI18n, recent: &[PathBuf]) -> Result<tauri::menu::Submenu<R>, Box<dyn std::error::Error>> {
     let new_item = MenuItem::with_id(
         app,
         "file_new",
@@ -79,15 +83,40 @@ fn build_file_menu<R: tauri::Runtime>(app: &AppHandle<R>, i18n: &MenuI18n) -> Re
         app,
         "file_clear_recent",
         i18n.file_clear_recent(),
-        true,
+        !recent.is_empty(),
         None::<&str>,
     )?;
     
-    // TODO: 添加最近文件列表项
-    let recent_submenu = SubmenuBuilder::new(app, i18n.file_open_recent())
+    // 最近文件列表项，id 中的序号对应列表中的位置
+    let mut recent_submenu = SubmenuBuilder::new(app, i18n.file_open_recent());
+    for (index, path) in recent.iter().enumerate() {
+        let item = MenuItem::with_id(
+            app,
+            format!("{}{}", RECENT_ITEM_PREFIX, index),
+            path.display().to_string(),
+            true,
+            None::<&str>,
+        )?;
+        recent_submenu = recent_submenu.item(&item);
+    }
+    if !recent.is_empty() {
+        recent_submenu = recent_submenu.separator();
+    }
+    let recent_submenu = recent_submenu
         .item(&clear_recent)
         .build()?;
     
+    // 导入子菜单，把其他格式的配置合并到当前配置
+    let import_submenu = SubmenuBuilder::new(app, i18n.file_import())
+        .item(&MenuItem::with_id(
+            app,
+            "file_import_wireguard",
+            i18n.file_import_wireguard(),
+            true,
+            None::<&str>,
+        )?)
+        .build()?;
+    
     let save_item = MenuItem::with_id(
         app,
         "file_save",
@@ -117,6 +146,7 @@ fn build_file_menu<R: tauri::Runtime>(app: &AppHandle<R>, i18n: &MenuI18n) -> Re
         .item(&new_item)
         .item(&open_item)
         .item(&recent_submenu)
+        .item(&import_submenu)
         .separator()
         .item(&save_item)
         .item(&save_as_item)
@@ -231,19 +261,15 @@ fn build_view_menu<R: tauri::Runtime>(app: &AppHandle<R>, i18n: &MenuI18n) -> Re
         Some("CmdOrCtrl+B"),
     )?;
     
-    // 语言选择使用复选框菜单项
-    let language_zh = CheckMenuItemBuilder::with_id("view_language_zh", i18n.view_language_zh())
-        .checked(i18n.is_zh())
-        .build(app)?;
-    
-    let language_en = CheckMenuItemBuilder::with_id("view_language_en", i18n.view_language_en())
-        .checked(!i18n.is_zh())
-        .build(app)?;
-    
-    let language_submenu = SubmenuBuilder::new(app, i18n.view_language())
-        .item(&language_zh)
-        .item(&language_en)
-        .build()?;
+    // 语言选择使用复选框菜单项，按内置目录表生成
+    let mut language_submenu = SubmenuBuilder::new(app, i18n.view_language());
+    for (code, name) in available_locales() {
+        let item = CheckMenuItemBuilder::with_id(format!("view_language_{}", code), name)
+            .checked(i18n.locale == code)
+            .build(app)?;
+        language_submenu = language_submenu.item(&item);
+    }
+    let language_submenu = language_submenu.build()?;
     
     let menu = SubmenuBuilder::new(app, i18n.view_menu())
         .item(&form_mode)
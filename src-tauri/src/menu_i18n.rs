@@ -1,328 +1,163 @@
-/// 菜单多语言文本定义
-pub struct MenuI18n {
-    pub locale: String,
+//! 菜单多语言文本
+//!
+//! 文本来自 `locales/menu/<locale>.json` 目录表，编译时内置。缺失的语言或
+//! 文本回退到英文。
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+/// 内置的目录表，顺序即语言子菜单中的顺序
+const CATALOGS: [(&str, &str); 5] = [
+    ("zh", include_str!("../locales/menu/zh.json")),
+    ("en", include_str!("../locales/menu/en.json")),
+    ("ja", include_str!("../locales/menu/ja.json")),
+    ("ru", include_str!("../locales/menu/ru.json")),
+    ("fa", include_str!("../locales/menu/fa.json")),
+];
+
+/// 回退语言
+pub const FALLBACK_LOCALE: &str = "en";
+
+/// 单个语言的目录表
+#[derive(Debug, Deserialize)]
+pub struct Catalog {
+    /// 语言自称，用于语言子菜单
+    pub name: String,
+    pub messages: HashMap<String, String>,
 }
 
-impl MenuI18n {
-    pub fn new(locale: &str) -> Self {
-        Self {
-            locale: locale.to_string(),
-        }
-    }
-
-    pub fn is_zh(&self) -> bool {
-        self.locale == "zh"
-    }
-
-    // 应用菜单（macOS）或文件菜单
-    pub fn app_name(&self) -> &str {
-        if self.is_zh() {
-            "sing-config"
-        } else {
-            "sing-config"
-        }
-    }
-
-    pub fn file_menu(&self) -> &str {
-        if self.is_zh() {
-            "文件"
-        } else {
-            "File"
-        }
-    }
-
-    pub fn file_new(&self) -> &str {
-        if self.is_zh() {
-            "新建"
-        } else {
-            "New"
-        }
-    }
+/// 解析后的目录表，按 CATALOGS 的顺序排列
+fn catalogs() -> &'static [(&'static str, Catalog)] {
+    static PARSED: OnceLock<Vec<(&'static str, Catalog)>> = OnceLock::new();
+    PARSED.get_or_init(|| {
+        CATALOGS
+            .iter()
+            .filter_map(|(code, text)| match serde_json::from_str(text) {
+                Ok(catalog) => Some((*code, catalog)),
+                Err(e) => {
+                    eprintln!("Failed to parse menu catalog {}: {}", code, e);
+                    None
+                }
+            })
+            .collect()
+    })
+}
 
-    pub fn file_open(&self) -> &str {
-        if self.is_zh() {
-            "打开"
-        } else {
-            "Open"
-        }
-    }
+fn catalog(locale: &str) -> Option<&'static Catalog> {
+    catalogs()
+        .iter()
+        .find(|(code, _)| *code == locale)
+        .map(|(_, catalog)| catalog)
+}
 
-    pub fn file_open_recent(&self) -> &str {
-        if self.is_zh() {
-            "打开最近"
-        } else {
-            "Open Recent"
-        }
-    }
+/// 可用语言（代码, 语言自称）
+pub fn available_locales() -> Vec<(&'static str, &'static str)> {
+    catalogs()
+        .iter()
+        .map(|(code, catalog)| (*code, catalog.name.as_str()))
+        .collect()
+}
 
-    pub fn file_clear_recent(&self) -> &str {
-        if self.is_zh() {
-            "清除列表"
-        } else {
-            "Clear List"
-        }
-    }
+/// 是否有该语言的目录表
+pub fn has_locale(locale: &str) -> bool {
+    catalog(locale).is_some()
+}
 
-    pub fn file_save(&self) -> &str {
-        if self.is_zh() {
-            "保存"
-        } else {
-            "Save"
-        }
-    }
+/// 菜单多语言文本定义
+pub struct MenuI18n {
+    pub locale: String,
+}
 
-    pub fn file_save_as(&self) -> &str {
-        if self.is_zh() {
-            "另存为"
-        } else {
-            "Save As"
-        }
-    }
+/// 为每个文本键生成同名的访问方法
+macro_rules! menu_texts {
+    ($($key:ident),* $(,)?) => {
+        /// 菜单使用的全部文本键
+        pub const MENU_KEYS: &[&str] = &[$(stringify!($key)),*];
 
-    pub fn file_quit(&self) -> &str {
-        if self.is_zh() {
-            "退出"
-        } else {
-            "Quit"
+        impl MenuI18n {
+            $(
+                pub fn $key(&self) -> &str {
+                    self.text(stringify!($key))
+                }
+            )*
         }
-    }
+    };
+}
 
+menu_texts! {
+    // 应用菜单（macOS）或文件菜单
+    app_name,
+    file_menu,
+    file_new,
+    file_open,
+    file_open_recent,
+    file_clear_recent,
+    file_import,
+    file_import_wireguard,
+    file_save,
+    file_save_as,
+    file_quit,
     // 编辑菜单
-    pub fn edit_menu(&self) -> &str {
-        if self.is_zh() {
-            "编辑"
-        } else {
-            "Edit"
-        }
-    }
-
-    pub fn edit_undo(&self) -> &str {
-        if self.is_zh() {
-            "撤销"
-        } else {
-            "Undo"
-        }
-    }
-
-    pub fn edit_redo(&self) -> &str {
-        if self.is_zh() {
-            "重做"
-        } else {
-            "Redo"
-        }
-    }
-
-    pub fn edit_cut(&self) -> &str {
-        if self.is_zh() {
-            "剪切"
-        } else {
-            "Cut"
-        }
-    }
-
-    pub fn edit_copy(&self) -> &str {
-        if self.is_zh() {
-            "复制"
-        } else {
-            "Copy"
-        }
-    }
-
-    pub fn edit_paste(&self) -> &str {
-        if self.is_zh() {
-            "粘贴"
-        } else {
-            "Paste"
-        }
-    }
-
-    pub fn edit_find(&self) -> &str {
-        if self.is_zh() {
-            "查找"
-        } else {
-            "Find"
-        }
-    }
-
-    pub fn edit_replace(&self) -> &str {
-        if self.is_zh() {
-            "替换"
-        } else {
-            "Replace"
-        }
-    }
-
-    pub fn edit_format(&self) -> &str {
-        if self.is_zh() {
-            "格式化"
-        } else {
-            "Format"
-        }
-    }
-
+    edit_menu,
+    edit_undo,
+    edit_redo,
+    edit_cut,
+    edit_copy,
+    edit_paste,
+    edit_find,
+    edit_replace,
+    edit_format,
     // 视图菜单
-    pub fn view_menu(&self) -> &str {
-        if self.is_zh() {
-            "视图"
-        } else {
-            "View"
-        }
-    }
-
-    pub fn view_form_mode(&self) -> &str {
-        if self.is_zh() {
-            "表单模式"
-        } else {
-            "Form Mode"
-        }
-    }
-
-    pub fn view_json_mode(&self) -> &str {
-        if self.is_zh() {
-            "JSON 模式"
-        } else {
-            "JSON Mode"
-        }
-    }
-
-    pub fn view_toggle_sidebar(&self) -> &str {
-        if self.is_zh() {
-            "显示/隐藏侧边栏"
-        } else {
-            "Toggle Sidebar"
-        }
-    }
-
-    pub fn view_language(&self) -> &str {
-        if self.is_zh() {
-            "语言"
-        } else {
-            "Language"
-        }
-    }
-
-    pub fn view_language_zh(&self) -> &str {
-        "中文"
-    }
-
-    pub fn view_language_en(&self) -> &str {
-        "English"
-    }
-
+    view_menu,
+    view_form_mode,
+    view_json_mode,
+    view_toggle_sidebar,
+    view_language,
     // 工具菜单
-    pub fn tools_menu(&self) -> &str {
-        if self.is_zh() {
-            "工具"
-        } else {
-            "Tools"
-        }
-    }
-
-    pub fn tools_run_check(&self) -> &str {
-        if self.is_zh() {
-            "运行检查"
-        } else {
-            "Run Check"
-        }
-    }
-
-    pub fn tools_run_validation(&self) -> &str {
-        if self.is_zh() {
-            "运行验证"
-        } else {
-            "Run Validation"
-        }
-    }
-
-    pub fn tools_wizard(&self) -> &str {
-        if self.is_zh() {
-            "向导"
-        } else {
-            "Wizard"
-        }
-    }
-
-    pub fn tools_templates(&self) -> &str {
-        if self.is_zh() {
-            "模板库"
-        } else {
-            "Template Library"
-        }
-    }
-
+    tools_menu,
+    tools_run_check,
+    tools_run_validation,
+    tools_wizard,
+    tools_templates,
     // 设置菜单
-    pub fn settings_menu(&self) -> &str {
-        if self.is_zh() {
-            "设置"
-        } else {
-            "Settings"
-        }
-    }
-
-    pub fn settings_preferences(&self) -> &str {
-        if self.is_zh() {
-            "偏好设置"
-        } else {
-            "Preferences"
-        }
-    }
-
+    settings_menu,
+    settings_preferences,
     // 帮助菜单
-    pub fn help_menu(&self) -> &str {
-        if self.is_zh() {
-            "帮助"
-        } else {
-            "Help"
-        }
-    }
-
-    pub fn help_shortcuts(&self) -> &str {
-        if self.is_zh() {
-            "快捷键"
-        } else {
-            "Keyboard Shortcuts"
-        }
-    }
-
-    pub fn help_documentation(&self) -> &str {
-        if self.is_zh() {
-            "文档"
-        } else {
-            "Documentation"
-        }
-    }
-
-    pub fn help_about(&self) -> &str {
-        if self.is_zh() {
-            "关于"
-        } else {
-            "About"
-        }
-    }
-
+    help_menu,
+    help_shortcuts,
+    help_documentation,
+    help_about,
     // 托盘菜单
-    pub fn tray_show(&self) -> &str {
-        if self.is_zh() {
-            "显示主窗口"
-        } else {
-            "Show Main Window"
-        }
-    }
+    tray_show,
+    tray_hide,
+    tray_quit,
+    tray_core_start,
+    tray_core_stop,
+    tray_core_restart,
+    tray_core_stopped,
+    tray_core_running,
+    tray_core_restarting,
+    tray_core_failed,
+    tray_selectors,
+    tray_refresh_subscriptions,
+}
 
-    pub fn tray_hide(&self) -> &str {
-        if self.is_zh() {
-            "隐藏窗口"
-        } else {
-            "Hide Window"
+impl MenuI18n {
+    pub fn new(locale: &str) -> Self {
+        Self {
+            locale: locale.to_string(),
         }
     }
 
-    pub fn tray_quit(&self) -> &str {
-        if self.is_zh() {
-            "退出"
-        } else {
-            "Quit"
-        }
+    /// 按键取文本，当前语言缺失时回退到英文，仍缺失则返回键本身
+    pub fn text<'a>(&self, key: &'a str) -> &'a str {
+        [self.locale.as_str(), FALLBACK_LOCALE]
+            .into_iter()
+            .filter_map(catalog)
+            .find_map(|catalog| catalog.messages.get(key))
+            .map(String::as_str)
+            .unwrap_or(key)
     }
 }
-
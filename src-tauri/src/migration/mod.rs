@@ -0,0 +1,289 @@
+//! 配置迁移，参考 docs/migration.md
+//!
+//! 每个破坏性变更对应一个带 id 的迁移步骤，按版本顺序执行。步骤只处理
+//! 旧字段，对已是新格式的配置不做改动，因此可以重复执行。
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::config::{Config, DnsRule, RouteRule};
+
+mod v1_10;
+mod v1_11;
+mod v1_12;
+mod v1_8;
+
+// 官方 geoip / geosite 规则集的下载地址，Clash 转换也会用到
+pub(crate) use v1_8::{GEOIP_URL, GEOSITE_URL};
+
+/// sing-box 版本号（只比较主次版本）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    /// 解析 "1.12"、"1.12.0"、"v1.12.0-beta.1" 等写法
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim().trim_start_matches('v');
+        let mut parts = text.split(['.', '-']);
+        let mut number = || -> Option<u32> { parts.next()?.parse().ok() };
+        match (number(), number()) {
+            (Some(major), Some(minor)) => Ok(Self::new(major, minor)),
+            _ => Err(format!("invalid version: {text}")),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// 迁移可达的最新版本
+pub const LATEST: Version = Version::new(1, 12);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    /// 已执行并修改了配置
+    Applied,
+    /// 已执行，但配置中没有需要迁移的内容
+    Unchanged,
+    /// 版本高于目标版本，未执行
+    Skipped,
+}
+
+/// 单个迁移步骤的执行报告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepReport {
+    pub id: String,
+    pub version: String,
+    pub title: String,
+    pub status: StepStatus,
+    pub changes: Vec<String>,
+    /// 无法自动迁移、需要人工确认的内容
+    pub warnings: Vec<String>,
+}
+
+impl StepReport {
+    pub(crate) fn change(&mut self, message: impl Into<String>) {
+        self.changes.push(message.into());
+    }
+
+    pub(crate) fn warn(&mut self, message: impl Into<String>) {
+        self.warnings.push(message.into());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationResult {
+    pub config: Value,
+    pub steps: Vec<StepReport>,
+}
+
+impl MigrationResult {
+    /// 是否有步骤修改了配置
+    pub fn changed(&self) -> bool {
+        self.steps
+            .iter()
+            .any(|step| step.status == StepStatus::Applied)
+    }
+}
+
+/// 迁移步骤
+pub struct MigrationStep {
+    pub id: &'static str,
+    /// 引入该变更的版本
+    pub version: Version,
+    pub title: &'static str,
+    apply: fn(&mut Config, &mut StepReport),
+}
+
+impl MigrationStep {
+    fn report(&self, status: StepStatus) -> StepReport {
+        StepReport {
+            id: self.id.to_string(),
+            version: self.version.to_string(),
+            title: self.title.to_string(),
+            status,
+            changes: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// 执行本步骤，不检查版本
+    pub fn run(&self, config: &mut Config) -> StepReport {
+        let mut report = self.report(StepStatus::Unchanged);
+        (self.apply)(config, &mut report);
+        if !report.changes.is_empty() {
+            report.status = StepStatus::Applied;
+        }
+        report
+    }
+}
+
+/// 全部迁移步骤，按执行顺序排列
+pub fn steps() -> Vec<MigrationStep> {
+    vec![
+        MigrationStep {
+            id: "clash-api-cache-file",
+            version: Version::new(1, 8),
+            title: "Move Clash API cache settings to experimental.cache_file",
+            apply: v1_8::cache_file,
+        },
+        MigrationStep {
+            id: "geoip-to-rule-set",
+            version: Version::new(1, 8),
+            title: "Replace GeoIP with rule-sets",
+            apply: v1_8::geoip,
+        },
+        MigrationStep {
+            id: "geosite-to-rule-set",
+            version: Version::new(1, 8),
+            title: "Replace Geosite with rule-sets",
+            apply: v1_8::geosite,
+        },
+        MigrationStep {
+            id: "tun-address",
+            version: Version::new(1, 10),
+            title: "Merge TUN address fields",
+            apply: v1_10::tun_address,
+        },
+        MigrationStep {
+            id: "rule-set-ip-cidr-match-source",
+            version: Version::new(1, 10),
+            title: "Rename rule_set_ipcidr_match_source",
+            apply: v1_10::rule_set_ip_cidr_match_source,
+        },
+        MigrationStep {
+            id: "inbound-fields",
+            version: Version::new(1, 11),
+            title: "Replace legacy inbound fields with rule actions",
+            apply: v1_11::inbound_fields,
+        },
+        MigrationStep {
+            id: "special-outbounds",
+            version: Version::new(1, 11),
+            title: "Replace block and dns outbounds with rule actions",
+            apply: v1_11::special_outbounds,
+        },
+        MigrationStep {
+            id: "destination-override",
+            version: Version::new(1, 11),
+            title: "Move direct outbound destination override to route options",
+            apply: v1_11::destination_override,
+        },
+        MigrationStep {
+            id: "wireguard-endpoint",
+            version: Version::new(1, 11),
+            title: "Convert WireGuard outbounds to endpoints",
+            apply: v1_11::wireguard_endpoint,
+        },
+        MigrationStep {
+            id: "dns-rule-server",
+            version: Version::new(1, 12),
+            title: "Convert DNS rule server lists to a single tag",
+            apply: v1_12::rule_server,
+        },
+        MigrationStep {
+            id: "dns-servers",
+            version: Version::new(1, 12),
+            title: "Convert DNS servers to the new format",
+            apply: v1_12::dns_servers,
+        },
+        MigrationStep {
+            id: "domain-resolver",
+            version: Version::new(1, 12),
+            title: "Replace outbound DNS rules and domain_strategy with domain_resolver",
+            apply: v1_12::domain_resolver,
+        },
+    ]
+}
+
+/// 把配置迁移到目标版本
+pub fn migrate(mut config: Config, target: Version) -> Result<(Config, Vec<StepReport>), String> {
+    if target > LATEST {
+        return Err(format!(
+            "unsupported target version {target}, latest is {LATEST}"
+        ));
+    }
+
+    let reports = steps()
+        .into_iter()
+        .map(|step| {
+            if step.version <= target {
+                step.run(&mut config)
+            } else {
+                step.report(StepStatus::Skipped)
+            }
+        })
+        .collect();
+    Ok((config, reports))
+}
+
+/// 迁移 JSON 配置，目标版本写法同 [`Version::parse`]
+pub fn migrate_value(config: Value, target: &str) -> Result<MigrationResult, String> {
+    let target = Version::parse(target)?;
+    let original = config;
+    let config = Config::from_value(original.clone()).map_err(|e| e.to_string())?;
+    let (config, steps) = migrate(config, target)?;
+    let config = config
+        .to_value_with_layout(&original)
+        .map_err(|e| e.to_string())?;
+    Ok(MigrationResult { config, steps })
+}
+
+/// 从 `extra` 中取出旧字段，保持其余字段顺序不变
+///
+/// 值为 null 时视为未设置并移除；值的格式不对时保留原字段并记录警告，不静默丢弃
+pub(crate) fn take<T: DeserializeOwned>(
+    extra: &mut Map<String, Value>,
+    key: &str,
+    report: &mut StepReport,
+) -> Option<T> {
+    let value = extra.get(key)?;
+    if value.is_null() {
+        extra.shift_remove(key);
+        return None;
+    }
+    match T::deserialize(value) {
+        Ok(parsed) => {
+            extra.shift_remove(key);
+            Some(parsed)
+        }
+        Err(e) => {
+            report.warn(format!("{key}: cannot migrate {value}: {e}"));
+            None
+        }
+    }
+}
+
+/// 依次访问路由规则及逻辑规则中的子规则
+pub(crate) fn visit_route_rules(rules: &mut [RouteRule], f: &mut impl FnMut(&mut RouteRule)) {
+    for rule in rules {
+        f(rule);
+        if let Some(children) = rule.rules.as_mut() {
+            visit_route_rules(children, f);
+        }
+    }
+}
+
+/// 依次访问 DNS 规则及逻辑规则中的子规则
+pub(crate) fn visit_dns_rules(rules: &mut [DnsRule], f: &mut impl FnMut(&mut DnsRule)) {
+    for rule in rules {
+        f(rule);
+        if let Some(children) = rule.rules.as_mut() {
+            visit_dns_rules(children, f);
+        }
+    }
+}
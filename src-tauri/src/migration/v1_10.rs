@@ -0,0 +1,135 @@
+//! 1.10.0：TUN 地址字段合并、规则集源地址匹配字段改名
+
+use serde_json::Value;
+
+use crate::config::{Config, InboundType, Listable};
+
+use super::{visit_dns_rules, visit_route_rules, StepReport};
+
+/// inet4_*/inet6_* 地址字段合并为不区分协议族的字段
+const TUN_FIELDS: [(&str, &str, &str); 3] = [
+    ("address", "inet4_address", "inet6_address"),
+    (
+        "route_address",
+        "inet4_route_address",
+        "inet6_route_address",
+    ),
+    (
+        "route_exclude_address",
+        "inet4_route_exclude_address",
+        "inet6_route_exclude_address",
+    ),
+];
+
+pub(super) fn tun_address(config: &mut Config, report: &mut StepReport) {
+    let Some(inbounds) = config.inbounds.as_mut() else {
+        return;
+    };
+    for (index, inbound) in inbounds.iter_mut().enumerate() {
+        if inbound.kind != InboundType::Tun {
+            continue;
+        }
+        let name = inbound
+            .tag
+            .clone()
+            .unwrap_or_else(|| format!("inbounds[{index}]"));
+        for (merged, inet4, inet6) in TUN_FIELDS {
+            // 格式不对的字段原样保留并给出警告
+            if let Some(value) = inbound
+                .extra
+                .get(merged)
+                .filter(|value| strings(value).is_none())
+            {
+                if inbound.extra.contains_key(inet4) || inbound.extra.contains_key(inet6) {
+                    report.warn(format!(
+                        "{name}: cannot merge {inet4}/{inet6} into {merged} {value}, expected a list of addresses"
+                    ));
+                }
+                continue;
+            }
+            let mut legacy = Vec::new();
+            for key in [inet4, inet6] {
+                let Some(value) = inbound.extra.get(key) else {
+                    continue;
+                };
+                match strings(value) {
+                    Some(values) => {
+                        inbound.extra.shift_remove(key);
+                        legacy.extend(values);
+                    }
+                    None => report.warn(format!(
+                        "{name}: cannot migrate {key} {value}, expected an address or a list of addresses"
+                    )),
+                }
+            }
+            if legacy.is_empty() {
+                continue;
+            }
+
+            if merged == "address" {
+                let mut address = inbound
+                    .address
+                    .take()
+                    .map(Listable::into_vec)
+                    .unwrap_or_default();
+                append_unique(&mut address, legacy);
+                inbound.address = Some(Listable::Many(address));
+            } else {
+                let mut values = inbound
+                    .extra
+                    .get(merged)
+                    .and_then(strings)
+                    .unwrap_or_default();
+                append_unique(&mut values, legacy);
+                inbound.extra.insert(merged.to_string(), values.into());
+            }
+            report.change(format!("{name}: merged {inet4}/{inet6} into {merged}"));
+        }
+    }
+}
+
+/// rule_set_ipcidr_match_source 改名为 rule_set_ip_cidr_match_source
+pub(super) fn rule_set_ip_cidr_match_source(config: &mut Config, report: &mut StepReport) {
+    let mut renamed = 0;
+    if let Some(rules) = config.route.as_mut().and_then(|route| route.rules.as_mut()) {
+        visit_route_rules(rules, &mut |rule| {
+            if let Some(value) = rule.items.rule_set_ipcidr_match_source.take() {
+                rule.items.rule_set_ip_cidr_match_source = Some(value);
+                renamed += 1;
+            }
+        });
+    }
+    if let Some(rules) = config.dns.as_mut().and_then(|dns| dns.rules.as_mut()) {
+        visit_dns_rules(rules, &mut |rule| {
+            if let Some(value) = rule.items.rule_set_ipcidr_match_source.take() {
+                rule.items.rule_set_ip_cidr_match_source = Some(value);
+                renamed += 1;
+            }
+        });
+    }
+    if renamed > 0 {
+        report.change(format!(
+            "renamed rule_set_ipcidr_match_source to rule_set_ip_cidr_match_source in {renamed} rule(s)"
+        ));
+    }
+}
+
+/// 字段可以写成单个字符串或字符串数组，其他写法返回 None
+fn strings(value: &Value) -> Option<Vec<String>> {
+    match value {
+        Value::String(value) => Some(vec![value.clone()]),
+        Value::Array(values) => values
+            .iter()
+            .map(|value| value.as_str().map(str::to_string))
+            .collect(),
+        _ => None,
+    }
+}
+
+fn append_unique(target: &mut Vec<String>, values: Vec<String>) {
+    for value in values {
+        if !target.contains(&value) {
+            target.push(value);
+        }
+    }
+}
@@ -0,0 +1,416 @@
+//! 1.11.0：特殊出站、入站字段与目标地址覆盖改为规则动作，WireGuard 出站改为端点
+
+use serde_json::Value;
+
+use crate::config::{
+    Config, Endpoint, EndpointType, Listable, OutboundType, Route, RouteAction, RouteRule,
+    RuleItems, WireGuardPeer,
+};
+
+use super::{take, StepReport};
+
+/// 入站的 sniff/domain_strategy 等字段改为以入站标签匹配的路由规则
+///
+/// 生成的规则插入到现有规则之前，先嗅探再解析，与旧版的处理顺序一致。
+pub(super) fn inbound_fields(config: &mut Config, report: &mut StepReport) {
+    let Some(inbounds) = config.inbounds.as_mut() else {
+        return;
+    };
+    let mut used_tags: Vec<String> = inbounds
+        .iter()
+        .filter_map(|inbound| inbound.tag.clone())
+        .collect();
+    let mut generated = Vec::new();
+
+    for inbound in inbounds.iter_mut() {
+        let sniff: Option<bool> = take(&mut inbound.extra, "sniff", report);
+        let sniff_timeout: Option<String> = take(&mut inbound.extra, "sniff_timeout", report);
+        let override_destination: Option<bool> =
+            take(&mut inbound.extra, "sniff_override_destination", report);
+        let domain_strategy: Option<String> = take(&mut inbound.extra, "domain_strategy", report);
+        let udp_disable_domain_unmapping: Option<bool> =
+            take(&mut inbound.extra, "udp_disable_domain_unmapping", report);
+        let sniff = sniff == Some(true);
+        let udp_disable_domain_unmapping = udp_disable_domain_unmapping == Some(true);
+        if !sniff && domain_strategy.is_none() && !udp_disable_domain_unmapping {
+            if sniff_timeout.is_some() || override_destination.is_some() {
+                report.change(format!(
+                    "{}: removed unused sniff options",
+                    inbound.tag.as_deref().unwrap_or(inbound.kind.as_str())
+                ));
+            }
+            continue;
+        }
+
+        let tag = match inbound.tag.clone() {
+            Some(tag) => tag,
+            None => {
+                let tag = unique_tag(&format!("{}-in", inbound.kind), &used_tags);
+                used_tags.push(tag.clone());
+                inbound.tag = Some(tag.clone());
+                report.change(format!("tagged {} inbound as {tag}", inbound.kind));
+                tag
+            }
+        };
+        let matcher = || RuleItems {
+            inbound: Some(Listable::One(tag.clone())),
+            ..Default::default()
+        };
+
+        if sniff {
+            generated.push(RouteRule {
+                items: matcher(),
+                action: Some(RouteAction::Sniff),
+                timeout: sniff_timeout,
+                ..Default::default()
+            });
+            report.change(format!("{tag}: replaced sniff with a sniff rule action"));
+            if override_destination == Some(true) {
+                report.warn(format!(
+                    "{tag}: sniff_override_destination has no rule action equivalent and was removed"
+                ));
+            }
+        }
+        if let Some(strategy) = domain_strategy {
+            generated.push(RouteRule {
+                items: matcher(),
+                action: Some(RouteAction::Resolve),
+                strategy: Some(strategy),
+                ..Default::default()
+            });
+            report.change(format!(
+                "{tag}: replaced domain_strategy with a resolve rule action"
+            ));
+        }
+        if udp_disable_domain_unmapping {
+            let mut rule = RouteRule {
+                items: matcher(),
+                action: Some(RouteAction::RouteOptions),
+                ..Default::default()
+            };
+            rule.extra.insert(
+                "udp_disable_domain_unmapping".to_string(),
+                Value::Bool(true),
+            );
+            generated.push(rule);
+            report.change(format!(
+                "{tag}: moved udp_disable_domain_unmapping to route options"
+            ));
+        }
+    }
+
+    if !generated.is_empty() {
+        let route = config.route.get_or_insert_with(Route::default);
+        let rules = route.rules.get_or_insert_with(Vec::new);
+        rules.splice(0..0, generated);
+    }
+}
+
+/// block/dns 出站改为 reject/hijack-dns 动作
+pub(super) fn special_outbounds(config: &mut Config, report: &mut StepReport) {
+    let Some(outbounds) = config.outbounds.as_mut() else {
+        return;
+    };
+    let mut block = Vec::new();
+    let mut dns = Vec::new();
+    outbounds.retain(|outbound| {
+        let target = match outbound.kind {
+            OutboundType::Block => &mut block,
+            OutboundType::Dns => &mut dns,
+            _ => return true,
+        };
+        target.push(outbound.tag.clone().unwrap_or_default());
+        false
+    });
+    if block.is_empty() && dns.is_empty() {
+        return;
+    }
+    for tag in block.iter().chain(&dns) {
+        report.change(format!("removed outbound {tag}"));
+    }
+
+    for outbound in outbounds.iter_mut() {
+        if let Some(members) = outbound.outbounds.as_mut() {
+            let before = members.len();
+            members.retain(|member| !block.contains(member) && !dns.contains(member));
+            if members.len() != before {
+                report.warn(format!(
+                    "{}: removed block/dns members from the group",
+                    outbound.tag.as_deref().unwrap_or_default()
+                ));
+            }
+        }
+    }
+
+    clear_references(config, &[block.as_slice(), dns.as_slice()].concat(), report);
+
+    let Some(route) = config.route.as_mut() else {
+        return;
+    };
+    let action_for = |tag: &str| {
+        if block.iter().any(|t| t == tag) {
+            Some(RouteAction::Reject)
+        } else if dns.iter().any(|t| t == tag) {
+            Some(RouteAction::HijackDns)
+        } else {
+            None
+        }
+    };
+
+    let rules = route.rules.get_or_insert_with(Vec::new);
+    let mut hijack = false;
+    for (index, rule) in rules.iter_mut().enumerate() {
+        if rule.action() != RouteAction::Route {
+            continue;
+        }
+        let Some(action) = rule.outbound.as_deref().and_then(action_for) else {
+            continue;
+        };
+        hijack |= action == RouteAction::HijackDns;
+        report.change(format!(
+            "route.rules[{index}]: replaced outbound with {action} action"
+        ));
+        rule.outbound = None;
+        rule.action = Some(action);
+    }
+
+    if let Some(action) = route.final_outbound.as_deref().and_then(action_for) {
+        hijack |= action == RouteAction::HijackDns;
+        report.change(format!(
+            "replaced route.final with a trailing {action} rule"
+        ));
+        route.final_outbound = None;
+        rules.push(RouteRule {
+            action: Some(action),
+            ..Default::default()
+        });
+    }
+
+    // hijack-dns 依赖嗅探出的协议，旧版由入站的 sniff 隐式完成
+    if hijack && !rules.iter().any(|rule| rule.action() == RouteAction::Sniff) {
+        rules.insert(
+            0,
+            RouteRule {
+                action: Some(RouteAction::Sniff),
+                ..Default::default()
+            },
+        );
+        report.change("added a sniff rule so hijack-dns can match DNS traffic");
+    }
+    if rules.is_empty() {
+        route.rules = None;
+    }
+}
+
+/// 清除 selector 的 default 以及各处 detour 中指向已移除出站的引用，使用 sing-box 的默认值
+fn clear_references(config: &mut Config, removed: &[String], report: &mut StepReport) {
+    let mut clear = |field: &mut Option<String>, location: &str, name: &str| {
+        let Some(tag) = field
+            .as_deref()
+            .filter(|tag| removed.iter().any(|t| t == tag))
+        else {
+            return;
+        };
+        report.warn(format!(
+            "{location}: cleared {name} {tag}, the outbound was removed"
+        ));
+        *field = None;
+    };
+
+    for outbound in config.outbounds.iter_mut().flatten() {
+        let tag = outbound.tag.clone().unwrap_or_default();
+        clear(&mut outbound.default, &tag, "default");
+        clear(&mut outbound.dial.detour, &tag, "detour");
+    }
+    for endpoint in config.endpoints.iter_mut().flatten() {
+        let tag = endpoint.tag.clone().unwrap_or_default();
+        clear(&mut endpoint.dial.detour, &tag, "detour");
+    }
+    if let Some(servers) = config.dns.as_mut().and_then(|dns| dns.servers.as_mut()) {
+        for (index, server) in servers.iter_mut().enumerate() {
+            let tag = server
+                .tag
+                .clone()
+                .unwrap_or_else(|| format!("dns.servers[{index}]"));
+            clear(&mut server.dial.detour, &tag, "detour");
+        }
+    }
+    if let Some(ntp) = config.ntp.as_mut() {
+        clear(&mut ntp.dial.detour, "ntp", "detour");
+    }
+    if let Some(rule_sets) = config
+        .route
+        .as_mut()
+        .and_then(|route| route.rule_set.as_mut())
+    {
+        for rule_set in rule_sets {
+            let tag = rule_set.tag.clone().unwrap_or_default();
+            clear(&mut rule_set.download_detour, &tag, "download_detour");
+        }
+    }
+    if let Some(clash_api) = config
+        .experimental
+        .as_mut()
+        .and_then(|experimental| experimental.clash_api.as_mut())
+    {
+        clear(
+            &mut clash_api.external_ui_download_detour,
+            "experimental.clash_api",
+            "external_ui_download_detour",
+        );
+    }
+}
+
+/// direct 出站的 override_address/override_port 移到路由到该出站的规则上
+pub(super) fn destination_override(config: &mut Config, report: &mut StepReport) {
+    let Some(outbounds) = config.outbounds.as_mut() else {
+        return;
+    };
+    let mut overrides = Vec::new();
+    for outbound in outbounds.iter_mut() {
+        if outbound.kind != OutboundType::Direct {
+            continue;
+        }
+        let address: Option<String> = take(&mut outbound.extra, "override_address", report);
+        let port: Option<u16> = take(&mut outbound.extra, "override_port", report);
+        if address.is_some() || port.is_some() {
+            overrides.push((outbound.tag.clone().unwrap_or_default(), address, port));
+        }
+    }
+
+    for (tag, address, port) in overrides {
+        let route = config.route.get_or_insert_with(Route::default);
+        let rules = route.rules.get_or_insert_with(Vec::new);
+        let mut moved = false;
+        for rule in rules.iter_mut() {
+            if rule.action() == RouteAction::Route && rule.outbound.as_deref() == Some(tag.as_str())
+            {
+                rule.override_address = rule.override_address.take().or_else(|| address.clone());
+                rule.override_port = rule.override_port.or(port);
+                moved = true;
+            }
+        }
+        if route.final_outbound.as_deref() == Some(tag.as_str()) {
+            rules.push(RouteRule {
+                action: Some(RouteAction::Route),
+                outbound: Some(tag.clone()),
+                override_address: address.clone(),
+                override_port: port,
+                ..Default::default()
+            });
+            moved = true;
+        }
+        if moved {
+            report.change(format!("{tag}: moved destination override to route rules"));
+        } else {
+            report.warn(format!(
+                "{tag}: no rule routes to this outbound, destination override was removed"
+            ));
+        }
+    }
+}
+
+/// WireGuard 出站改为同标签的 WireGuard 端点
+pub(super) fn wireguard_endpoint(config: &mut Config, report: &mut StepReport) {
+    let Some(outbounds) = config.outbounds.as_mut() else {
+        return;
+    };
+    let (legacy, rest): (Vec<_>, Vec<_>) = std::mem::take(outbounds)
+        .into_iter()
+        .partition(|outbound| outbound.kind == OutboundType::WireGuard);
+    *outbounds = rest;
+    if legacy.is_empty() {
+        return;
+    }
+
+    for mut outbound in legacy {
+        let tag = outbound.tag.clone().unwrap_or_default();
+        let extra = &mut outbound.extra;
+        let mut endpoint = Endpoint {
+            kind: EndpointType::WireGuard,
+            tag: outbound.tag.clone(),
+            system: take(extra, "system_interface", report),
+            name: take(extra, "interface_name", report),
+            mtu: take(extra, "mtu", report),
+            address: take(extra, "local_address", report),
+            private_key: take(extra, "private_key", report),
+            workers: take(extra, "workers", report),
+            dial: outbound.dial.clone(),
+            ..Default::default()
+        };
+        if extra.shift_remove("gso").is_some() {
+            report.change(format!("{tag}: removed gso, it is enabled automatically"));
+        }
+
+        let peers: Option<Vec<Value>> = take(extra, "peers", report);
+        let peers = match peers {
+            Some(peers) => peers
+                .into_iter()
+                .map(|peer| match peer {
+                    Value::Object(mut peer) => legacy_peer(
+                        take(&mut peer, "server", report),
+                        take(&mut peer, "server_port", report),
+                        &mut peer,
+                        report,
+                    ),
+                    _ => WireGuardPeer::default(),
+                })
+                .collect(),
+            None => {
+                if let Some(key) = extra.shift_remove("peer_public_key") {
+                    extra.insert("public_key".to_string(), key);
+                }
+                vec![legacy_peer(
+                    outbound.server.take(),
+                    outbound.server_port.take(),
+                    extra,
+                    report,
+                )]
+            }
+        };
+        endpoint.peers = Some(peers);
+
+        if !outbound.extra.is_empty() {
+            let keys: Vec<_> = outbound.extra.keys().map(String::as_str).collect();
+            report.warn(format!(
+                "{tag}: dropped unsupported fields: {}",
+                keys.join(", ")
+            ));
+        }
+        report.change(format!("{tag}: converted WireGuard outbound to endpoint"));
+        config.endpoints.get_or_insert_with(Vec::new).push(endpoint);
+    }
+}
+
+/// 旧版对端字段转换为端点对端，未指定 allowed_ips 时放行全部流量
+fn legacy_peer(
+    address: Option<String>,
+    port: Option<u16>,
+    fields: &mut serde_json::Map<String, Value>,
+    report: &mut StepReport,
+) -> WireGuardPeer {
+    WireGuardPeer {
+        address,
+        port,
+        public_key: take(fields, "public_key", report),
+        pre_shared_key: take(fields, "pre_shared_key", report),
+        allowed_ips: take(fields, "allowed_ips", report).or_else(|| {
+            Some(Listable::Many(vec![
+                "0.0.0.0/0".to_string(),
+                "::/0".to_string(),
+            ]))
+        }),
+        reserved: take(fields, "reserved", report),
+        ..Default::default()
+    }
+}
+
+fn unique_tag(base: &str, used: &[String]) -> String {
+    if !used.iter().any(|tag| tag == base) {
+        return base.to_string();
+    }
+    (1..)
+        .map(|n| format!("{base}-{n}"))
+        .find(|tag| !used.contains(tag))
+        .unwrap_or_default()
+}
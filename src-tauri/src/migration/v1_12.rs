@@ -0,0 +1,469 @@
+//! 1.12.0：新的 DNS 服务器格式与域名解析器
+
+use serde_json::Value;
+
+use crate::config::shared::DomainResolverOptions;
+use crate::config::{
+    Config, DialOptions, Dns, DnsAction, DnsRule, DnsServer, DnsServerType, DomainResolver,
+    Listable, Route, RuleItems,
+};
+
+use super::{take, visit_dns_rules, StepReport};
+
+/// 旧格式的服务器地址
+#[derive(Debug, PartialEq, Eq)]
+enum LegacyAddress {
+    Local,
+    FakeIp,
+    Dhcp(Option<String>),
+    Rcode(String),
+    Remote {
+        kind: DnsServerType,
+        server: String,
+        port: Option<u16>,
+        path: Option<String>,
+    },
+}
+
+/// 解析 address 字段，如 `tls://1.1.1.1`、`https://dns.google/dns-query`、`dhcp://auto`
+fn parse_address(address: &str) -> Result<LegacyAddress, String> {
+    match address {
+        "local" => return Ok(LegacyAddress::Local),
+        "fakeip" => return Ok(LegacyAddress::FakeIp),
+        _ => {}
+    }
+    let (scheme, rest) = address.split_once("://").unwrap_or(("udp", address));
+    match scheme {
+        "dhcp" => {
+            let interface = rest.trim_end_matches('/');
+            return Ok(LegacyAddress::Dhcp(
+                (!interface.is_empty() && interface != "auto").then(|| interface.to_string()),
+            ));
+        }
+        "rcode" => return Ok(LegacyAddress::Rcode(rest.to_string())),
+        _ => {}
+    }
+
+    let kind = match scheme {
+        "udp" => DnsServerType::Udp,
+        "tcp" => DnsServerType::Tcp,
+        "tls" => DnsServerType::Tls,
+        "quic" => DnsServerType::Quic,
+        "https" => DnsServerType::Https,
+        "h3" => DnsServerType::H3,
+        _ => return Err(format!("unsupported DNS server address: {address}")),
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], Some(&rest[index..])),
+        None => (rest, None),
+    };
+    let (server, port) = split_host_port(authority)
+        .ok_or_else(|| format!("invalid DNS server address: {address}"))?;
+    let path = path
+        .filter(|_| matches!(kind, DnsServerType::Https | DnsServerType::H3))
+        .filter(|path| *path != "/dns-query")
+        .map(str::to_string);
+    Ok(LegacyAddress::Remote {
+        kind,
+        server,
+        port,
+        path,
+    })
+}
+
+/// 拆分 host[:port]，IPv6 地址可以带方括号
+fn split_host_port(authority: &str) -> Option<(String, Option<u16>)> {
+    if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']')?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => Some(port.parse().ok()?),
+            None if rest.is_empty() => None,
+            None => return None,
+        };
+        return Some((host.to_string(), port));
+    }
+    match authority.rsplit_once(':') {
+        // 不带方括号的 IPv6 地址
+        Some((host, _)) if host.contains(':') => Some((authority.to_string(), None)),
+        Some((host, port)) => Some((host.to_string(), Some(port.parse().ok()?))),
+        None if authority.is_empty() => None,
+        None => Some((authority.to_string(), None)),
+    }
+}
+
+/// rcode:// 的取值与 predefined 动作的 rcode 对应关系
+fn rcode_name(value: &str) -> Option<&'static str> {
+    Some(match value {
+        "success" => "NOERROR",
+        "format_error" => "FORMERR",
+        "server_failure" => "SERVFAIL",
+        "name_error" => "NXDOMAIN",
+        "not_implemented" => "NOTIMP",
+        "refused" => "REFUSED",
+        _ => return None,
+    })
+}
+
+/// 旧版写成数组的 DNS 规则 server 改为单个标签，多出的标签给出警告
+pub(super) fn rule_server(config: &mut Config, report: &mut StepReport) {
+    let Some(rules) = config.dns.as_mut().and_then(|dns| dns.rules.as_mut()) else {
+        return;
+    };
+    visit_dns_rules(rules, &mut |rule| {
+        let Some(Listable::Many(tags)) = &rule.server else {
+            return;
+        };
+        let list = tags.join(", ");
+        let mut tags = tags.clone().into_iter();
+        rule.server = tags.next().map(Listable::One);
+        let dropped: Vec<_> = tags.collect();
+        let Some(tag) = rule.server_tag() else {
+            report.change("removed empty DNS rule server list");
+            return;
+        };
+        report.change(format!("changed DNS rule server [{list}] to {tag}"));
+        if !dropped.is_empty() {
+            report.warn(format!(
+                "DNS rule server [{list}]: sing-box uses a single server, dropped {}",
+                dropped.join(", ")
+            ));
+        }
+    });
+}
+
+/// 旧格式 DNS 服务器转换为带 type 的新格式
+pub(super) fn dns_servers(config: &mut Config, report: &mut StepReport) {
+    let Some(dns) = config.dns.as_mut() else {
+        return;
+    };
+    if !dns.servers().iter().any(DnsServer::is_legacy) {
+        return;
+    }
+    // 未设置 final 时使用第一个服务器
+    let default_index = match &dns.final_server {
+        Some(tag) => dns
+            .servers()
+            .iter()
+            .position(|server| server.tag.as_ref() == Some(tag)),
+        None => Some(0),
+    };
+
+    let mut servers = dns.servers.take().unwrap_or_default();
+    let mut rcodes = Vec::new();
+    let mut index = 0;
+    servers.retain_mut(|server| {
+        let is_default = default_index == Some(index);
+        index += 1;
+        if !server.is_legacy() {
+            return true;
+        }
+        let name = server
+            .tag
+            .clone()
+            .unwrap_or_else(|| format!("dns.servers[{}]", index - 1));
+        let address = server.address.take().unwrap_or_default();
+        let parsed = match parse_address(&address) {
+            Ok(parsed) => parsed,
+            Err(message) => {
+                server.address = Some(address);
+                report.warn(format!("{name}: {message}"));
+                return true;
+            }
+        };
+
+        match parsed {
+            LegacyAddress::Rcode(code) => {
+                match rcode_name(&code) {
+                    Some(rcode) => rcodes.push((server.tag.clone().unwrap_or_default(), rcode)),
+                    None => report.warn(format!("{name}: unknown rcode {code}")),
+                }
+                report.change(format!(
+                    "{name}: replaced rcode server with predefined rule actions"
+                ));
+                return false;
+            }
+            LegacyAddress::Local => server.kind = Some(DnsServerType::Local),
+            LegacyAddress::Dhcp(interface) => {
+                server.kind = Some(DnsServerType::Dhcp);
+                server.interface = interface;
+            }
+            LegacyAddress::FakeIp => {
+                server.kind = Some(DnsServerType::FakeIp);
+                if let Some(Value::Object(fakeip)) = &dns.fakeip {
+                    server.inet4_range = fakeip
+                        .get("inet4_range")
+                        .and_then(Value::as_str)
+                        .map(str::to_string);
+                    server.inet6_range = fakeip
+                        .get("inet6_range")
+                        .and_then(Value::as_str)
+                        .map(str::to_string);
+                }
+            }
+            LegacyAddress::Remote {
+                kind,
+                server: host,
+                port,
+                path,
+            } => {
+                server.kind = Some(kind);
+                server.server = Some(host);
+                server.server_port = port;
+                server.path = path.map(Listable::One);
+            }
+        }
+
+        if let Some(resolver) = server.address_resolver.take() {
+            let strategy = server.address_strategy.take();
+            server.dial.domain_resolver = Some(match strategy {
+                Some(strategy) => DomainResolver::Options(DomainResolverOptions {
+                    server: Some(resolver),
+                    strategy: Some(strategy),
+                    ..Default::default()
+                }),
+                None => DomainResolver::Tag(resolver),
+            });
+            report.change(format!(
+                "{name}: replaced address_resolver with domain_resolver"
+            ));
+        } else if server.address_strategy.take().is_some() {
+            report.warn(format!(
+                "{name}: removed address_strategy without address_resolver"
+            ));
+        }
+
+        if let Some(strategy) = server.strategy.take() {
+            move_to_rules(dns_rules(&mut dns.rules), server.tag.as_deref(), |rule| {
+                rule.strategy.get_or_insert(strategy.clone());
+            });
+            if is_default && dns.strategy.is_none() {
+                dns.strategy = Some(strategy);
+            }
+            report.change(format!("{name}: moved strategy to DNS rules"));
+        }
+        if let Some(client_subnet) = take::<String>(&mut server.extra, "client_subnet", report) {
+            move_to_rules(dns_rules(&mut dns.rules), server.tag.as_deref(), |rule| {
+                rule.client_subnet.get_or_insert(client_subnet.clone());
+            });
+            if is_default && dns.client_subnet.is_none() {
+                dns.client_subnet = Some(client_subnet);
+            }
+            report.change(format!("{name}: moved client_subnet to DNS rules"));
+        }
+
+        report.change(format!(
+            "{name}: converted {address} to type {}",
+            server
+                .kind
+                .as_ref()
+                .map(DnsServerType::as_str)
+                .unwrap_or_default()
+        ));
+        true
+    });
+    dns.servers = Some(servers);
+
+    if dns.fakeip.take().is_some() {
+        report.change("moved dns.fakeip ranges to the fakeip server");
+    }
+
+    for (tag, rcode) in rcodes {
+        move_to_rules(dns_rules(&mut dns.rules), Some(&tag), |rule| {
+            rule.server = None;
+            rule.action = Some(DnsAction::Predefined);
+            rule.rcode = Some(rcode.to_string());
+        });
+        if dns.final_server.as_deref() == Some(tag.as_str()) {
+            dns.final_server = None;
+            report.warn(format!(
+                "dns.final referenced rcode server {tag} and was removed"
+            ));
+        }
+    }
+}
+
+fn dns_rules(rules: &mut Option<Vec<DnsRule>>) -> &mut [DnsRule] {
+    rules.as_deref_mut().unwrap_or_default()
+}
+
+/// 对路由到指定服务器的 DNS 规则执行修改
+fn move_to_rules(rules: &mut [DnsRule], tag: Option<&str>, mut f: impl FnMut(&mut DnsRule)) {
+    let Some(tag) = tag else {
+        return;
+    };
+    visit_dns_rules(rules, &mut |rule| {
+        if rule.action() == DnsAction::Route && rule.server_tag() == Some(tag) {
+            f(rule);
+        }
+    });
+}
+
+/// 旧版出站 DNS 规则与 domain_strategy 改为 domain_resolver
+pub(super) fn domain_resolver(config: &mut Config, report: &mut StepReport) {
+    outbound_rules(config, report);
+    domain_strategy(config, report);
+}
+
+/// `outbound` 匹配项的 DNS 规则改为出站的 domain_resolver
+fn outbound_rules(config: &mut Config, report: &mut StepReport) {
+    let Some(rules) = config.dns.as_mut().and_then(|dns| dns.rules.as_mut()) else {
+        return;
+    };
+    let mut resolvers = Vec::new();
+    rules.retain_mut(|rule| {
+        let Some(outbounds) = rule.outbound.take() else {
+            return true;
+        };
+        let Some(server) = rule.server_tag().map(str::to_string) else {
+            report.warn("removed outbound item from a DNS rule without server");
+            return true;
+        };
+        resolvers.push((outbounds.into_vec(), server));
+        // 只匹配出站的规则整体移除，带其他匹配项的规则只去掉 outbound
+        let only_outbound =
+            !rule.is_logical() && rule.items == RuleItems::default() && rule.invert != Some(true);
+        if !only_outbound {
+            report.warn("removed outbound item from a DNS rule with other match items");
+        }
+        !only_outbound
+    });
+    if rules.is_empty() {
+        if let Some(dns) = config.dns.as_mut() {
+            dns.rules = None;
+        }
+    }
+    if !resolvers.is_empty() {
+        report.change(format!(
+            "migrated {} outbound DNS rule(s) to domain_resolver",
+            resolvers.len()
+        ));
+    }
+
+    for (outbounds, server) in resolvers {
+        for tag in outbounds {
+            if tag == "any" {
+                let route = config.route.get_or_insert_with(Route::default);
+                if route.default_domain_resolver.is_none() {
+                    route.default_domain_resolver = Some(DomainResolver::Tag(server.clone()));
+                    report.change(format!("set route.default_domain_resolver to {server}"));
+                }
+                continue;
+            }
+            let dial = config
+                .outbounds
+                .iter_mut()
+                .flatten()
+                .filter(|outbound| outbound.tag.as_deref() == Some(tag.as_str()))
+                .map(|outbound| &mut outbound.dial)
+                .chain(
+                    config
+                        .endpoints
+                        .iter_mut()
+                        .flatten()
+                        .filter(|endpoint| endpoint.tag.as_deref() == Some(tag.as_str()))
+                        .map(|endpoint| &mut endpoint.dial),
+                )
+                .next();
+            match dial {
+                Some(dial) if dial.domain_resolver.is_none() => {
+                    dial.domain_resolver = Some(DomainResolver::Tag(server.clone()));
+                    report.change(format!("{tag}: set domain_resolver to {server}"));
+                }
+                Some(_) => {}
+                None => report.warn(format!(
+                    "outbound {tag} referenced by a DNS rule does not exist"
+                )),
+            }
+        }
+    }
+}
+
+/// 拨号字段中的 domain_strategy 合并到 domain_resolver
+fn domain_strategy(config: &mut Config, report: &mut StepReport) {
+    let has_legacy = config
+        .outbounds()
+        .iter()
+        .map(|outbound| &outbound.dial)
+        .chain(config.endpoints().iter().map(|endpoint| &endpoint.dial))
+        .any(|dial| dial.domain_strategy.is_some());
+    if !has_legacy {
+        return;
+    }
+    let resolver = default_resolver(config, report);
+
+    let dials = config
+        .outbounds
+        .iter_mut()
+        .flatten()
+        .map(|outbound| (outbound.tag.clone(), &mut outbound.dial))
+        .chain(
+            config
+                .endpoints
+                .iter_mut()
+                .flatten()
+                .map(|endpoint| (endpoint.tag.clone(), &mut endpoint.dial)),
+        );
+    for (tag, dial) in dials {
+        let Some(strategy) = dial.domain_strategy.take() else {
+            continue;
+        };
+        set_strategy(dial, &resolver, strategy);
+        report.change(format!(
+            "{}: replaced domain_strategy with domain_resolver",
+            tag.unwrap_or_default()
+        ));
+    }
+}
+
+fn set_strategy(dial: &mut DialOptions, resolver: &str, strategy: String) {
+    let options = match dial.domain_resolver.take() {
+        Some(DomainResolver::Options(options)) => options,
+        Some(DomainResolver::Tag(server)) => DomainResolverOptions {
+            server: Some(server),
+            ..Default::default()
+        },
+        None => DomainResolverOptions {
+            server: Some(resolver.to_string()),
+            ..Default::default()
+        },
+    };
+    dial.domain_resolver = Some(DomainResolver::Options(DomainResolverOptions {
+        strategy: options.strategy.clone().or(Some(strategy)),
+        ..options
+    }));
+}
+
+/// domain_strategy 需要指定解析服务器：优先使用默认解析器，其次是 local 服务器，都没有时新建
+fn default_resolver(config: &mut Config, report: &mut StepReport) -> String {
+    if let Some(server) = config
+        .route
+        .as_ref()
+        .and_then(|route| route.default_domain_resolver.as_ref())
+        .and_then(DomainResolver::server)
+    {
+        return server.to_string();
+    }
+    let dns = config.dns.get_or_insert_with(Dns::default);
+    if let Some(tag) = dns
+        .servers()
+        .iter()
+        .find(|server| server.kind == Some(DnsServerType::Local))
+        .and_then(|server| server.tag.clone())
+    {
+        return tag;
+    }
+
+    let mut tag = "local".to_string();
+    let mut n = 1;
+    while dns.server(&tag).is_some() {
+        tag = format!("local-{n}");
+        n += 1;
+    }
+    dns.servers.get_or_insert_with(Vec::new).push(DnsServer {
+        kind: Some(DnsServerType::Local),
+        tag: Some(tag.clone()),
+        ..Default::default()
+    });
+    report.change(format!("added local DNS server {tag} for domain_resolver"));
+    tag
+}
@@ -0,0 +1,212 @@
+//! 1.8.0：独立缓存文件、GeoIP/Geosite 改为规则集
+
+use serde_json::Value;
+
+use crate::config::{
+    CacheFile, Config, Experimental, Listable, Route, RuleItems, RuleSet, RuleSetType,
+};
+
+use super::{take, visit_dns_rules, visit_route_rules, StepReport};
+
+pub(crate) const GEOIP_URL: &str = "https://raw.githubusercontent.com/SagerNet/sing-geoip/rule-set";
+pub(crate) const GEOSITE_URL: &str = "https://raw.githubusercontent.com/SagerNet/sing-geosite/rule-set";
+
+/// clash_api 中的缓存字段移动到 experimental.cache_file
+pub(super) fn cache_file(config: &mut Config, report: &mut StepReport) {
+    let Some(experimental) = config.experimental.as_mut() else {
+        return;
+    };
+    let Some(clash_api) = experimental.clash_api.as_mut() else {
+        return;
+    };
+
+    let path: Option<String> = take(&mut clash_api.extra, "cache_file", report);
+    let cache_id: Option<String> = take(&mut clash_api.extra, "cache_id", report);
+    let store_fakeip: Option<bool> = take(&mut clash_api.extra, "store_fakeip", report);
+    let mut dropped = Vec::new();
+    for key in ["store_mode", "store_selected"] {
+        if clash_api.extra.shift_remove(key).is_some() {
+            dropped.push(key);
+        }
+    }
+    if path.is_none() && cache_id.is_none() && store_fakeip.is_none() && dropped.is_empty() {
+        return;
+    }
+
+    let cache_file = experimental
+        .cache_file
+        .get_or_insert_with(CacheFile::default);
+    cache_file.enabled = Some(true);
+    if path.is_some() {
+        cache_file.path = path;
+    }
+    if cache_id.is_some() {
+        cache_file.cache_id = cache_id;
+    }
+    if store_fakeip.is_some() {
+        cache_file.store_fakeip = store_fakeip;
+    }
+    report.change("moved clash_api cache settings to experimental.cache_file");
+    for key in dropped {
+        report.change(format!(
+            "removed clash_api.{key}, the cache file always stores it now"
+        ));
+    }
+}
+
+/// GeoIP 规则项改为 geoip-<code> 规则集，private 改为 ip_is_private
+pub(super) fn geoip(config: &mut Config, report: &mut StepReport) {
+    let mut codes = Vec::new();
+    let mut private = false;
+    let mut mixed = false;
+    let mut migrate = |items: &mut RuleItems| {
+        let mut destination = false;
+        let mut source = false;
+        if let Some(geoip) = items.geoip.take() {
+            for code in geoip.into_vec() {
+                if code == "private" {
+                    items.ip_is_private = Some(true);
+                    private = true;
+                } else {
+                    add_rule_set(items, "geoip", &code, &mut codes);
+                    destination = true;
+                }
+            }
+        }
+        if let Some(source_geoip) = items.source_geoip.take() {
+            for code in source_geoip.into_vec() {
+                if code == "private" {
+                    items.source_ip_is_private = Some(true);
+                    private = true;
+                } else {
+                    add_rule_set(items, "geoip", &code, &mut codes);
+                    source = true;
+                }
+            }
+            if source {
+                items.rule_set_ipcidr_match_source = Some(true);
+            }
+        }
+        mixed |= destination && source;
+    };
+    visit_all_items(config, &mut migrate);
+
+    if private {
+        report.change("replaced geoip private with ip_is_private");
+    }
+    if mixed {
+        report.warn(
+            "a rule used both geoip and source_geoip; its rule-sets now all match the source address",
+        );
+    }
+    finish(config, report, "geoip", GEOIP_URL, codes);
+}
+
+/// Geosite 规则项改为 geosite-<code> 规则集
+pub(super) fn geosite(config: &mut Config, report: &mut StepReport) {
+    let mut codes = Vec::new();
+    let mut migrate = |items: &mut RuleItems| {
+        if let Some(geosite) = items.geosite.take() {
+            for code in geosite.into_vec() {
+                add_rule_set(items, "geosite", &code, &mut codes);
+            }
+        }
+    };
+    visit_all_items(config, &mut migrate);
+    finish(config, report, "geosite", GEOSITE_URL, codes);
+}
+
+fn visit_all_items(config: &mut Config, f: &mut impl FnMut(&mut RuleItems)) {
+    if let Some(rules) = config.route.as_mut().and_then(|route| route.rules.as_mut()) {
+        visit_route_rules(rules, &mut |rule| f(&mut rule.items));
+    }
+    if let Some(rules) = config.dns.as_mut().and_then(|dns| dns.rules.as_mut()) {
+        visit_dns_rules(rules, &mut |rule| f(&mut rule.items));
+    }
+}
+
+fn add_rule_set(items: &mut RuleItems, prefix: &str, code: &str, codes: &mut Vec<String>) {
+    let tag = format!("{prefix}-{code}");
+    let mut tags = items
+        .rule_set
+        .take()
+        .map(Listable::into_vec)
+        .unwrap_or_default();
+    if !tags.contains(&tag) {
+        tags.push(tag.clone());
+    }
+    items.rule_set = Some(if tags.len() == 1 {
+        Listable::One(tags.remove(0))
+    } else {
+        Listable::Many(tags)
+    });
+    if !codes.contains(&tag) {
+        codes.push(tag);
+    }
+}
+
+/// 补全规则集定义、移除旧的数据库设置并启用缓存文件
+fn finish(
+    config: &mut Config,
+    report: &mut StepReport,
+    kind: &str,
+    base_url: &str,
+    tags: Vec<String>,
+) {
+    if tags.is_empty() && config.route.is_none() {
+        return;
+    }
+    let route = config.route.get_or_insert_with(Route::default);
+    let legacy = match kind {
+        "geoip" => route.geoip.take(),
+        _ => route.geosite.take(),
+    };
+    let download_detour = legacy
+        .as_ref()
+        .and_then(|value| value.get("download_detour"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    if legacy.is_some() {
+        report.change(format!("removed route.{kind}"));
+    }
+    if let Some(Value::Object(mut legacy)) = legacy {
+        legacy.shift_remove("download_detour");
+        if !legacy.is_empty() {
+            let keys: Vec<_> = legacy.keys().map(String::as_str).collect();
+            report.warn(format!("dropped route.{kind} options: {}", keys.join(", ")));
+        }
+    }
+    if tags.is_empty() {
+        return;
+    }
+
+    let rule_sets = route.rule_set.get_or_insert_with(Vec::new);
+    for tag in tags {
+        report.change(format!("replaced {kind} items with rule-set {tag}"));
+        if rule_sets
+            .iter()
+            .any(|rule_set| rule_set.tag.as_deref() == Some(&tag))
+        {
+            continue;
+        }
+        rule_sets.push(RuleSet {
+            kind: Some(RuleSetType::Remote),
+            url: Some(format!("{base_url}/{tag}.srs")),
+            tag: Some(tag),
+            format: Some("binary".to_string()),
+            download_detour: download_detour.clone(),
+            ..Default::default()
+        });
+    }
+
+    let experimental = config
+        .experimental
+        .get_or_insert_with(Experimental::default);
+    let cache_file = experimental
+        .cache_file
+        .get_or_insert_with(CacheFile::default);
+    if cache_file.enabled != Some(true) {
+        cache_file.enabled = Some(true);
+        report.change("enabled experimental.cache_file to keep rule-set cache");
+    }
+}
@@ -0,0 +1,432 @@
+//! 配置预检（语义检查）
+//!
+//! 移植自前端 `src/lib/preflight.ts`。每条检查都是带 id 的独立规则，
+//! 可以单独启用或禁用。
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{Config, InboundType, Listable, OutboundType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueLevel {
+    Error,
+    Warning,
+    Info,
+}
+
+/// 与前端 `PreflightIssue` 结构一致
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreflightIssue {
+    pub level: IssueLevel,
+    pub path: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+/// 检查上下文
+pub struct PreflightContext<'a> {
+    pub config: &'a Config,
+    locale: String,
+}
+
+impl PreflightContext<'_> {
+    pub fn is_zh(&self) -> bool {
+        self.locale == "zh"
+    }
+
+    /// 按当前语言选择文本
+    pub fn text(&self, zh: String, en: String) -> String {
+        if self.is_zh() {
+            zh
+        } else {
+            en
+        }
+    }
+}
+
+/// 预检规则
+pub trait PreflightRule: Send + Sync {
+    /// 规则 id，用于启用/禁用
+    fn id(&self) -> &'static str;
+    fn check(&self, ctx: &PreflightContext, issues: &mut Vec<PreflightIssue>);
+}
+
+/// 预检引擎，按注册顺序执行已启用的规则
+pub struct Preflight {
+    rules: Vec<Box<dyn PreflightRule>>,
+    disabled: HashSet<String>,
+}
+
+impl Default for Preflight {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Preflight {
+    /// 包含全部内置规则
+    pub fn new() -> Self {
+        Self {
+            rules: vec![
+                Box::new(PortConflicts),
+                Box::new(TagReferences),
+                Box::new(RequiredFields),
+                Box::new(TlsConfig),
+            ],
+            disabled: HashSet::new(),
+        }
+    }
+
+    /// 注册自定义规则
+    pub fn with_rule(mut self, rule: impl PreflightRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    pub fn disable(&mut self, id: &str) {
+        self.disabled.insert(id.to_string());
+    }
+
+    pub fn enable(&mut self, id: &str) {
+        self.disabled.remove(id);
+    }
+
+    pub fn is_enabled(&self, id: &str) -> bool {
+        !self.disabled.contains(id)
+    }
+
+    pub fn rule_ids(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|rule| rule.id()).collect()
+    }
+
+    pub fn run(&self, config: &Config, locale: &str) -> Vec<PreflightIssue> {
+        let ctx = PreflightContext {
+            config,
+            locale: locale.to_string(),
+        };
+        let mut issues = Vec::new();
+        for rule in self.rules.iter().filter(|rule| self.is_enabled(rule.id())) {
+            rule.check(&ctx, &mut issues);
+        }
+        issues
+    }
+
+    /// 检查 JSON 配置：无法解析的字段作为错误报告并跳过，其余部分照常检查
+    pub fn run_value(
+        &self,
+        value: Value,
+        locale: &str,
+    ) -> serde_json::Result<Vec<PreflightIssue>> {
+        let (config, skipped) = Config::from_value_lenient(value)?;
+        let zh = locale == "zh";
+        let mut issues: Vec<_> = skipped
+            .into_iter()
+            .map(|field| PreflightIssue {
+                level: IssueLevel::Error,
+                message: if zh {
+                    format!("无法解析该字段，已跳过检查：{}", field.message)
+                } else {
+                    format!("Field could not be parsed and was skipped: {}", field.message)
+                },
+                path: field.path,
+                fix: None,
+            })
+            .collect();
+        issues.extend(self.run(&config, locale));
+        Ok(issues)
+    }
+}
+
+/// 入站端口冲突
+pub struct PortConflicts;
+
+impl PreflightRule for PortConflicts {
+    fn id(&self) -> &'static str {
+        "port-conflicts"
+    }
+
+    fn check(&self, ctx: &PreflightContext, issues: &mut Vec<PreflightIssue>) {
+        // 保持端口首次出现的顺序
+        let mut ports: Vec<(u16, Vec<(String, usize)>)> = Vec::new();
+        for (idx, inbound) in ctx.config.inbounds().iter().enumerate() {
+            let Some(port) = inbound.listen_port.filter(|port| *port != 0) else {
+                continue;
+            };
+            let tag = inbound.tag.clone().unwrap_or_else(|| format!("inbound-{}", idx));
+            match ports.iter_mut().find(|(existing, _)| *existing == port) {
+                Some((_, items)) => items.push((tag, idx)),
+                None => ports.push((port, vec![(tag, idx)])),
+            }
+        }
+
+        for (port, items) in ports.into_iter().filter(|(_, items)| items.len() > 1) {
+            let tags = items.iter().map(|(tag, _)| tag.as_str()).collect::<Vec<_>>().join(", ");
+            issues.push(PreflightIssue {
+                level: IssueLevel::Error,
+                path: format!("/inbounds/{}/listen_port", items[0].1),
+                message: ctx.text(
+                    format!("端口 {} 被多个入站使用: {}", port, tags),
+                    format!("Port {} is used by multiple inbounds: {}", port, tags),
+                ),
+                fix: Some(ctx.text(
+                    "修改其中一个入站的端口".to_string(),
+                    "Change the port of one of the inbounds".to_string(),
+                )),
+            });
+        }
+    }
+}
+
+/// 出站、DNS 服务器标签引用
+pub struct TagReferences;
+
+impl PreflightRule for TagReferences {
+    fn id(&self) -> &'static str {
+        "tag-references"
+    }
+
+    fn check(&self, ctx: &PreflightContext, issues: &mut Vec<PreflightIssue>) {
+        let config = ctx.config;
+        let outbound_tags: HashSet<&str> = config.outbound_tags().into_iter().collect();
+
+        // 检查路由规则中的出站引用
+        if let Some(route) = &config.route {
+            for (idx, rule) in route.rules().iter().enumerate() {
+                let Some(tag) = rule.outbound.as_deref().filter(|tag| !tag.is_empty()) else {
+                    continue;
+                };
+                if !outbound_tags.contains(tag) {
+                    issues.push(PreflightIssue {
+                        level: IssueLevel::Error,
+                        path: format!("/route/rules/{}/outbound", idx),
+                        message: ctx.text(
+                            format!("路由规则引用了不存在的出站标签: {}", tag),
+                            format!("Route rule references non-existent outbound tag: {}", tag),
+                        ),
+                        fix: Some(ctx.text(
+                            format!("创建标签为 \"{}\" 的出站，或修改路由规则", tag),
+                            format!("Create an outbound with tag \"{}\", or modify the route rule", tag),
+                        )),
+                    });
+                }
+            }
+
+            if let Some(tag) = route.final_outbound.as_deref().filter(|tag| !tag.is_empty()) {
+                if !outbound_tags.contains(tag) {
+                    issues.push(PreflightIssue {
+                        level: IssueLevel::Error,
+                        path: "/route/final".to_string(),
+                        message: ctx.text(
+                            format!("默认路由引用了不存在的出站标签: {}", tag),
+                            format!("Default route references non-existent outbound tag: {}", tag),
+                        ),
+                        fix: Some(ctx.text(
+                            format!("创建标签为 \"{}\" 的出站，或修改默认路由", tag),
+                            format!("Create an outbound with tag \"{}\", or modify the default route", tag),
+                        )),
+                    });
+                }
+            }
+        }
+
+        // 检查 DNS 规则中的服务器引用
+        if let Some(dns) = &config.dns {
+            let server_tags: HashSet<&str> =
+                dns.servers().iter().filter_map(|server| server.tag.as_deref()).collect();
+            for (idx, rule) in dns.rules().iter().enumerate() {
+                let Some(server) = &rule.server else {
+                    continue;
+                };
+                // 旧版数组写法逐项检查
+                let many = matches!(server, Listable::Many(_));
+                for (pos, tag) in server.iter().enumerate() {
+                    if tag.is_empty() || server_tags.contains(tag.as_str()) {
+                        continue;
+                    }
+                    let path = if many {
+                        format!("/dns/rules/{}/server/{}", idx, pos)
+                    } else {
+                        format!("/dns/rules/{}/server", idx)
+                    };
+                    issues.push(PreflightIssue {
+                        level: IssueLevel::Error,
+                        path,
+                        message: ctx.text(
+                            format!("DNS 规则引用了不存在的 DNS 服务器标签: {}", tag),
+                            format!("DNS rule references non-existent DNS server tag: {}", tag),
+                        ),
+                        fix: Some(ctx.text(
+                            format!("创建标签为 \"{}\" 的 DNS 服务器，或修改 DNS 规则", tag),
+                            format!("Create a DNS server with tag \"{}\", or modify the DNS rule", tag),
+                        )),
+                    });
+                }
+            }
+        }
+
+        // 检查 Selector/URLTest 出站的标签引用
+        for (idx, outbound) in config.outbounds().iter().enumerate() {
+            if !outbound.kind.is_group() {
+                continue;
+            }
+            for tag in outbound.members() {
+                if !outbound_tags.contains(tag.as_str()) {
+                    issues.push(PreflightIssue {
+                        level: IssueLevel::Error,
+                        path: format!("/outbounds/{}/outbounds", idx),
+                        message: ctx.text(
+                            format!("{} 出站引用了不存在的标签: {}", outbound.kind, tag),
+                            format!("{} outbound references non-existent tag: {}", outbound.kind, tag),
+                        ),
+                        fix: Some(ctx.text(
+                            format!("创建标签为 \"{}\" 的出站，或从列表中移除", tag),
+                            format!("Create an outbound with tag \"{}\", or remove it from the list", tag),
+                        )),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// 协议必填字段
+pub struct RequiredFields;
+
+impl RequiredFields {
+    fn missing(
+        ctx: &PreflightContext,
+        path: String,
+        message: (String, String),
+        fix: (&str, &str),
+    ) -> PreflightIssue {
+        PreflightIssue {
+            level: IssueLevel::Error,
+            path,
+            message: ctx.text(message.0, message.1),
+            fix: Some(ctx.text(fix.0.to_string(), fix.1.to_string())),
+        }
+    }
+}
+
+fn is_blank(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(str::is_empty)
+}
+
+impl PreflightRule for RequiredFields {
+    fn id(&self) -> &'static str {
+        "required-fields"
+    }
+
+    fn check(&self, ctx: &PreflightContext, issues: &mut Vec<PreflightIssue>) {
+        // 检查出站必填字段
+        for (idx, outbound) in ctx.config.outbounds().iter().enumerate() {
+            match outbound.kind {
+                OutboundType::Vmess | OutboundType::Vless if is_blank(&outbound.uuid) => {
+                    issues.push(Self::missing(
+                        ctx,
+                        format!("/outbounds/{}/uuid", idx),
+                        (
+                            format!("{} 出站缺少必填字段: uuid", outbound.kind),
+                            format!("{} outbound missing required field: uuid", outbound.kind),
+                        ),
+                        ("添加 UUID 字段", "Add UUID field"),
+                    ));
+                }
+                OutboundType::Trojan if is_blank(&outbound.password) => {
+                    issues.push(Self::missing(
+                        ctx,
+                        format!("/outbounds/{}/password", idx),
+                        (
+                            "Trojan 出站缺少必填字段: password".to_string(),
+                            "Trojan outbound missing required field: password".to_string(),
+                        ),
+                        ("添加密码字段", "Add password field"),
+                    ));
+                }
+                OutboundType::Shadowsocks => {
+                    if is_blank(&outbound.password) {
+                        issues.push(Self::missing(
+                            ctx,
+                            format!("/outbounds/{}/password", idx),
+                            (
+                                "Shadowsocks 出站缺少必填字段: password".to_string(),
+                                "Shadowsocks outbound missing required field: password".to_string(),
+                            ),
+                            ("添加密码字段", "Add password field"),
+                        ));
+                    }
+                    if is_blank(&outbound.method) {
+                        issues.push(Self::missing(
+                            ctx,
+                            format!("/outbounds/{}/method", idx),
+                            (
+                                "Shadowsocks 出站缺少必填字段: method".to_string(),
+                                "Shadowsocks outbound missing required field: method".to_string(),
+                            ),
+                            ("选择加密方法", "Select encryption method"),
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // 检查入站必填字段
+        for (idx, inbound) in ctx.config.inbounds().iter().enumerate() {
+            if !inbound.users().is_empty() {
+                continue;
+            }
+            let message = match inbound.kind {
+                InboundType::Vmess | InboundType::Vless => (
+                    format!("{} 入站需要至少一个用户", inbound.kind),
+                    format!("{} inbound requires at least one user", inbound.kind),
+                ),
+                InboundType::Trojan => (
+                    "Trojan 入站需要至少一个用户".to_string(),
+                    "Trojan inbound requires at least one user".to_string(),
+                ),
+                _ => continue,
+            };
+            issues.push(Self::missing(
+                ctx,
+                format!("/inbounds/{}/users", idx),
+                message,
+                ("添加至少一个用户", "Add at least one user"),
+            ));
+        }
+    }
+}
+
+/// TLS 配置合理性
+pub struct TlsConfig;
+
+impl PreflightRule for TlsConfig {
+    fn id(&self) -> &'static str {
+        "tls-config"
+    }
+
+    fn check(&self, ctx: &PreflightContext, issues: &mut Vec<PreflightIssue>) {
+        for (idx, outbound) in ctx.config.outbounds().iter().enumerate() {
+            let tls_enabled = outbound.tls.as_ref().is_some_and(|tls| tls.is_enabled());
+            if tls_enabled && outbound.server_port == Some(80) {
+                issues.push(PreflightIssue {
+                    level: IssueLevel::Warning,
+                    path: format!("/outbounds/{}/tls", idx),
+                    message: ctx.text(
+                        "使用 TLS 但服务器端口为 80，建议使用 443".to_string(),
+                        "TLS is enabled but server port is 80, recommend using 443".to_string(),
+                    ),
+                    fix: Some(ctx.text(
+                        "将 server_port 改为 443".to_string(),
+                        "Change server_port to 443".to_string(),
+                    )),
+                });
+            }
+        }
+    }
+}
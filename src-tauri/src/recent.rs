@@ -0,0 +1,68 @@
+//! 最近打开的文件（MRU 列表），保存在应用配置目录下的 recent.json
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// 列表文件名
+pub const FILE_NAME: &str = "recent.json";
+
+/// 最多保留的条目数
+pub const MAX_ENTRIES: usize = 10;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentFiles {
+    files: Vec<PathBuf>,
+}
+
+impl RecentFiles {
+    /// 读取列表，文件不存在或内容损坏时为空
+    pub fn load(path: &Path) -> Self {
+        let mut recent: Self = fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        recent.files.truncate(MAX_ENTRIES);
+        recent
+    }
+
+    /// 写入列表，自动创建所在目录
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+
+    /// 最近的文件在前
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Path> {
+        self.files.get(index).map(PathBuf::as_path)
+    }
+
+    /// 移到列表最前面，重复路径只保留一条
+    pub fn add(&mut self, file: impl AsRef<Path>) {
+        let file = file.as_ref();
+        let file = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        self.files.retain(|existing| *existing != file);
+        self.files.insert(0, file);
+        self.files.truncate(MAX_ENTRIES);
+    }
+
+    /// 移除已不存在的文件，返回是否有变化
+    pub fn prune(&mut self) -> bool {
+        let before = self.files.len();
+        self.files.retain(|file| file.is_file());
+        self.files.len() != before
+    }
+
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+}
@@ -0,0 +1,352 @@
+//! AdGuard DNS 过滤规则 → 规则集源文件，参考 docs/configuration/rule-set/adguard.md
+//!
+//! `||example.org^` 转换为 domain_suffix，`|example.org^` 和 hosts 中的 0.0.0.0 条目转换为
+//! domain，带通配符或未锚定的写法转换为 domain_regex。`@@` 例外规则与拦截规则组成
+//! and 逻辑规则，`$important` 的拦截规则不受普通例外规则影响。
+
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr};
+
+use serde::{Deserialize, Serialize};
+
+use super::{RuleSetSource, VERSION_2};
+use crate::config::{HeadlessRule, LogicalMode, RuleItems, RuleType};
+
+/// 转换结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdGuardConversion {
+    pub source: RuleSetSource,
+    pub stats: AdGuardStats,
+}
+
+/// 转换统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdGuardStats {
+    /// 除空行和注释外的行数
+    pub lines: usize,
+    /// 已转换的拦截规则
+    pub blocked: usize,
+    /// 已转换的例外规则
+    pub exceptions: usize,
+    pub skipped: Vec<SkippedLine>,
+}
+
+/// 无法转换的一行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedLine {
+    /// 从 1 开始
+    pub line: usize,
+    pub text: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Item {
+    Domain(String),
+    Suffix(String),
+    Regex(String),
+}
+
+/// 同一类规则（拦截或例外）的去重集合
+#[derive(Default)]
+struct Items {
+    domain: Vec<String>,
+    domain_suffix: Vec<String>,
+    domain_regex: Vec<String>,
+    seen: HashSet<(u8, String)>,
+}
+
+impl Items {
+    fn add(&mut self, item: Item) {
+        let (kind, list, value) = match item {
+            Item::Domain(value) => (0, &mut self.domain, value),
+            Item::Suffix(value) => (1, &mut self.domain_suffix, value),
+            Item::Regex(value) => (2, &mut self.domain_regex, value),
+        };
+        if self.seen.insert((kind, value.clone())) {
+            list.push(value);
+        }
+    }
+
+    fn extend(&mut self, other: &Items) {
+        for value in &other.domain {
+            self.add(Item::Domain(value.clone()));
+        }
+        for value in &other.domain_suffix {
+            self.add(Item::Suffix(value.clone()));
+        }
+        for value in &other.domain_regex {
+            self.add(Item::Regex(value.clone()));
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    fn rule(&self, invert: bool) -> HeadlessRule {
+        let list = |values: &Vec<String>| (!values.is_empty()).then(|| values.clone().into());
+        HeadlessRule {
+            items: RuleItems {
+                domain: list(&self.domain),
+                domain_suffix: list(&self.domain_suffix),
+                domain_regex: list(&self.domain_regex),
+                ..Default::default()
+            },
+            invert: invert.then_some(true),
+            ..Default::default()
+        }
+    }
+
+    /// 匹配本集合且不匹配 exceptions
+    fn rule_except(&self, exceptions: &Items) -> HeadlessRule {
+        if exceptions.is_empty() {
+            return self.rule(false);
+        }
+        HeadlessRule {
+            kind: Some(RuleType::Logical),
+            mode: Some(LogicalMode::And),
+            rules: Some(vec![self.rule(false), exceptions.rule(true)]),
+            ..Default::default()
+        }
+    }
+}
+
+/// 解析过滤列表；无法转换的行记入统计，不会导致整体失败
+pub fn convert(text: &str) -> AdGuardConversion {
+    let lines: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !is_comment(line))
+        .collect();
+    // 每行都是域名时按精确匹配的域名列表处理
+    let simple = !lines.is_empty() && lines.iter().all(|(_, line)| is_domain(line));
+
+    let mut stats = AdGuardStats {
+        lines: lines.len(),
+        ..Default::default()
+    };
+    let mut blocked = Items::default();
+    let mut important = Items::default();
+    let mut exceptions = Items::default();
+    let mut important_exceptions = Items::default();
+    for (line, text) in lines {
+        if simple {
+            blocked.add(Item::Domain(text.to_lowercase()));
+            stats.blocked += 1;
+            continue;
+        }
+        let parsed = if text.split_whitespace().nth(1).is_some() {
+            parse_hosts(text).map(|domains| (false, false, domains))
+        } else {
+            parse_rule(text).map(|(exception, important, item)| (exception, important, vec![item]))
+        };
+        match parsed {
+            Ok((exception, is_important, items)) => {
+                let target = match (exception, is_important) {
+                    (false, false) => &mut blocked,
+                    (false, true) => &mut important,
+                    (true, false) => &mut exceptions,
+                    (true, true) => &mut important_exceptions,
+                };
+                for item in items {
+                    target.add(item);
+                }
+                if exception {
+                    stats.exceptions += 1;
+                } else {
+                    stats.blocked += 1;
+                }
+            }
+            Err(message) => stats.skipped.push(SkippedLine {
+                line,
+                text: text.to_string(),
+                message,
+            }),
+        }
+    }
+
+    // $important 的例外规则同时作用于普通拦截规则
+    exceptions.extend(&important_exceptions);
+    let mut rules = Vec::new();
+    if !blocked.is_empty() {
+        rules.push(blocked.rule_except(&exceptions));
+    }
+    if !important.is_empty() {
+        rules.push(important.rule_except(&important_exceptions));
+    }
+    AdGuardConversion {
+        source: RuleSetSource {
+            version: VERSION_2,
+            rules,
+        },
+        stats,
+    }
+}
+
+fn is_comment(line: &str) -> bool {
+    if line.starts_with('!') || (line.starts_with('[') && line.ends_with(']')) {
+        return true;
+    }
+    // "##"、"#@#" 等是元素隐藏规则，不是注释
+    line.starts_with('#') && !is_cosmetic(line)
+}
+
+fn is_cosmetic(line: &str) -> bool {
+    ["##", "#@#", "#?#", "#$#", "#%#"]
+        .iter()
+        .any(|marker| line.contains(marker))
+}
+
+fn is_domain(text: &str) -> bool {
+    text.contains('.')
+        && text.split('.').all(|label| {
+            !label.is_empty()
+                && label
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        })
+        && text.parse::<IpAddr>().is_err()
+}
+
+/// hosts 格式："0.0.0.0 example.org [example.com ...]"，只接受 0.0.0.0
+fn parse_hosts(line: &str) -> Result<Vec<Item>, String> {
+    let content = line.split('#').next().unwrap_or_default();
+    let mut fields = content.split_whitespace();
+    let address = fields
+        .next()
+        .and_then(|field| field.parse::<IpAddr>().ok())
+        .ok_or_else(|| "invalid rule".to_string())?;
+    if address != IpAddr::V4(Ipv4Addr::UNSPECIFIED) {
+        return Err(format!(
+            "hosts entries for {address} are not supported, only 0.0.0.0"
+        ));
+    }
+    let domains: Vec<Item> = fields
+        .filter(|host| is_domain(host))
+        .map(|host| Item::Domain(host.to_lowercase()))
+        .collect();
+    if domains.is_empty() {
+        return Err("no domain in hosts entry".to_string());
+    }
+    Ok(domains)
+}
+
+/// 返回（是否例外规则，是否 $important，匹配条件）
+fn parse_rule(line: &str) -> Result<(bool, bool, Item), String> {
+    if is_cosmetic(line) {
+        return Err("cosmetic rules are not supported".to_string());
+    }
+    let (exception, rule) = match line.strip_prefix("@@") {
+        Some(rule) => (true, rule),
+        None => (false, line),
+    };
+    let (pattern, modifiers) = split_modifiers(rule);
+    let mut important = false;
+    for modifier in modifiers.iter().flat_map(|m| m.split(',')) {
+        match modifier.trim() {
+            "important" => important = true,
+            "dnsrewrite=0.0.0.0" => {}
+            other => return Err(format!("modifier ${other} is not supported")),
+        }
+    }
+    Ok((exception, important, parse_pattern(pattern)?))
+}
+
+/// "pattern$modifiers"；正则中的 "$" 不是修饰符分隔符
+fn split_modifiers(rule: &str) -> (&str, Option<&str>) {
+    if rule.starts_with('/') {
+        if let Some(end) = rule.rfind('/').filter(|end| *end > 0) {
+            match &rule[end + 1..] {
+                "" => return (rule, None),
+                rest if rest.starts_with('$') => return (&rule[..=end], Some(&rest[1..])),
+                _ => {}
+            }
+        }
+    }
+    match rule.split_once('$') {
+        Some((pattern, modifiers)) => (pattern, Some(modifiers)),
+        None => (rule, None),
+    }
+}
+
+enum Anchor {
+    /// "||"：域名本身或子域名
+    Domain,
+    /// "|"：从开头匹配
+    Start,
+    None,
+}
+
+fn parse_pattern(pattern: &str) -> Result<Item, String> {
+    if pattern.len() > 2 && pattern.starts_with('/') && pattern.ends_with('/') {
+        return Ok(Item::Regex(pattern[1..pattern.len() - 1].to_string()));
+    }
+    let (anchor, mut body) = if let Some(body) = pattern.strip_prefix("||") {
+        (Anchor::Domain, body)
+    } else if let Some(body) = pattern.strip_prefix('|') {
+        (Anchor::Start, body)
+    } else {
+        (Anchor::None, pattern)
+    };
+    // 协议被忽略
+    if let Some((scheme, rest)) = body.split_once("://") {
+        if scheme.chars().all(|c| c.is_ascii_alphabetic()) {
+            body = rest;
+        }
+    }
+    let (body, end) = match body
+        .strip_suffix("^|")
+        .or_else(|| body.strip_suffix('^'))
+        .or_else(|| body.strip_suffix('|'))
+    {
+        Some(body) => (body, true),
+        None => (body, false),
+    };
+    if body.trim_matches('*').is_empty() {
+        return Err("rule matches every domain".to_string());
+    }
+    if body.contains('/') {
+        return Err("paths are not supported".to_string());
+    }
+    if body.contains(':') {
+        return Err("ports are not supported".to_string());
+    }
+    if body.contains('^') || body.contains('|') {
+        return Err("separators inside the pattern are not supported".to_string());
+    }
+    if body.parse::<IpAddr>().is_ok() || body.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return Err("IP hosts are not supported".to_string());
+    }
+    if !body
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '-' | '.' | '_' | '*'))
+    {
+        return Err("invalid domain pattern".to_string());
+    }
+    let body = body.to_lowercase();
+    if !body.contains('*') && end {
+        match anchor {
+            Anchor::Domain => return Ok(Item::Suffix(body)),
+            Anchor::Start => return Ok(Item::Domain(body)),
+            Anchor::None => {}
+        }
+    }
+    let mut regex = match anchor {
+        Anchor::Domain => r"(^|\.)".to_string(),
+        Anchor::Start => "^".to_string(),
+        Anchor::None => String::new(),
+    };
+    for c in body.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '.' => regex.push_str(r"\."),
+            c => regex.push(c),
+        }
+    }
+    if end {
+        regex.push('$');
+    }
+    Ok(Item::Regex(regex))
+}
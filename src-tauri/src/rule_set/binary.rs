@@ -0,0 +1,569 @@
+//! .srs 二进制格式的读写，与 sing-box common/srs 一致
+
+use std::io::{Read, Write};
+use std::net::IpAddr;
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use super::domain::DomainSet;
+use super::ip_set::{self, IpRange};
+use super::{RuleSetSource, VERSION_1, VERSION_3, VERSION_CURRENT};
+use crate::config::{HeadlessRule, Listable, LogicalMode, QueryType, RuleItems, RuleType};
+
+const MAGIC: &[u8; 3] = b"SRS";
+
+const RULE_TYPE_DEFAULT: u8 = 0;
+const RULE_TYPE_LOGICAL: u8 = 1;
+const LOGICAL_AND: u8 = 0;
+const LOGICAL_OR: u8 = 1;
+
+const ITEM_QUERY_TYPE: u8 = 0;
+const ITEM_NETWORK: u8 = 1;
+const ITEM_DOMAIN: u8 = 2;
+const ITEM_DOMAIN_KEYWORD: u8 = 3;
+const ITEM_DOMAIN_REGEX: u8 = 4;
+const ITEM_SOURCE_IP_CIDR: u8 = 5;
+const ITEM_IP_CIDR: u8 = 6;
+const ITEM_SOURCE_PORT: u8 = 7;
+const ITEM_SOURCE_PORT_RANGE: u8 = 8;
+const ITEM_PORT: u8 = 9;
+const ITEM_PORT_RANGE: u8 = 10;
+const ITEM_PROCESS_NAME: u8 = 11;
+const ITEM_PROCESS_PATH: u8 = 12;
+const ITEM_PACKAGE_NAME: u8 = 13;
+const ITEM_WIFI_SSID: u8 = 14;
+const ITEM_WIFI_BSSID: u8 = 15;
+const ITEM_ADGUARD_DOMAIN: u8 = 16;
+const ITEM_PROCESS_PATH_REGEX: u8 = 17;
+const ITEM_NETWORK_TYPE: u8 = 18;
+const ITEM_NETWORK_IS_EXPENSIVE: u8 = 19;
+const ITEM_NETWORK_IS_CONSTRAINED: u8 = 20;
+const ITEM_FINAL: u8 = 0xFF;
+
+/// 规则集中可以使用的字段，其余字段在编译时报错
+const SUPPORTED_ITEMS: &[&str] = &[
+    "query_type",
+    "network",
+    "domain",
+    "domain_suffix",
+    "domain_keyword",
+    "domain_regex",
+    "source_ip_cidr",
+    "ip_cidr",
+    "source_port",
+    "source_port_range",
+    "port",
+    "port_range",
+    "process_name",
+    "process_path",
+    "process_path_regex",
+    "package_name",
+    "network_type",
+    "network_is_expensive",
+    "network_is_constrained",
+    "wifi_ssid",
+    "wifi_bssid",
+];
+
+/// DNS 查询类型名称与编号
+const QUERY_TYPES: &[(&str, u16)] = &[
+    ("A", 1),
+    ("NS", 2),
+    ("CNAME", 5),
+    ("SOA", 6),
+    ("PTR", 12),
+    ("MX", 15),
+    ("TXT", 16),
+    ("AAAA", 28),
+    ("SRV", 33),
+    ("NAPTR", 35),
+    ("OPT", 41),
+    ("DS", 43),
+    ("RRSIG", 46),
+    ("NSEC", 47),
+    ("DNSKEY", 48),
+    ("SVCB", 64),
+    ("HTTPS", 65),
+    ("ANY", 255),
+    ("CAA", 257),
+];
+
+/// 网络类型名称，下标即编号
+const NETWORK_TYPES: &[&str] = &["wifi", "cellular", "ethernet", "other"];
+
+fn check_version(version: u8) -> Result<(), String> {
+    if !(VERSION_1..=VERSION_CURRENT).contains(&version) {
+        return Err(format!(
+            "unsupported rule-set version {version}, expected {VERSION_1} to {VERSION_CURRENT}"
+        ));
+    }
+    Ok(())
+}
+
+/// 编码规则列表，生成完整的 .srs 文件内容
+pub(super) fn write(rules: &[HeadlessRule], version: u8) -> Result<Vec<u8>, String> {
+    check_version(version)?;
+    let mut body = Vec::new();
+    write_uvarint(&mut body, rules.len() as u64);
+    for (index, rule) in rules.iter().enumerate() {
+        write_rule(&mut body, rule, version).map_err(|e| format!("rule[{index}]: {e}"))?;
+    }
+    let mut data = MAGIC.to_vec();
+    data.push(version);
+    let mut encoder = ZlibEncoder::new(data, Compression::best());
+    encoder.write_all(&body).map_err(|e| e.to_string())?;
+    encoder.finish().map_err(|e| e.to_string())
+}
+
+fn write_rule(out: &mut Vec<u8>, rule: &HeadlessRule, version: u8) -> Result<(), String> {
+    match rule.kind.as_ref().unwrap_or(&RuleType::Default) {
+        RuleType::Default => write_default_rule(out, rule, version),
+        RuleType::Logical => write_logical_rule(out, rule, version),
+        other => Err(format!("unknown rule type: {other}")),
+    }
+}
+
+fn write_logical_rule(out: &mut Vec<u8>, rule: &HeadlessRule, version: u8) -> Result<(), String> {
+    if let Some(key) = rule.extra.keys().next() {
+        return Err(format!("{key} is not supported in rule-sets"));
+    }
+    out.push(RULE_TYPE_LOGICAL);
+    out.push(match &rule.mode {
+        Some(LogicalMode::And) => LOGICAL_AND,
+        Some(LogicalMode::Or) => LOGICAL_OR,
+        Some(other) => return Err(format!("unknown logical mode: {other}")),
+        None => return Err("missing logical mode".to_string()),
+    });
+    let rules = rule.rules.as_deref().unwrap_or_default();
+    if rules.is_empty() {
+        return Err("missing sub rules".to_string());
+    }
+    write_uvarint(out, rules.len() as u64);
+    for (index, sub) in rules.iter().enumerate() {
+        write_rule(out, sub, version).map_err(|e| format!("rules[{index}]: {e}"))?;
+    }
+    out.push(rule.invert.unwrap_or(false) as u8);
+    Ok(())
+}
+
+fn write_default_rule(out: &mut Vec<u8>, rule: &HeadlessRule, version: u8) -> Result<(), String> {
+    let items = &rule.items;
+    let used = serde_json::to_value(items).map_err(|e| e.to_string())?;
+    if let Some(key) = used
+        .as_object()
+        .into_iter()
+        .flat_map(|map| map.keys())
+        .chain(rule.extra.keys())
+        .find(|key| !SUPPORTED_ITEMS.contains(&key.as_str()))
+    {
+        return Err(format!("{key} is not supported in rule-sets"));
+    }
+
+    out.push(RULE_TYPE_DEFAULT);
+    if let Some(query_types) = non_empty(&items.query_type) {
+        let codes = query_types
+            .iter()
+            .map(query_type_code)
+            .collect::<Result<Vec<_>, _>>()?;
+        write_uint16_item(out, ITEM_QUERY_TYPE, &codes);
+    }
+    write_string_item(out, ITEM_NETWORK, &items.network);
+    let domains = strings(&items.domain);
+    let suffixes = strings(&items.domain_suffix);
+    if !domains.is_empty() || !suffixes.is_empty() {
+        out.push(ITEM_DOMAIN);
+        write_domain_set(
+            out,
+            &DomainSet::new(&domains, &suffixes, version == VERSION_1),
+        );
+    }
+    write_string_item(out, ITEM_DOMAIN_KEYWORD, &items.domain_keyword);
+    write_string_item(out, ITEM_DOMAIN_REGEX, &items.domain_regex);
+    write_ip_item(out, ITEM_SOURCE_IP_CIDR, &items.source_ip_cidr)?;
+    write_ip_item(out, ITEM_IP_CIDR, &items.ip_cidr)?;
+    if let Some(ports) = non_empty(&items.source_port) {
+        write_uint16_item(out, ITEM_SOURCE_PORT, ports.as_slice());
+    }
+    write_string_item(out, ITEM_SOURCE_PORT_RANGE, &items.source_port_range);
+    if let Some(ports) = non_empty(&items.port) {
+        write_uint16_item(out, ITEM_PORT, ports.as_slice());
+    }
+    write_string_item(out, ITEM_PORT_RANGE, &items.port_range);
+    write_string_item(out, ITEM_PROCESS_NAME, &items.process_name);
+    write_string_item(out, ITEM_PROCESS_PATH, &items.process_path);
+    write_string_item(out, ITEM_PROCESS_PATH_REGEX, &items.process_path_regex);
+    write_string_item(out, ITEM_PACKAGE_NAME, &items.package_name);
+    if let Some(network_types) = non_empty(&items.network_type) {
+        require_version(version, VERSION_3, "network_type")?;
+        let codes = network_types
+            .iter()
+            .map(|name| {
+                NETWORK_TYPES
+                    .iter()
+                    .position(|known| known == name)
+                    .map(|code| code as u8)
+                    .ok_or_else(|| format!("unknown network type: {name}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        out.push(ITEM_NETWORK_TYPE);
+        write_uvarint(out, codes.len() as u64);
+        out.extend(codes);
+    }
+    if items.network_is_expensive == Some(true) {
+        require_version(version, VERSION_3, "network_is_expensive")?;
+        out.push(ITEM_NETWORK_IS_EXPENSIVE);
+    }
+    if items.network_is_constrained == Some(true) {
+        require_version(version, VERSION_3, "network_is_constrained")?;
+        out.push(ITEM_NETWORK_IS_CONSTRAINED);
+    }
+    write_string_item(out, ITEM_WIFI_SSID, &items.wifi_ssid);
+    write_string_item(out, ITEM_WIFI_BSSID, &items.wifi_bssid);
+    out.push(ITEM_FINAL);
+    out.push(rule.invert.unwrap_or(false) as u8);
+    Ok(())
+}
+
+fn require_version(version: u8, required: u8, item: &str) -> Result<(), String> {
+    if version < required {
+        return Err(format!(
+            "{item} is only supported in rule-set version {required} or later"
+        ));
+    }
+    Ok(())
+}
+
+fn non_empty<T>(value: &Option<Listable<T>>) -> Option<&Listable<T>> {
+    value.as_ref().filter(|list| !list.is_empty())
+}
+
+fn strings(value: &Option<Listable<String>>) -> Vec<String> {
+    value.iter().flatten().cloned().collect()
+}
+
+pub(crate) fn query_type_code(query_type: &QueryType) -> Result<u16, String> {
+    match query_type {
+        QueryType::Code(code) => Ok(*code),
+        QueryType::Name(name) => QUERY_TYPES
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+            .map(|(_, code)| *code)
+            .ok_or_else(|| format!("unknown query type: {name}")),
+    }
+}
+
+fn query_type_value(code: u16) -> QueryType {
+    match QUERY_TYPES.iter().find(|(_, known)| *known == code) {
+        Some((name, _)) => QueryType::Name(name.to_string()),
+        None => QueryType::Code(code),
+    }
+}
+
+fn write_uvarint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_uvarint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn write_string_item(out: &mut Vec<u8>, item: u8, value: &Option<Listable<String>>) {
+    let Some(values) = non_empty(value) else {
+        return;
+    };
+    out.push(item);
+    write_uvarint(out, values.as_slice().len() as u64);
+    for value in values {
+        write_bytes(out, value.as_bytes());
+    }
+}
+
+fn write_uint16_item(out: &mut Vec<u8>, item: u8, values: &[u16]) {
+    out.push(item);
+    write_uvarint(out, values.len() as u64);
+    for value in values {
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+fn write_ip_item(
+    out: &mut Vec<u8>,
+    item: u8,
+    value: &Option<Listable<String>>,
+) -> Result<(), String> {
+    let Some(cidrs) = non_empty(value) else {
+        return Ok(());
+    };
+    let ranges = ip_set::build(cidrs.as_slice())?;
+    out.push(item);
+    out.push(1);
+    out.extend_from_slice(&(ranges.len() as u64).to_be_bytes());
+    for range in ranges {
+        write_bytes(out, &ip_bytes(range.from));
+        write_bytes(out, &ip_bytes(range.to));
+    }
+    Ok(())
+}
+
+fn ip_bytes(addr: IpAddr) -> Vec<u8> {
+    match addr {
+        IpAddr::V4(addr) => addr.octets().to_vec(),
+        IpAddr::V6(addr) => addr.octets().to_vec(),
+    }
+}
+
+fn write_domain_set(out: &mut Vec<u8>, set: &DomainSet) {
+    out.push(0);
+    for words in [&set.leaves, &set.label_bitmap] {
+        write_uvarint(out, words.len() as u64);
+        for word in words {
+            out.extend_from_slice(&word.to_be_bytes());
+        }
+    }
+    write_bytes(out, &set.labels);
+}
+
+/// 解析完整的 .srs 文件内容
+pub(super) fn read(data: &[u8]) -> Result<RuleSetSource, String> {
+    if data.len() < 4 || &data[..3] != MAGIC {
+        return Err("not a binary rule-set: invalid magic".to_string());
+    }
+    let version = data[3];
+    check_version(version)?;
+    let mut body = Vec::new();
+    ZlibDecoder::new(&data[4..])
+        .read_to_end(&mut body)
+        .map_err(|e| format!("invalid rule-set data: {e}"))?;
+    let mut reader = Reader { data: &body };
+    let count = reader.len(1)?;
+    let mut rules = Vec::with_capacity(count);
+    for index in 0..count {
+        rules.push(reader.rule().map_err(|e| format!("rule[{index}]: {e}"))?);
+    }
+    if !reader.data.is_empty() {
+        return Err("trailing data after rules".to_string());
+    }
+    Ok(RuleSetSource { version, rules })
+}
+
+/// 带边界检查的读取器
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if n > self.data.len() {
+            return Err("unexpected end of data".to_string());
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn uvarint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte < 0x80 {
+                return Ok(value);
+            }
+        }
+        Err("invalid varint".to_string())
+    }
+
+    /// 读取元素个数，每个元素至少占 size 字节
+    fn len(&mut self, size: usize) -> Result<usize, String> {
+        let len = self.uvarint()?;
+        if len > (self.data.len() / size) as u64 {
+            return Err("length exceeds remaining data".to_string());
+        }
+        Ok(len as usize)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], String> {
+        let len = self.len(1)?;
+        self.take(len)
+    }
+
+    fn strings(&mut self) -> Result<Listable<String>, String> {
+        let count = self.len(1)?;
+        let mut values = Vec::with_capacity(count);
+        for _ in 0..count {
+            let bytes = self.bytes()?;
+            values.push(String::from_utf8(bytes.to_vec()).map_err(|_| "invalid UTF-8 string")?);
+        }
+        Ok(values.into())
+    }
+
+    fn uint16s(&mut self) -> Result<Vec<u16>, String> {
+        let count = self.len(2)?;
+        let bytes = self.take(count * 2)?;
+        Ok(bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect())
+    }
+
+    fn uint64s(&mut self) -> Result<Vec<u64>, String> {
+        let count = self.len(8)?;
+        let bytes = self.take(count * 8)?;
+        Ok(bytes
+            .chunks_exact(8)
+            .map(|word| u64::from_be_bytes(word.try_into().unwrap_or_default()))
+            .collect())
+    }
+
+    fn rule(&mut self) -> Result<HeadlessRule, String> {
+        match self.byte()? {
+            RULE_TYPE_DEFAULT => self.default_rule(),
+            RULE_TYPE_LOGICAL => self.logical_rule(),
+            other => Err(format!("unknown rule type: {other}")),
+        }
+    }
+
+    fn logical_rule(&mut self) -> Result<HeadlessRule, String> {
+        let mode = match self.byte()? {
+            LOGICAL_AND => LogicalMode::And,
+            LOGICAL_OR => LogicalMode::Or,
+            other => return Err(format!("unknown logical mode: {other}")),
+        };
+        let count = self.len(1)?;
+        let mut rules = Vec::with_capacity(count);
+        for index in 0..count {
+            rules.push(self.rule().map_err(|e| format!("rules[{index}]: {e}"))?);
+        }
+        Ok(HeadlessRule {
+            kind: Some(RuleType::Logical),
+            mode: Some(mode),
+            rules: Some(rules),
+            invert: self.invert()?,
+            ..Default::default()
+        })
+    }
+
+    fn default_rule(&mut self) -> Result<HeadlessRule, String> {
+        let mut items = RuleItems::default();
+        loop {
+            match self.byte()? {
+                ITEM_QUERY_TYPE => {
+                    let codes = self.uint16s()?;
+                    items.query_type = Some(
+                        codes
+                            .into_iter()
+                            .map(query_type_value)
+                            .collect::<Vec<_>>()
+                            .into(),
+                    );
+                }
+                ITEM_NETWORK => items.network = Some(self.strings()?),
+                ITEM_DOMAIN => {
+                    let (domains, suffixes) = self.domain_set()?.dump()?;
+                    items.domain = (!domains.is_empty()).then(|| domains.into());
+                    items.domain_suffix = (!suffixes.is_empty()).then(|| suffixes.into());
+                }
+                ITEM_DOMAIN_KEYWORD => items.domain_keyword = Some(self.strings()?),
+                ITEM_DOMAIN_REGEX => items.domain_regex = Some(self.strings()?),
+                ITEM_SOURCE_IP_CIDR => items.source_ip_cidr = Some(self.ip_set()?),
+                ITEM_IP_CIDR => items.ip_cidr = Some(self.ip_set()?),
+                ITEM_SOURCE_PORT => items.source_port = Some(self.uint16s()?.into()),
+                ITEM_SOURCE_PORT_RANGE => items.source_port_range = Some(self.strings()?),
+                ITEM_PORT => items.port = Some(self.uint16s()?.into()),
+                ITEM_PORT_RANGE => items.port_range = Some(self.strings()?),
+                ITEM_PROCESS_NAME => items.process_name = Some(self.strings()?),
+                ITEM_PROCESS_PATH => items.process_path = Some(self.strings()?),
+                ITEM_PROCESS_PATH_REGEX => items.process_path_regex = Some(self.strings()?),
+                ITEM_PACKAGE_NAME => items.package_name = Some(self.strings()?),
+                ITEM_WIFI_SSID => items.wifi_ssid = Some(self.strings()?),
+                ITEM_WIFI_BSSID => items.wifi_bssid = Some(self.strings()?),
+                ITEM_NETWORK_TYPE => {
+                    let count = self.len(1)?;
+                    let names = self
+                        .take(count)?
+                        .iter()
+                        .map(|code| match NETWORK_TYPES.get(*code as usize) {
+                            Some(name) => Ok(name.to_string()),
+                            None => Err(format!("unknown network type: {code}")),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    items.network_type = Some(names.into());
+                }
+                ITEM_NETWORK_IS_EXPENSIVE => items.network_is_expensive = Some(true),
+                ITEM_NETWORK_IS_CONSTRAINED => items.network_is_constrained = Some(true),
+                ITEM_ADGUARD_DOMAIN => {
+                    return Err("AdGuard domain rules cannot be decompiled".to_string())
+                }
+                ITEM_FINAL => break,
+                other => return Err(format!("unknown rule item: {other}")),
+            }
+        }
+        Ok(HeadlessRule {
+            items,
+            invert: self.invert()?,
+            ..Default::default()
+        })
+    }
+
+    fn invert(&mut self) -> Result<Option<bool>, String> {
+        match self.byte()? {
+            0 => Ok(None),
+            1 => Ok(Some(true)),
+            other => Err(format!("invalid invert flag: {other}")),
+        }
+    }
+
+    fn domain_set(&mut self) -> Result<DomainSet, String> {
+        let version = self.byte()?;
+        if version != 0 {
+            return Err(format!("unsupported domain matcher version {version}"));
+        }
+        Ok(DomainSet {
+            leaves: self.uint64s()?,
+            label_bitmap: self.uint64s()?,
+            labels: self.bytes()?.to_vec(),
+        })
+    }
+
+    fn ip_set(&mut self) -> Result<Listable<String>, String> {
+        let version = self.byte()?;
+        if version != 1 {
+            return Err(format!("unsupported IP set version {version}"));
+        }
+        let count = u64::from_be_bytes(self.take(8)?.try_into().unwrap_or_default());
+        // 每个区间至少占 2 + 4 + 4 字节
+        if count > (self.data.len() / 10) as u64 {
+            return Err("length exceeds remaining data".to_string());
+        }
+        let mut ranges = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            ranges.push(IpRange {
+                from: self.ip()?,
+                to: self.ip()?,
+            });
+        }
+        Ok(ip_set::prefixes(&ranges)?.into())
+    }
+
+    fn ip(&mut self) -> Result<IpAddr, String> {
+        let bytes = self.bytes()?;
+        match bytes.len() {
+            4 => Ok(IpAddr::from(<[u8; 4]>::try_from(bytes).unwrap_or_default())),
+            16 => Ok(IpAddr::from(
+                <[u8; 16]>::try_from(bytes).unwrap_or_default(),
+            )),
+            len => Err(format!("invalid IP address length {len}")),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! domain / domain_suffix 的紧凑前缀树（succinct set），与 sing 的 domain.Matcher 序列化一致
+//!
+//! 每个域名按字符反转后作为键；后缀规则在键尾追加标记字符，"\r" 表示只匹配子域名，
+//! "\n"（版本 2 起）表示匹配域名本身及子域名。
+
+use std::collections::{BTreeSet, HashSet};
+
+/// ".example.com" 形式的后缀
+const PREFIX_LABEL: u8 = b'\r';
+/// "example.com" 形式的后缀，版本 2 起使用
+const ROOT_LABEL: u8 = b'\n';
+
+/// 序列化后的前缀树
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct DomainSet {
+    pub leaves: Vec<u64>,
+    pub label_bitmap: Vec<u64>,
+    pub labels: Vec<u8>,
+}
+
+fn reverse(domain: &str) -> String {
+    domain.chars().rev().collect()
+}
+
+fn set_bit(bitmap: &mut Vec<u64>, index: usize, value: bool) {
+    while index >> 6 >= bitmap.len() {
+        bitmap.push(0);
+    }
+    if value {
+        bitmap[index >> 6] |= 1 << (index & 63);
+    }
+}
+
+fn get_bit(bitmap: &[u64], index: usize) -> bool {
+    bitmap
+        .get(index >> 6)
+        .is_some_and(|word| word & (1 << (index & 63)) != 0)
+}
+
+impl DomainSet {
+    /// legacy 为真时（版本 1）"example.com" 后缀拆成精确匹配加 ".example.com" 后缀
+    pub fn new(domains: &[String], suffixes: &[String], legacy: bool) -> Self {
+        let mut keys: Vec<Vec<u8>> = Vec::new();
+        let mut seen = HashSet::new();
+        for suffix in suffixes.iter().filter(|suffix| !suffix.is_empty()) {
+            if !seen.insert(suffix.clone()) {
+                continue;
+            }
+            if suffix.starts_with('.') {
+                keys.push(Self::key(suffix, Some(PREFIX_LABEL)));
+            } else if legacy {
+                keys.push(Self::key(suffix, None));
+                let dotted = format!(".{suffix}");
+                if seen.insert(dotted.clone()) {
+                    keys.push(Self::key(&dotted, Some(PREFIX_LABEL)));
+                }
+            } else {
+                keys.push(Self::key(suffix, Some(ROOT_LABEL)));
+            }
+        }
+        for domain in domains.iter().filter(|domain| !domain.is_empty()) {
+            if seen.insert(domain.clone()) {
+                keys.push(Self::key(domain, None));
+            }
+        }
+        keys.sort();
+        Self::build(&keys)
+    }
+
+    fn key(domain: &str, label: Option<u8>) -> Vec<u8> {
+        let mut key = reverse(domain).into_bytes();
+        key.extend(label);
+        key
+    }
+
+    /// 按层次遍历生成 LOUDS 编码：每个节点的子节点标签依次记为 0 位，以 1 位结束
+    fn build(keys: &[Vec<u8>]) -> Self {
+        let mut set = Self::default();
+        let mut label_index = 0;
+        let mut queue = vec![(0, keys.len(), 0)];
+        let mut i = 0;
+        while i < queue.len() {
+            let (mut start, end, column) = queue[i];
+            if start < end && keys[start].len() == column {
+                start += 1;
+                set_bit(&mut set.leaves, i, true);
+            }
+            let mut j = start;
+            while j < end {
+                let from = j;
+                while j < end && keys[j][column] == keys[from][column] {
+                    j += 1;
+                }
+                queue.push((from, j, column + 1));
+                set.labels.push(keys[from][column]);
+                set_bit(&mut set.label_bitmap, label_index, false);
+                label_index += 1;
+            }
+            set_bit(&mut set.label_bitmap, label_index, true);
+            label_index += 1;
+            i += 1;
+        }
+        set
+    }
+
+    /// 还原所有键
+    fn keys(&self) -> Result<Vec<Vec<u8>>, String> {
+        let count = self.labels.len() + 1;
+        // 第 k 个标签对应编号为 k + 1 的节点
+        let mut parents = vec![0usize; count];
+        let mut node = 0;
+        let mut label = 0;
+        let mut index = 0;
+        while node < count {
+            if index >> 6 >= self.label_bitmap.len() {
+                return Err("truncated domain set".to_string());
+            }
+            if get_bit(&self.label_bitmap, index) {
+                node += 1;
+            } else {
+                if label >= self.labels.len() {
+                    return Err("invalid domain set".to_string());
+                }
+                parents[label + 1] = node;
+                label += 1;
+            }
+            index += 1;
+        }
+        let mut keys: Vec<Vec<u8>> = vec![Vec::new(); count];
+        for node in 1..count {
+            if parents[node] >= node {
+                return Err("invalid domain set".to_string());
+            }
+            let mut key = keys[parents[node]].clone();
+            key.push(self.labels[node - 1]);
+            keys[node] = key;
+        }
+        Ok((0..count)
+            .filter(|node| get_bit(&self.leaves, *node))
+            .map(|node| std::mem::take(&mut keys[node]))
+            .collect())
+    }
+
+    /// 还原为 domain 和 domain_suffix；同时有 "x" 和 ".x" 时合并为后缀 "x"
+    pub fn dump(&self) -> Result<(Vec<String>, Vec<String>), String> {
+        let mut domains = BTreeSet::new();
+        let mut dotted = BTreeSet::new();
+        let mut suffixes = BTreeSet::new();
+        for key in self.keys()? {
+            let (label, rest) = match key.split_last() {
+                Some((&label, rest)) if label == PREFIX_LABEL || label == ROOT_LABEL => {
+                    (Some(label), rest.to_vec())
+                }
+                _ => (None, key),
+            };
+            let domain = reverse(
+                &String::from_utf8(rest).map_err(|_| "invalid domain in domain set".to_string())?,
+            );
+            match label {
+                Some(PREFIX_LABEL) => dotted.insert(domain),
+                Some(_) => suffixes.insert(domain),
+                None => domains.insert(domain),
+            };
+        }
+        for suffix in dotted {
+            match suffix.strip_prefix('.') {
+                Some(root) if domains.remove(root) => suffixes.insert(root.to_string()),
+                _ => suffixes.insert(suffix),
+            };
+        }
+        Ok((
+            domains.into_iter().collect(),
+            suffixes.into_iter().collect(),
+        ))
+    }
+}
@@ -0,0 +1,120 @@
+//! IP 地址集合：CIDR 列表合并为有序、不重叠的地址区间，与 netipx.IPSet 的序列化一致
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// 闭区间 [from, to]，同一区间内的地址族相同
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct IpRange {
+    pub from: IpAddr,
+    pub to: IpAddr,
+}
+
+fn bits(v6: bool) -> u32 {
+    if v6 {
+        128
+    } else {
+        32
+    }
+}
+
+fn to_u128(addr: IpAddr) -> (bool, u128) {
+    match addr {
+        IpAddr::V4(addr) => (false, u32::from(addr) as u128),
+        IpAddr::V6(addr) => (true, u128::from(addr)),
+    }
+}
+
+fn from_u128(v6: bool, value: u128) -> IpAddr {
+    if v6 {
+        IpAddr::V6(Ipv6Addr::from(value))
+    } else {
+        IpAddr::V4(Ipv4Addr::from(value as u32))
+    }
+}
+
+/// 低 size 位全为 1
+fn mask(size: u32) -> u128 {
+    if size >= 128 {
+        u128::MAX
+    } else {
+        (1u128 << size) - 1
+    }
+}
+
+/// "10.0.0.0/8" 或单个地址 → 区间，主机位被忽略
+fn parse(cidr: &str) -> Result<(bool, u128, u128), String> {
+    let invalid = || format!("invalid IP CIDR: {cidr}");
+    let (addr, len) = match cidr.split_once('/') {
+        Some((addr, len)) => (addr, Some(len.parse::<u32>().map_err(|_| invalid())?)),
+        None => (cidr, None),
+    };
+    let (v6, value) = to_u128(addr.parse::<IpAddr>().map_err(|_| invalid())?);
+    let width = bits(v6);
+    let len = len.unwrap_or(width);
+    if len > width {
+        return Err(invalid());
+    }
+    let host = mask(width - len);
+    Ok((v6, value & !host, (value & !host) | host))
+}
+
+/// 合并 CIDR 列表：IPv4 在前，区间升序，重叠或相邻的区间合并
+pub(super) fn build(cidrs: &[String]) -> Result<Vec<IpRange>, String> {
+    let mut ranges = cidrs
+        .iter()
+        .map(|cidr| parse(cidr.trim()))
+        .collect::<Result<Vec<_>, _>>()?;
+    ranges.sort();
+    let mut merged: Vec<(bool, u128, u128)> = Vec::new();
+    for (v6, from, to) in ranges {
+        match merged.last_mut() {
+            Some((last_v6, _, last_to))
+                if *last_v6 == v6 && last_to.checked_add(1).is_none_or(|next| next >= from) =>
+            {
+                *last_to = (*last_to).max(to);
+            }
+            _ => merged.push((v6, from, to)),
+        }
+    }
+    Ok(merged
+        .into_iter()
+        .map(|(v6, from, to)| IpRange {
+            from: from_u128(v6, from),
+            to: from_u128(v6, to),
+        })
+        .collect())
+}
+
+/// 区间 → 覆盖它的最少 CIDR
+pub(super) fn prefixes(ranges: &[IpRange]) -> Result<Vec<String>, String> {
+    let mut result = Vec::new();
+    for range in ranges {
+        let (v6, mut from) = to_u128(range.from);
+        let (to_v6, to) = to_u128(range.to);
+        if v6 != to_v6 || from > to {
+            return Err("invalid IP range".to_string());
+        }
+        let width = bits(v6);
+        loop {
+            // 从 from 开始、不超过 to 的最大对齐块
+            let mut size = from.trailing_zeros().min(width);
+            while size > 0 && (from | mask(size)) > to {
+                size -= 1;
+            }
+            result.push(format!("{}/{}", from_u128(v6, from), width - size));
+            let last = from | mask(size);
+            if last >= to {
+                break;
+            }
+            from = last + 1;
+        }
+    }
+    Ok(result)
+}
+
+/// CIDR 或单个地址是否包含 addr，IPv4 映射的 IPv6 地址按 IPv4 处理
+pub(crate) fn contains(cidr: &str, addr: IpAddr) -> Result<bool, String> {
+    let (v6, from, to) = parse(cidr.trim())?;
+    let (addr_v6, value) = to_u128(addr.to_canonical());
+    Ok(v6 == addr_v6 && (from..=to).contains(&value))
+}
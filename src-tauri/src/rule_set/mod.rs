@@ -0,0 +1,66 @@
+//! 规则集源格式（JSON）与二进制 .srs 格式互转，参考 docs/configuration/rule-set/source-format.md
+//!
+//! 二进制格式与 `sing-box rule-set compile` 的输出一致：魔数 "SRS"、一字节版本号，
+//! 其后是 zlib 压缩的规则列表。
+
+pub mod adguard;
+mod binary;
+mod domain;
+pub(crate) mod ip_set;
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::HeadlessRule;
+
+pub(crate) use binary::query_type_code;
+
+/// 1.8.0 起的初始版本
+pub const VERSION_1: u8 = 1;
+/// 1.10.0 起优化了 domain_suffix 的存储
+pub const VERSION_2: u8 = 2;
+/// 1.11.0 起支持 network_type、network_is_expensive、network_is_constrained
+pub const VERSION_3: u8 = 3;
+/// 当前最高版本
+pub const VERSION_CURRENT: u8 = VERSION_3;
+
+/// 规则集源文件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleSetSource {
+    pub version: u8,
+    pub rules: Vec<HeadlessRule>,
+}
+
+impl RuleSetSource {
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| format!("invalid rule-set source: {e}"))
+    }
+
+    pub fn to_json_pretty(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+}
+
+/// 编译为 .srs，version 为空时使用源文件中的版本
+pub fn compile(source: &RuleSetSource, version: Option<u8>) -> Result<Vec<u8>, String> {
+    binary::write(&source.rules, version.unwrap_or(source.version))
+}
+
+/// 反编译 .srs，版本取自文件头
+pub fn decompile(data: &[u8]) -> Result<RuleSetSource, String> {
+    binary::read(data)
+}
+
+/// 编译源文件并写入 output
+pub fn compile_file(input: &Path, output: &Path, version: Option<u8>) -> Result<(), String> {
+    let text = std::fs::read_to_string(input).map_err(|e| format!("{}: {e}", input.display()))?;
+    let data = compile(&RuleSetSource::from_json(&text)?, version)?;
+    std::fs::write(output, data).map_err(|e| format!("{}: {e}", output.display()))
+}
+
+/// 读取 .srs 文件并返回源格式
+pub fn decompile_file(input: &Path) -> Result<RuleSetSource, String> {
+    let data = std::fs::read(input).map_err(|e| format!("{}: {e}", input.display()))?;
+    decompile(&data)
+}
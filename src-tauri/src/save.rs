@@ -0,0 +1,202 @@
+//! 配置文件的原子保存
+//!
+//! 先写入同目录下的临时文件并 fsync，再 rename 覆盖原文件，保存中途崩溃时原文件保持完整。
+//! 覆盖前把原文件轮转为 `config.json.1.bak`（最新）到 `config.json.N.bak`。
+
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::validation;
+
+/// 默认保留的备份数
+pub const DEFAULT_BACKUPS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveResult {
+    /// 实际写入的文件，路径是符号链接时为链接指向的文件
+    pub path: String,
+    /// 本次生成的备份，原文件不存在或不保留备份时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup: Option<String>,
+    /// 不影响保存的问题，如无法保留原文件的属主
+    pub warnings: Vec<String>,
+}
+
+/// 校验配置后原子写入，校验失败时不修改文件
+pub fn save_config(path: &Path, text: &str, backups: usize) -> Result<SaveResult, String> {
+    let result = validation::validate_text(text);
+    if let Some(error) = result.errors.first() {
+        let location = if error.path.is_empty() {
+            "/"
+        } else {
+            &error.path
+        };
+        return Err(format!(
+            "config is not saved: {} validation error(s), first at {location}: {}",
+            result.errors.len(),
+            error.message
+        ));
+    }
+    write_atomic(path, text.as_bytes(), backups).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// 原子写入文件，保留原文件的权限和属主，并轮转 backups 份备份
+pub fn write_atomic(path: &Path, data: &[u8], backups: usize) -> io::Result<SaveResult> {
+    let target = resolve_symlink(path)?;
+    let name = target
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let dir = target
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let original = match fs::metadata(&target) {
+        Ok(metadata) if metadata.is_file() => Some(metadata),
+        Ok(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not a regular file",
+            ))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+
+    let temp = dir.join(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    let mut warnings = Vec::new();
+    let mut backup = None;
+    let saved = write_temp(&temp, data, original.as_ref(), &mut warnings)
+        .and_then(|()| {
+            if original.is_some() && backups > 0 {
+                backup = Some(rotate(&target, backups)?);
+            }
+            Ok(())
+        })
+        .and_then(|()| fs::rename(&temp, &target));
+    if let Err(e) = saved {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+    sync_dir(dir);
+
+    Ok(SaveResult {
+        path: target.to_string_lossy().into_owned(),
+        backup: backup.map(|backup| backup.to_string_lossy().into_owned()),
+        warnings,
+    })
+}
+
+/// 第 index 份备份的路径
+pub fn backup_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{index}.bak"));
+    path.with_file_name(name)
+}
+
+/// 覆盖符号链接会把链接替换为普通文件，因此写入链接指向的文件
+fn resolve_symlink(path: &Path) -> io::Result<PathBuf> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => fs::canonicalize(path),
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+fn write_temp(
+    temp: &Path,
+    data: &[u8],
+    original: Option<&Metadata>,
+    warnings: &mut Vec<String>,
+) -> io::Result<()> {
+    let mut file = match create_temp(temp, original) {
+        // 上次崩溃遗留或他人预先放置的临时文件不复用，删除后重新创建
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            fs::remove_file(temp)?;
+            create_temp(temp, original)?
+        }
+        result => result?,
+    };
+    // 先设置权限和属主再写入，写入过程中内容不会比原文件更容易被读取
+    if let Some(original) = original {
+        file.set_permissions(original.permissions())?;
+        keep_owner(&file, original, warnings)?;
+    }
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+/// 创建临时文件，unix 上直接使用原文件的权限位
+fn create_temp(temp: &Path, original: Option<&Metadata>) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if let Some(original) = original {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(original.permissions().mode() & 0o777);
+    }
+    #[cfg(not(unix))]
+    let _ = original;
+    options.open(temp)
+}
+
+/// 非 root 用户通常无法把文件交给其他用户，此时给出警告并继续保存
+#[cfg(unix)]
+fn keep_owner(file: &File, original: &Metadata, warnings: &mut Vec<String>) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let current = file.metadata()?;
+    if current.uid() == original.uid() && current.gid() == original.gid() {
+        return Ok(());
+    }
+    if let Err(e) = std::os::unix::fs::fchown(file, Some(original.uid()), Some(original.gid())) {
+        warnings.push(format!(
+            "cannot keep owner {}:{}: {e}",
+            original.uid(),
+            original.gid()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn keep_owner(_file: &File, _original: &Metadata, _warnings: &mut Vec<String>) -> io::Result<()> {
+    Ok(())
+}
+
+/// 轮转备份：N-1 → N，……，1 → 2，再把当前文件保存为 1
+fn rotate(target: &Path, backups: usize) -> io::Result<PathBuf> {
+    for index in (1..backups).rev() {
+        let from = backup_path(target, index);
+        if from.exists() {
+            fs::rename(&from, backup_path(target, index + 1))?;
+        }
+    }
+    let backup = backup_path(target, 1);
+    match fs::remove_file(&backup) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    // 硬链接不复制内容，且保留原文件的权限和属主；文件系统不支持时复制
+    if fs::hard_link(target, &backup).is_err() {
+        fs::copy(target, &backup)?;
+    }
+    Ok(backup)
+}
+
+/// rename 之后同步目录项，确保断电后新文件名可见
+#[cfg(unix)]
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+/// Windows 不能用 File::open 打开目录，跳过
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) {}
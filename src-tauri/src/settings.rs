@@ -0,0 +1,73 @@
+//! 应用设置，保存在应用配置目录下的 settings.json
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::menu_i18n::{has_locale, FALLBACK_LOCALE};
+
+/// 设置文件名
+pub const FILE_NAME: &str = "settings.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// 用户选择的界面语言，未选择时跟随系统
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// 用户指定的 sing-box 可执行文件或所在目录
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub core_path: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl AppSettings {
+    /// 读取设置，文件不存在或内容损坏时使用默认值
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// 写入设置，自动创建所在目录
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+
+    /// 当前使用的语言
+    pub fn locale(&self) -> String {
+        self.locale
+            .as_deref()
+            .map(normalize_locale)
+            .unwrap_or_else(system_locale)
+    }
+}
+
+/// 把 "zh-CN"、"en_US.UTF-8" 等写法归一为有菜单目录表的语言，没有时使用英文
+pub fn normalize_locale(locale: &str) -> String {
+    let language = locale
+        .split(['-', '_', '.'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if has_locale(&language) {
+        language
+    } else {
+        FALLBACK_LOCALE.to_string()
+    }
+}
+
+/// 系统语言
+pub fn system_locale() -> String {
+    sys_locale::get_locale()
+        .map(|locale| normalize_locale(&locale))
+        .unwrap_or_else(|| FALLBACK_LOCALE.to_string())
+}
@@ -0,0 +1,580 @@
+//! 出站 → 分享链接，以及由服务端入站生成客户端链接
+//!
+//! 生成的链接与 import 使用相同的格式，可以原样导入回来。
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::{encode_component, reality_public_key};
+use crate::config::shared::{Reality, Utls};
+use crate::config::{
+    Inbound, InboundType, Listable, Multiplex, Outbound, OutboundType, Tls, Transport,
+};
+
+/// 由入站用户生成的客户端链接
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientLink {
+    pub tag: String,
+    pub link: String,
+}
+
+/// 生成出站的分享链接
+pub fn outbound_link(outbound: &Outbound) -> Result<String, String> {
+    match outbound.kind {
+        OutboundType::Vmess => vmess_link(outbound),
+        OutboundType::Vless => {
+            let uuid = required(&outbound.uuid, "uuid")?;
+            let mut link = LinkBuilder::new("vless", outbound, uuid)?;
+            link.param("encryption", "none");
+            link.param_opt("flow", outbound.flow.as_deref());
+            link.param_opt("packetEncoding", outbound.packet_encoding.as_deref());
+            link.stream(outbound, "none")?;
+            Ok(link.build())
+        }
+        OutboundType::Trojan => {
+            let password = required(&outbound.password, "password")?;
+            let mut link = LinkBuilder::new("trojan", outbound, password)?;
+            link.stream(outbound, "tls")?;
+            Ok(link.build())
+        }
+        OutboundType::Shadowsocks => shadowsocks_link(outbound),
+        OutboundType::Hysteria2 => hysteria2_link(outbound),
+        OutboundType::Tuic => {
+            let uuid = required(&outbound.uuid, "uuid")?;
+            let password = outbound.password.as_deref().unwrap_or_default();
+            let userinfo = format!("{}:{}", uuid, encode_component(password));
+            let mut link = LinkBuilder::with_userinfo("tuic", outbound, userinfo)?;
+            for key in ["congestion_control", "udp_relay_mode"] {
+                link.param_opt(key, outbound.extra.get(key).and_then(Value::as_str));
+            }
+            if let Some(tls) = &outbound.tls {
+                link.tls_basics(tls, "allow_insecure");
+                if tls.disable_sni == Some(true) {
+                    link.param("disable_sni", "1");
+                }
+            }
+            Ok(link.build())
+        }
+        OutboundType::AnyTls => {
+            let password = required(&outbound.password, "password")?;
+            let mut link = LinkBuilder::new("anytls", outbound, password)?;
+            if let Some(tls) = &outbound.tls {
+                link.tls_basics(tls, "insecure");
+            }
+            Ok(link.build())
+        }
+        _ => Err(format!(
+            "{} outbounds have no share link format",
+            outbound.kind.as_str()
+        )),
+    }
+}
+
+/// 由服务端入站生成客户端出站，每个用户一个
+///
+/// `server` 为客户端连接使用的地址。reality 公钥由私钥计算，short_id 取第一个。
+pub fn inbound_outbounds(inbound: &Inbound, server: &str) -> Result<Vec<Outbound>, String> {
+    let kind = match inbound.kind {
+        InboundType::Vless => OutboundType::Vless,
+        InboundType::Vmess => OutboundType::Vmess,
+        InboundType::Trojan => OutboundType::Trojan,
+        InboundType::Shadowsocks => OutboundType::Shadowsocks,
+        InboundType::Hysteria2 => OutboundType::Hysteria2,
+        InboundType::Tuic => OutboundType::Tuic,
+        InboundType::AnyTls => OutboundType::AnyTls,
+        _ => {
+            return Err(format!(
+                "{} inbounds have no client share link",
+                inbound.kind.as_str()
+            ))
+        }
+    };
+    let server = server.trim();
+    if server.is_empty() {
+        return Err("missing server address".to_string());
+    }
+    let port = inbound
+        .listen_port
+        .ok_or_else(|| "inbound has no listen_port".to_string())?;
+    let tls = inbound.tls.as_ref().map(client_tls).transpose()?.flatten();
+    let multiplex = inbound
+        .multiplex
+        .as_ref()
+        .filter(|multiplex| multiplex.is_enabled())
+        .map(|multiplex| Multiplex {
+            enabled: Some(true),
+            padding: multiplex.padding,
+            ..Default::default()
+        });
+    let base_tag = inbound
+        .tag
+        .clone()
+        .unwrap_or_else(|| inbound.kind.as_str().to_string());
+    let base = Outbound {
+        kind: kind.clone(),
+        server: Some(server.to_string()),
+        server_port: Some(port),
+        tls,
+        multiplex,
+        transport: inbound.transport.clone(),
+        ..Default::default()
+    };
+
+    // 单用户 shadowsocks 直接使用入站的密码
+    if kind == OutboundType::Shadowsocks && inbound.users().is_empty() {
+        let mut outbound = base;
+        outbound.tag = Some(base_tag);
+        outbound.method = inbound.method.clone();
+        outbound.password = inbound.password.clone();
+        return Ok(vec![outbound]);
+    }
+    if inbound.users().is_empty() {
+        return Err("inbound has no users".to_string());
+    }
+
+    let mut outbounds = Vec::new();
+    for (index, user) in inbound.users().iter().enumerate() {
+        let name = user.name.clone().unwrap_or_else(|| (index + 1).to_string());
+        let mut outbound = base.clone();
+        outbound.tag = Some(format!("{base_tag}-{name}"));
+        match kind {
+            OutboundType::Vless => {
+                outbound.uuid = user.uuid.clone();
+                outbound.flow = user.flow.clone();
+            }
+            OutboundType::Vmess => {
+                outbound.uuid = user.uuid.clone();
+                outbound.alter_id = user.alter_id.filter(|aid| *aid > 0);
+            }
+            OutboundType::Tuic => {
+                outbound.uuid = user.uuid.clone();
+                outbound.password = user.password.clone();
+                if let Some(value) = inbound.extra.get("congestion_control") {
+                    outbound
+                        .extra
+                        .insert("congestion_control".to_string(), value.clone());
+                }
+            }
+            OutboundType::Shadowsocks => {
+                outbound.method = inbound.method.clone();
+                // 多用户 2022 方法的客户端密码为 "服务端密钥:用户密钥"
+                outbound.password = match (&inbound.password, &user.password) {
+                    (Some(server), Some(user)) => Some(format!("{server}:{user}")),
+                    (_, user) => user.clone(),
+                };
+            }
+            OutboundType::Hysteria2 => {
+                outbound.password = user.password.clone();
+                if let Some(obfs) = inbound.extra.get("obfs") {
+                    outbound.extra.insert("obfs".to_string(), obfs.clone());
+                }
+            }
+            _ => outbound.password = user.password.clone(),
+        }
+        outbounds.push(outbound);
+    }
+    Ok(outbounds)
+}
+
+/// 由服务端入站生成客户端分享链接
+pub fn inbound_links(inbound: &Inbound, server: &str) -> Result<Vec<ClientLink>, String> {
+    inbound_outbounds(inbound, server)?
+        .iter()
+        .map(|outbound| {
+            Ok(ClientLink {
+                tag: outbound.tag.clone().unwrap_or_default(),
+                link: outbound_link(outbound)?,
+            })
+        })
+        .collect()
+}
+
+/// 服务端 TLS → 客户端 TLS，未启用时为 None
+fn client_tls(tls: &Tls) -> Result<Option<Tls>, String> {
+    if !tls.is_enabled() {
+        return Ok(None);
+    }
+    // ACME 证书的域名可作为 SNI
+    let acme_domain = tls
+        .extra
+        .get("acme")
+        .and_then(|acme| acme.get("domain"))
+        .and_then(|domain| match domain {
+            Value::String(domain) => Some(domain.clone()),
+            Value::Array(domains) => domains.first()?.as_str().map(str::to_string),
+            _ => None,
+        });
+    let mut client = Tls {
+        enabled: Some(true),
+        server_name: tls.server_name.clone().or(acme_domain),
+        alpn: tls.alpn.clone(),
+        ..Default::default()
+    };
+    if let Some(reality) = tls.reality.as_ref().filter(|r| r.enabled == Some(true)) {
+        let private_key = reality
+            .private_key
+            .as_deref()
+            .ok_or_else(|| "reality inbound has no private_key".to_string())?;
+        // 服务端 server_name 为空时使用握手目标
+        if client.server_name.is_none() {
+            client.server_name = reality
+                .handshake
+                .as_ref()
+                .and_then(|handshake| handshake.get("server"))
+                .and_then(Value::as_str)
+                .map(str::to_string);
+        }
+        client.utls = Some(Utls {
+            enabled: Some(true),
+            fingerprint: Some("chrome".to_string()),
+            ..Default::default()
+        });
+        client.reality = Some(Reality {
+            enabled: Some(true),
+            public_key: Some(reality_public_key(private_key)?),
+            short_id: reality
+                .short_id
+                .as_ref()
+                .and_then(|ids| ids.first().cloned())
+                .map(Listable::One),
+            ..Default::default()
+        });
+    }
+    Ok(Some(client))
+}
+
+fn required<'a>(value: &'a Option<String>, field: &str) -> Result<&'a str, String> {
+    value
+        .as_deref()
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| format!("missing {field}"))
+}
+
+fn join(values: &Listable<String>) -> String {
+    values.iter().cloned().collect::<Vec<_>>().join(",")
+}
+
+fn ws_host(transport: &Transport) -> Option<String> {
+    let headers = transport.headers.as_ref()?;
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("host"))
+        .and_then(|(_, value)| match value {
+            Value::String(host) => Some(host.clone()),
+            Value::Array(hosts) => hosts.first()?.as_str().map(str::to_string),
+            _ => None,
+        })
+        .or_else(|| {
+            transport
+                .host
+                .as_ref()
+                .and_then(|host| host.first().cloned())
+        })
+}
+
+/// ws 路径，early data 写作 ?ed=N
+fn ws_path(transport: &Transport) -> Option<String> {
+    let path = transport.path.clone().unwrap_or_default();
+    let early_data = transport
+        .max_early_data
+        .filter(|_| transport.early_data_header_name.as_deref() == Some("Sec-WebSocket-Protocol"));
+    match early_data {
+        Some(ed) => {
+            let base = if path.is_empty() { "/" } else { path.as_str() };
+            Some(format!("{base}?ed={ed}"))
+        }
+        None => Some(path).filter(|path| !path.is_empty()),
+    }
+}
+
+/// 链接中的主机，IPv6 地址加方括号
+fn host(outbound: &Outbound) -> Result<String, String> {
+    let server = required(&outbound.server, "server")?;
+    Ok(if server.contains(':') {
+        format!("[{server}]")
+    } else {
+        server.to_string()
+    })
+}
+
+/// scheme://userinfo@host:port?query#tag
+struct LinkBuilder {
+    scheme: &'static str,
+    userinfo: String,
+    host: String,
+    port: String,
+    query: Vec<(String, String)>,
+    tag: Option<String>,
+}
+
+impl LinkBuilder {
+    fn new(scheme: &'static str, outbound: &Outbound, secret: &str) -> Result<Self, String> {
+        Self::with_userinfo(scheme, outbound, encode_component(secret))
+    }
+
+    /// userinfo 已编码
+    fn with_userinfo(
+        scheme: &'static str,
+        outbound: &Outbound,
+        userinfo: String,
+    ) -> Result<Self, String> {
+        let port = outbound
+            .server_port
+            .ok_or_else(|| "missing server_port".to_string())?;
+        Ok(Self {
+            scheme,
+            userinfo,
+            host: host(outbound)?,
+            port: port.to_string(),
+            query: Vec::new(),
+            tag: outbound.tag.clone(),
+        })
+    }
+
+    fn param(&mut self, key: &str, value: &str) {
+        self.query.push((key.to_string(), value.to_string()));
+    }
+
+    fn param_opt(&mut self, key: &str, value: Option<&str>) {
+        if let Some(value) = value.filter(|value| !value.is_empty()) {
+            self.param(key, value);
+        }
+    }
+
+    fn tls_basics(&mut self, tls: &Tls, insecure_key: &str) {
+        self.param_opt("sni", tls.server_name.as_deref());
+        if let Some(alpn) = &tls.alpn {
+            self.param("alpn", &join(alpn));
+        }
+        if let Some(utls) = tls.utls.as_ref().filter(|utls| utls.enabled == Some(true)) {
+            self.param_opt("fp", utls.fingerprint.as_deref());
+        }
+        if tls.insecure == Some(true) {
+            self.param(insecure_key, "1");
+        }
+    }
+
+    /// vless / trojan 的 security、传输层和多路复用参数
+    fn stream(&mut self, outbound: &Outbound, default_security: &str) -> Result<(), String> {
+        let tls = outbound.tls.as_ref().filter(|tls| tls.is_enabled());
+        let reality = tls
+            .and_then(|tls| tls.reality.as_ref())
+            .filter(|reality| reality.enabled == Some(true));
+        let security = match (tls, reality) {
+            (None, _) => "none",
+            (Some(_), Some(_)) => "reality",
+            (Some(_), None) => "tls",
+        };
+        if security != default_security {
+            self.param("security", security);
+        }
+        if let Some(tls) = tls {
+            self.tls_basics(tls, "allowInsecure");
+        }
+        if let Some(reality) = reality {
+            self.param_opt("pbk", reality.public_key.as_deref());
+            self.param_opt(
+                "sid",
+                reality
+                    .short_id
+                    .as_ref()
+                    .and_then(|ids| ids.first())
+                    .map(String::as_str),
+            );
+        }
+
+        match outbound.transport.as_ref() {
+            None => self.param("type", "tcp"),
+            Some(transport) => match transport.kind.as_deref().unwrap_or_default() {
+                "ws" => {
+                    self.param("type", "ws");
+                    self.param_opt("host", ws_host(transport).as_deref());
+                    self.param_opt("path", ws_path(transport).as_deref());
+                }
+                kind @ ("http" | "httpupgrade") => {
+                    self.param("type", kind);
+                    if let Some(host) = &transport.host {
+                        self.param("host", &join(host));
+                    }
+                    self.param_opt("path", transport.path.as_deref());
+                }
+                "grpc" => {
+                    self.param("type", "grpc");
+                    self.param_opt("serviceName", transport.service_name.as_deref());
+                }
+                "quic" => self.param("type", "quic"),
+                other => return Err(format!("unsupported transport: {other}")),
+            },
+        }
+
+        if let Some(multiplex) = outbound.multiplex.as_ref().filter(|m| m.is_enabled()) {
+            self.param("mux", multiplex.protocol.as_deref().unwrap_or("1"));
+            if let Some(max_streams) = multiplex.max_streams {
+                self.param("mux_max_streams", &max_streams.to_string());
+            }
+            if multiplex.padding == Some(true) {
+                self.param("mux_padding", "1");
+            }
+        }
+        Ok(())
+    }
+
+    fn build(self) -> String {
+        let mut link = format!("{}://", self.scheme);
+        if !self.userinfo.is_empty() {
+            link.push_str(&self.userinfo);
+            link.push('@');
+        }
+        link.push_str(&self.host);
+        link.push(':');
+        link.push_str(&self.port);
+        if !self.query.is_empty() {
+            let query: Vec<String> = self
+                .query
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, encode_component(value)))
+                .collect();
+            link.push('?');
+            link.push_str(&query.join("&"));
+        }
+        if let Some(tag) = self.tag.filter(|tag| !tag.is_empty()) {
+            link.push('#');
+            link.push_str(&encode_component(&tag));
+        }
+        link
+    }
+}
+
+/// vmess://base64(JSON)，v2rayN 格式
+fn vmess_link(outbound: &Outbound) -> Result<String, String> {
+    let uuid = required(&outbound.uuid, "uuid")?;
+    let server = required(&outbound.server, "server")?;
+    let port = outbound
+        .server_port
+        .ok_or_else(|| "missing server_port".to_string())?;
+    let mut object = Map::new();
+    let mut set = |key: &str, value: Value| {
+        object.insert(key.to_string(), value);
+    };
+    set("v", json!("2"));
+    set("ps", json!(outbound.tag.clone().unwrap_or_default()));
+    set("add", json!(server));
+    set("port", json!(port.to_string()));
+    set("id", json!(uuid));
+    set("aid", json!(outbound.alter_id.unwrap_or(0).to_string()));
+    if let Some(security) = &outbound.security {
+        set("scy", json!(security));
+    }
+
+    let (net, host, path) = match outbound.transport.as_ref() {
+        None => ("tcp", None, None),
+        Some(transport) => match transport.kind.as_deref().unwrap_or_default() {
+            "ws" => ("ws", ws_host(transport), ws_path(transport)),
+            "http" => (
+                "h2",
+                transport.host.as_ref().map(join),
+                transport.path.clone(),
+            ),
+            "httpupgrade" => (
+                "httpupgrade",
+                transport.host.as_ref().map(join),
+                transport.path.clone(),
+            ),
+            "grpc" => ("grpc", None, transport.service_name.clone()),
+            "quic" => ("quic", None, None),
+            other => return Err(format!("unsupported transport: {other}")),
+        },
+    };
+    set("net", json!(net));
+    set("type", json!("none"));
+    set("host", json!(host.unwrap_or_default()));
+    set("path", json!(path.unwrap_or_default()));
+
+    match outbound.tls.as_ref().filter(|tls| tls.is_enabled()) {
+        Some(tls) => {
+            set("tls", json!("tls"));
+            set("sni", json!(tls.server_name.clone().unwrap_or_default()));
+            set(
+                "alpn",
+                json!(tls.alpn.as_ref().map(join).unwrap_or_default()),
+            );
+            let fingerprint = tls
+                .utls
+                .as_ref()
+                .filter(|utls| utls.enabled == Some(true))
+                .and_then(|utls| utls.fingerprint.clone());
+            set("fp", json!(fingerprint.unwrap_or_default()));
+        }
+        None => set("tls", json!("")),
+    }
+    let text = serde_json::to_string(&object).map_err(|e| e.to_string())?;
+    Ok(format!("vmess://{}", STANDARD.encode(text)))
+}
+
+/// SIP002：2022 方法使用百分号编码的明文，其余使用 base64url
+fn shadowsocks_link(outbound: &Outbound) -> Result<String, String> {
+    let method = required(&outbound.method, "method")?;
+    let password = required(&outbound.password, "password")?;
+    let userinfo = if method.starts_with("2022-") {
+        format!(
+            "{}:{}",
+            encode_component(method),
+            encode_component(password)
+        )
+    } else {
+        URL_SAFE_NO_PAD.encode(format!("{method}:{password}"))
+    };
+    let mut link = LinkBuilder::with_userinfo("ss", outbound, userinfo)?;
+    if let Some(plugin) = outbound.plugin.as_deref().filter(|p| !p.is_empty()) {
+        let value = match outbound.plugin_opts.as_deref().filter(|o| !o.is_empty()) {
+            Some(opts) => format!("{plugin};{opts}"),
+            None => plugin.to_string(),
+        };
+        link.param("plugin", &value);
+    }
+    Ok(link.build())
+}
+
+/// hysteria2://password@host:port,start-end?sni=...
+fn hysteria2_link(outbound: &Outbound) -> Result<String, String> {
+    let password = outbound.password.as_deref().unwrap_or_default();
+    let mut ports: Vec<String> = outbound
+        .server_port
+        .map(|port| port.to_string())
+        .into_iter()
+        .collect();
+    if let Some(ranges) = &outbound.server_ports {
+        ports.extend(ranges.iter().map(|range| range.replace(':', "-")));
+    }
+    if ports.is_empty() {
+        return Err("missing server_port".to_string());
+    }
+    let mut link = LinkBuilder {
+        scheme: "hysteria2",
+        userinfo: encode_component(password),
+        host: host(outbound)?,
+        port: ports.join(","),
+        query: Vec::new(),
+        tag: outbound.tag.clone(),
+    };
+    if let Some(tls) = &outbound.tls {
+        link.tls_basics(tls, "insecure");
+    }
+    if let Some(obfs) = outbound.extra.get("obfs") {
+        if obfs.get("type").and_then(Value::as_str) == Some("salamander") {
+            link.param("obfs", "salamander");
+            link.param_opt(
+                "obfs-password",
+                obfs.get("password").and_then(Value::as_str),
+            );
+        }
+    }
+    for (key, param) in [("up_mbps", "upmbps"), ("down_mbps", "downmbps")] {
+        if let Some(mbps) = outbound.extra.get(key).and_then(Value::as_u64) {
+            link.param(param, &mbps.to_string());
+        }
+    }
+    Ok(link.build())
+}
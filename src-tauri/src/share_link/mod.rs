@@ -0,0 +1,166 @@
+//! 代理分享链接：vmess / vless / trojan / ss / hysteria2 / tuic / anytls 与出站互转
+
+mod export;
+mod import;
+mod qr;
+
+pub use export::{inbound_links, inbound_outbounds, outbound_link, ClientLink};
+pub use import::{import_links, parse_link, ImportResult, LinkError};
+pub(crate) use import::unique_tag;
+pub use qr::{qr_png, qr_svg, QrFormat};
+
+use base64::alphabet;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+/// 链接各部分中需要编码的字符：除 RFC 3986 非保留字符外全部编码
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// 兼容标准/URL 安全字母表、有无填充的 base64 解码器
+const LENIENT_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new()
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent)
+        .with_decode_allow_trailing_bits(true),
+);
+
+/// 宽松的 base64 解码，忽略空白并接受 URL 安全字母表，订阅内容也使用这种编码
+pub fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let normalized: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            c => c,
+        })
+        .collect();
+    LENIENT_BASE64.decode(normalized).ok()
+}
+
+/// 解码为 UTF-8 文本
+pub fn decode_base64_text(text: &str) -> Option<String> {
+    String::from_utf8(decode_base64(text)?).ok()
+}
+
+/// 百分号解码，不把 '+' 当作空格（base64 密码中常见 '+'）
+fn percent_decode(text: &str) -> String {
+    percent_decode_str(text).decode_utf8_lossy().into_owned()
+}
+
+fn encode_component(text: &str) -> String {
+    utf8_percent_encode(text, COMPONENT).to_string()
+}
+
+/// 由 reality 私钥计算公钥（均为 base64url 无填充的 X25519 密钥）
+pub fn reality_public_key(private_key: &str) -> Result<String, String> {
+    let bytes: [u8; 32] = decode_base64(private_key)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| "invalid reality private_key".to_string())?;
+    let public_key = x25519_dalek::x25519(bytes, x25519_dalek::X25519_BASEPOINT_BYTES);
+    Ok(URL_SAFE_NO_PAD.encode(public_key))
+}
+
+/// 拆分后的链接：scheme://userinfo@host:port/path?query#fragment，忽略 scheme 和 path
+#[derive(Debug, Clone, Default)]
+struct LinkUrl {
+    /// 未解码，由各协议自行拆分后解码
+    userinfo: Option<String>,
+    /// IPv6 地址不含方括号
+    host: String,
+    /// 未解析的端口，hysteria2 可为 "443,1000-2000"
+    port: Option<String>,
+    /// 已解码
+    query: Vec<(String, String)>,
+    /// 已解码
+    fragment: Option<String>,
+}
+
+impl LinkUrl {
+    fn parse(link: &str) -> Result<Self, String> {
+        let (_, rest) = link
+            .split_once("://")
+            .ok_or_else(|| "missing scheme".to_string())?;
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(percent_decode(fragment))),
+            None => (rest, None),
+        };
+        let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let authority = rest
+            .split_once('/')
+            .map_or(rest, |(authority, _)| authority);
+        let (userinfo, host_port) = match authority.rsplit_once('@') {
+            Some((userinfo, host_port)) => (Some(userinfo.to_string()), host_port),
+            None => (None, authority),
+        };
+        let (host, port) = if let Some(bracketed) = host_port.strip_prefix('[') {
+            let (host, rest) = bracketed
+                .split_once(']')
+                .ok_or_else(|| format!("invalid IPv6 address: {host_port}"))?;
+            (host, rest.strip_prefix(':'))
+        } else {
+            match host_port.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (host_port, None),
+            }
+        };
+        let query = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (percent_decode(key), percent_decode(value))
+            })
+            .collect();
+        Ok(Self {
+            userinfo,
+            host: percent_decode(host),
+            port: port.filter(|port| !port.is_empty()).map(str::to_string),
+            query,
+            fragment,
+        })
+    }
+
+    /// 第一个非空的参数值
+    fn param(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, v)| k == key && !v.is_empty())
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// 参数为 1 / true 时为 true
+    fn flag(&self, key: &str) -> Option<bool> {
+        self.param(key)
+            .map(|value| matches!(value.to_ascii_lowercase().as_str(), "1" | "true"))
+    }
+
+    fn server(&self) -> Result<String, String> {
+        if self.host.is_empty() {
+            return Err("missing server address".to_string());
+        }
+        Ok(self.host.clone())
+    }
+
+    fn server_port(&self) -> Result<u16, String> {
+        let port = self
+            .port
+            .as_deref()
+            .ok_or_else(|| "missing server port".to_string())?;
+        parse_port(port)
+    }
+}
+
+fn parse_port(port: &str) -> Result<u16, String> {
+    port.trim()
+        .parse::<u16>()
+        .ok()
+        .filter(|port| *port != 0)
+        .ok_or_else(|| format!("invalid port: {port}"))
+}
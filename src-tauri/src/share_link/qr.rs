@@ -0,0 +1,75 @@
+//! 分享链接二维码，输出 PNG 或 SVG
+
+use std::path::Path;
+
+use qrcode::render::svg;
+use qrcode::{Color, QrCode};
+use serde::{Deserialize, Serialize};
+
+/// 二维码四周的空白宽度（模块数），规范要求至少 4
+const QUIET_ZONE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QrFormat {
+    Png,
+    Svg,
+}
+
+impl QrFormat {
+    /// 按扩展名判断格式
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(Self::Png),
+            "svg" => Some(Self::Svg),
+            _ => None,
+        }
+    }
+}
+
+fn encode(text: &str) -> Result<QrCode, String> {
+    QrCode::new(text.as_bytes()).map_err(|e| e.to_string())
+}
+
+/// 灰度 PNG，每个模块 module_size 像素
+pub fn qr_png(text: &str, module_size: u32) -> Result<Vec<u8>, String> {
+    let code = encode(text)?;
+    let scale = module_size.max(1) as usize;
+    let modules = code.width() + QUIET_ZONE * 2;
+    let size = modules * scale;
+    let colors = code.to_colors();
+
+    let mut pixels = vec![0xffu8; size * size];
+    for (index, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let x = (index % code.width() + QUIET_ZONE) * scale;
+        let y = (index / code.width() + QUIET_ZONE) * scale;
+        for row in y..y + scale {
+            pixels[row * size + x..row * size + x + scale].fill(0);
+        }
+    }
+
+    let mut out = Vec::new();
+    let side = u32::try_from(size).map_err(|e| e.to_string())?;
+    let mut encoder = png::Encoder::new(&mut out, side, side);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer
+        .write_image_data(&pixels)
+        .map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+/// SVG 文本，带空白边
+pub fn qr_svg(text: &str) -> Result<String, String> {
+    Ok(encode(text)?
+        .render::<svg::Color<'_>>()
+        .min_dimensions(256, 256)
+        .quiet_zone(true)
+        .build())
+}
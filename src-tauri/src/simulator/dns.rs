@@ -0,0 +1,491 @@
+//! DNS 模拟：依次评估 dns.rules，得到查询使用的服务器和应答的来源
+//!
+//! 带地址过滤字段（ip_cidr、ip_is_private、ip_accept_any）的规则先忽略这些字段匹配，
+//! 命中后用服务器的应答再匹配一次，不满足则继续评估后面的规则（docs/configuration/dns/rule.md）。
+
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{list, Connection, Matcher, Metadata, Rule, State};
+use crate::config::{Config, Dns, DnsAction, DnsRule, DnsServer, DnsServerType, QueryType};
+use crate::rule_set::query_type_code;
+
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+
+/// 模拟的 DNS 查询
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DnsQuery {
+    /// domain 为查询的域名，其余字段描述发起查询的连接
+    #[serde(flatten)]
+    pub connection: Connection,
+    /// 缺省为 A
+    pub query_type: Option<QueryType>,
+    /// 发起查询的出站，用于旧版 outbound 规则项
+    pub outbound: Option<String>,
+    /// 上游服务器会返回的地址，用于地址过滤字段
+    pub answers: Vec<IpAddr>,
+}
+
+/// 一条规则的评估记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleTrace {
+    /// dns.rules 中的序号
+    pub index: usize,
+    pub matched: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<DnsAction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// 应答的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnswerSource {
+    /// 上游服务器，即查询中填写的 answers
+    Upstream,
+    FakeIp,
+    Hosts,
+    /// predefined 动作或旧版 rcode 服务器
+    Predefined,
+    /// reject 动作
+    Rejected,
+}
+
+/// 处理查询的服务器
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerInfo {
+    pub tag: String,
+    /// 服务器类型；旧格式为地址的协议（udp、tls、fakeip、rcode 等）
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detour: Option<String>,
+}
+
+/// 模拟结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsDecision {
+    /// 最终动作：route、reject 或 predefined
+    pub action: DnsAction,
+    /// 命中的规则序号，为空表示没有规则命中、使用 dns.final
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<ServerInfo>,
+    pub source: AnswerSource,
+    /// 应答中的地址或记录
+    pub answers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rcode: Option<String>,
+    /// fakeip 应答使用的地址范围
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fakeip_range: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_cache: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewrite_ttl: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_subnet: Option<String>,
+    pub trace: Vec<RuleTrace>,
+    pub warnings: Vec<String>,
+}
+
+/// 服务器对查询的应答
+struct Answer {
+    source: AnswerSource,
+    addresses: Vec<IpAddr>,
+    rcode: Option<String>,
+    fakeip_range: Option<String>,
+}
+
+/// 模拟一次 DNS 查询，本地规则集和 hosts 文件的相对路径基于 base_dir
+pub fn simulate(config: &Config, query: &DnsQuery, base_dir: Option<&Path>) -> DnsDecision {
+    let default_dns = Dns::default();
+    let dns = config.dns.as_ref().unwrap_or(&default_dns);
+    let mut matcher = Matcher::new(config, base_dir);
+    let query_type = match query.query_type.as_ref().map(query_type_code) {
+        None => TYPE_A,
+        Some(Ok(code)) => code,
+        Some(Err(e)) => {
+            matcher.warn(e);
+            TYPE_A
+        }
+    };
+    let mut metadata = Metadata::new(&query.connection);
+    metadata.domain = metadata
+        .domain
+        .map(|domain| domain.trim_end_matches('.').to_string());
+    metadata.addresses = Vec::new();
+    metadata.query_type = Some(query_type);
+    metadata.outbound = query.outbound.clone();
+    metadata.ignore_destination_ip = true;
+
+    let mut decision = DnsDecision {
+        action: DnsAction::Route,
+        rule: None,
+        server: None,
+        source: AnswerSource::Upstream,
+        answers: Vec::new(),
+        rcode: None,
+        fakeip_range: None,
+        strategy: None,
+        disable_cache: dns.disable_cache,
+        rewrite_ttl: None,
+        client_subnet: dns.client_subnet.clone(),
+        trace: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let resolver = Resolver {
+        dns,
+        query,
+        query_type,
+        domain: metadata.domain.clone(),
+        base_dir,
+    };
+
+    for (index, rule) in dns.rules().iter().enumerate() {
+        let matched = matcher.matches(rule, &metadata, &mut State::default());
+        let mut trace = RuleTrace {
+            index,
+            matched,
+            action: None,
+            note: None,
+        };
+        if !matched {
+            decision.trace.push(trace);
+            continue;
+        }
+        let action = rule.action();
+        trace.action = Some(action.clone());
+        let is_final = match &action {
+            DnsAction::Route => {
+                let Some(tag) = rule.server_tag() else {
+                    matcher.warn(format!("dns.rules[{index}] has no server and is skipped"));
+                    decision.trace.push(trace);
+                    continue;
+                };
+                let Some(server) = dns.server(tag) else {
+                    matcher.warn(format!("DNS server {tag} is not defined"));
+                    decision.trace.push(trace);
+                    continue;
+                };
+                let strategy = rule
+                    .strategy
+                    .clone()
+                    .or_else(|| server.strategy.clone())
+                    .or_else(|| dns.strategy.clone());
+                let answer = resolver.answer(server, strategy.as_deref(), &mut matcher);
+                if has_address_filter(&mut matcher, rule) {
+                    let mut response = metadata.clone();
+                    response.addresses = answer.addresses.clone();
+                    response.ignore_destination_ip = false;
+                    if !matcher.matches(rule, &response, &mut State::default()) {
+                        trace.note = Some(format!(
+                            "response [{}] from {tag} does not match the address filter",
+                            join(&answer.addresses)
+                        ));
+                        decision.trace.push(trace);
+                        continue;
+                    }
+                }
+                set_options(&mut decision, rule);
+                decision.strategy = strategy;
+                decision.server = Some(server_info(server));
+                apply(&mut decision, answer);
+                true
+            }
+            DnsAction::RouteOptions => {
+                set_options(&mut decision, rule);
+                false
+            }
+            DnsAction::Reject => {
+                let method = rule.method.as_deref().unwrap_or("default");
+                decision.source = AnswerSource::Rejected;
+                decision.rcode = (method != "drop").then(|| "REFUSED".to_string());
+                trace.note = Some(format!("method {method}"));
+                true
+            }
+            DnsAction::Predefined => {
+                decision.source = AnswerSource::Predefined;
+                decision.rcode = Some(rule.rcode.clone().unwrap_or_else(|| "NOERROR".to_string()));
+                decision.answers = rule.extra.get("answer").map(strings).unwrap_or_default();
+                true
+            }
+            DnsAction::Other(name) => {
+                matcher.warn(format!(
+                    "unknown action {name} in dns.rules[{index}] is skipped"
+                ));
+                false
+            }
+        };
+        decision.trace.push(trace);
+        if is_final {
+            decision.action = action;
+            decision.rule = Some(index);
+            break;
+        }
+    }
+
+    if decision.rule.is_none() {
+        let server = match dns.final_server.as_deref() {
+            Some(tag) => {
+                let server = dns.server(tag);
+                if server.is_none() {
+                    matcher.warn(format!("dns.final {tag} is not defined"));
+                }
+                server
+            }
+            None => dns.servers().first(),
+        };
+        match server {
+            Some(server) => {
+                let strategy = server.strategy.clone().or_else(|| dns.strategy.clone());
+                let answer = resolver.answer(server, strategy.as_deref(), &mut matcher);
+                decision.strategy = strategy;
+                decision.server = Some(server_info(server));
+                apply(&mut decision, answer);
+            }
+            None => {
+                matcher.warn("no DNS server is defined, the system resolver is used".to_string());
+                decision.answers = resolver
+                    .upstream(dns.strategy.as_deref())
+                    .iter()
+                    .map(IpAddr::to_string)
+                    .collect();
+            }
+        }
+    }
+    decision.warnings = matcher.warnings;
+    decision
+}
+
+fn set_options(decision: &mut DnsDecision, rule: &DnsRule) {
+    if rule.disable_cache.is_some() {
+        decision.disable_cache = rule.disable_cache;
+    }
+    if rule.rewrite_ttl.is_some() {
+        decision.rewrite_ttl = rule.rewrite_ttl;
+    }
+    if rule.client_subnet.is_some() {
+        decision.client_subnet = rule.client_subnet.clone();
+    }
+}
+
+fn apply(decision: &mut DnsDecision, answer: Answer) {
+    decision.source = answer.source;
+    decision.answers = answer.addresses.iter().map(IpAddr::to_string).collect();
+    decision.rcode = answer.rcode;
+    decision.fakeip_range = answer.fakeip_range;
+}
+
+/// 规则（包括引用的规则集）是否带地址过滤字段
+fn has_address_filter(matcher: &mut Matcher, rule: &DnsRule) -> bool {
+    if let Some((_, rules)) = rule.logical() {
+        return rules.iter().any(|rule| has_address_filter(matcher, rule));
+    }
+    let items = &rule.items;
+    list(&items.ip_cidr).is_some()
+        || list(&items.geoip).is_some()
+        || items.ip_is_private == Some(true)
+        || rule.ip_accept_any()
+        || list(&items.rule_set)
+            .is_some_and(|tags| tags.iter().any(|tag| matcher.rule_set_has_ip_cidr(tag)))
+}
+
+fn server_info(server: &DnsServer) -> ServerInfo {
+    let kind = match (&server.kind, server.address.as_deref()) {
+        (Some(kind), _) => kind.to_string(),
+        (None, Some(address)) => legacy_kind(address).to_string(),
+        (None, None) => "unknown".to_string(),
+    };
+    let address = server.address.clone().or_else(|| {
+        let host = server.server.as_deref()?;
+        Some(match server.server_port {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        })
+    });
+    ServerInfo {
+        tag: server.tag.clone().unwrap_or_default(),
+        kind,
+        address,
+        detour: server.dial.detour.clone(),
+    }
+}
+
+/// 旧格式地址的协议，如 "tls://1.1.1.1" 为 tls，"8.8.8.8" 为 udp
+fn legacy_kind(address: &str) -> &str {
+    match address.split_once("://") {
+        Some((scheme, _)) => scheme,
+        None if address == "local" || address == "fakeip" => address,
+        None => "udp",
+    }
+}
+
+fn join(addresses: &[IpAddr]) -> String {
+    addresses
+        .iter()
+        .map(IpAddr::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(value) => vec![value.clone()],
+        Value::Array(values) => values
+            .iter()
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 按服务器类型生成应答
+struct Resolver<'a> {
+    dns: &'a Dns,
+    query: &'a DnsQuery,
+    query_type: u16,
+    domain: Option<String>,
+    base_dir: Option<&'a Path>,
+}
+
+impl Resolver<'_> {
+    fn answer(&self, server: &DnsServer, strategy: Option<&str>, matcher: &mut Matcher) -> Answer {
+        let legacy = server
+            .kind
+            .is_none()
+            .then_some(server.address.as_deref())
+            .flatten();
+        let mut answer = Answer {
+            source: AnswerSource::Upstream,
+            addresses: Vec::new(),
+            rcode: None,
+            fakeip_range: None,
+        };
+        if server.kind == Some(DnsServerType::FakeIp) || legacy == Some("fakeip") {
+            answer.source = AnswerSource::FakeIp;
+            let range = match self.query_type {
+                TYPE_A => "inet4_range",
+                TYPE_AAAA => "inet6_range",
+                _ => return answer,
+            };
+            // 旧格式的地址范围在 dns.fakeip 中
+            let value = if legacy.is_some() {
+                self.dns
+                    .fakeip
+                    .as_ref()
+                    .and_then(|fakeip| fakeip.get(range)?.as_str())
+            } else if self.query_type == TYPE_A {
+                server.inet4_range.as_deref()
+            } else {
+                server.inet6_range.as_deref()
+            };
+            match value {
+                Some(value) => {
+                    answer.addresses = first_address(value).into_iter().collect();
+                    answer.fakeip_range = Some(value.to_string());
+                }
+                None => matcher.warn(format!(
+                    "fakeip server {} has no {range}",
+                    server.tag.as_deref().unwrap_or_default()
+                )),
+            }
+        } else if let Some(rcode) = legacy.and_then(|address| address.strip_prefix("rcode://")) {
+            answer.source = AnswerSource::Predefined;
+            answer.rcode = Some(rcode.to_string());
+        } else if server.kind == Some(DnsServerType::Hosts) {
+            answer.source = AnswerSource::Hosts;
+            answer.addresses = self.hosts(server, matcher);
+        } else {
+            answer.addresses = self.upstream(strategy);
+        }
+        answer
+    }
+
+    /// 查询中填写的应答，按查询类型和域名策略过滤
+    fn upstream(&self, strategy: Option<&str>) -> Vec<IpAddr> {
+        let allowed = |addr: &IpAddr| match (self.query_type, strategy) {
+            (TYPE_A, Some("ipv6_only")) | (TYPE_AAAA, Some("ipv4_only")) => false,
+            (TYPE_A, _) => addr.is_ipv4(),
+            (TYPE_AAAA, _) => addr.is_ipv6(),
+            _ => true,
+        };
+        self.query.answers.iter().copied().filter(allowed).collect()
+    }
+
+    /// predefined 和 path 中的 hosts 条目；未填写 path 时不读取系统 hosts 文件
+    fn hosts(&self, server: &DnsServer, matcher: &mut Matcher) -> Vec<IpAddr> {
+        let Some(domain) = self.domain.as_deref() else {
+            return Vec::new();
+        };
+        let mut entries: Vec<String> = server
+            .predefined
+            .iter()
+            .flatten()
+            .filter(|(name, _)| name.eq_ignore_ascii_case(domain))
+            .flat_map(|(_, value)| strings(value))
+            .collect();
+        let paths = server
+            .path
+            .as_ref()
+            .map(|path| path.as_slice())
+            .unwrap_or_default();
+        if paths.is_empty() {
+            matcher.warn(
+                "the system hosts file is not read, only predefined entries are used".to_string(),
+            );
+        }
+        for path in paths {
+            let path = match self.base_dir {
+                Some(dir) if Path::new(path).is_relative() => dir.join(path),
+                _ => PathBuf::from(path),
+            };
+            match std::fs::read_to_string(&path) {
+                Ok(text) => entries.extend(hosts_file_entries(&text, domain)),
+                Err(e) => matcher.warn(format!("{}: {e}", path.display())),
+            }
+        }
+        entries
+            .iter()
+            .filter_map(|entry| entry.trim().parse::<IpAddr>().ok())
+            .filter(|addr| match self.query_type {
+                TYPE_A => addr.is_ipv4(),
+                TYPE_AAAA => addr.is_ipv6(),
+                _ => false,
+            })
+            .collect()
+    }
+}
+
+/// hosts 文件中某个域名的地址
+fn hosts_file_entries(text: &str, domain: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.split('#').next()?;
+            let mut fields = line.split_whitespace();
+            let addr = fields.next()?;
+            fields
+                .any(|name| name.eq_ignore_ascii_case(domain))
+                .then(|| addr.to_string())
+        })
+        .collect()
+}
+
+/// 地址范围中第一个可分配的地址（网络地址的下一个）
+fn first_address(range: &str) -> Option<IpAddr> {
+    let addr: IpAddr = range.split('/').next()?.trim().parse().ok()?;
+    Some(match addr {
+        IpAddr::V4(addr) => IpAddr::V4((u32::from(addr).checked_add(1)?).into()),
+        IpAddr::V6(addr) => IpAddr::V6((u128::from(addr).checked_add(1)?).into()),
+    })
+}
@@ -0,0 +1,636 @@
+//! 规则模拟：不启动内核，按 sing-box 的匹配逻辑在本地评估规则
+//!
+//! 默认规则的字段分为目标地址、目标端口、来源地址、来源端口四组，组内为或、组间为与
+//! （docs/configuration/route/rule.md）。各组的匹配结果记录在 [`State`] 中，
+//! 引用的规则集与外层规则共享这些结果，即规则集中的字段与外层规则合并计算。
+
+pub mod dns;
+pub mod route;
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{
+    Config, DnsRule, HeadlessRule, Listable, LogicalMode, RouteRule, RuleItems, RuleSet,
+    RuleSetType,
+};
+use crate::rule_set::{self, ip_set, query_type_code, RuleSetSource};
+
+/// 规则集互相引用的深度上限
+const MAX_RULE_SET_DEPTH: usize = 8;
+
+/// 模拟的连接，未填写的字段视为未知，使用这些字段的规则项不匹配
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Connection {
+    /// 入站标签
+    pub inbound: Option<String>,
+    /// tcp 或 udp
+    pub network: Option<String>,
+    pub domain: Option<String>,
+    /// 目标 IP；同时填写了域名时为域名的解析结果，在 resolve 动作之后才可见
+    pub ip: Option<IpAddr>,
+    pub port: Option<u16>,
+    pub source_ip: Option<IpAddr>,
+    pub source_port: Option<u16>,
+    /// 域名需要嗅探才能得到，如 tun 入站收到的是 IP 连接
+    pub sniff_domain: bool,
+    /// 嗅探得到的协议（tls、http、quic、dns 等），在 sniff 动作之后才可见
+    pub protocol: Option<String>,
+    pub client: Option<String>,
+    pub auth_user: Option<String>,
+    pub process_name: Option<String>,
+    pub process_path: Option<String>,
+    pub package_name: Option<String>,
+    pub user: Option<String>,
+    pub user_id: Option<u32>,
+    pub clash_mode: Option<String>,
+    pub network_type: Option<String>,
+    pub network_is_expensive: bool,
+    pub network_is_constrained: bool,
+    pub wifi_ssid: Option<String>,
+    pub wifi_bssid: Option<String>,
+}
+
+/// 规则评估时可见的连接信息，随 sniff、resolve 等动作变化
+#[derive(Debug, Clone)]
+pub(crate) struct Metadata<'a> {
+    pub connection: &'a Connection,
+    pub domain: Option<String>,
+    /// 目标地址；DNS 规则中为查询结果
+    pub addresses: Vec<IpAddr>,
+    pub protocol: Option<String>,
+    pub client: Option<String>,
+    /// DNS 查询类型
+    pub query_type: Option<u16>,
+    /// 发起 DNS 查询的出站
+    pub outbound: Option<String>,
+    /// DNS 规则在得到查询结果之前先忽略地址过滤字段
+    pub ignore_destination_ip: bool,
+}
+
+impl<'a> Metadata<'a> {
+    /// 连接刚进入路由时的状态
+    pub fn new(connection: &'a Connection) -> Self {
+        let domain = connection
+            .domain
+            .as_deref()
+            .map(str::trim)
+            .filter(|domain| !domain.is_empty())
+            .map(str::to_lowercase);
+        // 目标是域名时，IP 要在 resolve 之后才知道
+        let addresses = if domain.is_none() || connection.sniff_domain {
+            connection.ip.into_iter().collect()
+        } else {
+            Vec::new()
+        };
+        Self {
+            connection,
+            domain: domain.filter(|_| !connection.sniff_domain),
+            addresses,
+            protocol: None,
+            client: None,
+            query_type: None,
+            outbound: None,
+            ignore_destination_ip: false,
+        }
+    }
+}
+
+/// 默认规则各字段组的匹配结果，每条路由规则开始评估时清空
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct State {
+    source_address: bool,
+    source_port: bool,
+    destination_address: bool,
+    destination_port: bool,
+    /// rule_set_ip_cidr_match_source：规则集中的 ip_cidr 匹配来源地址
+    ip_cidr_match_source: bool,
+    /// rule_set_ip_cidr_accept_empty：规则集中的 ip_cidr 接受空的查询结果
+    ip_cidr_accept_empty: bool,
+}
+
+/// 路由规则、DNS 规则与规则集中的规则
+pub(crate) trait Rule {
+    /// 逻辑规则的模式和子规则
+    fn logical(&self) -> Option<(LogicalMode, &[Self])>
+    where
+        Self: Sized;
+    fn items(&self) -> &RuleItems;
+    fn invert(&self) -> bool;
+
+    /// DNS 规则的 outbound 字段
+    fn outbound(&self) -> Option<&Listable<String>> {
+        None
+    }
+
+    /// DNS 规则的 ip_accept_any 字段
+    fn ip_accept_any(&self) -> bool {
+        false
+    }
+
+    /// DNS 规则的 rule_set_ip_cidr_accept_empty 字段
+    fn ip_cidr_accept_empty(&self) -> bool {
+        false
+    }
+}
+
+impl Rule for RouteRule {
+    fn logical(&self) -> Option<(LogicalMode, &[Self])> {
+        self.is_logical().then(|| {
+            (
+                self.mode.clone().unwrap_or(LogicalMode::And),
+                self.rules.as_deref().unwrap_or_default(),
+            )
+        })
+    }
+
+    fn items(&self) -> &RuleItems {
+        &self.items
+    }
+
+    fn invert(&self) -> bool {
+        self.invert.unwrap_or(false)
+    }
+}
+
+impl Rule for HeadlessRule {
+    fn logical(&self) -> Option<(LogicalMode, &[Self])> {
+        self.is_logical().then(|| {
+            (
+                self.mode.clone().unwrap_or(LogicalMode::And),
+                self.rules.as_deref().unwrap_or_default(),
+            )
+        })
+    }
+
+    fn items(&self) -> &RuleItems {
+        &self.items
+    }
+
+    fn invert(&self) -> bool {
+        self.invert.unwrap_or(false)
+    }
+}
+
+impl Rule for DnsRule {
+    fn logical(&self) -> Option<(LogicalMode, &[Self])> {
+        self.is_logical().then(|| {
+            (
+                self.mode.clone().unwrap_or(LogicalMode::And),
+                self.rules.as_deref().unwrap_or_default(),
+            )
+        })
+    }
+
+    fn items(&self) -> &RuleItems {
+        &self.items
+    }
+
+    fn invert(&self) -> bool {
+        self.invert.unwrap_or(false)
+    }
+
+    fn outbound(&self) -> Option<&Listable<String>> {
+        self.outbound.as_ref()
+    }
+
+    fn ip_accept_any(&self) -> bool {
+        self.ip_accept_any.unwrap_or(false)
+    }
+
+    fn ip_cidr_accept_empty(&self) -> bool {
+        self.rule_set_ip_cidr_accept_empty.unwrap_or(false)
+    }
+}
+
+/// 规则匹配器，缓存已加载的规则集和编译好的正则表达式
+pub(crate) struct Matcher {
+    definitions: HashMap<String, RuleSet>,
+    base_dir: Option<PathBuf>,
+    rule_sets: HashMap<String, Option<Rc<Vec<HeadlessRule>>>>,
+    regexes: HashMap<String, Option<Regex>>,
+    depth: usize,
+    pub warnings: Vec<String>,
+}
+
+impl Matcher {
+    /// 本地规则集的相对路径基于 base_dir（通常是配置文件所在目录）
+    pub fn new(config: &Config, base_dir: Option<&Path>) -> Self {
+        let definitions = config
+            .route
+            .as_ref()
+            .map(|route| route.rule_sets())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|rule_set| Some((rule_set.tag.clone()?, rule_set.clone())))
+            .collect();
+        Self {
+            definitions,
+            base_dir: base_dir.map(Path::to_path_buf),
+            rule_sets: HashMap::new(),
+            regexes: HashMap::new(),
+            depth: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn warn(&mut self, message: String) {
+        if !self.warnings.contains(&message) {
+            self.warnings.push(message);
+        }
+    }
+
+    /// 评估一条规则，state 在同一条顶层规则内共享
+    pub fn matches<R: Rule>(&mut self, rule: &R, metadata: &Metadata, state: &mut State) -> bool {
+        let matched = match rule.logical() {
+            Some((mode, rules)) => {
+                let mut sub_rule = |rule: &R| {
+                    *state = State {
+                        ip_cidr_match_source: state.ip_cidr_match_source,
+                        ip_cidr_accept_empty: state.ip_cidr_accept_empty,
+                        ..State::default()
+                    };
+                    self.matches(rule, metadata, state)
+                };
+                match mode {
+                    LogicalMode::Or => rules.iter().any(&mut sub_rule),
+                    _ => rules.iter().all(&mut sub_rule),
+                }
+            }
+            None => self.default_matches(rule, metadata, state),
+        };
+        matched != rule.invert()
+    }
+
+    fn default_matches<R: Rule>(
+        &mut self,
+        rule: &R,
+        metadata: &Metadata,
+        state: &mut State,
+    ) -> bool {
+        let items = rule.items();
+        let connection = metadata.connection;
+        let source_ips: Vec<IpAddr> = connection.source_ip.into_iter().collect();
+
+        let source_address = [
+            list(&items.source_ip_cidr).map(|cidrs| self.cidr_matches(cidrs, &source_ips)),
+            items
+                .source_ip_is_private
+                .filter(|value| *value)
+                .map(|_| source_ips.iter().any(is_private)),
+            list(&items.source_geoip).map(|_| self.unsupported("source_geoip")),
+        ];
+        let source_port = [
+            list(&items.source_port).map(|ports| {
+                connection
+                    .source_port
+                    .is_some_and(|port| ports.contains(&port))
+            }),
+            list(&items.source_port_range)
+                .map(|ranges| self.port_range_matches(ranges, connection.source_port)),
+        ];
+        let destination_ips = if state.ip_cidr_match_source {
+            &source_ips
+        } else {
+            &metadata.addresses
+        };
+        let domain = metadata.domain.as_deref();
+        let destination_address = [
+            list(&items.domain).map(|domains| {
+                domain.is_some_and(|domain| {
+                    domains
+                        .iter()
+                        .any(|value| value.eq_ignore_ascii_case(domain))
+                })
+            }),
+            list(&items.domain_suffix).map(|suffixes| {
+                domain.is_some_and(|domain| {
+                    suffixes.iter().any(|suffix| suffix_matches(domain, suffix))
+                })
+            }),
+            list(&items.domain_keyword).map(|keywords| {
+                domain.is_some_and(|domain| {
+                    keywords
+                        .iter()
+                        .any(|keyword| domain.contains(&keyword.to_lowercase()))
+                })
+            }),
+            list(&items.domain_regex).map(|patterns| self.regex_matches(patterns, domain)),
+            list(&items.geosite).map(|_| self.unsupported("geosite")),
+        ];
+        // 与域名字段同属目标地址组，DNS 规则中作为地址过滤字段
+        let destination_ip = if metadata.ignore_destination_ip {
+            Vec::new()
+        } else {
+            let accept_empty = state.ip_cidr_accept_empty && destination_ips.is_empty();
+            vec![
+                list(&items.geoip).map(|_| self.unsupported("geoip")),
+                list(&items.ip_cidr)
+                    .map(|cidrs| accept_empty || self.cidr_matches(cidrs, destination_ips)),
+                items
+                    .ip_is_private
+                    .filter(|value| *value)
+                    .map(|_| metadata.addresses.iter().any(is_private)),
+                rule.ip_accept_any()
+                    .then_some(!metadata.addresses.is_empty()),
+            ]
+        };
+        let destination: Vec<Option<bool>> = destination_address
+            .into_iter()
+            .chain(destination_ip)
+            .collect();
+        let destination_port = [
+            list(&items.port)
+                .map(|ports| connection.port.is_some_and(|port| ports.contains(&port))),
+            list(&items.port_range).map(|ranges| self.port_range_matches(ranges, connection.port)),
+        ];
+        let groups = [
+            group(&mut state.source_address, &source_address),
+            group(&mut state.source_port, &source_port),
+            group(&mut state.destination_address, &destination),
+            group(&mut state.destination_port, &destination_port),
+        ];
+
+        // DNS 查询中 A 为 4、AAAA 为 6
+        let ip_version = match metadata.query_type {
+            Some(1) => Some(4),
+            Some(28) => Some(6),
+            Some(_) => None,
+            None => metadata
+                .addresses
+                .first()
+                .map(|addr| if addr.is_ipv4() { 4 } else { 6 }),
+        };
+        let others = [
+            list(&items.inbound).map(|tags| one_of(tags, &connection.inbound)),
+            items.ip_version.map(|version| ip_version == Some(version)),
+            list(&items.network).map(|networks| one_of(networks, &connection.network)),
+            list(&items.auth_user).map(|users| one_of(users, &connection.auth_user)),
+            list(&items.protocol).map(|protocols| one_of(protocols, &metadata.protocol)),
+            list(&items.client).map(|clients| one_of(clients, &metadata.client)),
+            list(&items.query_type).map(|types| {
+                metadata.query_type.is_some_and(|code| {
+                    types
+                        .iter()
+                        .any(|query_type| query_type_code(query_type) == Ok(code))
+                })
+            }),
+            rule.outbound().filter(|tags| !tags.is_empty()).map(|tags| {
+                metadata
+                    .outbound
+                    .as_ref()
+                    .is_some_and(|outbound| tags.iter().any(|tag| tag == outbound || tag == "any"))
+            }),
+            list(&items.process_name).map(|names| one_of(names, &connection.process_name)),
+            list(&items.process_path).map(|paths| one_of(paths, &connection.process_path)),
+            list(&items.process_path_regex)
+                .map(|patterns| self.regex_matches(patterns, connection.process_path.as_deref())),
+            list(&items.package_name).map(|names| one_of(names, &connection.package_name)),
+            list(&items.user).map(|users| one_of(users, &connection.user)),
+            list(&items.user_id).map(|ids| connection.user_id.is_some_and(|id| ids.contains(&id))),
+            items.clash_mode.as_ref().map(|mode| {
+                connection
+                    .clash_mode
+                    .as_ref()
+                    .is_some_and(|current| current.eq_ignore_ascii_case(mode))
+            }),
+            list(&items.network_type).map(|types| one_of(types, &connection.network_type)),
+            items
+                .network_is_expensive
+                .filter(|value| *value)
+                .map(|_| connection.network_is_expensive),
+            items
+                .network_is_constrained
+                .filter(|value| *value)
+                .map(|_| connection.network_is_constrained),
+            list(&items.wifi_ssid).map(|ssids| one_of(ssids, &connection.wifi_ssid)),
+            list(&items.wifi_bssid).map(|bssids| one_of(bssids, &connection.wifi_bssid)),
+        ];
+        if others.contains(&Some(false)) {
+            return false;
+        }
+        // 规则集最后评估，它的地址字段会补充外层规则的字段组
+        if let Some(tags) = list(&items.rule_set) {
+            let match_source = items
+                .rule_set_ip_cidr_match_source
+                .or(items.rule_set_ipcidr_match_source)
+                .unwrap_or(false);
+            let saved = *state;
+            state.ip_cidr_match_source = match_source;
+            state.ip_cidr_accept_empty = rule.ip_cidr_accept_empty();
+            let matched = tags
+                .iter()
+                .any(|tag| self.rule_set_matches(tag, metadata, state));
+            state.ip_cidr_match_source = saved.ip_cidr_match_source;
+            state.ip_cidr_accept_empty = saved.ip_cidr_accept_empty;
+            if !matched {
+                return false;
+            }
+        }
+        let flags = [
+            state.source_address,
+            state.source_port,
+            state.destination_address,
+            state.destination_port,
+        ];
+        groups
+            .iter()
+            .zip(flags)
+            .all(|(present, matched)| !present || matched)
+    }
+
+    fn rule_set_matches(&mut self, tag: &str, metadata: &Metadata, state: &mut State) -> bool {
+        let Some(rules) = self.rule_set(tag) else {
+            return false;
+        };
+        if self.depth >= MAX_RULE_SET_DEPTH {
+            self.warn(format!("rule-set {tag} is nested too deeply"));
+            return false;
+        }
+        self.depth += 1;
+        let matched = rules.iter().any(|rule| self.matches(rule, metadata, state));
+        self.depth -= 1;
+        matched
+    }
+
+    /// 规则集中是否有 ip_cidr 规则，DNS 规则据此判断是否要用查询结果再匹配一次
+    pub fn rule_set_has_ip_cidr(&mut self, tag: &str) -> bool {
+        fn has_ip_cidr(rule: &HeadlessRule) -> bool {
+            match rule.logical() {
+                Some((_, rules)) => rules.iter().any(has_ip_cidr),
+                None => list(&rule.items().ip_cidr).is_some(),
+            }
+        }
+        self.rule_set(tag)
+            .is_some_and(|rules| rules.iter().any(has_ip_cidr))
+    }
+
+    /// 加载规则集，无法评估的规则集给出警告并视为不匹配
+    fn rule_set(&mut self, tag: &str) -> Option<Rc<Vec<HeadlessRule>>> {
+        if let Some(rules) = self.rule_sets.get(tag) {
+            return rules.clone();
+        }
+        let rules = match self.load_rule_set(tag) {
+            Ok(rules) => Some(Rc::new(rules)),
+            Err(e) => {
+                self.warn(format!("rule-set {tag} is treated as not matching: {e}"));
+                None
+            }
+        };
+        self.rule_sets.insert(tag.to_string(), rules.clone());
+        rules
+    }
+
+    fn load_rule_set(&self, tag: &str) -> Result<Vec<HeadlessRule>, String> {
+        let rule_set = self
+            .definitions
+            .get(tag)
+            .ok_or("not defined in route.rule_set")?;
+        match rule_set.kind {
+            Some(RuleSetType::Inline) => Ok(rule_set.rules.clone().unwrap_or_default()),
+            Some(RuleSetType::Local) => {
+                let path = Path::new(rule_set.path.as_deref().ok_or("path is missing")?);
+                let path = match &self.base_dir {
+                    Some(dir) if path.is_relative() => dir.join(path),
+                    _ => path.to_path_buf(),
+                };
+                let binary = match rule_set.format.as_deref() {
+                    Some(format) => format == "binary",
+                    None => path.extension().is_some_and(|ext| ext == "srs"),
+                };
+                let source = if binary {
+                    rule_set::decompile_file(&path)?
+                } else {
+                    let text = std::fs::read_to_string(&path)
+                        .map_err(|e| format!("{}: {e}", path.display()))?;
+                    RuleSetSource::from_json(&text)?
+                };
+                Ok(source.rules)
+            }
+            Some(RuleSetType::Remote) => Err("remote rule-sets are not downloaded".to_string()),
+            _ => Err("unknown rule-set type".to_string()),
+        }
+    }
+
+    fn unsupported(&mut self, item: &str) -> bool {
+        self.warn(format!(
+            "{item} is removed in sing-box 1.12 and is treated as not matching"
+        ));
+        false
+    }
+
+    fn cidr_matches(&mut self, cidrs: &[String], addresses: &[IpAddr]) -> bool {
+        cidrs.iter().any(|cidr| {
+            addresses.iter().any(|addr| {
+                ip_set::contains(cidr, *addr).unwrap_or_else(|e| {
+                    self.warn(e);
+                    false
+                })
+            })
+        })
+    }
+
+    fn port_range_matches(&mut self, ranges: &[String], port: Option<u16>) -> bool {
+        let Some(port) = port else {
+            return false;
+        };
+        ranges.iter().any(|range| match parse_port_range(range) {
+            Some((from, to)) => (from..=to).contains(&port),
+            None => {
+                self.warn(format!("invalid port range: {range}"));
+                false
+            }
+        })
+    }
+
+    fn regex_matches(&mut self, patterns: &[String], value: Option<&str>) -> bool {
+        let Some(value) = value else {
+            return false;
+        };
+        patterns.iter().any(|pattern| {
+            if !self.regexes.contains_key(pattern) {
+                let regex = Regex::new(pattern)
+                    .map_err(|e| self.warn(format!("invalid regular expression {pattern}: {e}")))
+                    .ok();
+                self.regexes.insert(pattern.clone(), regex);
+            }
+            self.regexes[pattern]
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(value))
+        })
+    }
+}
+
+/// 非空列表
+fn list<T>(value: &Option<Listable<T>>) -> Option<&[T]> {
+    value
+        .as_ref()
+        .map(Listable::as_slice)
+        .filter(|values| !values.is_empty())
+}
+
+/// 记录字段组的匹配结果，返回该组是否有字段
+fn group(matched: &mut bool, items: &[Option<bool>]) -> bool {
+    let present = items.iter().any(Option::is_some);
+    if present && !*matched {
+        *matched = items.contains(&Some(true));
+    }
+    present
+}
+
+fn one_of(values: &[String], value: &Option<String>) -> bool {
+    value.as_ref().is_some_and(|value| values.contains(value))
+}
+
+/// "example.com" 匹配自身及子域名，".example.com" 只匹配子域名
+fn suffix_matches(domain: &str, suffix: &str) -> bool {
+    let suffix = suffix.to_lowercase();
+    if suffix.starts_with('.') {
+        return domain.ends_with(&suffix);
+    }
+    domain == suffix || domain.ends_with(&format!(".{suffix}"))
+}
+
+/// "1000:2000"、":3000" 或 "4000:"
+fn parse_port_range(range: &str) -> Option<(u16, u16)> {
+    let (from, to) = range.trim().split_once(':')?;
+    let from = if from.is_empty() {
+        0
+    } else {
+        from.parse().ok()?
+    };
+    let to = if to.is_empty() {
+        u16::MAX
+    } else {
+        to.parse().ok()?
+    };
+    (from <= to).then_some((from, to))
+}
+
+/// 非公网地址：私有、回环、链路本地、未指定和组播地址
+fn is_private(addr: &IpAddr) -> bool {
+    match addr.to_canonical() {
+        IpAddr::V4(addr) => {
+            addr.is_private()
+                || addr.is_loopback()
+                || addr.is_link_local()
+                || addr.is_unspecified()
+                || addr.is_multicast()
+                || addr.is_broadcast()
+        }
+        IpAddr::V6(addr) => {
+            let first = addr.segments()[0];
+            addr.is_loopback()
+                || addr.is_unspecified()
+                || addr.is_multicast()
+                || first & 0xfe00 == 0xfc00
+                || first & 0xffc0 == 0xfe80
+        }
+    }
+}
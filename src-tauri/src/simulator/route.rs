@@ -0,0 +1,234 @@
+//! 路由模拟：依次评估 route.rules 并执行规则动作，得到连接最终的去向
+//!
+//! sniff、resolve、route-options 是非最终动作，执行后继续评估后面的规则；
+//! route、reject、hijack-dns 是最终动作。没有规则命中时使用 route.final。
+
+use std::net::IpAddr;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Connection, Matcher, Metadata, State};
+use crate::config::{Config, RouteAction, RouteRule};
+
+/// 嗅探可以得到域名的协议
+const DOMAIN_PROTOCOLS: [&str; 3] = ["http", "tls", "quic"];
+
+/// 一条规则的评估记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleTrace {
+    /// route.rules 中的序号
+    pub index: usize,
+    pub matched: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<RouteAction>,
+    /// 动作的效果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// 模拟结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteDecision {
+    /// 最终动作：route、reject 或 hijack-dns
+    pub action: RouteAction,
+    /// route 动作的出站
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outbound: Option<String>,
+    /// 命中的规则序号，为空表示没有规则命中、使用 route.final
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<usize>,
+    /// reject 动作的拒绝方式
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_port: Option<u16>,
+    /// 路由结束时可见的域名、IP 和协议
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    pub addresses: Vec<IpAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    pub trace: Vec<RuleTrace>,
+    pub warnings: Vec<String>,
+}
+
+/// 模拟一条连接的路由，本地规则集的相对路径基于 base_dir
+pub fn simulate(
+    config: &Config,
+    connection: &Connection,
+    base_dir: Option<&Path>,
+) -> RouteDecision {
+    let mut matcher = Matcher::new(config, base_dir);
+    let mut metadata = Metadata::new(connection);
+    let mut decision = RouteDecision {
+        action: RouteAction::Route,
+        outbound: None,
+        rule: None,
+        method: None,
+        override_address: None,
+        override_port: None,
+        domain: None,
+        addresses: Vec::new(),
+        protocol: None,
+        trace: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    // 旧版入站 sniff 选项相当于在路由前执行 sniff 动作
+    let legacy_sniff = connection.inbound.as_deref().is_some_and(|tag| {
+        config.inbounds().iter().any(|inbound| {
+            inbound.tag.as_deref() == Some(tag)
+                && inbound.extra.get("sniff").and_then(|value| value.as_bool()) == Some(true)
+        })
+    });
+    if legacy_sniff {
+        let note = sniff(&mut metadata, &[]);
+        matcher.warn(format!(
+            "inbound sniff option is deprecated, {note} before routing"
+        ));
+    }
+
+    let rules = config
+        .route
+        .as_ref()
+        .map(|route| route.rules())
+        .unwrap_or_default();
+    for (index, rule) in rules.iter().enumerate() {
+        let matched = matcher.matches(rule, &metadata, &mut State::default());
+        let mut trace = RuleTrace {
+            index,
+            matched,
+            action: None,
+            note: None,
+        };
+        if !matched {
+            decision.trace.push(trace);
+            continue;
+        }
+        let action = rule.action();
+        let is_final = match &action {
+            RouteAction::Route => {
+                set_options(&mut decision, rule);
+                decision.outbound = rule.outbound.clone();
+                if decision.outbound.is_none() {
+                    matcher.warn(format!("route.rules[{index}] has no outbound"));
+                }
+                true
+            }
+            RouteAction::Reject => {
+                decision.method =
+                    Some(rule.method.clone().unwrap_or_else(|| "default".to_string()));
+                true
+            }
+            RouteAction::HijackDns => true,
+            RouteAction::RouteOptions => {
+                set_options(&mut decision, rule);
+                false
+            }
+            RouteAction::Sniff => {
+                let sniffers = rule
+                    .sniffer
+                    .as_ref()
+                    .map(|sniffer| sniffer.as_slice())
+                    .unwrap_or_default();
+                trace.note = Some(sniff(&mut metadata, sniffers));
+                false
+            }
+            RouteAction::Resolve => {
+                trace.note = Some(resolve(&mut metadata, rule.strategy.as_deref()));
+                false
+            }
+            RouteAction::Other(name) => {
+                matcher.warn(format!(
+                    "unknown action {name} in route.rules[{index}] is skipped"
+                ));
+                false
+            }
+        };
+        trace.action = Some(action.clone());
+        decision.trace.push(trace);
+        if is_final {
+            decision.action = action;
+            decision.rule = Some(index);
+            break;
+        }
+    }
+
+    if decision.rule.is_none() {
+        decision.outbound = config
+            .route
+            .as_ref()
+            .and_then(|route| route.final_outbound.clone())
+            .or_else(|| config.outbound_tags().first().map(|tag| tag.to_string()))
+            // 没有出站时内核会创建默认的 direct 出站
+            .or_else(|| Some("direct".to_string()));
+    }
+    decision.domain = metadata.domain;
+    decision.addresses = metadata.addresses;
+    decision.protocol = metadata.protocol;
+    decision.warnings = matcher.warnings;
+    decision
+}
+
+fn set_options(decision: &mut RouteDecision, rule: &RouteRule) {
+    if rule.override_address.is_some() {
+        decision.override_address = rule.override_address.clone();
+    }
+    if rule.override_port.is_some() {
+        decision.override_port = rule.override_port;
+    }
+}
+
+/// 嗅探出协议；http、tls、quic 还能得到域名
+fn sniff(metadata: &mut Metadata, sniffers: &[String]) -> String {
+    let connection = metadata.connection;
+    let Some(protocol) = connection.protocol.as_deref() else {
+        return "nothing sniffed".to_string();
+    };
+    if !sniffers.is_empty() && !sniffers.iter().any(|sniffer| sniffer == protocol) {
+        return format!("{protocol} is not enabled in sniffer");
+    }
+    metadata.protocol = Some(protocol.to_string());
+    metadata.client = connection.client.clone();
+    if metadata.domain.is_none() && DOMAIN_PROTOCOLS.contains(&protocol) {
+        if let Some(domain) = connection
+            .domain
+            .as_deref()
+            .filter(|domain| !domain.is_empty())
+        {
+            metadata.domain = Some(domain.to_lowercase());
+            return format!("sniffed {protocol}, domain {domain}");
+        }
+    }
+    format!("sniffed {protocol}")
+}
+
+/// 把目标域名解析为连接中填写的 IP
+fn resolve(metadata: &mut Metadata, strategy: Option<&str>) -> String {
+    if !metadata.addresses.is_empty() {
+        return "destination is already an IP address".to_string();
+    }
+    let Some(domain) = metadata.domain.as_deref() else {
+        return "no domain to resolve".to_string();
+    };
+    let Some(ip) = metadata.connection.ip else {
+        return format!("no IP given for {domain}");
+    };
+    let allowed = match strategy {
+        Some("ipv4_only") => ip.is_ipv4(),
+        Some("ipv6_only") => ip.is_ipv6(),
+        _ => true,
+    };
+    if !allowed {
+        return format!(
+            "{ip} is excluded by strategy {}",
+            strategy.unwrap_or_default()
+        );
+    }
+    let note = format!("resolved {domain} to {ip}");
+    metadata.addresses.push(ip);
+    note
+}
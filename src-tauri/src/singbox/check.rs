@@ -0,0 +1,181 @@
+//! 运行 `sing-box check`，把输出解析为带 JSON Pointer 路径的诊断
+
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::preflight::{IssueLevel, PreflightIssue};
+
+/// 与 `validation::ValidationResult` 结构一致，诊断沿用预检问题的格式
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub valid: bool,
+    pub diagnostics: Vec<PreflightIssue>,
+}
+
+/// 把配置写入临时文件后运行 `sing-box check -c`
+///
+/// 配置中的相对路径（如 rule_set 的 path）按 `work_dir` 解析，通常是文档所在目录。
+/// 输出中的临时文件路径会替换为 `file_name`。
+pub fn check(
+    binary: &Path,
+    config: &Value,
+    work_dir: Option<&Path>,
+    file_name: &str,
+) -> io::Result<CheckResult> {
+    let mut file = tempfile::Builder::new()
+        .prefix("sing-config-")
+        .suffix(".json")
+        .tempfile()?;
+    serde_json::to_writer_pretty(&mut file, config).map_err(io::Error::other)?;
+    file.flush()?;
+
+    let mut command = Command::new(binary);
+    command
+        .arg("check")
+        .arg("--disable-color")
+        .arg("-c")
+        .arg(file.path());
+    if let Some(dir) = work_dir {
+        command.current_dir(dir);
+    }
+    let output = command.output()?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr)
+        .replace(&file.path().display().to_string(), file_name);
+    let mut diagnostics = parse_check_output(&stderr);
+    let valid = output.status.success();
+    if !valid && !diagnostics.iter().any(|d| d.level == IssueLevel::Error) {
+        diagnostics.push(PreflightIssue {
+            level: IssueLevel::Error,
+            path: String::new(),
+            message: format!("sing-box check exited with {}", output.status),
+            fix: None,
+        });
+    }
+    Ok(CheckResult { valid, diagnostics })
+}
+
+/// 解析 `sing-box check` 的 stderr，每个非空行一条诊断
+///
+/// ```text
+/// FATAL[0000] initialize outbound[1]: missing server address
+/// WARN[0000] legacy special outbounds is deprecated in sing-box 1.11.0 ...
+/// ```
+pub fn parse_check_output(stderr: &str) -> Vec<PreflightIssue> {
+    stderr.lines().filter_map(parse_line).collect()
+}
+
+fn parse_line(line: &str) -> Option<PreflightIssue> {
+    let line = strip_ansi(line);
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let (level, message) = split_level(line);
+    let path = message
+        .split(": ")
+        .find_map(segment_pointer)
+        .unwrap_or_default();
+    Some(PreflightIssue {
+        level,
+        path,
+        message: message.to_string(),
+        fix: None,
+    })
+}
+
+/// 去掉终端颜色控制序列
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // ESC [ ... 终止字母
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// 识别日志级别前缀，如 `FATAL[0000]` 或带时间戳的 `+0800 2024-01-01 00:00:00 ERROR`
+///
+/// 没有级别前缀的行视为错误。
+fn split_level(line: &str) -> (IssueLevel, &str) {
+    let mut rest = line;
+    for _ in 0..4 {
+        let Some((word, tail)) = rest.split_once(char::is_whitespace) else {
+            break;
+        };
+        let name = word.split('[').next().unwrap_or_default();
+        let level = match name {
+            "FATAL" | "PANIC" | "ERROR" => Some(IssueLevel::Error),
+            "WARN" => Some(IssueLevel::Warning),
+            "INFO" | "DEBUG" | "TRACE" => Some(IssueLevel::Info),
+            _ => None,
+        };
+        if let Some(level) = level {
+            return (level, tail.trim_start());
+        }
+        rest = tail.trim_start();
+    }
+    (IssueLevel::Error, line)
+}
+
+/// 把错误链中的一段（如 `initialize outbound[1]`、`outbounds[0].tls.server_name`）
+/// 转换为 JSON Pointer，不是配置路径时返回 None
+fn segment_pointer(segment: &str) -> Option<String> {
+    let mut words = segment.split_whitespace().rev();
+    let word = words.next()?;
+    let dns = words
+        .next()
+        .is_some_and(|prev| prev.eq_ignore_ascii_case("dns"));
+
+    let mut parts = word.split('.');
+    let (root, index) = split_index(parts.next()?)?;
+    let mut pointer = match (root, index, dns) {
+        ("rule", Some(_), true) => "/dns/rules".to_string(),
+        ("server", Some(_), true) => "/dns/servers".to_string(),
+        ("rule", Some(_), false) => "/route/rules".to_string(),
+        ("rule-set" | "rule_set", _, _) => "/route/rule_set".to_string(),
+        ("inbound" | "outbound" | "endpoint" | "service", Some(_), _) => format!("/{}s", root),
+        ("inbounds" | "outbounds" | "endpoints" | "services", _, _) => format!("/{}", root),
+        ("log" | "dns" | "ntp" | "route" | "experimental" | "certificate", None, _) => {
+            format!("/{}", root)
+        }
+        _ => return None,
+    };
+    if let Some(index) = index {
+        pointer.push_str(&format!("/{}", index));
+    }
+    for part in parts {
+        let (field, index) = split_index(part)?;
+        pointer.push_str(&format!("/{}", field));
+        if let Some(index) = index {
+            pointer.push_str(&format!("/{}", index));
+        }
+    }
+    Some(pointer)
+}
+
+/// 拆分 `name[3]` 形式的路径片段
+fn split_index(part: &str) -> Option<(&str, Option<usize>)> {
+    let (name, index) = match part.split_once('[') {
+        Some((name, rest)) => (name, Some(rest.strip_suffix(']')?.parse().ok()?)),
+        None => (part, None),
+    };
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    valid.then_some((name, index))
+}
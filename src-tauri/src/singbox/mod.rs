@@ -0,0 +1,114 @@
+//! 本地 sing-box 内核：定位可执行文件、读取版本、运行 `sing-box check`，以及守护
+//! `sing-box run` 进程
+
+mod check;
+pub mod supervisor;
+
+pub use check::{check, parse_check_output, CheckResult};
+pub use supervisor::{CoreStatus, Launch, Supervisor};
+
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+/// 可执行文件名
+pub const BINARY_NAME: &str = if cfg!(windows) {
+    "sing-box.exe"
+} else {
+    "sing-box"
+};
+
+/// 已找到的内核
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoreInfo {
+    pub path: PathBuf,
+    /// 如 "1.12.0"
+    pub version: String,
+    /// 编译标签，如 with_gvisor、with_quic
+    pub tags: Vec<String>,
+}
+
+impl CoreInfo {
+    /// 运行 `sing-box version` 读取版本信息
+    pub fn detect(binary: &Path) -> io::Result<Self> {
+        let output = Command::new(binary).arg("version").output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "{} version exited with {}",
+                binary.display(),
+                output.status
+            )));
+        }
+        let text = String::from_utf8_lossy(&output.stdout);
+        let (version, tags) = parse_version_output(&text).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a sing-box binary", binary.display()),
+            )
+        })?;
+        Ok(Self {
+            path: binary.to_path_buf(),
+            version,
+            tags,
+        })
+    }
+}
+
+/// 解析 `sing-box version` 的输出，返回版本号和编译标签
+///
+/// ```text
+/// sing-box version 1.12.0
+///
+/// Environment: go1.24.2 linux/amd64
+/// Tags: with_gvisor,with_quic
+/// ```
+pub fn parse_version_output(text: &str) -> Option<(String, Vec<String>)> {
+    let mut lines = text.lines();
+    let version = lines
+        .next()?
+        .trim()
+        .strip_prefix("sing-box version ")?
+        .trim()
+        .to_string();
+    let tags = lines
+        .find_map(|line| line.trim().strip_prefix("Tags:"))
+        .map(|tags| {
+            tags.split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    Some((version, tags))
+}
+
+/// 按顺序查找内核：用户配置的路径、PATH 中的目录、应用数据目录
+///
+/// 用户配置的路径指向目录时在该目录下查找。
+pub fn locate(
+    configured: Option<&Path>,
+    path_var: Option<&OsStr>,
+    data_dir: Option<&Path>,
+) -> Option<PathBuf> {
+    let configured = configured.map(|path| {
+        if path.is_dir() {
+            path.join(BINARY_NAME)
+        } else {
+            path.to_path_buf()
+        }
+    });
+    let search = path_var
+        .map(|paths| std::env::split_paths(paths).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|dir| dir.join(BINARY_NAME));
+    configured
+        .into_iter()
+        .chain(search)
+        .chain(data_dir.map(|dir| dir.join(BINARY_NAME)))
+        .find(|path| path.is_file())
+}
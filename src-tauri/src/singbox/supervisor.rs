@@ -0,0 +1,399 @@
+//! sing-box 进程守护：启动/停止/重启 `sing-box run`，异常退出后按退避策略重启，
+//! 并把 stdout/stderr 逐行转发出去
+
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// 检查子进程是否退出的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 停止时等待进程自行退出的时间，超时后强制结束
+#[cfg(unix)]
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 内核运行状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "lowercase")]
+pub enum CoreStatus {
+    Stopped,
+    Running { pid: u32 },
+    /// 异常退出后等待重启，attempt 从 1 开始
+    Restarting { attempt: u32, delay_ms: u64 },
+    /// 无法启动或重启次数用尽
+    Failed { message: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogLine {
+    pub stream: LogStream,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupervisorEvent {
+    Status(CoreStatus),
+    Log(LogLine),
+}
+
+/// 接收状态变化和日志，会在后台线程中调用
+pub type EventSink = Arc<dyn Fn(SupervisorEvent) + Send + Sync>;
+
+/// 启动参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Launch {
+    pub binary: PathBuf,
+    pub config: PathBuf,
+    /// 配置中相对路径的解析目录，通常是配置文件所在目录
+    pub work_dir: Option<PathBuf>,
+}
+
+impl Launch {
+    fn spawn(&self) -> io::Result<Child> {
+        let mut command = Command::new(&self.binary);
+        command
+            .arg("run")
+            .arg("--disable-color")
+            .arg("-c")
+            .arg(&self.config)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(dir) = &self.work_dir {
+            command.current_dir(dir);
+        }
+        command.spawn()
+    }
+}
+
+/// 异常退出后的重启策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    /// 第一次重启前的等待时间，之后每次翻倍
+    pub initial: Duration,
+    pub max: Duration,
+    /// 连续重启次数上限
+    pub max_retries: u32,
+    /// 进程运行超过该时间后退出，重新从第一次重启开始计数
+    pub reset_after: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(30),
+            max_retries: 5,
+            reset_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Backoff {
+    /// 第 attempt 次重启前的等待时间
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+}
+
+struct State {
+    status: CoreStatus,
+    /// 每次启动或停止加一，旧的守护线程发现不一致后退出
+    generation: u64,
+    launch: Option<(Launch, EventSink)>,
+    monitor: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    wake: Condvar,
+    backoff: Backoff,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 仍是当前这次启动时更新状态并通知
+    fn set_status(&self, generation: u64, status: CoreStatus, sink: &EventSink) {
+        {
+            let mut state = self.lock();
+            if state.generation != generation {
+                return;
+            }
+            state.status = status.clone();
+        }
+        sink(SupervisorEvent::Status(status));
+    }
+}
+
+/// sing-box 进程守护
+pub struct Supervisor {
+    shared: Arc<Shared>,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new(Backoff::default())
+    }
+}
+
+impl Supervisor {
+    pub fn new(backoff: Backoff) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    status: CoreStatus::Stopped,
+                    generation: 0,
+                    launch: None,
+                    monitor: None,
+                }),
+                wake: Condvar::new(),
+                backoff,
+            }),
+        }
+    }
+
+    pub fn status(&self) -> CoreStatus {
+        self.shared.lock().status.clone()
+    }
+
+    /// 最近一次的启动参数
+    pub fn launch(&self) -> Option<Launch> {
+        self.shared.lock().launch.as_ref().map(|(launch, _)| launch.clone())
+    }
+
+    /// 启动内核，已在运行时先停止
+    ///
+    /// 第一次启动失败（如可执行文件不存在）直接返回错误，之后的重启失败通过状态通知。
+    pub fn start(&self, launch: Launch, sink: EventSink) -> io::Result<()> {
+        self.stop();
+        let child = match launch.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                let status = CoreStatus::Failed {
+                    message: e.to_string(),
+                };
+                self.shared.lock().status = status.clone();
+                sink(SupervisorEvent::Status(status));
+                return Err(e);
+            }
+        };
+
+        let mut state = self.shared.lock();
+        state.generation += 1;
+        state.status = CoreStatus::Running { pid: child.id() };
+        state.launch = Some((launch.clone(), sink.clone()));
+        let generation = state.generation;
+        let shared = self.shared.clone();
+        state.monitor = Some(thread::spawn(move || {
+            supervise(&shared, generation, &launch, &sink, child)
+        }));
+        Ok(())
+    }
+
+    /// 停止内核，等待进程退出
+    pub fn stop(&self) {
+        let (monitor, sink, changed) = {
+            let mut state = self.shared.lock();
+            state.generation += 1;
+            let changed = state.status != CoreStatus::Stopped;
+            state.status = CoreStatus::Stopped;
+            let sink = state.launch.as_ref().map(|(_, sink)| sink.clone());
+            (state.monitor.take(), sink, changed)
+        };
+        self.shared.wake.notify_all();
+        if let Some(monitor) = monitor {
+            let _ = monitor.join();
+        }
+        if let (true, Some(sink)) = (changed, sink) {
+            sink(SupervisorEvent::Status(CoreStatus::Stopped));
+        }
+    }
+
+    /// 用最近一次的启动参数重新启动
+    pub fn restart(&self) -> io::Result<()> {
+        let (launch, sink) = self
+            .shared
+            .lock()
+            .launch
+            .clone()
+            .ok_or_else(|| io::Error::other("sing-box has not been started"))?;
+        self.start(launch, sink)
+    }
+}
+
+impl Drop for Supervisor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 守护线程：等待进程退出，异常退出时按退避策略重启
+fn supervise(
+    shared: &Shared,
+    generation: u64,
+    launch: &Launch,
+    sink: &EventSink,
+    mut child: Child,
+) {
+    let mut attempt = 0;
+    loop {
+        let started = Instant::now();
+        shared.set_status(generation, CoreStatus::Running { pid: child.id() }, sink);
+        let readers = forward_logs(&mut child, sink);
+        let exit = wait_or_stop(shared, generation, &mut child);
+        for reader in readers {
+            let _ = reader.join();
+        }
+        // 已被停止或重新启动，状态由 stop 设置
+        let Some(exit) = exit else {
+            return;
+        };
+        if exit.success() {
+            shared.set_status(generation, CoreStatus::Stopped, sink);
+            return;
+        }
+
+        if started.elapsed() >= shared.backoff.reset_after {
+            attempt = 0;
+        }
+        attempt += 1;
+        if attempt > shared.backoff.max_retries {
+            let message = format!("sing-box exited with {}", exit);
+            shared.set_status(generation, CoreStatus::Failed { message }, sink);
+            return;
+        }
+        let delay = shared.backoff.delay(attempt);
+        let delay_ms = delay.as_millis().try_into().unwrap_or(u64::MAX);
+        shared.set_status(generation, CoreStatus::Restarting { attempt, delay_ms }, sink);
+        if !sleep_unless_stopped(shared, generation, delay) {
+            return;
+        }
+        child = match launch.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                let message = e.to_string();
+                shared.set_status(generation, CoreStatus::Failed { message }, sink);
+                return;
+            }
+        };
+    }
+}
+
+/// 等待进程退出；被停止时结束进程并返回 None
+fn wait_or_stop(shared: &Shared, generation: u64, child: &mut Child) -> Option<ExitStatus> {
+    let mut state = shared.lock();
+    loop {
+        if state.generation != generation {
+            drop(state);
+            terminate(child);
+            return None;
+        }
+        match child.try_wait() {
+            Ok(Some(exit)) => return Some(exit),
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("Failed to wait for sing-box: {}", e);
+                drop(state);
+                terminate(child);
+                return None;
+            }
+        }
+        state = shared
+            .wake
+            .wait_timeout(state, POLL_INTERVAL)
+            .unwrap_or_else(|e| e.into_inner())
+            .0;
+    }
+}
+
+/// 等待重启间隔，期间被停止时返回 false
+fn sleep_unless_stopped(shared: &Shared, generation: u64, delay: Duration) -> bool {
+    let deadline = Instant::now() + delay;
+    let mut state = shared.lock();
+    loop {
+        if state.generation != generation {
+            return false;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        state = shared
+            .wake
+            .wait_timeout(state, deadline - now)
+            .unwrap_or_else(|e| e.into_inner())
+            .0;
+    }
+}
+
+/// 先请求进程自行退出（sing-box 需要清理路由和系统代理），超时后强制结束
+fn terminate(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        // SAFETY: 只向自己启动且尚未回收的子进程发送信号
+        let pid = child.id() as libc::pid_t;
+        if unsafe { libc::kill(pid, libc::SIGTERM) } == 0 {
+            let deadline = Instant::now() + STOP_TIMEOUT;
+            while Instant::now() < deadline {
+                if let Ok(Some(_)) = child.try_wait() {
+                    return;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// 为 stdout 和 stderr 各启动一个线程逐行转发
+fn forward_logs(child: &mut Child, sink: &EventSink) -> Vec<JoinHandle<()>> {
+    let stdout = child
+        .stdout
+        .take()
+        .map(|pipe| forward_stream(pipe, LogStream::Stdout, sink.clone()));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|pipe| forward_stream(pipe, LogStream::Stderr, sink.clone()));
+    stdout.into_iter().chain(stderr).collect()
+}
+
+fn forward_stream(
+    pipe: impl Read + Send + 'static,
+    stream: LogStream,
+    sink: EventSink,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(pipe);
+        let mut buffer = Vec::new();
+        loop {
+            buffer.clear();
+            match reader.read_until(b'\n', &mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    let line = String::from_utf8_lossy(&buffer)
+                        .trim_end_matches(['\r', '\n'])
+                        .to_string();
+                    sink(SupervisorEvent::Log(LogLine { stream, line }));
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,107 @@
+//! 拉取订阅内容并识别格式
+
+use std::time::Duration;
+
+use crate::config::{Config, Outbound, OutboundType};
+use crate::{clash, share_link};
+
+/// 请求超时
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 订阅内容大小上限
+const MAX_BODY_SIZE: u64 = 16 * 1024 * 1024;
+
+/// 部分订阅服务按 User-Agent 返回不同格式，带上 sing-box 以获取 sing-box 可用的节点
+const USER_AGENT: &str = concat!("sing-config/", env!("CARGO_PKG_VERSION"), " (sing-box)");
+
+/// 解析出的节点和无法解析的条目
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Parsed {
+    pub outbounds: Vec<Outbound>,
+    pub skipped: Vec<String>,
+}
+
+/// 下载订阅内容
+pub fn fetch(url: &str) -> Result<String, String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(FETCH_TIMEOUT))
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let response = agent
+        .get(url)
+        .header("User-Agent", USER_AGENT)
+        .call()
+        .map_err(|e| e.to_string())?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("subscription server returned {status}"));
+    }
+    response
+        .into_body()
+        .with_config()
+        .limit(MAX_BODY_SIZE)
+        .read_to_string()
+        .map_err(|e| e.to_string())
+}
+
+/// 识别订阅格式并解析出节点：sing-box JSON、Clash YAML，或（base64 编码的）分享链接列表
+pub fn parse_content(text: &str) -> Result<Parsed, String> {
+    let text = text.trim_start_matches('\u{feff}').trim();
+    if text.is_empty() {
+        return Err("subscription is empty".to_string());
+    }
+
+    let parsed = if text.starts_with('{') {
+        let config = Config::from_json(text).map_err(|e| format!("invalid sing-box config: {e}"))?;
+        let outbounds = config
+            .outbounds
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|outbound| is_proxy(&outbound.kind))
+            .collect();
+        Parsed {
+            outbounds,
+            skipped: Vec::new(),
+        }
+    } else if let Some(profile) = clash::parse_yaml(text)
+        .ok()
+        .filter(clash::is_profile)
+    {
+        let (outbounds, unsupported) = clash::convert_proxies(&profile);
+        Parsed {
+            outbounds,
+            skipped: unsupported
+                .into_iter()
+                .map(|item| format!("{}: {}", item.path, item.message))
+                .collect(),
+        }
+    } else {
+        let result = share_link::import_links(text, &[]);
+        Parsed {
+            outbounds: result.outbounds,
+            skipped: result
+                .errors
+                .into_iter()
+                .map(|error| format!("line {}: {}", error.line, error.message))
+                .collect(),
+        }
+    };
+
+    if parsed.outbounds.is_empty() {
+        return Err(match parsed.skipped.first() {
+            Some(first) => format!("no usable proxies found ({first})"),
+            None => "no proxies found".to_string(),
+        });
+    }
+    Ok(parsed)
+}
+
+/// 订阅中的代理节点，跳过分组和 direct/block/dns 等内置出站
+fn is_proxy(kind: &OutboundType) -> bool {
+    !kind.is_group()
+        && !matches!(
+            kind,
+            OutboundType::Direct | OutboundType::Block | OutboundType::Dns
+        )
+}
@@ -0,0 +1,367 @@
+//! 订阅：保存订阅地址，拉取节点（base64 链接列表、sing-box JSON、Clash YAML），
+//! 加上前缀后合并到配置中，并重新生成托管的 selector/urltest 分组
+//!
+//! 订阅列表和最近一次拉取到的节点保存在应用配置目录下的 subscriptions.json。
+
+mod fetch;
+mod scheduler;
+
+pub use fetch::{fetch, parse_content, Parsed};
+pub use scheduler::Scheduler;
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::config::{Config, Outbound, OutboundType};
+use crate::share_link::unique_tag;
+
+/// 订阅列表文件名
+pub const FILE_NAME: &str = "subscriptions.json";
+
+/// 托管分组的默认标签
+pub const DEFAULT_GROUP_TAG: &str = "subscriptions";
+
+/// 默认自动刷新间隔（分钟）
+pub const DEFAULT_INTERVAL_MINUTES: u64 = 360;
+
+/// 托管分组类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupKind {
+    #[default]
+    Selector,
+    UrlTest,
+}
+
+/// 每次刷新都会重新生成的分组出站
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManagedGroup {
+    pub tag: String,
+    #[serde(rename = "type", default)]
+    pub kind: GroupKind,
+    /// urltest 测试地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// urltest 测试间隔，如 "3m"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+}
+
+impl Default for ManagedGroup {
+    fn default() -> Self {
+        Self {
+            tag: DEFAULT_GROUP_TAG.to_string(),
+            kind: GroupKind::default(),
+            url: None,
+            interval: None,
+        }
+    }
+}
+
+impl ManagedGroup {
+    fn to_outbound(&self, members: Vec<String>) -> Outbound {
+        let kind = match self.kind {
+            GroupKind::Selector => OutboundType::Selector,
+            GroupKind::UrlTest => OutboundType::UrlTest,
+        };
+        let mut outbound = Outbound::new(kind, self.tag.clone());
+        outbound.outbounds = Some(members);
+        if self.kind == GroupKind::UrlTest {
+            outbound.url = self.url.clone();
+            outbound.interval = self.interval.clone();
+        }
+        outbound
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Subscription {
+    /// 唯一名称
+    pub name: String,
+    pub url: String,
+    /// 节点标签前缀，默认为 "[name] "
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 最近一次成功拉取的时间（Unix 秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<u64>,
+    /// 最近一次拉取失败的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 最近一次拉取到的节点，标签已加前缀
+    #[serde(default)]
+    pub outbounds: Vec<Outbound>,
+}
+
+impl Subscription {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn prefix(&self) -> String {
+        self.prefix
+            .clone()
+            .filter(|prefix| !prefix.is_empty())
+            .unwrap_or_else(|| format!("[{}] ", self.name))
+    }
+
+    /// 拉取并解析，成功时替换保存的节点
+    pub fn refresh(&mut self, now: u64) -> RefreshResult {
+        let result = fetch(&self.url).and_then(|text| parse_content(&text));
+        match result {
+            Ok(parsed) => {
+                let prefix = self.prefix();
+                self.outbounds = parsed
+                    .outbounds
+                    .into_iter()
+                    .map(|mut outbound| {
+                        let tag = outbound.tag.take().unwrap_or_default();
+                        outbound.tag = Some(format!("{prefix}{tag}"));
+                        outbound
+                    })
+                    .collect();
+                self.updated_at = Some(now);
+                self.error = None;
+                RefreshResult {
+                    name: self.name.clone(),
+                    count: self.outbounds.len(),
+                    skipped: parsed.skipped,
+                    error: None,
+                }
+            }
+            Err(message) => {
+                self.error = Some(message.clone());
+                RefreshResult {
+                    name: self.name.clone(),
+                    count: self.outbounds.len(),
+                    skipped: Vec::new(),
+                    error: Some(message),
+                }
+            }
+        }
+    }
+}
+
+/// 单个订阅的刷新结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefreshResult {
+    pub name: String,
+    /// 当前保存的节点数，失败时为上次的数量
+    pub count: usize,
+    /// 无法解析的条目
+    pub skipped: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subscriptions {
+    #[serde(default)]
+    pub subscriptions: Vec<Subscription>,
+    #[serde(default)]
+    pub group: ManagedGroup,
+    /// 自动刷新间隔（分钟），0 表示不自动刷新
+    #[serde(default = "default_interval")]
+    pub interval_minutes: u64,
+    /// 上次合并时生成的节点标签，下次合并时只移除这些节点
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub applied: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn default_interval() -> u64 {
+    DEFAULT_INTERVAL_MINUTES
+}
+
+impl Default for Subscriptions {
+    fn default() -> Self {
+        Self {
+            subscriptions: Vec::new(),
+            group: ManagedGroup::default(),
+            interval_minutes: DEFAULT_INTERVAL_MINUTES,
+            applied: Vec::new(),
+            extra: Map::new(),
+        }
+    }
+}
+
+/// 当前 Unix 时间（秒）
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+impl Subscriptions {
+    /// 读取列表，文件不存在或内容损坏时为空
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// 写入列表，自动创建所在目录
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Subscription> {
+        self.subscriptions.iter().find(|sub| sub.name == name)
+    }
+
+    /// 添加或按名称替换订阅，替换时保留已拉取的节点
+    pub fn upsert(&mut self, mut subscription: Subscription) -> Result<(), String> {
+        if subscription.name.trim().is_empty() {
+            return Err("subscription name is empty".to_string());
+        }
+        if !subscription.url.starts_with("http://") && !subscription.url.starts_with("https://") {
+            return Err(format!("unsupported subscription URL: {}", subscription.url));
+        }
+        match self
+            .subscriptions
+            .iter_mut()
+            .find(|sub| sub.name == subscription.name)
+        {
+            Some(existing) => {
+                if existing.url == subscription.url && subscription.outbounds.is_empty() {
+                    subscription.outbounds = std::mem::take(&mut existing.outbounds);
+                    subscription.updated_at = existing.updated_at;
+                }
+                *existing = subscription;
+            }
+            None => self.subscriptions.push(subscription),
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.subscriptions.len();
+        self.subscriptions.retain(|sub| sub.name != name);
+        self.subscriptions.len() != len
+    }
+
+    /// 刷新所有启用的订阅，失败的保留上次的节点
+    pub fn refresh(&mut self) -> Vec<RefreshResult> {
+        let now = now();
+        self.subscriptions
+            .iter_mut()
+            .filter(|sub| sub.enabled)
+            .map(|sub| sub.refresh(now))
+            .collect()
+    }
+
+    /// 写回在列表副本上刷新的结果
+    ///
+    /// 按名称对应；拉取期间被删除或修改了地址的订阅以当前列表为准。
+    pub fn merge_refreshed(&mut self, refreshed: Vec<Subscription>) {
+        for fresh in refreshed {
+            if let Some(sub) = self
+                .subscriptions
+                .iter_mut()
+                .find(|sub| sub.name == fresh.name && sub.url == fresh.url)
+            {
+                sub.outbounds = fresh.outbounds;
+                sub.updated_at = fresh.updated_at;
+                sub.error = fresh.error;
+            }
+        }
+    }
+
+    /// 是否有启用的订阅已到刷新时间
+    pub fn is_due(&self, now: u64) -> bool {
+        if self.interval_minutes == 0 {
+            return false;
+        }
+        let interval = self.interval_minutes * 60;
+        self.subscriptions
+            .iter()
+            .filter(|sub| sub.enabled)
+            .any(|sub| sub.updated_at.is_none_or(|at| now.saturating_sub(at) >= interval))
+    }
+
+    /// 把订阅节点合并到配置中
+    ///
+    /// 先移除上次合并的节点（记录在 applied 中的标签，以及带订阅前缀的标签）和托管分组，
+    /// 再追加启用订阅的节点，并在原位置重新生成托管分组。用户加入分组的其他出站保留，
+    /// 排在订阅节点之后。没有任何成员时不生成分组。
+    pub fn apply(&mut self, config: &mut Config) {
+        let outbounds = config.outbounds.get_or_insert_with(Vec::new);
+        let group_index = outbounds
+            .iter()
+            .position(|outbound| outbound.tag.as_deref() == Some(self.group.tag.as_str()));
+        let generated: HashSet<&str> = self.applied.iter().map(String::as_str).collect();
+        let prefixes: Vec<String> = self.subscriptions.iter().map(Subscription::prefix).collect();
+        let is_managed = |tag: &str| {
+            tag == self.group.tag
+                || generated.contains(tag)
+                || prefixes.iter().any(|prefix| tag.starts_with(prefix))
+        };
+        let kept: Vec<String> = group_index
+            .map(|index| {
+                outbounds[index]
+                    .members()
+                    .iter()
+                    .filter(|tag| !is_managed(tag))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        // 分组在移除托管节点后的位置
+        let group_index = group_index.map(|index| {
+            outbounds[..index]
+                .iter()
+                .filter(|outbound| !is_managed(outbound.tag.as_deref().unwrap_or_default()))
+                .count()
+        });
+        outbounds.retain(|outbound| !is_managed(outbound.tag.as_deref().unwrap_or_default()));
+
+        let mut taken: HashSet<String> = outbounds
+            .iter()
+            .filter_map(|outbound| outbound.tag.clone())
+            .collect();
+        let mut members = Vec::new();
+        let mut nodes = Vec::new();
+        for subscription in self.subscriptions.iter().filter(|sub| sub.enabled) {
+            for outbound in &subscription.outbounds {
+                let mut outbound = outbound.clone();
+                let tag = unique_tag(outbound.tag.take().unwrap_or_default(), &mut taken);
+                outbound.tag = Some(tag.clone());
+                members.push(tag);
+                nodes.push(outbound);
+            }
+        }
+        self.applied = members.clone();
+        members.extend(kept);
+        if members.is_empty() {
+            return;
+        }
+
+        let group = self.group.to_outbound(members);
+        outbounds.insert(group_index.unwrap_or(outbounds.len()), group);
+        outbounds.extend(nodes);
+    }
+}
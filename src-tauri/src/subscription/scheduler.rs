@@ -0,0 +1,61 @@
+//! 定时刷新：后台线程按固定间隔调用回调，由回调判断是否有订阅需要刷新
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// 后台定时器，停止或释放时结束线程
+pub struct Scheduler {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Scheduler {
+    /// 启动后先等待 period，之后每隔 period 调用一次 tick
+    pub fn start(period: Duration, tick: impl Fn() + Send + 'static) -> Self {
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let signal = stop.clone();
+        let thread = thread::spawn(move || {
+            let (stopped, wake) = &*signal;
+            loop {
+                let deadline = Instant::now() + period;
+                let mut guard = stopped.lock().unwrap_or_else(|e| e.into_inner());
+                while !*guard {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    guard = wake
+                        .wait_timeout(guard, deadline - now)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0;
+                }
+                if *guard {
+                    return;
+                }
+                drop(guard);
+                tick();
+            }
+        });
+        Self {
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// 停止定时器，等待正在执行的回调结束
+    pub fn stop(&mut self) {
+        let (stopped, wake) = &*self.stop;
+        *stopped.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        wake.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
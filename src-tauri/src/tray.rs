@@ -1,13 +1,30 @@
-use tauri::{Manager, Runtime};
+use tauri::{Emitter, Manager, Runtime};
 use tauri::image::Image;
 use crate::menu_i18n::MenuI18n;
+use crate::clash_api::Proxy;
+use crate::singbox::{CoreStatus, Supervisor};
+use std::sync::{Mutex, MutexGuard};
+
+/// 托盘图标 id，用于更新托盘菜单
+const TRAY_ID: &str = "main";
+
+/// 出站选择菜单项 id 前缀，后接 selector 名称、换行和成员名称
+const SELECT_ITEM_PREFIX: &str = "tray_select:";
+
+/// 运行中内核的 selector 出站，用于构建出站选择子菜单
+#[derive(Default)]
+pub struct TraySelectors(Mutex<Vec<Proxy>>);
+
+impl TraySelectors {
+    pub fn lock(&self) -> MutexGuard<'_, Vec<Proxy>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
 
 /// 构建系统托盘
 /// 参考：Tauri 2 System Tray API
 /// 文档：https://v2.tauri.app/
-pub fn build_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<tauri::tray::TrayIcon<R>, Box<dyn std::error::Error>> {
-    // 获取当前语言
-    let locale = "zh"; // TODO: 从存储获取
+pub fn build_tray<R: Runtime>(app: &tauri::AppHandle<R>, locale: &str) -> Result<tauri::tray::TrayIcon<R>, Box<dyn std::error::Error>> {
     let i18n = MenuI18n::new(locale);
     
     // 创建托盘菜单
@@ -19,7 +36,7 @@ pub fn build_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<tauri::tray::
     let icon_bytes = include_bytes!("../icons/icon.png");
     let icon = Image::new_owned(icon_bytes.to_vec(), 512, 512);
     
-    let tray = tauri::tray::TrayIconBuilder::new()
+    let tray = tauri::tray::TrayIconBuilder::with_id(TRAY_ID)
         .menu(&menu)
         .icon(icon)
         // 设置左键点击不显示菜单，而是切换窗口显示/隐藏（通过菜单项实现）
@@ -49,21 +66,77 @@ pub fn build_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<tauri::tray::
     Ok(tray)
 }
 
+/// 按语言重建托盘菜单
+pub fn update_tray_menu<R: Runtime>(app: &tauri::AppHandle<R>, locale: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let menu = build_tray_menu(app, &MenuI18n::new(locale))?;
+        tray.set_menu(Some(menu))?;
+    }
+    Ok(())
+}
+
 /// 构建托盘菜单
 fn build_tray_menu<R: Runtime>(
     app: &tauri::AppHandle<R>,
     i18n: &MenuI18n,
 ) -> Result<tauri::menu::Menu<R>, Box<dyn std::error::Error>> {
-    use tauri::menu::{MenuBuilder, MenuItem};
+    use tauri::menu::{CheckMenuItem, MenuBuilder, MenuItem, SubmenuBuilder};
     
     let show = MenuItem::with_id(app, "tray_show", i18n.tray_show(), true, None::<&str>)?;
     let hide = MenuItem::with_id(app, "tray_hide", i18n.tray_hide(), true, None::<&str>)?;
     let quit = MenuItem::with_id(app, "tray_quit", i18n.tray_quit(), true, None::<&str>)?;
     
-    let menu = MenuBuilder::new(app)
+    // sing-box 内核状态（仅显示）与控制项
+    let status = app
+        .try_state::<Supervisor>()
+        .map(|supervisor| supervisor.status())
+        .unwrap_or(CoreStatus::Stopped);
+    let active = matches!(status, CoreStatus::Running { .. } | CoreStatus::Restarting { .. });
+    let status_text = match status {
+        CoreStatus::Stopped => i18n.tray_core_stopped(),
+        CoreStatus::Running { .. } => i18n.tray_core_running(),
+        CoreStatus::Restarting { .. } => i18n.tray_core_restarting(),
+        CoreStatus::Failed { .. } => i18n.tray_core_failed(),
+    };
+    let core_status = MenuItem::with_id(app, "tray_core_status", status_text, false, None::<&str>)?;
+    let core_start = MenuItem::with_id(app, "tray_core_start", i18n.tray_core_start(), !active, None::<&str>)?;
+    let core_stop = MenuItem::with_id(app, "tray_core_stop", i18n.tray_core_stop(), active, None::<&str>)?;
+    let core_restart = MenuItem::with_id(app, "tray_core_restart", i18n.tray_core_restart(), active, None::<&str>)?;
+    let refresh_subscriptions = MenuItem::with_id(app, "tray_refresh_subscriptions", i18n.tray_refresh_subscriptions(), true, None::<&str>)?;
+    
+    let mut menu = MenuBuilder::new(app)
         .item(&show)
         .item(&hide)
         .separator()
+        .item(&core_status)
+        .item(&core_start)
+        .item(&core_stop)
+        .item(&core_restart);
+    
+    // 出站选择：每个 selector 一个子菜单，勾选当前成员
+    let selectors = app
+        .try_state::<TraySelectors>()
+        .map(|selectors| selectors.lock().clone())
+        .unwrap_or_default();
+    if !selectors.is_empty() {
+        let mut submenu = SubmenuBuilder::new(app, i18n.tray_selectors());
+        for selector in &selectors {
+            let mut group = SubmenuBuilder::new(app, &selector.name);
+            for member in &selector.all {
+                let id = format!("{}{}\n{}", SELECT_ITEM_PREFIX, selector.name, member);
+                let checked = selector.now.as_deref() == Some(member.as_str());
+                let item = CheckMenuItem::with_id(app, id, member, true, checked, None::<&str>)?;
+                group = group.item(&item);
+            }
+            submenu = submenu.item(&group.build()?);
+        }
+        menu = menu.item(&submenu.build()?);
+    }
+    
+    let menu = menu
+        .separator()
+        .item(&refresh_subscriptions)
+        .separator()
         .item(&quit)
         .build()?;
     
@@ -85,10 +158,63 @@ pub fn handle_tray_event<R: Runtime>(app: &tauri::AppHandle<R>, event: &tauri::m
                 let _ = window.hide();
             }
         }
+        "tray_core_start" | "tray_core_stop" | "tray_core_restart" => {
+            // 停止内核需要等待进程退出，不能阻塞主线程
+            let app = app.clone();
+            let action = id.as_ref().to_string();
+            std::thread::spawn(move || {
+                let result = match action.as_str() {
+                    "tray_core_start" => crate::tray_core_config(&app)
+                        .ok_or_else(|| "No config file to run".to_string())
+                        .and_then(|config| crate::start_core_with(&app, config)),
+                    "tray_core_stop" => Ok(crate::stop_core_with(&app)),
+                    _ => crate::restart_core_with(&app),
+                };
+                if let Err(e) = result {
+                    eprintln!("Failed to control sing-box: {}", e);
+                    if let Some(window) = app.get_webview_window("main") {
+                        let _ = window.emit("core-error", e);
+                    }
+                }
+            });
+        }
+        "tray_refresh_subscriptions" => {
+            // 拉取订阅可能很慢，放到后台线程
+            let app = app.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = crate::refresh_subscriptions_with(&app) {
+                    eprintln!("Failed to refresh subscriptions: {}", e);
+                }
+            });
+        }
         "tray_quit" => {
             app.exit(0);
         }
-        _ => {}
+        id => {
+            let Some((selector, member)) = id
+                .strip_prefix(SELECT_ITEM_PREFIX)
+                .and_then(|rest| rest.split_once('\n'))
+            else {
+                return;
+            };
+            let app = app.clone();
+            let (selector, member) = (selector.to_string(), member.to_string());
+            std::thread::spawn(move || {
+                let result = crate::clash_client(&app)
+                    .and_then(|client| client.select(&selector, &member));
+                match result {
+                    Ok(()) => {
+                        if let Some(window) = app.get_webview_window("main") {
+                            let payload = serde_json::json!({ "selector": selector, "member": member });
+                            let _ = window.emit("clash-selected", payload);
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to select {} in {}: {}", member, selector, e),
+                }
+                // 失败时也刷新，恢复勾选状态
+                crate::refresh_tray_selectors(&app);
+            });
+        }
     }
 }
 
@@ -0,0 +1,245 @@
+//! 基于内置 schema.json 的 JSON Schema 校验
+//!
+//! 返回结构与前端 `src/lib/validation.ts` 的 `ValidationResult` 一致。
+//! `message` 为英文，供命令行和日志使用；前端按 `kind` 和 `params` 生成本地化文本。
+
+use std::sync::OnceLock;
+
+use jsonschema::error::{TypeKind, ValidationErrorKind};
+use jsonschema::Validator;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// 随应用打包的 sing-box 配置 Schema
+pub const SCHEMA: &str = include_str!("../../schema.json");
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationError {
+    /// JSON Pointer 形式的路径（如 /inbounds/0/listen_port），根节点为空字符串
+    pub path: String,
+    pub message: String,
+    /// 出错的 Schema 关键字（如 type、required），JSON 语法错误为 syntax
+    pub kind: String,
+    /// 生成本地化文本所需的参数，如 type 错误的 expected 和 received
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub params: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationResult {
+    pub valid: bool,
+    pub errors: Vec<ValidationError>,
+}
+
+impl ValidationResult {
+    fn from_errors(errors: Vec<ValidationError>) -> Self {
+        Self {
+            valid: errors.is_empty(),
+            errors,
+        }
+    }
+}
+
+/// 编译后的校验器只构建一次
+fn validator() -> Result<&'static Validator, String> {
+    static VALIDATOR: OnceLock<Result<Validator, String>> = OnceLock::new();
+    VALIDATOR
+        .get_or_init(|| {
+            let mut schema: Value = serde_json::from_str(SCHEMA).map_err(|e| e.to_string())?;
+            merge_all_of(&mut schema);
+            jsonschema::draft7::new(&schema).map_err(|e| e.to_string())
+        })
+        .as_ref()
+        .map_err(Clone::clone)
+}
+
+/// 展开 `allOf`，与前端 json-schema-library 的合并行为保持一致
+///
+/// schema.json 用 `allOf: [{$ref: DialerOptions}, {...}]` 组合字段，而被引用的
+/// 片段带有 `additionalProperties: false`。严格按 draft-07 求值时，每个片段都会把
+/// 另一片段的字段视为多余属性，因此先把 `allOf` 合并为单个对象 Schema。
+fn merge_all_of(schema: &mut Value) {
+    let definitions = schema.get("definitions").cloned().unwrap_or_default();
+    merge_all_of_node(schema, &definitions);
+}
+
+fn merge_all_of_node(node: &mut Value, definitions: &Value) {
+    match node {
+        Value::Object(object) => {
+            if let Some(Value::Array(members)) = object.remove("allOf") {
+                let mut merged = Map::new();
+                for member in members {
+                    let mut member = match member.get("$ref").and_then(Value::as_str) {
+                        Some(reference) => reference
+                            .strip_prefix("#/definitions/")
+                            .and_then(|name| definitions.get(name))
+                            .cloned()
+                            .unwrap_or(member),
+                        None => member,
+                    };
+                    merge_all_of_node(&mut member, definitions);
+                    if let Value::Object(member) = member {
+                        merge_schema_object(&mut merged, member);
+                    }
+                }
+                let own = std::mem::take(object);
+                merge_schema_object(&mut merged, own);
+                *object = merged;
+            }
+            for value in object.values_mut() {
+                merge_all_of_node(value, definitions);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                merge_all_of_node(item, definitions);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `properties` 与 `required` 取并集，其余关键字以后出现的为准
+fn merge_schema_object(target: &mut Map<String, Value>, source: Map<String, Value>) {
+    for (key, value) in source {
+        match (key.as_str(), target.get_mut(&key), value) {
+            ("properties", Some(Value::Object(existing)), Value::Object(incoming)) => {
+                existing.extend(incoming);
+            }
+            ("required", Some(Value::Array(existing)), Value::Array(incoming)) => {
+                for item in incoming {
+                    if !existing.contains(&item) {
+                        existing.push(item);
+                    }
+                }
+            }
+            (_, _, value) => {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+/// 校验已解析的配置
+pub fn validate(config: &Value) -> ValidationResult {
+    let validator = match validator() {
+        Ok(validator) => validator,
+        Err(message) => {
+            return ValidationResult::from_errors(vec![ValidationError {
+                path: String::new(),
+                message,
+                kind: "schema".to_string(),
+                params: Map::new(),
+            }])
+        }
+    };
+
+    let mut errors = Vec::new();
+    for error in validator.iter_errors(config) {
+        collect_errors(&error, &mut errors);
+    }
+    ValidationResult::from_errors(errors)
+}
+
+/// 转换 jsonschema 的错误
+///
+/// 入站、出站等以 `type` 区分的 `oneOf` 只展开与实例类型匹配的分支，
+/// 从而把错误定位到具体字段，而不是整个对象。
+fn collect_errors(error: &jsonschema::ValidationError<'_>, errors: &mut Vec<ValidationError>) {
+    if let ValidationErrorKind::OneOfNotValid { context } = error.kind() {
+        let type_path = format!("{}/type", error.instance_path().as_str());
+        let mut matching = context.iter().filter(|branch| {
+            !branch.iter().any(|sub| {
+                matches!(sub.kind(), ValidationErrorKind::Constant { .. })
+                    && sub.instance_path().as_str() == type_path
+            })
+        });
+        if let (Some(branch), None) = (matching.next(), matching.next()) {
+            for sub in branch {
+                collect_errors(sub, errors);
+            }
+            return;
+        }
+    }
+
+    errors.push(ValidationError {
+        path: error.instance_path().as_str().to_string(),
+        message: error.masked().to_string(),
+        kind: error.kind().keyword().to_string(),
+        params: params(error),
+    });
+}
+
+fn params(error: &jsonschema::ValidationError<'_>) -> Map<String, Value> {
+    let mut params = Map::new();
+    match error.kind() {
+        ValidationErrorKind::Type { kind } => {
+            let expected = match kind {
+                TypeKind::Single(kind) => Value::from(kind.as_str()),
+                TypeKind::Multiple(kinds) => kinds.iter().map(|kind| kind.as_str()).collect(),
+            };
+            params.insert("expected".to_string(), expected);
+            params.insert("received".to_string(), json_type(error.instance()).into());
+        }
+        ValidationErrorKind::Required { property } => {
+            params.insert("property".to_string(), property.clone());
+        }
+        ValidationErrorKind::AdditionalProperties { unexpected } => {
+            params.insert("properties".to_string(), unexpected.clone().into());
+        }
+        ValidationErrorKind::Constant { expected_value } => {
+            params.insert("expected".to_string(), expected_value.clone());
+        }
+        ValidationErrorKind::Enum { options } => {
+            params.insert("options".to_string(), options.clone());
+        }
+        ValidationErrorKind::Minimum { limit }
+        | ValidationErrorKind::Maximum { limit }
+        | ValidationErrorKind::ExclusiveMinimum { limit }
+        | ValidationErrorKind::ExclusiveMaximum { limit } => {
+            params.insert("limit".to_string(), limit.clone());
+        }
+        ValidationErrorKind::MinLength { limit }
+        | ValidationErrorKind::MaxLength { limit }
+        | ValidationErrorKind::MinItems { limit }
+        | ValidationErrorKind::MaxItems { limit } => {
+            params.insert("limit".to_string(), (*limit).into());
+        }
+        ValidationErrorKind::Pattern { pattern } => {
+            params.insert("pattern".to_string(), pattern.clone().into());
+        }
+        _ => {}
+    }
+    params
+}
+
+/// 与 JSON Schema 的类型名一致，整数报告为 integer
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_i64() || number.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// 校验 JSON 文本，语法错误同样以错误项返回
+pub fn validate_text(text: &str) -> ValidationResult {
+    match serde_json::from_str::<Value>(text) {
+        Ok(config) => validate(&config),
+        Err(e) => {
+            let mut params = Map::new();
+            params.insert("line".to_string(), e.line().into());
+            params.insert("column".to_string(), e.column().into());
+            ValidationResult::from_errors(vec![ValidationError {
+                path: String::new(),
+                message: e.to_string(),
+                kind: "syntax".to_string(),
+                params,
+            }])
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! wg-quick 配置文件（.conf）→ WireGuard 端点
+//!
+//! [Interface] 对应端点本身，每个 [Peer] 对应一个对端。DNS 中的 IP 地址转换为经由
+//! 该端点的 DNS 服务器，PostUp 等脚本和路由表设置没有对应项，记入警告。
+
+use std::collections::HashSet;
+use std::net::IpAddr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{DnsServer, DnsServerType, Endpoint, EndpointType, Listable, WireGuardPeer};
+use crate::share_link::unique_tag;
+
+/// 导入结果：端点、经由端点的 DNS 服务器和被忽略的行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfImport {
+    pub endpoint: Endpoint,
+    pub dns_servers: Vec<DnsServer>,
+    pub warnings: Vec<ConfWarning>,
+}
+
+/// 被忽略的一行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfWarning {
+    /// 从 1 开始
+    pub line: usize,
+    pub message: String,
+}
+
+enum Section {
+    None,
+    Interface,
+    Peer,
+}
+
+/// 不带前缀长度的地址补全为 /32 或 /128
+fn prefix(address: &str) -> Result<String, String> {
+    if address.contains('/') {
+        return Ok(address.to_string());
+    }
+    match address.parse::<IpAddr>() {
+        Ok(IpAddr::V4(_)) => Ok(format!("{address}/32")),
+        Ok(IpAddr::V6(_)) => Ok(format!("{address}/128")),
+        Err(_) => Err(format!("invalid address: {address}")),
+    }
+}
+
+fn list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+/// "host:port" 或 "[v6]:port"
+fn endpoint_address(value: &str) -> Result<(String, u16), String> {
+    let (host, port) = match value.strip_prefix('[') {
+        Some(rest) => rest
+            .split_once("]:")
+            .ok_or_else(|| format!("invalid endpoint: {value}"))?,
+        None => value
+            .rsplit_once(':')
+            .ok_or_else(|| format!("endpoint has no port: {value}"))?,
+    };
+    let port = port
+        .parse()
+        .map_err(|_| format!("invalid endpoint port: {port}"))?;
+    Ok((host.to_string(), port))
+}
+
+fn number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid {key}: {value}"))
+}
+
+/// 解析 wg-quick 配置；tag 与 `existing_tags` 重复时追加 -2、-3 等后缀
+pub fn import_conf(text: &str, tag: &str, existing_tags: &[String]) -> Result<ConfImport, String> {
+    let mut taken: HashSet<String> = existing_tags.iter().cloned().collect();
+    let base = match tag.trim() {
+        "" => "wg",
+        tag => tag,
+    };
+    let tag = unique_tag(base.to_string(), &mut taken);
+    let mut endpoint = Endpoint {
+        kind: EndpointType::WireGuard,
+        tag: Some(tag.clone()),
+        ..Default::default()
+    };
+    let mut addresses = Vec::new();
+    let mut peers: Vec<WireGuardPeer> = Vec::new();
+    let mut dns = Vec::new();
+    let mut warnings = Vec::new();
+    let mut has_interface = false;
+    let mut section = Section::None;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            section = match name.trim().to_ascii_lowercase().as_str() {
+                "interface" => {
+                    has_interface = true;
+                    Section::Interface
+                }
+                "peer" => {
+                    peers.push(WireGuardPeer::default());
+                    Section::Peer
+                }
+                other => {
+                    warnings.push(ConfWarning {
+                        line: line_no,
+                        message: format!("section [{other}] is ignored"),
+                    });
+                    Section::None
+                }
+            };
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .ok_or_else(|| format!("line {line_no}: expected key = value"))?;
+        let lower = key.to_ascii_lowercase();
+        let at_line = |message: String| format!("line {line_no}: {message}");
+        let mut ignored = false;
+        match section {
+            Section::Interface => match lower.as_str() {
+                "privatekey" => endpoint.private_key = Some(value.to_string()),
+                "address" => {
+                    for address in list(value) {
+                        addresses.push(prefix(address).map_err(at_line)?);
+                    }
+                }
+                "listenport" => endpoint.listen_port = Some(number(key, value).map_err(at_line)?),
+                "mtu" => endpoint.mtu = Some(number(key, value).map_err(at_line)?),
+                "dns" => {
+                    for server in list(value) {
+                        if server.parse::<IpAddr>().is_ok() {
+                            dns.push(server.to_string());
+                        } else {
+                            warnings.push(ConfWarning {
+                                line: line_no,
+                                message: format!("DNS search domain {server} is ignored"),
+                            });
+                        }
+                    }
+                }
+                _ => ignored = true,
+            },
+            Section::Peer => {
+                let peer = peers.last_mut().expect("peer section has a peer");
+                match lower.as_str() {
+                    "publickey" => peer.public_key = Some(value.to_string()),
+                    "presharedkey" => peer.pre_shared_key = Some(value.to_string()),
+                    "allowedips" => {
+                        let mut allowed = peer
+                            .allowed_ips
+                            .take()
+                            .map(Listable::into_vec)
+                            .unwrap_or_default();
+                        for ip in list(value) {
+                            allowed.push(prefix(ip).map_err(at_line)?);
+                        }
+                        peer.allowed_ips = Some(Listable::Many(allowed));
+                    }
+                    "endpoint" => {
+                        let (host, port) = endpoint_address(value).map_err(at_line)?;
+                        peer.address = Some(host);
+                        peer.port = Some(port);
+                    }
+                    "persistentkeepalive" if value.eq_ignore_ascii_case("off") => {}
+                    "persistentkeepalive" => {
+                        peer.persistent_keepalive_interval =
+                            Some(number(key, value).map_err(at_line)?)
+                    }
+                    _ => ignored = true,
+                }
+            }
+            Section::None => ignored = true,
+        }
+        if ignored {
+            warnings.push(ConfWarning {
+                line: line_no,
+                message: format!("{key} is ignored"),
+            });
+        }
+    }
+
+    if !has_interface {
+        return Err("missing [Interface] section".to_string());
+    }
+    if endpoint.private_key.is_none() {
+        return Err("[Interface] has no PrivateKey".to_string());
+    }
+    if addresses.is_empty() {
+        return Err("[Interface] has no Address".to_string());
+    }
+    if peers.is_empty() {
+        return Err("missing [Peer] section".to_string());
+    }
+    for (index, peer) in peers.iter().enumerate() {
+        if peer.public_key.is_none() {
+            return Err(format!("peer {} has no PublicKey", index + 1));
+        }
+        if peer.allowed_ips.is_none() {
+            return Err(format!("peer {} has no AllowedIPs", index + 1));
+        }
+    }
+    endpoint.address = Some(Listable::Many(addresses));
+    endpoint.peers = Some(peers);
+
+    let dns_servers = dns
+        .into_iter()
+        .enumerate()
+        .map(|(index, server)| {
+            let mut dns_server = DnsServer {
+                kind: Some(DnsServerType::Udp),
+                tag: Some(match index {
+                    0 => format!("{tag}-dns"),
+                    n => format!("{tag}-dns-{}", n + 1),
+                }),
+                server: Some(server),
+                ..Default::default()
+            };
+            dns_server.dial.detour = Some(tag.clone());
+            dns_server
+        })
+        .collect();
+    Ok(ConfImport {
+        endpoint,
+        dns_servers,
+        warnings,
+    })
+}
@@ -0,0 +1,114 @@
+use serde_json::json;
+use sing_config_lib::rule_set::{adguard::convert, compile, decompile};
+
+const FILTER: &str = "
+[Adblock Plus 2.0]
+! Title: test filter
+# hosts comment
+||ads.example.com^
+||tracker.example.org^$important
+|exact.example.net^
+@@||good.ads.example.com^
+||*.cdn.example.com^
+/^ad[0-9]+\\./
+0.0.0.0 hosts.example.com hosts2.example.com # trailing comment
+127.0.0.1 localhost.example.com
+||example.org/ads.js
+||10.0.0.
+||example.com^$third-party
+example.com##.banner
+||metrics.example.com^$dnsrewrite=0.0.0.0
+";
+
+#[test]
+fn converts_filter_list() {
+    let result = convert(FILTER);
+    assert_eq!(
+        serde_json::to_value(&result.source).unwrap(),
+        json!({"version": 2, "rules": [
+            {
+                "type": "logical",
+                "mode": "and",
+                "rules": [
+                    {
+                        "domain": ["exact.example.net", "hosts.example.com", "hosts2.example.com"],
+                        "domain_suffix": ["ads.example.com", "metrics.example.com"],
+                        "domain_regex": ["(^|\\.).*\\.cdn\\.example\\.com$", "^ad[0-9]+\\."]
+                    },
+                    {"domain_suffix": ["good.ads.example.com"], "invert": true}
+                ]
+            },
+            {"domain_suffix": ["tracker.example.org"]}
+        ]})
+    );
+
+    let stats = &result.stats;
+    assert_eq!(stats.lines, 13);
+    assert_eq!(stats.blocked, 7);
+    assert_eq!(stats.exceptions, 1);
+    let skipped: Vec<(usize, &str)> = stats
+        .skipped
+        .iter()
+        .map(|line| (line.line, line.message.as_str()))
+        .collect();
+    assert_eq!(
+        skipped,
+        [
+            (
+                12,
+                "hosts entries for 127.0.0.1 are not supported, only 0.0.0.0"
+            ),
+            (13, "paths are not supported"),
+            (14, "IP hosts are not supported"),
+            (15, "modifier $third-party is not supported"),
+            (16, "cosmetic rules are not supported"),
+        ]
+    );
+
+    // 转换结果可以直接编译
+    let data = compile(&result.source, None).unwrap();
+    assert_eq!(decompile(&data).unwrap().rules.len(), 2);
+}
+
+#[test]
+fn plain_domain_lists_match_exactly() {
+    let result = convert("! comment\nExample.com\nads.example.org\n");
+    assert_eq!(
+        serde_json::to_value(&result.source.rules).unwrap(),
+        json!([{"domain": ["example.com", "ads.example.org"]}])
+    );
+    assert_eq!(result.stats.blocked, 2);
+
+    // 混有其他写法时，无锚点的域名按子串匹配
+    let result = convert("example.com\n||ads.example.org^\n");
+    assert_eq!(
+        serde_json::to_value(&result.source.rules).unwrap(),
+        json!([{"domain_suffix": ["ads.example.org"], "domain_regex": ["example\\.com"]}])
+    );
+}
+
+#[test]
+fn important_exceptions_apply_to_all_rules() {
+    let result = convert("||a.com^\n||b.com^$important\n@@||x.a.com^$important\n@@||y.a.com^\n");
+    assert_eq!(
+        serde_json::to_value(&result.source.rules).unwrap(),
+        json!([
+            {
+                "type": "logical",
+                "mode": "and",
+                "rules": [
+                    {"domain_suffix": ["a.com"]},
+                    {"domain_suffix": ["y.a.com", "x.a.com"], "invert": true}
+                ]
+            },
+            {
+                "type": "logical",
+                "mode": "and",
+                "rules": [
+                    {"domain_suffix": ["b.com"]},
+                    {"domain_suffix": ["x.a.com"], "invert": true}
+                ]
+            }
+        ])
+    );
+}
@@ -0,0 +1,392 @@
+use serde_json::json;
+use sing_config_lib::clash::{convert_yaml, export_profile};
+use sing_config_lib::config::Config;
+
+const PROFILE: &str = r#"
+mixed-port: 7890
+allow-lan: false
+mode: rule
+log-level: warning
+external-controller: 127.0.0.1:9090
+secret: s3cret
+
+dns:
+  enable: true
+  ipv6: false
+  enhanced-mode: fake-ip
+  fake-ip-range: 198.18.0.1/16
+  fake-ip-filter:
+    - "+.lan"
+    - "*.msftconnecttest.com"
+  default-nameserver:
+    - 223.5.5.5
+  nameserver:
+    - https://dns.alidns.com/dns-query
+    - tls://1.1.1.1#Proxy
+  fallback:
+    - 8.8.8.8
+  nameserver-policy:
+    "geosite:cn,private": 223.5.5.5
+    "+.corp.example": [tcp://10.0.0.53:5353, 10.0.0.54]
+
+proxies:
+  - name: hk
+    type: ss
+    server: hk.example.com
+    port: 8388
+    cipher: aes-128-gcm
+    password: pass
+  - name: jp
+    type: trojan
+    server: jp.example.com
+    port: 443
+    password: pass
+    sni: jp.example.com
+  - name: wg
+    type: wireguard
+    server: 1.2.3.4
+    port: 51820
+
+proxy-groups:
+  - name: Proxy
+    type: select
+    proxies: [Auto, hk, jp, wg, DIRECT, REJECT]
+  - name: Auto
+    type: url-test
+    proxies: [hk, jp]
+    url: https://www.gstatic.com/generate_204
+    interval: 300
+    tolerance: 50
+  - name: Chain
+    type: relay
+    proxies: [hk, jp]
+
+rule-providers:
+  ads:
+    type: inline
+    behavior: domain
+    payload:
+      - "+.ads.example"
+      - tracker.example
+  cn-ip:
+    type: http
+    behavior: ipcidr
+    format: mrs
+    url: https://raw.githubusercontent.com/MetaCubeX/meta-rules-dat/meta/geo/geoip/cn.mrs
+    interval: 86400
+  custom:
+    type: http
+    behavior: classical
+    format: yaml
+    url: https://example.com/custom.yaml
+
+rules:
+  - DOMAIN-SUFFIX,google.com,Proxy
+  - RULE-SET,ads,REJECT
+  - AND,((NETWORK,UDP),(DST-PORT,443)),REJECT-DROP
+  - NOT,((DOMAIN-KEYWORD,example)),Auto
+  - GEOSITE,CN,DIRECT
+  - RULE-SET,custom,DIRECT
+  - IP-CIDR,10.0.0.0/8,DIRECT,no-resolve
+  - GEOIP,CN,DIRECT
+  - DST-PORT,8000-9000,Chain
+  - SUB-RULE,(NETWORK,TCP),sub
+  - MATCH,Proxy
+"#;
+
+#[test]
+fn converts_full_profile() {
+    let result = convert_yaml(PROFILE).unwrap();
+    let config = result.config.to_value().unwrap();
+
+    assert_eq!(config["log"], json!({ "level": "warn" }));
+    assert_eq!(
+        config["inbounds"],
+        json!([{ "type": "mixed", "tag": "mixed-in", "listen": "127.0.0.1", "listen_port": 7890 }])
+    );
+    assert_eq!(
+        config["experimental"]["clash_api"],
+        json!({ "external_controller": "127.0.0.1:9090", "secret": "s3cret" })
+    );
+
+    // 分组在前，direct 在最后；不支持的成员和 relay 分组被丢弃
+    let tags: Vec<&str> = config["outbounds"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|outbound| outbound["tag"].as_str().unwrap())
+        .collect();
+    assert_eq!(tags, ["Proxy", "Auto", "hk", "jp", "direct"]);
+    assert_eq!(
+        config["outbounds"][0],
+        json!({ "type": "selector", "tag": "Proxy", "outbounds": ["Auto", "hk", "jp", "direct"] })
+    );
+    assert_eq!(
+        config["outbounds"][1],
+        json!({
+            "type": "urltest",
+            "tag": "Auto",
+            "outbounds": ["hk", "jp"],
+            "url": "https://www.gstatic.com/generate_204",
+            "interval": "300s",
+            "tolerance": 50
+        })
+    );
+
+    let route = &config["route"];
+    assert_eq!(
+        route["rules"],
+        json!([
+            { "action": "sniff" },
+            { "protocol": "dns", "action": "hijack-dns" },
+            { "domain_suffix": "google.com", "outbound": "Proxy" },
+            { "rule_set": "ads", "action": "reject" },
+            {
+                "type": "logical",
+                "mode": "and",
+                "rules": [{ "network": "udp" }, { "port": 443 }],
+                "action": "reject",
+                "method": "drop"
+            },
+            { "domain_keyword": "example", "invert": true, "outbound": "Auto" },
+            { "rule_set": "geosite-cn", "outbound": "direct" },
+            { "ip_cidr": "10.0.0.0/8", "outbound": "direct" },
+            { "action": "resolve" },
+            { "rule_set": "geoip-cn", "outbound": "direct" }
+        ])
+    );
+    assert_eq!(route["final"], "Proxy");
+    assert_eq!(route["default_domain_resolver"], "nameserver-1");
+
+    let rule_sets = route["rule_set"].as_array().unwrap();
+    let ads = rule_sets.iter().find(|rule_set| rule_set["tag"] == "ads").unwrap();
+    assert_eq!(
+        ads["rules"],
+        json!([{ "domain": "tracker.example", "domain_suffix": "ads.example" }])
+    );
+    let cn_ip = rule_sets.iter().find(|rule_set| rule_set["tag"] == "cn-ip").unwrap();
+    assert_eq!(
+        cn_ip["url"],
+        "https://raw.githubusercontent.com/MetaCubeX/meta-rules-dat/sing/geo/geoip/cn.srs"
+    );
+    assert_eq!(cn_ip["format"], "binary");
+    assert_eq!(cn_ip["update_interval"], "86400s");
+    assert!(rule_sets.iter().all(|rule_set| rule_set["tag"] != "custom"));
+    assert!(rule_sets.iter().any(|rule_set| rule_set["tag"] == "geosite-private"));
+
+    let paths: Vec<&str> = result.unsupported.iter().map(|item| item.path.as_str()).collect();
+    for path in [
+        "proxies[2]",
+        "proxy-groups[0]",
+        "proxy-groups[2]",
+        "rule-providers.custom",
+        "rules[5]",
+        "rules[8]",
+        "rules[9]",
+        "dns.fallback",
+        "dns.nameserver-policy.+.corp.example",
+    ] {
+        assert!(paths.contains(&path), "missing report for {path}: {paths:?}");
+    }
+}
+
+#[test]
+fn converts_dns_with_fakeip_and_policies() {
+    let config = convert_yaml(PROFILE).unwrap().config.to_value().unwrap();
+    let dns = &config["dns"];
+
+    assert_eq!(
+        dns["servers"],
+        json!([
+            { "type": "udp", "tag": "bootstrap-1", "server": "223.5.5.5" },
+            {
+                "type": "https",
+                "tag": "nameserver-1",
+                "server": "dns.alidns.com",
+                "domain_resolver": "bootstrap-1"
+            },
+            { "type": "tls", "tag": "nameserver-2", "server": "1.1.1.1", "detour": "Proxy" },
+            { "type": "udp", "tag": "policy-1", "server": "223.5.5.5" },
+            { "type": "tcp", "tag": "policy-2", "server": "10.0.0.53", "server_port": 5353 },
+            { "type": "fakeip", "tag": "fakeip", "inet4_range": "198.18.0.1/16" }
+        ])
+    );
+    assert_eq!(
+        dns["rules"],
+        json!([
+            { "rule_set": ["geosite-cn", "geosite-private"], "server": "policy-1" },
+            { "domain_suffix": "corp.example", "server": "policy-2" },
+            {
+                "domain_suffix": "lan",
+                "domain_regex": "^[^.]+\\.msftconnecttest\\.com$",
+                "server": "nameserver-1"
+            },
+            { "query_type": ["A", "AAAA"], "server": "fakeip" }
+        ])
+    );
+    assert_eq!(dns["final"], "nameserver-1");
+    assert_eq!(dns["strategy"], "ipv4_only");
+}
+
+#[test]
+fn rejects_non_mapping_yaml() {
+    assert!(convert_yaml("- a\n- b\n").is_err());
+    assert!(convert_yaml("proxies: [").is_err());
+}
+
+fn export(config: serde_json::Value) -> (serde_json::Value, Vec<String>) {
+    let config = Config::from_value(config).unwrap();
+    let result = export_profile(&config).unwrap();
+    let profile = serde_yaml::from_str(&result.yaml).unwrap();
+    let paths = result.warnings.into_iter().map(|item| item.path).collect();
+    (profile, paths)
+}
+
+#[test]
+fn exports_proxies_groups_and_rules() {
+    let (profile, warnings) = export(json!({
+        "log": { "level": "warn" },
+        "inbounds": [{ "type": "mixed", "tag": "mixed-in", "listen": "127.0.0.1", "listen_port": 7890 }],
+        "outbounds": [
+            { "type": "selector", "tag": "Proxy", "outbounds": ["Auto", "hk", "wg", "direct"], "default": "hk" },
+            { "type": "urltest", "tag": "Auto", "outbounds": ["hk", "jp"], "interval": "5m" },
+            {
+                "type": "vmess",
+                "tag": "hk",
+                "server": "hk.example.com",
+                "server_port": 443,
+                "uuid": "b831381d-6324-4d53-ad4f-8cda48b30811",
+                "tls": { "enabled": true, "server_name": "hk.example.com" },
+                "transport": { "type": "ws", "path": "/ray" }
+            },
+            {
+                "type": "hysteria2",
+                "tag": "jp",
+                "server": "jp.example.com",
+                "server_ports": ["20000:30000"],
+                "password": "pass",
+                "obfs": { "type": "salamander", "password": "obfs" },
+                "tls": { "enabled": true, "server_name": "jp.example.com" }
+            },
+            { "type": "tor", "tag": "wg" },
+            { "type": "direct", "tag": "direct" }
+        ],
+        "route": {
+            "rules": [
+                { "action": "sniff" },
+                { "protocol": "dns", "action": "hijack-dns" },
+                { "clash_mode": "direct", "outbound": "direct" },
+                { "domain_suffix": ["google.com", "youtube.com"], "outbound": "Proxy" },
+                { "network": "udp", "port": 443, "action": "reject", "method": "drop" },
+                { "rule_set": "ads", "action": "reject" },
+                { "ip_is_private": true, "outbound": "direct" },
+                { "action": "resolve" },
+                { "rule_set": "geoip-cn", "outbound": "direct" },
+                { "rule_set": "local-list", "outbound": "direct" }
+            ],
+            "rule_set": [
+                {
+                    "type": "inline",
+                    "tag": "ads",
+                    "rules": [{ "domain_suffix": "ads.example" }, { "domain_keyword": "tracker" }]
+                },
+                {
+                    "type": "remote",
+                    "tag": "geoip-cn",
+                    "format": "binary",
+                    "url": "https://raw.githubusercontent.com/SagerNet/sing-geoip/rule-set/geoip-cn.srs"
+                },
+                { "type": "local", "tag": "local-list", "format": "binary", "path": "list.srs" }
+            ],
+            "final": "Proxy"
+        }
+    }));
+
+    assert_eq!(profile["mixed-port"], 7890);
+    assert_eq!(profile["log-level"], "warning");
+    assert_eq!(
+        profile["proxies"][0],
+        json!({
+            "name": "hk",
+            "type": "vmess",
+            "server": "hk.example.com",
+            "port": 443,
+            "uuid": "b831381d-6324-4d53-ad4f-8cda48b30811",
+            "alterId": 0,
+            "cipher": "auto",
+            "udp": true,
+            "tls": true,
+            "servername": "hk.example.com",
+            "network": "ws",
+            "ws-opts": { "path": "/ray" }
+        })
+    );
+    assert_eq!(profile["proxies"][1]["ports"], "20000-30000");
+    assert_eq!(profile["proxies"][1]["obfs-password"], "obfs");
+    assert_eq!(
+        profile["proxy-groups"],
+        json!([
+            { "name": "Proxy", "type": "select", "proxies": ["hk", "Auto", "DIRECT"] },
+            {
+                "name": "Auto",
+                "type": "url-test",
+                "proxies": ["hk", "jp"],
+                "url": "https://www.gstatic.com/generate_204",
+                "interval": 300
+            }
+        ])
+    );
+    assert_eq!(
+        profile["rules"],
+        json!([
+            "DOMAIN-SUFFIX,google.com,Proxy",
+            "DOMAIN-SUFFIX,youtube.com,Proxy",
+            "AND,((DST-PORT,443),(NETWORK,UDP)),REJECT-DROP",
+            "RULE-SET,ads,REJECT",
+            "GEOIP,LAN,DIRECT,no-resolve",
+            "GEOIP,cn,DIRECT",
+            "MATCH,Proxy"
+        ])
+    );
+    assert_eq!(
+        profile["rule-providers"],
+        json!({
+            "ads": {
+                "type": "inline",
+                "behavior": "classical",
+                "payload": ["DOMAIN-SUFFIX,ads.example", "DOMAIN-KEYWORD,tracker"]
+            }
+        })
+    );
+    for path in [
+        "outbounds[0]",
+        "outbounds[4]",
+        "route.rules[2]",
+        "route.rules[9]",
+        "route.rule_set[2]",
+    ] {
+        assert!(
+            warnings.iter().any(|warning| warning == path),
+            "missing warning for {path}: {warnings:?}"
+        );
+    }
+}
+
+#[test]
+fn exported_profile_converts_back() {
+    let result = convert_yaml(PROFILE).unwrap();
+    let exported = export_profile(&result.config).unwrap();
+    let config = convert_yaml(&exported.yaml)
+        .unwrap()
+        .config
+        .to_value()
+        .unwrap();
+    let original = result.config.to_value().unwrap();
+
+    assert_eq!(config["outbounds"], original["outbounds"]);
+    assert_eq!(config["route"]["final"], original["route"]["final"]);
+    assert!(exported
+        .warnings
+        .iter()
+        .any(|warning| warning.path == "dns"));
+}
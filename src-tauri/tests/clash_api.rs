@@ -0,0 +1,194 @@
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::json;
+use sing_config_lib::clash_api::{ClashClient, Traffic};
+use sing_config_lib::config::Config;
+
+/// 记录下来的请求：方法、带查询串的路径、Authorization、请求体
+type Recorded = Arc<Mutex<Vec<(String, String, Option<String>, String)>>>;
+
+/// 启动模拟的 Clash API，按方法和路径（不含查询串）返回预设的响应
+fn mock_server(routes: Vec<(&'static str, &'static str, u16, serde_json::Value)>) -> (String, Recorded) {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let host = server.server_addr().to_ip().unwrap().to_string();
+    let recorded: Recorded = Arc::default();
+    let log = recorded.clone();
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let auth = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Authorization"))
+                .map(|h| h.value.to_string());
+            let method = request.method().to_string();
+            let url = request.url().to_string();
+            log.lock().unwrap().push((method.clone(), url.clone(), auth, body));
+
+            let path = url.split('?').next().unwrap();
+            let response = match routes.iter().find(|(m, p, _, _)| *m == method && *p == path) {
+                Some((_, _, 204, _)) => tiny_http::Response::from_string("").with_status_code(204),
+                Some((_, _, status, body)) => {
+                    tiny_http::Response::from_string(body.to_string()).with_status_code(*status)
+                }
+                None => tiny_http::Response::from_string(r#"{"message":"not found"}"#)
+                    .with_status_code(404),
+            };
+            let _ = request.respond(response);
+        }
+    });
+    (host, recorded)
+}
+
+fn proxies_body() -> serde_json::Value {
+    json!({
+        "proxies": {
+            "direct": { "type": "Direct", "name": "direct", "udp": true, "history": [] },
+            "hk 01": { "type": "Shadowsocks", "name": "hk 01", "udp": true, "history": [
+                { "time": "2025-01-01T00:00:00Z", "delay": 120 }
+            ] },
+            "proxy": { "type": "Selector", "name": "proxy", "now": "hk 01", "all": ["hk 01", "auto", "direct"], "history": [] },
+            "auto": { "type": "URLTest", "name": "auto", "now": "hk 01", "all": ["hk 01"], "history": [] }
+        }
+    })
+}
+
+#[test]
+fn creates_client_from_config() {
+    let config = Config::from_value(json!({
+        "experimental": { "clash_api": { "external_controller": "0.0.0.0:9090", "secret": "s3cret" } }
+    }))
+    .unwrap();
+    assert_eq!(ClashClient::from_config(&config).unwrap().host(), "127.0.0.1:9090");
+    assert_eq!(ClashClient::new("[::]:9090", None).host(), "[::1]:9090");
+    assert_eq!(ClashClient::new(":9090", None).host(), "127.0.0.1:9090");
+    assert!(ClashClient::from_config(&Config::from_value(json!({})).unwrap()).is_none());
+}
+
+#[test]
+fn lists_proxies_and_selectors() {
+    let (host, recorded) = mock_server(vec![("GET", "/proxies", 200, proxies_body())]);
+    let client = ClashClient::new(&host, Some("s3cret"));
+
+    let proxies = client.proxies().unwrap();
+    let names: Vec<_> = proxies.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["direct", "hk 01", "proxy", "auto"]);
+    assert_eq!(proxies[1].history[0].delay, 120);
+
+    let selectors = client.selectors().unwrap();
+    assert_eq!(selectors.len(), 1);
+    assert_eq!(selectors[0].now.as_deref(), Some("hk 01"));
+    assert_eq!(selectors[0].all, ["hk 01", "auto", "direct"]);
+    assert_eq!(recorded.lock().unwrap()[0].2.as_deref(), Some("Bearer s3cret"));
+}
+
+#[test]
+fn switches_selector_member() {
+    let (host, recorded) = mock_server(vec![("PUT", "/proxies/%F0%9F%87%AF%F0%9F%87%B5%20jp", 204, json!(null))]);
+    let client = ClashClient::new(&host, None);
+    client.select("🇯🇵 jp", "direct").unwrap();
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded[0].0, "PUT");
+    assert_eq!(recorded[0].2, None);
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&recorded[0].3).unwrap(), json!({ "name": "direct" }));
+    drop(recorded);
+
+    let error = client.select("missing", "direct").unwrap_err();
+    assert!(error.contains("not found"), "{}", error);
+}
+
+#[test]
+fn probes_delays() {
+    let (host, recorded) = mock_server(vec![
+        ("GET", "/proxies/hk%2001/delay", 200, json!({ "delay": 87 })),
+        ("GET", "/group/auto/delay", 200, json!({ "hk 01": 87, "jp 01": 140 })),
+    ]);
+    let client = ClashClient::new(&host, None);
+    assert_eq!(client.proxy_delay("hk 01", "http://cp.cloudflare.com", 3000).unwrap(), 87);
+    let delays = client.group_delay("auto", "http://cp.cloudflare.com", 3000).unwrap();
+    assert_eq!(delays["jp 01"], 140);
+
+    let url = &recorded.lock().unwrap()[0].1;
+    assert!(url.contains("url=http"), "{}", url);
+    assert!(url.contains("timeout=3000"), "{}", url);
+}
+
+#[test]
+fn lists_and_closes_connections() {
+    let (host, recorded) = mock_server(vec![
+        ("GET", "/connections", 200, json!({
+            "downloadTotal": 2048, "uploadTotal": 1024, "memory": 4096,
+            "connections": [{
+                "id": "c1", "upload": 10, "download": 20, "start": "2025-01-01T00:00:00Z",
+                "chains": ["hk 01", "proxy"], "rule": "final", "rulePayload": "",
+                "metadata": { "network": "tcp", "host": "example.com", "destinationPort": "443" }
+            }]
+        })),
+        ("DELETE", "/connections/c1", 204, json!(null)),
+        ("DELETE", "/connections", 204, json!(null)),
+    ]);
+    let client = ClashClient::new(&host, None);
+    let connections = client.connections().unwrap();
+    assert_eq!(connections.download_total, 2048);
+    assert_eq!(connections.connections[0].chains, ["hk 01", "proxy"]);
+    assert_eq!(connections.connections[0].metadata["host"], "example.com");
+
+    client.close_connections(Some("c1")).unwrap();
+    client.close_connections(None).unwrap();
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded[1].1, "/connections/c1");
+    assert_eq!(recorded[2].1, "/connections");
+}
+
+#[test]
+fn accepts_null_connection_list() {
+    let (host, _) = mock_server(vec![(
+        "GET",
+        "/connections",
+        200,
+        json!({ "downloadTotal": 0, "uploadTotal": 0, "connections": null }),
+    )]);
+    let connections = ClashClient::new(&host, None).connections().unwrap();
+    assert!(connections.connections.is_empty());
+}
+
+#[test]
+fn streams_traffic_over_websocket() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let host = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        // 握手回调的错误类型由 tungstenite 决定
+        #[allow(clippy::result_large_err)]
+        let callback = |request: &tungstenite::handshake::server::Request,
+                        response: tungstenite::handshake::server::Response| {
+            assert_eq!(request.uri().path(), "/traffic");
+            assert_eq!(request.headers()["Authorization"], "Bearer s3cret");
+            Ok(response)
+        };
+        let mut socket = tungstenite::accept_hdr(stream, callback).unwrap();
+        for i in 1..=3u64 {
+            let text = json!({ "up": i, "down": i * 10 }).to_string();
+            socket.send(tungstenite::Message::text(text)).unwrap();
+        }
+        // 保持连接直到客户端关闭
+        while socket.read().is_ok() {}
+    });
+
+    let (sender, receiver) = mpsc::channel();
+    let client = ClashClient::new(&host, Some("s3cret"));
+    let subscription = client.subscribe("/traffic", move |traffic: Traffic| {
+        let _ = sender.send(traffic);
+    });
+    let received: Vec<_> = (0..3)
+        .map(|_| receiver.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect();
+    subscription.stop();
+    assert_eq!(received[2], Traffic { up: 3, down: 30 });
+}
@@ -0,0 +1,193 @@
+use std::path::Path;
+use std::process::Command;
+
+use serde_json::{json, Value};
+use sing_config_lib::cli::{run, EXIT_ERROR, EXIT_FAILED, EXIT_OK};
+
+/// 执行命令，返回退出码、标准输出和标准错误
+fn cli(args: &[&str]) -> (i32, String, String) {
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let code = run(
+        args.iter().map(|arg| arg.to_string()),
+        &mut stdout,
+        &mut stderr,
+    );
+    (
+        code,
+        String::from_utf8(stdout).unwrap(),
+        String::from_utf8(stderr).unwrap(),
+    )
+}
+
+fn write(dir: &Path, name: &str, text: &str) -> String {
+    let path = dir.join(name);
+    std::fs::write(&path, text).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+fn json(text: &str) -> Value {
+    serde_json::from_str(text).unwrap()
+}
+
+#[test]
+fn validates_and_lints_with_exit_codes() {
+    let dir = tempfile::tempdir().unwrap();
+    let valid = write(
+        dir.path(),
+        "valid.json",
+        &json!({ "outbounds": [{ "type": "direct", "tag": "direct" }] }).to_string(),
+    );
+    let (code, stdout, _) = cli(&["validate", &valid]);
+    assert_eq!(code, EXIT_OK);
+    assert_eq!(json(&stdout), json!({ "valid": true, "errors": [] }));
+
+    let invalid = write(dir.path(), "invalid.json", "{ \"log\": ");
+    let (code, stdout, _) = cli(&["validate", &invalid]);
+    assert_eq!(code, EXIT_FAILED);
+    assert_eq!(json(&stdout)["valid"], false);
+
+    let dangling = write(
+        dir.path(),
+        "dangling.json",
+        &json!({
+            "outbounds": [{ "type": "direct", "tag": "direct" }],
+            "route": { "final": "proxy" }
+        })
+        .to_string(),
+    );
+    let (code, stdout, _) = cli(&["lint", &dangling]);
+    assert_eq!(code, EXIT_FAILED);
+    let issues = json(&stdout);
+    assert_eq!(issues[0]["level"], "error");
+    assert_eq!(issues[0]["path"], "/route/final");
+
+    let (code, stdout, _) = cli(&["lint", &dangling, "--disable=tag-references"]);
+    assert_eq!(code, EXIT_OK);
+    assert_eq!(json(&stdout), json!([]));
+
+    // 参数错误打印用法，退出码为 2
+    let (code, _, stderr) = cli(&["lint", &dangling, "--disable", "no-such-rule"]);
+    assert_eq!(code, EXIT_ERROR);
+    assert!(stderr.starts_with("unknown preflight rule: no-such-rule"));
+    let (code, _, stderr) = cli(&["validate"]);
+    assert_eq!(code, EXIT_ERROR);
+    assert!(stderr.contains("Usage: sing-config-cli"));
+    let (code, _, stderr) = cli(&["lint", "missing.json"]);
+    assert_eq!(code, EXIT_ERROR);
+    assert!(json(&stderr)["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("missing.json: "));
+}
+
+#[test]
+fn migrates_and_formats_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let legacy = json!({
+        "outbounds": [{ "type": "direct", "tag": "direct" }, { "type": "block", "tag": "block" }]
+    });
+    let path = write(dir.path(), "config.json", &legacy.to_string());
+
+    let (code, stdout, _) = cli(&["migrate", &path, "--to", "1.12"]);
+    assert_eq!(code, EXIT_OK);
+    let report = json(&stdout);
+    assert_eq!(report["changed"], true);
+    assert_eq!(
+        report["config"]["outbounds"],
+        json!([{ "type": "direct", "tag": "direct" }])
+    );
+
+    // 单行 JSON 未格式化；--write 之后通过检查
+    let (code, stdout, _) = cli(&["fmt", &path, "--check"]);
+    assert_eq!(code, EXIT_FAILED);
+    assert_eq!(json(&stdout), json!({ "formatted": false }));
+    assert_eq!(cli(&["fmt", &path, "--write"]).0, EXIT_OK);
+    assert_eq!(cli(&["fmt", &path, "--check"]).0, EXIT_OK);
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.starts_with("{\n  \"outbounds\": [\n"));
+
+    let (code, stdout, _) = cli(&["migrate", &path, "--write"]);
+    assert_eq!(code, EXIT_OK);
+    assert!(json(&stdout).get("config").is_none());
+    let migrated: Value = json(&std::fs::read_to_string(&path).unwrap());
+    assert_eq!(migrated["outbounds"].as_array().unwrap().len(), 1);
+    assert_eq!(cli(&["fmt", &path, "--check"]).0, EXIT_OK);
+}
+
+#[test]
+fn imports_clash_and_diffs_configs() {
+    let dir = tempfile::tempdir().unwrap();
+    let profile = write(
+        dir.path(),
+        "clash.yaml",
+        "proxies:\n  - { name: hk, type: ss, server: 1.2.3.4, port: 8388, cipher: aes-128-gcm, password: pw }\n  - { name: bad, type: unknown }\n",
+    );
+    let output = dir.path().join("imported.json");
+    let (code, stdout, _) = cli(&["import", &profile, "--output", output.to_str().unwrap()]);
+    assert_eq!(code, EXIT_OK);
+    assert_eq!(json(&stdout)["unsupported"][0]["path"], "proxies[1]");
+    let imported = json(&std::fs::read_to_string(&output).unwrap());
+    assert!(imported["outbounds"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|outbound| outbound["tag"] == "hk"));
+
+    let old = write(
+        dir.path(),
+        "old.json",
+        &json!({ "log": { "level": "info" }, "outbounds": [{ "type": "direct", "tag": "direct" }] })
+            .to_string(),
+    );
+    let new = write(
+        dir.path(),
+        "new.json",
+        &json!({ "log": { "level": "warn" }, "outbounds": [] }).to_string(),
+    );
+    let (code, stdout, _) = cli(&["diff", &old, &old]);
+    assert_eq!(code, EXIT_OK);
+    assert_eq!(json(&stdout), json!([]));
+    let (code, stdout, _) = cli(&["diff", &old, &new]);
+    assert_eq!(code, EXIT_FAILED);
+    assert_eq!(
+        json(&stdout),
+        json!([
+            { "path": "log.level", "kind": "modified", "old_value": "info", "new_value": "warn" },
+            { "path": "outbounds[\"direct\"]", "kind": "removed", "old_value": { "type": "direct", "tag": "direct" } }
+        ])
+    );
+
+    // 作为 git 合并驱动：merge %O %A %B --write
+    let theirs = write(
+        dir.path(),
+        "theirs.json",
+        &json!({ "log": { "level": "info" }, "outbounds": [{ "type": "direct", "tag": "direct" }, { "type": "block", "tag": "block" }] })
+            .to_string(),
+    );
+    let (code, stdout, _) = cli(&["merge", &old, &new, &theirs, "--write"]);
+    assert_eq!(code, EXIT_OK);
+    assert_eq!(json(&stdout), json!({ "conflicts": [] }));
+    assert_eq!(
+        json(&std::fs::read_to_string(&new).unwrap()),
+        json!({ "log": { "level": "warn" }, "outbounds": [{ "type": "block", "tag": "block" }] })
+    );
+    let (code, stdout, _) = cli(&["merge", &old, &old, &theirs, "-", "--write"]);
+    assert_eq!(code, EXIT_ERROR);
+    assert!(stdout.is_empty());
+}
+
+#[test]
+fn binary_exits_with_command_status() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write(dir.path(), "config.json", "[]");
+    let output = Command::new(env!("CARGO_BIN_EXE_sing-config-cli"))
+        .args(["validate", &path])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(EXIT_FAILED));
+    assert_eq!(
+        json(&String::from_utf8_lossy(&output.stdout))["valid"],
+        false
+    );
+}
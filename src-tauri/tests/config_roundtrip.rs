@@ -0,0 +1,112 @@
+use serde_json::{json, Value};
+use sing_config_lib::config::{Config, InboundType, Listable, OutboundType};
+
+fn load(relative: &str) -> Value {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(relative);
+    let text = std::fs::read_to_string(&path).expect("read example config");
+    serde_json::from_str(&text).expect("parse example config")
+}
+
+/// 比较序列化后的文本，键顺序和显式的 null 都要一致
+fn assert_roundtrip(original: Value) {
+    let config = Config::from_value(original.clone()).expect("deserialize typed config");
+    let output = config.to_value_with_layout(&original).expect("serialize typed config");
+    assert_eq!(
+        serde_json::to_string_pretty(&original).unwrap(),
+        serde_json::to_string_pretty(&output).unwrap()
+    );
+}
+
+#[test]
+fn full_example_roundtrips() {
+    assert_roundtrip(load("../config.full.example.json"));
+}
+
+#[test]
+fn public_examples_roundtrip() {
+    assert_roundtrip(load("../public/config.full.example.json"));
+    assert_roundtrip(load("../public/config.full.json"));
+}
+
+#[test]
+fn full_example_is_typed() {
+    let config = Config::from_value(load("../config.full.example.json")).unwrap();
+
+    let tun = config.inbounds().iter().find(|inbound| inbound.kind == InboundType::Tun).unwrap();
+    assert_eq!(tun.address, Some(Listable::Many(vec!["172.19.0.1/30".to_string()])));
+
+    let selector = config.outbound("selector-out").unwrap();
+    assert_eq!(selector.kind, OutboundType::Selector);
+    assert_eq!(selector.members(), ["shadowsocks-out", "vmess-out", "trojan-out"]);
+
+    let route = config.route.as_ref().unwrap();
+    assert_eq!(route.final_outbound.as_deref(), Some("selector-out"));
+    assert!(route.rules().iter().any(|rule| rule.is_logical()));
+}
+
+#[test]
+fn unknown_fields_and_types_are_preserved() {
+    let original = json!({
+        "x_comment": "kept",
+        "inbounds": [
+            { "type": "future-proto", "tag": "in", "listen_port": 1, "x_flag": [1, 2, 3] }
+        ],
+        "outbounds": [
+            {
+                "type": "vless",
+                "tag": "out",
+                "tls": { "enabled": true, "x_tls": { "nested": null } },
+                "x_dial": "value"
+            }
+        ],
+        "route": {
+            "rules": [{ "domain": "a.com", "x_rule": true, "action": "future-action" }],
+            "x_route": 1
+        }
+    });
+    assert_roundtrip(original.clone());
+
+    let config = Config::from_value(original).unwrap();
+    assert_eq!(config.inbounds()[0].kind, InboundType::Other("future-proto".to_string()));
+    assert_eq!(config.extra["x_comment"], "kept");
+}
+
+#[test]
+fn key_order_and_nulls_are_preserved() {
+    let original: Value = serde_json::from_str(
+        r#"{
+            "route": { "final": "proxy", "auto_detect_interface": null, "rules": [{ "outbound": "direct", "domain": "a.com" }] },
+            "outbounds": [
+                { "tag": "proxy", "type": "selector", "default": null, "outbounds": ["direct"] },
+                { "server_port": 443, "detour": null, "server": "a.com", "type": "trojan", "tag": "t", "password": "x" },
+                { "tag": "direct", "type": "direct" }
+            ],
+            "log": { "timestamp": true, "level": null, "disabled": false }
+        }"#,
+    )
+    .unwrap();
+    assert_roundtrip(original.clone());
+
+    // 类型化字段按结构体顺序输出并省略 null，按原文档恢复后新增的键排在后面
+    let mut config = Config::from_value(original.clone()).unwrap();
+    let plain = config.to_value().unwrap();
+    assert_eq!(plain["outbounds"][0].get("default"), None);
+    assert_ne!(
+        serde_json::to_string(&plain).unwrap(),
+        serde_json::to_string(&original).unwrap()
+    );
+
+    let outbounds = config.outbounds.as_mut().unwrap();
+    outbounds.swap(0, 2);
+    outbounds[1].dial.detour = Some("direct".to_string());
+    let output = config.to_value_with_layout(&original).unwrap();
+    assert_eq!(
+        serde_json::to_string(&output["outbounds"]).unwrap(),
+        serde_json::to_string(&json!([
+            { "tag": "direct", "type": "direct" },
+            { "server_port": 443, "detour": "direct", "server": "a.com", "type": "trojan", "tag": "t", "password": "x" },
+            { "tag": "proxy", "type": "selector", "default": null, "outbounds": ["direct"] }
+        ]))
+        .unwrap()
+    );
+}
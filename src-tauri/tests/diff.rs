@@ -0,0 +1,131 @@
+use serde_json::json;
+use sing_config_lib::diff::{diff, merge, ChangeKind};
+
+#[test]
+fn matches_tagged_elements_and_reports_moves() {
+    let old = json!({
+        "outbounds": [
+            { "type": "direct", "tag": "direct" },
+            { "type": "shadowsocks", "tag": "hk", "server": "1.1.1.1" },
+            { "type": "block", "tag": "block" }
+        ]
+    });
+    let new = json!({
+        "outbounds": [
+            { "type": "shadowsocks", "tag": "hk", "server": "2.2.2.2" },
+            { "type": "direct", "tag": "direct" },
+            { "type": "vmess", "tag": "jp" }
+        ]
+    });
+    let changes = diff(&old, &new);
+    let summary: Vec<(&str, ChangeKind)> = changes
+        .iter()
+        .map(|change| (change.path.as_str(), change.kind))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (r#"outbounds["block"]"#, ChangeKind::Removed),
+            (r#"outbounds["hk"]"#, ChangeKind::Moved),
+            (r#"outbounds["hk"].server"#, ChangeKind::Modified),
+            (r#"outbounds["jp"]"#, ChangeKind::Added),
+        ]
+    );
+    assert_eq!(changes[1].old_value, Some(json!(1)));
+    assert_eq!(changes[1].new_value, Some(json!(0)));
+
+    // 只调整顺序时每个移动的元素一条记录
+    let reordered =
+        json!({ "outbounds": [old["outbounds"][2], old["outbounds"][0], old["outbounds"][1]] });
+    let changes = diff(&old, &reordered);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path, r#"outbounds["block"]"#);
+
+    // 其他数组按内容对齐，插入规则不会让后面的规则都显示为修改
+    let old = json!({ "route": { "rules": [{ "outbound": "a" }, { "outbound": "b" }] } });
+    let new = json!({ "route": { "rules": [{ "outbound": "x" }, { "outbound": "a" }, { "outbound": "c" }] } });
+    let changes = diff(&old, &new);
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].path, "route.rules[0]");
+    assert_eq!(changes[0].kind, ChangeKind::Added);
+    assert_eq!(changes[1].path, "route.rules[2].outbound");
+    assert_eq!(changes[1].kind, ChangeKind::Modified);
+}
+
+#[test]
+fn merges_independent_edits() {
+    let base = json!({
+        "log": { "level": "info" },
+        "outbounds": [
+            { "type": "direct", "tag": "direct" },
+            { "type": "shadowsocks", "tag": "hk", "server": "1.1.1.1" },
+            { "type": "block", "tag": "block" }
+        ]
+    });
+    // ours 调整顺序并修改 hk，theirs 删除 block、新增 jp 并修改日志级别
+    let ours = json!({
+        "log": { "level": "info" },
+        "outbounds": [
+            { "type": "shadowsocks", "tag": "hk", "server": "2.2.2.2" },
+            { "type": "direct", "tag": "direct" },
+            { "type": "block", "tag": "block" }
+        ]
+    });
+    let theirs = json!({
+        "log": { "level": "warn" },
+        "outbounds": [
+            { "type": "direct", "tag": "direct" },
+            { "type": "vmess", "tag": "jp" },
+            { "type": "shadowsocks", "tag": "hk", "server": "1.1.1.1" }
+        ]
+    });
+    let result = merge(&base, &ours, &theirs);
+    assert!(result.conflicts.is_empty(), "{:?}", result.conflicts);
+    assert_eq!(
+        result.merged,
+        json!({
+            "log": { "level": "warn" },
+            "outbounds": [
+                { "type": "shadowsocks", "tag": "hk", "server": "2.2.2.2" },
+                { "type": "direct", "tag": "direct" },
+                { "type": "vmess", "tag": "jp" }
+            ]
+        })
+    );
+}
+
+#[test]
+fn reports_conflicts_and_keeps_ours() {
+    let base = json!({
+        "log": { "level": "info" },
+        "outbounds": [
+            { "type": "direct", "tag": "direct" },
+            { "type": "shadowsocks", "tag": "hk", "server": "1.1.1.1" }
+        ]
+    });
+    let ours = json!({
+        "log": { "level": "debug" },
+        "outbounds": [{ "type": "direct", "tag": "direct" }]
+    });
+    let theirs = json!({
+        "log": { "level": "warn" },
+        "outbounds": [
+            { "type": "direct", "tag": "direct" },
+            { "type": "shadowsocks", "tag": "hk", "server": "3.3.3.3" }
+        ]
+    });
+    let result = merge(&base, &ours, &theirs);
+    assert_eq!(result.merged, ours);
+    let paths: Vec<&str> = result
+        .conflicts
+        .iter()
+        .map(|conflict| conflict.path.as_str())
+        .collect();
+    assert_eq!(paths, ["log.level", r#"outbounds["hk"]"#]);
+    assert_eq!(result.conflicts[0].base, Some(json!("info")));
+    assert_eq!(result.conflicts[1].ours, None);
+    assert_eq!(
+        result.conflicts[1].theirs.as_ref().unwrap()["server"],
+        "3.3.3.3"
+    );
+}
@@ -0,0 +1,8 @@
+#!/bin/sh
+# 用 sing-box 重新生成 .srs 测试数据，版本号取自各 JSON 文件
+set -e
+cd "$(dirname "$0")"
+for source in sample-v*.json; do
+    sing-box rule-set compile "$source" -o "${source%.json}.srs"
+done
+sing-box version | head -n 1 > SING_BOX_VERSION
@@ -0,0 +1,328 @@
+use serde_json::json;
+use sing_config_lib::geo::{self, GeoDatabase, GeoKind};
+use sing_config_lib::rule_set::decompile;
+
+#[derive(Clone, Copy)]
+enum Record {
+    Empty,
+    Node(usize),
+    Data(usize),
+}
+
+/// 最小的 MaxMind DB 写入：二叉搜索树 + 数据区 + 元数据
+struct Mmdb {
+    bits: u32,
+    nodes: Vec<[Record; 2]>,
+}
+
+impl Mmdb {
+    fn new(bits: u32) -> Self {
+        Self {
+            bits,
+            nodes: vec![[Record::Empty; 2]],
+        }
+    }
+
+    fn insert(&mut self, prefix: u128, len: u32, record: Record) {
+        let bit = |depth: u32| ((prefix >> (self.bits - 1 - depth)) & 1) as usize;
+        let mut node = 0;
+        for depth in 0..len - 1 {
+            let b = bit(depth);
+            node = match self.nodes[node][b] {
+                Record::Node(next) => next,
+                _ => {
+                    self.nodes.push([Record::Empty; 2]);
+                    let next = self.nodes.len() - 1;
+                    self.nodes[node][b] = Record::Node(next);
+                    next
+                }
+            };
+        }
+        self.nodes[node][bit(len - 1)] = record;
+    }
+
+    /// 沿 ::/96 走到 IPv4 子树的根
+    fn ipv4_start(&self) -> usize {
+        (0..96).fold(0, |node, _| match self.nodes[node][0] {
+            Record::Node(next) => next,
+            _ => panic!("no ipv4 subtree"),
+        })
+    }
+
+    fn build(&self, record_size: u32, data: &[u8]) -> Vec<u8> {
+        let count = self.nodes.len();
+        let value = |record: Record| match record {
+            Record::Empty => count as u32,
+            Record::Node(node) => node as u32,
+            Record::Data(offset) => (count + 16 + offset) as u32,
+        };
+        let mut out = Vec::new();
+        for [left, right] in &self.nodes {
+            let (left, right) = (value(*left), value(*right));
+            match record_size {
+                24 => {
+                    out.extend_from_slice(&left.to_be_bytes()[1..]);
+                    out.extend_from_slice(&right.to_be_bytes()[1..]);
+                }
+                28 => {
+                    out.extend_from_slice(&left.to_be_bytes()[1..]);
+                    out.push(((left >> 24) as u8) << 4 | (right >> 24) as u8);
+                    out.extend_from_slice(&right.to_be_bytes()[1..]);
+                }
+                _ => {
+                    out.extend_from_slice(&left.to_be_bytes());
+                    out.extend_from_slice(&right.to_be_bytes());
+                }
+            }
+        }
+        out.extend_from_slice(&[0; 16]);
+        out.extend_from_slice(data);
+        out.extend_from_slice(b"\xAB\xCD\xEFMaxMind.com");
+        out.push(0xE4);
+        out.extend(string("node_count"));
+        out.push(0xC4);
+        out.extend_from_slice(&(count as u32).to_be_bytes());
+        out.extend(string("record_size"));
+        out.push(0xA2);
+        out.extend_from_slice(&(record_size as u16).to_be_bytes());
+        out.extend(string("ip_version"));
+        out.push(0xA2);
+        out.extend_from_slice(&(if self.bits == 128 { 6u16 } else { 4 }).to_be_bytes());
+        out.extend(string("database_type"));
+        out.extend(string("sing-geoip"));
+        out
+    }
+}
+
+fn string(value: &str) -> Vec<u8> {
+    let mut out = vec![0x40 | value.len() as u8];
+    out.extend_from_slice(value.as_bytes());
+    out
+}
+
+fn v4(a: u8, b: u8, c: u8, d: u8) -> u128 {
+    u32::from_be_bytes([a, b, c, d]) as u128
+}
+
+/// 数据区：0 处 "CN"，4 处 {country: {iso_code: "US"}}，后面是指向 "CN" 的指针
+fn data() -> (Vec<u8>, [usize; 3]) {
+    let mut data = string("CN");
+    let us = data.len();
+    data.push(0xE1);
+    data.extend(string("country"));
+    data.push(0xE1);
+    data.extend(string("iso_code"));
+    data.extend(string("US"));
+    let pointer = data.len();
+    data.extend_from_slice(&[0x20, 0x00]);
+    (data, [0, us, pointer])
+}
+
+fn geoip_v6() -> Vec<u8> {
+    let (data, [cn, us, pointer]) = data();
+    let mut db = Mmdb::new(128);
+    db.insert(v4(1, 0, 0, 0), 104, Record::Data(cn));
+    db.insert(v4(8, 8, 8, 0), 120, Record::Data(us));
+    db.insert(0x2001_0db8 << 96, 32, Record::Data(pointer));
+    let ipv4 = db.ipv4_start();
+    db.insert(0xffff << 32, 96, Record::Node(ipv4));
+    db.insert(0x2002 << 112, 16, Record::Node(ipv4));
+    db.build(24, &data)
+}
+
+fn geosite() -> Vec<u8> {
+    let codes: [(&str, &[(u8, &str)]); 2] = [
+        ("cn", &[(1, "cn"), (0, "example.cn")]),
+        (
+            "google",
+            &[(1, "google.com"), (2, "google"), (3, "^g\\.co$")],
+        ),
+    ];
+    let mut content = Vec::new();
+    let mut out = vec![0, codes.len() as u8];
+    for (code, items) in codes {
+        out.push(code.len() as u8);
+        out.extend_from_slice(code.as_bytes());
+        out.push(content.len() as u8);
+        out.push(items.len() as u8);
+        for (kind, value) in items {
+            content.push(*kind);
+            content.push(value.len() as u8);
+            content.extend_from_slice(value.as_bytes());
+        }
+    }
+    out.extend(content);
+    out
+}
+
+#[test]
+fn reads_geoip_database() {
+    let db = GeoDatabase::from_bytes(&geoip_v6()).unwrap();
+    assert_eq!(db.kind(), GeoKind::GeoIp);
+    assert_eq!(db.codes(), ["cn", "us"]);
+    // 别名子树只出现一次，IPv4 网段按 IPv4 表示
+    assert_eq!(
+        serde_json::to_value(db.source("CN").unwrap()).unwrap(),
+        json!({"version": 1, "rules": [{"ip_cidr": ["1.0.0.0/8", "2001:db8::/32"]}]})
+    );
+    assert_eq!(
+        serde_json::to_value(db.source("us").unwrap().rules).unwrap(),
+        json!([{"ip_cidr": ["8.8.8.0/24"]}])
+    );
+    assert!(db.source("jp").is_none());
+}
+
+#[test]
+fn reads_ipv4_databases_with_any_record_size() {
+    let (data, [cn, us, _]) = data();
+    let mut db = Mmdb::new(32);
+    db.insert(v4(1, 0, 0, 0), 8, Record::Data(cn));
+    db.insert(v4(8, 8, 8, 0), 24, Record::Data(us));
+    db.insert(v4(223, 0, 0, 0), 8, Record::Data(cn));
+    for record_size in [24, 28, 32] {
+        let GeoDatabase::GeoIp(geoip) =
+            GeoDatabase::from_bytes(&db.build(record_size, &data)).unwrap()
+        else {
+            panic!("not a geoip database");
+        };
+        assert_eq!(geoip.database_type, "sing-geoip");
+        assert_eq!(geoip.networks("cn").unwrap(), ["1.0.0.0/8", "223.0.0.0/8"]);
+        assert_eq!(geoip.networks("us").unwrap(), ["8.8.8.0/24"]);
+    }
+}
+
+#[test]
+fn reads_geosite_database() {
+    let db = GeoDatabase::from_bytes(&geosite()).unwrap();
+    assert_eq!(db.kind(), GeoKind::GeoSite);
+    assert_eq!(db.codes(), ["cn", "google"]);
+    assert_eq!(
+        serde_json::to_value(db.source("google").unwrap()).unwrap(),
+        json!({"version": 2, "rules": [{
+            "domain_suffix": ["google.com"],
+            "domain_keyword": ["google"],
+            "domain_regex": ["^g\\.co$"]
+        }]})
+    );
+
+    let mut truncated = geosite();
+    truncated.truncate(truncated.len() - 3);
+    assert!(GeoDatabase::from_bytes(&truncated).is_err());
+}
+
+#[test]
+fn exports_rule_sets() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = GeoDatabase::from_bytes(&geosite()).unwrap();
+    let codes = vec!["cn".to_string(), "google".to_string()];
+
+    let files = geo::export(&db, &codes, dir.path(), true).unwrap();
+    assert_eq!(
+        files,
+        [
+            dir.path().join("geosite-cn.srs"),
+            dir.path().join("geosite-google.srs")
+        ]
+    );
+    let source = decompile(&std::fs::read(&files[0]).unwrap()).unwrap();
+    assert_eq!(source, db.source("cn").unwrap());
+
+    let files = geo::export(&db, &codes[..1], dir.path(), false).unwrap();
+    let text = std::fs::read_to_string(&files[0]).unwrap();
+    assert!(text.contains("\"example.cn\""));
+
+    let err = geo::export(&db, &["jp".to_string()], dir.path(), true).unwrap_err();
+    assert_eq!(err, "geosite has no code jp");
+}
+
+#[test]
+fn localizes_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let geoip = dir.path().join("geoip.db");
+    let geosite_path = dir.path().join("geosite.db");
+    std::fs::write(&geoip, geoip_v6()).unwrap();
+    std::fs::write(&geosite_path, geosite()).unwrap();
+    let output = dir.path().join("rule-sets");
+
+    let config = json!({
+        "route": {
+            "rules": [
+                { "geoip": ["cn", "private"], "outbound": "direct" },
+                { "geosite": "google", "outbound": "proxy" }
+            ],
+            "geoip": { "path": "geoip.db" }
+        }
+    });
+    let result = geo::localize_value(config, &[geoip, geosite_path], &output).unwrap();
+
+    assert_eq!(
+        result.config["route"]["rules"],
+        json!([
+            { "ip_is_private": true, "rule_set": "geoip-cn", "outbound": "direct" },
+            { "rule_set": "geosite-google", "outbound": "proxy" }
+        ])
+    );
+    let path = |tag: &str| output.join(format!("{tag}.srs")).display().to_string();
+    assert_eq!(
+        result.config["route"]["rule_set"],
+        json!([
+            { "type": "local", "tag": "geoip-cn", "format": "binary", "path": path("geoip-cn") },
+            { "type": "local", "tag": "geosite-google", "format": "binary", "path": path("geosite-google") }
+        ])
+    );
+    assert_eq!(
+        result.report.files,
+        [path("geoip-cn"), path("geosite-google")]
+    );
+    assert!(result.report.warnings.is_empty());
+    assert!(result.config["route"].get("geoip").is_none());
+    assert!(result
+        .report
+        .steps
+        .iter()
+        .all(|step| !step.changes.is_empty()));
+    assert_eq!(
+        decompile(&std::fs::read(path("geosite-google")).unwrap())
+            .unwrap()
+            .rules
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn keeps_missing_codes_remote() {
+    let dir = tempfile::tempdir().unwrap();
+    let databases = [GeoDatabase::from_bytes(&geosite()).unwrap()];
+    let mut config = sing_config_lib::config::Config::from_value(json!({
+        "route": { "rules": [{ "geosite": ["cn", "netflix"], "geoip": "cn", "outbound": "direct" }] }
+    }))
+    .unwrap();
+
+    let report = geo::localize(&mut config, &databases, dir.path()).unwrap();
+    assert_eq!(report.files.len(), 1);
+    assert_eq!(
+        report.warnings,
+        ["geosite.db has no code netflix, rule-set geosite-netflix is still remote"]
+    );
+    let value = config.to_value().unwrap();
+    let kinds: Vec<_> = value["route"]["rule_set"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rule_set| {
+            (
+                rule_set["tag"].as_str().unwrap(),
+                rule_set["type"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("geoip-cn", "remote"),
+            ("geosite-cn", "local"),
+            ("geosite-netflix", "remote")
+        ]
+    );
+}
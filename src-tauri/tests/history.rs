@@ -0,0 +1,123 @@
+use std::fs;
+
+use serde_json::json;
+use sing_config_lib::diff::ChangeKind;
+use sing_config_lib::history::History;
+
+#[test]
+fn records_and_lists_revisions() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("config.json");
+    let history = History::open(&dir.path().join("history"), &file);
+    assert!(history.list().is_empty());
+
+    let first = history
+        .record(r#"{ "log": { "level": "info" } }"#, Some("  initial  "))
+        .unwrap();
+    let second = history
+        .record(r#"{ "log": { "level": "loud" } }"#, None)
+        .unwrap();
+    assert!(second.id.parse::<u64>().unwrap() > first.id.parse::<u64>().unwrap());
+    assert_eq!(first.note.as_deref(), Some("initial"));
+    assert!(first.valid);
+    assert!(!second.valid);
+    assert_eq!(second.errors, 1);
+
+    // 重新打开后读取同一份历史
+    let reopened = History::open(&dir.path().join("history"), &file);
+    assert_eq!(reopened.list(), [second.clone(), first.clone()]);
+    assert_eq!(
+        reopened.read(&first.id).unwrap(),
+        r#"{ "log": { "level": "info" } }"#
+    );
+    assert_eq!(
+        reopened.read("../index").unwrap_err(),
+        "revision ../index not found"
+    );
+}
+
+#[test]
+fn diffs_and_restores_revisions() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("config.json");
+    let history = History::open(&dir.path().join("history"), &file);
+    let old = r#"{ "log": { "level": "info" }, "outbounds": [] }"#;
+    let revision = history.record(old, Some("last week")).unwrap();
+    fs::write(&file, r#"{ "log": { "level": "warn" }, "outbounds": [] }"#).unwrap();
+
+    let changes = history.diff(&revision.id, None).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path, "log.level");
+    assert_eq!(changes[0].kind, ChangeKind::Modified);
+    assert_eq!(changes[0].old_value, Some(json!("info")));
+
+    // 与编辑器中未保存的配置比较
+    let current = json!({ "log": { "level": "info" } });
+    let changes = history.diff(&revision.id, Some(&current)).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path, "outbounds");
+    assert_eq!(changes[0].kind, ChangeKind::Removed);
+
+    let restored = history.restore(&revision.id, 1, false).unwrap();
+    assert_eq!(restored.text, old);
+    assert!(restored.valid);
+    assert_eq!(fs::read_to_string(&file).unwrap(), old);
+    assert!(fs::read_to_string(dir.path().join("config.json.1.bak"))
+        .unwrap()
+        .contains("warn"));
+    let revisions = history.list();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(
+        revisions[0].note,
+        Some(format!("Restored revision {}", revision.id))
+    );
+    assert!(history.diff(&revisions[0].id, None).unwrap().is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn shares_history_across_symlinks_and_new_files() {
+    use std::os::unix::fs::symlink;
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("history");
+    let real = dir.path().join("real");
+    fs::create_dir(&real).unwrap();
+    symlink(&real, dir.path().join("link")).unwrap();
+
+    // 文件创建前通过符号链接目录记录
+    let revision = History::open(&root, &dir.path().join("link").join("config.json"))
+        .record("{}", None)
+        .unwrap();
+    let file = real.join("config.json");
+    fs::write(&file, "{}").unwrap();
+    let revisions = History::open(&root, &file).list();
+    assert_eq!(revisions, [revision]);
+
+    symlink(&file, dir.path().join("alias.json")).unwrap();
+    assert_eq!(
+        History::open(&root, &dir.path().join("alias.json")).list(),
+        revisions
+    );
+}
+
+#[test]
+fn restores_invalid_revisions_only_when_forced() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("config.json");
+    let history = History::open(&dir.path().join("history"), &file);
+    let invalid = r#"{ "log": { "level": "loud" } }"#;
+    let revision = history.record(invalid, Some("Before first save")).unwrap();
+    fs::write(&file, "{}").unwrap();
+
+    let err = history.restore(&revision.id, 1, false).unwrap_err();
+    assert!(err.starts_with("config is not saved: 1 validation error(s)"), "{err}");
+    assert_eq!(fs::read_to_string(&file).unwrap(), "{}");
+    assert_eq!(history.list().len(), 1);
+
+    let restored = history.restore(&revision.id, 1, true).unwrap();
+    assert!(!restored.valid);
+    assert_eq!(restored.errors, 1);
+    assert_eq!(fs::read_to_string(&file).unwrap(), invalid);
+    assert_eq!(history.list().len(), 2);
+}
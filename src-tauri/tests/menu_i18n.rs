@@ -0,0 +1,30 @@
+use sing_config_lib::menu_i18n::{available_locales, has_locale, MenuI18n, MENU_KEYS};
+
+#[test]
+fn lists_bundled_locales() {
+    let codes: Vec<_> = available_locales().into_iter().map(|(code, _)| code).collect();
+    assert_eq!(codes, ["zh", "en", "ja", "ru", "fa"]);
+    assert!(has_locale("fa"));
+    assert!(!has_locale("de"));
+}
+
+#[test]
+fn every_catalog_covers_every_key() {
+    let english = MenuI18n::new("en");
+    for (code, _) in available_locales() {
+        let i18n = MenuI18n::new(code);
+        for key in MENU_KEYS {
+            assert_ne!(i18n.text(key), *key, "{code} is missing {key}");
+            if code != "en" && *key != "app_name" {
+                assert_ne!(i18n.text(key), english.text(key), "{code} did not translate {key}");
+            }
+        }
+    }
+}
+
+#[test]
+fn falls_back_to_english() {
+    assert_eq!(MenuI18n::new("ru").file_menu(), "Файл");
+    assert_eq!(MenuI18n::new("de").file_menu(), "File");
+    assert_eq!(MenuI18n::new("en").text("no_such_key"), "no_such_key");
+}
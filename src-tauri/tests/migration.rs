@@ -0,0 +1,530 @@
+use serde_json::{json, Value};
+use sing_config_lib::migration::{migrate_value, MigrationResult, StepStatus, Version};
+
+fn migrate(config: Value) -> MigrationResult {
+    migrate_value(config, "1.12").unwrap()
+}
+
+fn status(result: &MigrationResult, id: &str) -> StepStatus {
+    result.steps.iter().find(|step| step.id == id).unwrap().status
+}
+
+#[test]
+fn parses_target_versions() {
+    assert_eq!(Version::parse("1.12").unwrap(), Version::new(1, 12));
+    assert_eq!(Version::parse("v1.11.0-beta.3").unwrap(), Version::new(1, 11));
+    assert!(Version::parse("latest").is_err());
+    assert!(migrate_value(json!({}), "1.13").is_err());
+}
+
+#[test]
+fn skips_steps_after_target_version() {
+    let result = migrate_value(
+        json!({
+            "dns": { "servers": [{ "tag": "google", "address": "tls://8.8.8.8" }] },
+            "outbounds": [{ "type": "block", "tag": "block" }]
+        }),
+        "1.10",
+    )
+    .unwrap();
+    assert!(!result.changed());
+    assert_eq!(status(&result, "special-outbounds"), StepStatus::Skipped);
+    assert_eq!(status(&result, "dns-servers"), StepStatus::Skipped);
+    assert_eq!(status(&result, "tun-address"), StepStatus::Unchanged);
+}
+
+#[test]
+fn migrates_clash_api_cache_file() {
+    let result = migrate(json!({
+        "experimental": {
+            "clash_api": {
+                "external_controller": "127.0.0.1:9090",
+                "cache_file": "cache.db",
+                "cache_id": "my_profile2",
+                "store_mode": true,
+                "store_selected": true,
+                "store_fakeip": true
+            }
+        }
+    }));
+    assert_eq!(
+        result.config["experimental"],
+        json!({
+            "cache_file": {
+                "enabled": true,
+                "path": "cache.db",
+                "cache_id": "my_profile2",
+                "store_fakeip": true
+            },
+            "clash_api": { "external_controller": "127.0.0.1:9090" }
+        })
+    );
+}
+
+#[test]
+fn migrates_geoip_and_geosite_to_rule_sets() {
+    let result = migrate(json!({
+        "route": {
+            "rules": [
+                { "geoip": "private", "outbound": "direct" },
+                { "geoip": "cn", "geosite": "cn", "outbound": "direct" },
+                { "source_geoip": "us", "outbound": "direct" }
+            ],
+            "geoip": { "download_detour": "proxy" },
+            "geosite": { "download_detour": "proxy" }
+        }
+    }));
+    let route = &result.config["route"];
+    assert_eq!(
+        route["rules"],
+        json!([
+            { "ip_is_private": true, "outbound": "direct" },
+            { "rule_set": ["geoip-cn", "geosite-cn"], "outbound": "direct" },
+            { "rule_set": "geoip-us", "rule_set_ip_cidr_match_source": true, "outbound": "direct" }
+        ])
+    );
+    assert_eq!(
+        route["rule_set"][0],
+        json!({
+            "type": "remote",
+            "tag": "geoip-cn",
+            "format": "binary",
+            "url": "https://raw.githubusercontent.com/SagerNet/sing-geoip/rule-set/geoip-cn.srs",
+            "download_detour": "proxy"
+        })
+    );
+    let tags: Vec<_> = route["rule_set"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rule_set| rule_set["tag"].as_str().unwrap())
+        .collect();
+    assert_eq!(tags, ["geoip-cn", "geoip-us", "geosite-cn"]);
+    assert!(route.get("geoip").is_none() && route.get("geosite").is_none());
+    assert_eq!(result.config["experimental"]["cache_file"]["enabled"], true);
+}
+
+#[test]
+fn merges_tun_address_fields() {
+    let result = migrate(json!({
+        "inbounds": [{
+            "type": "tun",
+            "inet4_address": "172.19.0.1/30",
+            "inet6_address": "fdfe:dcba:9876::1/126",
+            "inet4_route_address": ["0.0.0.0/1", "128.0.0.0/1"],
+            "inet6_route_address": ["::/1", "8000::/1"],
+            "inet4_route_exclude_address": ["192.168.0.0/16"],
+            "inet6_route_exclude_address": ["fc00::/7"]
+        }]
+    }));
+    assert_eq!(
+        result.config["inbounds"][0],
+        json!({
+            "type": "tun",
+            "address": ["172.19.0.1/30", "fdfe:dcba:9876::1/126"],
+            "route_address": ["0.0.0.0/1", "128.0.0.0/1", "::/1", "8000::/1"],
+            "route_exclude_address": ["192.168.0.0/16", "fc00::/7"]
+        })
+    );
+}
+
+#[test]
+fn migrates_legacy_inbound_fields_and_special_outbounds() {
+    let result = migrate(json!({
+        "inbounds": [{
+            "type": "mixed",
+            "sniff": true,
+            "sniff_timeout": "1s",
+            "domain_strategy": "prefer_ipv4"
+        }],
+        "outbounds": [
+            { "type": "direct", "tag": "direct" },
+            { "type": "block", "tag": "block" },
+            { "type": "dns", "tag": "dns-out" },
+            { "type": "selector", "tag": "proxy", "outbounds": ["direct", "block"] }
+        ],
+        "route": {
+            "rules": [
+                { "protocol": "dns", "outbound": "dns-out" },
+                { "domain": "ads.example", "outbound": "block" }
+            ],
+            "final": "direct"
+        }
+    }));
+    let config = &result.config;
+    assert_eq!(config["inbounds"][0], json!({ "type": "mixed", "tag": "mixed-in" }));
+    assert_eq!(
+        config["outbounds"],
+        json!([
+            { "type": "direct", "tag": "direct" },
+            { "type": "selector", "tag": "proxy", "outbounds": ["direct"] }
+        ])
+    );
+    assert_eq!(
+        config["route"]["rules"],
+        json!([
+            { "inbound": "mixed-in", "action": "sniff", "timeout": "1s" },
+            { "inbound": "mixed-in", "action": "resolve", "strategy": "prefer_ipv4" },
+            { "protocol": "dns", "action": "hijack-dns" },
+            { "domain": "ads.example", "action": "reject" }
+        ])
+    );
+    let special = result.steps.iter().find(|step| step.id == "special-outbounds").unwrap();
+    assert_eq!(special.warnings, ["proxy: removed block/dns members from the group"]);
+}
+
+#[test]
+fn adds_sniff_rule_for_hijack_dns() {
+    let result = migrate(json!({
+        "outbounds": [{ "type": "dns", "tag": "dns" }],
+        "route": { "rules": [{ "protocol": "dns", "outbound": "dns" }] }
+    }));
+    assert_eq!(
+        result.config["route"]["rules"],
+        json!([{ "action": "sniff" }, { "protocol": "dns", "action": "hijack-dns" }])
+    );
+}
+
+#[test]
+fn moves_destination_override_to_route_rules() {
+    let result = migrate(json!({
+        "outbounds": [{
+            "type": "direct",
+            "tag": "direct",
+            "override_address": "1.1.1.1",
+            "override_port": 443
+        }],
+        "route": {
+            "rules": [{ "domain": "a.com", "outbound": "direct" }],
+            "final": "direct"
+        }
+    }));
+    assert_eq!(result.config["outbounds"][0], json!({ "type": "direct", "tag": "direct" }));
+    assert_eq!(
+        result.config["route"]["rules"],
+        json!([
+            { "domain": "a.com", "outbound": "direct", "override_address": "1.1.1.1", "override_port": 443 },
+            { "action": "route", "outbound": "direct", "override_address": "1.1.1.1", "override_port": 443 }
+        ])
+    );
+}
+
+#[test]
+fn converts_wireguard_outbound_to_endpoint() {
+    let result = migrate(json!({
+        "outbounds": [{
+            "type": "wireguard",
+            "tag": "wg-out",
+            "server": "127.0.0.1",
+            "server_port": 10001,
+            "system_interface": true,
+            "gso": true,
+            "interface_name": "wg0",
+            "local_address": ["10.0.0.1/32"],
+            "private_key": "<private_key>",
+            "peer_public_key": "<peer_public_key>",
+            "pre_shared_key": "<pre_shared_key>",
+            "reserved": [0, 0, 0],
+            "mtu": 1408,
+            "detour": "proxy"
+        }]
+    }));
+    assert_eq!(result.config["outbounds"], json!([]));
+    assert_eq!(
+        result.config["endpoints"],
+        json!([{
+            "type": "wireguard",
+            "tag": "wg-out",
+            "system": true,
+            "name": "wg0",
+            "mtu": 1408,
+            "address": ["10.0.0.1/32"],
+            "private_key": "<private_key>",
+            "peers": [{
+                "address": "127.0.0.1",
+                "port": 10001,
+                "public_key": "<peer_public_key>",
+                "pre_shared_key": "<pre_shared_key>",
+                "allowed_ips": ["0.0.0.0/0", "::/0"],
+                "reserved": [0, 0, 0]
+            }],
+            "detour": "proxy"
+        }])
+    );
+}
+
+#[test]
+fn converts_legacy_dns_servers() {
+    let result = migrate(json!({
+        "dns": {
+            "servers": [
+                { "tag": "local", "address": "local" },
+                { "tag": "tcp", "address": "tcp://1.1.1.1:5353" },
+                { "tag": "udp", "address": "1.1.1.1" },
+                { "tag": "v6", "address": "[2606:4700::1111]:53" },
+                { "tag": "tls", "address": "tls://1.1.1.1" },
+                { "tag": "doh", "address": "https://dns.google/dns-query", "address_resolver": "local" },
+                { "tag": "doh-path", "address": "https://1.1.1.1:8443/custom" },
+                { "tag": "quic", "address": "quic://dns.adguard.com", "address_resolver": "local", "address_strategy": "ipv4_only" },
+                { "tag": "h3", "address": "h3://1.1.1.1" },
+                { "tag": "dhcp", "address": "dhcp://auto" },
+                { "tag": "dhcp-en0", "address": "dhcp://en0" },
+                { "tag": "fakeip", "address": "fakeip" },
+                { "tag": "block", "address": "rcode://refused" }
+            ],
+            "rules": [{ "domain": "ads.example", "server": "block" }],
+            "fakeip": { "enabled": true, "inet4_range": "198.18.0.0/15", "inet6_range": "fc00::/18" }
+        }
+    }));
+    let dns = &result.config["dns"];
+    assert_eq!(
+        dns["servers"],
+        json!([
+            { "type": "local", "tag": "local" },
+            { "type": "tcp", "tag": "tcp", "server": "1.1.1.1", "server_port": 5353 },
+            { "type": "udp", "tag": "udp", "server": "1.1.1.1" },
+            { "type": "udp", "tag": "v6", "server": "2606:4700::1111", "server_port": 53 },
+            { "type": "tls", "tag": "tls", "server": "1.1.1.1" },
+            { "type": "https", "tag": "doh", "server": "dns.google", "domain_resolver": "local" },
+            { "type": "https", "tag": "doh-path", "server": "1.1.1.1", "server_port": 8443, "path": "/custom" },
+            {
+                "type": "quic",
+                "tag": "quic",
+                "server": "dns.adguard.com",
+                "domain_resolver": { "server": "local", "strategy": "ipv4_only" }
+            },
+            { "type": "h3", "tag": "h3", "server": "1.1.1.1" },
+            { "type": "dhcp", "tag": "dhcp" },
+            { "type": "dhcp", "tag": "dhcp-en0", "interface": "en0" },
+            { "type": "fakeip", "tag": "fakeip", "inet4_range": "198.18.0.0/15", "inet6_range": "fc00::/18" }
+        ])
+    );
+    assert_eq!(
+        dns["rules"],
+        json!([{ "domain": "ads.example", "action": "predefined", "rcode": "REFUSED" }])
+    );
+    assert!(dns.get("fakeip").is_none());
+}
+
+#[test]
+fn moves_server_strategy_and_client_subnet_to_rules() {
+    let result = migrate(json!({
+        "dns": {
+            "servers": [
+                { "tag": "default", "address": "1.1.1.1", "strategy": "ipv4_only" },
+                { "tag": "google", "address": "8.8.8.8", "strategy": "prefer_ipv6", "client_subnet": "1.1.1.1" }
+            ],
+            "rules": [{ "domain": "google.com", "server": "google" }]
+        }
+    }));
+    assert_eq!(
+        result.config["dns"],
+        json!({
+            "servers": [
+                { "type": "udp", "tag": "default", "server": "1.1.1.1" },
+                { "type": "udp", "tag": "google", "server": "8.8.8.8" }
+            ],
+            "rules": [{
+                "domain": "google.com",
+                "server": "google",
+                "strategy": "prefer_ipv6",
+                "client_subnet": "1.1.1.1"
+            }],
+            "strategy": "ipv4_only"
+        })
+    );
+}
+
+#[test]
+fn migrates_domain_resolver() {
+    let result = migrate(json!({
+        "dns": {
+            "servers": [{ "address": "local", "tag": "local" }, { "address": "1.1.1.1", "tag": "remote" }],
+            "rules": [
+                { "outbound": "any", "server": "local" },
+                { "outbound": "proxy", "server": "remote" }
+            ]
+        },
+        "outbounds": [
+            { "type": "socks", "tag": "proxy", "server": "example.org", "server_port": 2080 },
+            { "type": "socks", "tag": "other", "server": "example.net", "server_port": 2080, "domain_strategy": "prefer_ipv4" }
+        ]
+    }));
+    let config = &result.config;
+    assert!(config["dns"].get("rules").is_none());
+    assert_eq!(config["route"]["default_domain_resolver"], "local");
+    assert_eq!(config["outbounds"][0]["domain_resolver"], "remote");
+    assert_eq!(
+        config["outbounds"][1]["domain_resolver"],
+        json!({ "server": "local", "strategy": "prefer_ipv4" })
+    );
+    assert!(config["outbounds"][1].get("domain_strategy").is_none());
+}
+
+#[test]
+fn adds_local_server_for_domain_strategy() {
+    let result = migrate(json!({
+        "outbounds": [{
+            "type": "socks",
+            "server": "example.org",
+            "server_port": 2080,
+            "domain_strategy": "prefer_ipv4"
+        }]
+    }));
+    assert_eq!(result.config["dns"]["servers"], json!([{ "type": "local", "tag": "local" }]));
+    assert_eq!(
+        result.config["outbounds"][0]["domain_resolver"],
+        json!({ "server": "local", "strategy": "prefer_ipv4" })
+    );
+}
+
+#[test]
+fn migration_is_idempotent() {
+    let first = migrate(json!({
+        "inbounds": [{ "type": "tun", "inet4_address": "172.19.0.1/30", "sniff": true }],
+        "dns": { "servers": [{ "tag": "google", "address": "tls://8.8.8.8" }] },
+        "route": { "rules": [{ "geosite": "cn", "outbound": "direct" }] },
+        "outbounds": [{ "type": "direct", "tag": "direct" }]
+    }));
+    assert!(first.changed());
+    let second = migrate(first.config.clone());
+    assert!(!second.changed());
+    assert_eq!(second.config, first.config);
+}
+
+#[test]
+fn keeps_malformed_legacy_fields_with_warnings() {
+    let result = migrate(json!({
+        "inbounds": [
+            {
+                "type": "tun",
+                "tag": "tun-in",
+                "inet4_address": 172,
+                "inet6_address": "fdfe::1/126",
+                "domain_strategy": 4,
+                "sniff": true
+            }
+        ]
+    }));
+    let inbound = &result.config["inbounds"][0];
+    assert_eq!(inbound["inet4_address"], 172);
+    assert_eq!(inbound["address"], json!(["fdfe::1/126"]));
+    assert_eq!(inbound["domain_strategy"], 4);
+    assert!(inbound.get("sniff").is_none());
+
+    let warnings: Vec<&str> = result
+        .steps
+        .iter()
+        .flat_map(|step| &step.warnings)
+        .map(String::as_str)
+        .collect();
+    assert!(
+        warnings.iter().any(|w| w.starts_with("tun-in: cannot migrate inet4_address 172")),
+        "{warnings:?}"
+    );
+    assert!(
+        warnings.iter().any(|w| w.starts_with("domain_strategy: cannot migrate 4")),
+        "{warnings:?}"
+    );
+}
+
+#[test]
+fn clears_references_to_special_outbounds() {
+    let result = migrate(json!({
+        "outbounds": [
+            { "type": "direct", "tag": "direct" },
+            { "type": "block", "tag": "block" },
+            { "type": "selector", "tag": "proxy", "outbounds": ["direct", "block"], "default": "block" },
+            { "type": "socks", "tag": "chain", "server": "127.0.0.1", "server_port": 1080, "detour": "block" }
+        ],
+        "route": {
+            "rule_set": [
+                { "type": "remote", "tag": "ads", "url": "https://example.com/ads.srs", "download_detour": "block" }
+            ]
+        }
+    }));
+    let config = &result.config;
+    assert!(config["outbounds"][1].get("default").is_none());
+    assert!(config["outbounds"][2].get("detour").is_none());
+    assert!(config["route"]["rule_set"][0].get("download_detour").is_none());
+    let special = result.steps.iter().find(|step| step.id == "special-outbounds").unwrap();
+    assert_eq!(
+        special.warnings,
+        [
+            "proxy: removed block/dns members from the group",
+            "proxy: cleared default block, the outbound was removed",
+            "chain: cleared detour block, the outbound was removed",
+            "ads: cleared download_detour block, the outbound was removed",
+        ]
+    );
+}
+
+#[test]
+fn converts_dns_rule_server_lists() {
+    let result = migrate(json!({
+        "dns": {
+            "servers": [{ "type": "local", "tag": "local" }, { "type": "local", "tag": "backup" }],
+            "rules": [
+                { "domain": "a.com", "server": ["local"] },
+                { "domain": "b.com", "server": ["backup", "local"] }
+            ]
+        }
+    }));
+    assert_eq!(
+        result.config["dns"]["rules"],
+        json!([
+            { "domain": "a.com", "server": "local" },
+            { "domain": "b.com", "server": "backup" }
+        ])
+    );
+    let step = result.steps.iter().find(|step| step.id == "dns-rule-server").unwrap();
+    assert_eq!(step.status, StepStatus::Applied);
+    assert_eq!(
+        step.warnings,
+        ["DNS rule server [backup, local]: sing-box uses a single server, dropped local"]
+    );
+}
+
+#[test]
+fn keeps_layout_without_restoring_removed_fields() {
+    let original: Value = serde_json::from_str(
+        r#"{
+            "outbounds": [{ "tag": "dns", "type": "dns" }, { "tag": "direct", "type": "direct" }],
+            "route": {
+                "geoip": null,
+                "final": "direct",
+                "rules": [
+                    { "outbound": "dns", "protocol": "dns" },
+                    { "outbound": "direct", "domain": "a.com", "invert": null }
+                ]
+            }
+        }"#,
+    )
+    .unwrap();
+    let result = migrate(original);
+    // 已废弃的 geoip 不补回；插入 sniff 规则后 null 仍回到原来的规则上
+    assert_eq!(
+        serde_json::to_string(&result.config["route"]).unwrap(),
+        serde_json::to_string(&json!({
+            "final": "direct",
+            "rules": [
+                { "action": "sniff" },
+                { "protocol": "dns", "action": "hijack-dns" },
+                { "outbound": "direct", "domain": "a.com", "invert": null }
+            ]
+        }))
+        .unwrap()
+    );
+}
+
+#[test]
+fn removes_null_legacy_fields() {
+    let result = migrate(json!({
+        "inbounds": [{ "type": "mixed", "tag": "in", "listen_port": 1080, "sniff": null, "sniff_timeout": null }]
+    }));
+    assert_eq!(
+        result.config["inbounds"],
+        json!([{ "type": "mixed", "tag": "in", "listen_port": 1080 }])
+    );
+    assert!(result.steps.iter().all(|step| step.warnings.is_empty()));
+}
@@ -0,0 +1,170 @@
+use serde_json::json;
+use sing_config_lib::config::Config;
+use sing_config_lib::preflight::{IssueLevel, Preflight, PreflightContext, PreflightIssue, PreflightRule};
+
+fn config(value: serde_json::Value) -> Config {
+    Config::from_value(value).unwrap()
+}
+
+fn paths(issues: &[PreflightIssue]) -> Vec<&str> {
+    issues.iter().map(|issue| issue.path.as_str()).collect()
+}
+
+#[test]
+fn reports_port_conflicts() {
+    let config = config(json!({
+        "inbounds": [
+            { "type": "mixed", "tag": "a", "listen_port": 1080 },
+            { "type": "socks", "listen_port": 1080 },
+            { "type": "http", "tag": "c", "listen_port": 8080 }
+        ]
+    }));
+    let issues = Preflight::new().run(&config, "en");
+    assert_eq!(paths(&issues), ["/inbounds/0/listen_port"]);
+    assert_eq!(issues[0].message, "Port 1080 is used by multiple inbounds: a, inbound-1");
+}
+
+#[test]
+fn reports_dangling_tag_references() {
+    let config = config(json!({
+        "dns": {
+            "servers": [{ "type": "local", "tag": "local" }],
+            "rules": [{ "domain": "a.com", "server": "remote" }]
+        },
+        "endpoints": [{ "type": "wireguard", "tag": "wg" }],
+        "outbounds": [
+            { "type": "direct", "tag": "direct" },
+            { "type": "selector", "tag": "proxy", "outbounds": ["direct", "missing"] }
+        ],
+        "route": {
+            "rules": [
+                { "domain": "a.com", "outbound": "wg" },
+                { "domain": "b.com", "outbound": "gone" }
+            ],
+            "final": "nowhere"
+        }
+    }));
+    let issues = Preflight::new().run(&config, "en");
+    assert_eq!(
+        paths(&issues),
+        ["/route/rules/1/outbound", "/route/final", "/dns/rules/0/server", "/outbounds/1/outbounds"]
+    );
+    assert!(issues.iter().all(|issue| issue.level == IssueLevel::Error));
+}
+
+#[test]
+fn reports_missing_required_fields_in_chinese() {
+    let config = config(json!({
+        "inbounds": [{ "type": "trojan", "listen_port": 443 }],
+        "outbounds": [
+            { "type": "vless", "server": "a.com", "server_port": 443 },
+            { "type": "shadowsocks", "server": "a.com", "server_port": 8388 }
+        ]
+    }));
+    let issues = Preflight::new().run(&config, "zh");
+    assert_eq!(
+        paths(&issues),
+        ["/outbounds/0/uuid", "/outbounds/1/password", "/outbounds/1/method", "/inbounds/0/users"]
+    );
+    assert_eq!(issues[3].message, "Trojan 入站需要至少一个用户");
+}
+
+#[test]
+fn warns_about_tls_on_port_80() {
+    let config = config(json!({
+        "outbounds": [{
+            "type": "trojan", "server": "a.com", "server_port": 80, "password": "x",
+            "tls": { "enabled": true }
+        }]
+    }));
+    let issues = Preflight::new().run(&config, "en");
+    assert_eq!(paths(&issues), ["/outbounds/0/tls"]);
+    assert_eq!(issues[0].level, IssueLevel::Warning);
+}
+
+#[test]
+fn rules_can_be_disabled_and_extended() {
+    struct NoLog;
+
+    impl PreflightRule for NoLog {
+        fn id(&self) -> &'static str {
+            "no-log"
+        }
+
+        fn check(&self, ctx: &PreflightContext, issues: &mut Vec<PreflightIssue>) {
+            if ctx.config.log.is_none() {
+                issues.push(PreflightIssue {
+                    level: IssueLevel::Info,
+                    path: "/log".to_string(),
+                    message: "no log section".to_string(),
+                    fix: None,
+                });
+            }
+        }
+    }
+
+    let config = config(json!({
+        "inbounds": [
+            { "type": "mixed", "listen_port": 1080 },
+            { "type": "mixed", "listen_port": 1080 }
+        ]
+    }));
+    let mut engine = Preflight::new().with_rule(NoLog);
+    engine.disable("port-conflicts");
+    assert_eq!(
+        engine.rule_ids(),
+        ["port-conflicts", "tag-references", "required-fields", "tls-config", "no-log"]
+    );
+    assert_eq!(paths(&engine.run(&config, "en")), ["/log"]);
+
+    engine.enable("port-conflicts");
+    assert_eq!(engine.run(&config, "en").len(), 2);
+}
+
+#[test]
+fn lints_legacy_and_malformed_fields_per_field() {
+    let value = json!({
+        "dns": {
+            "servers": [{ "type": "local", "tag": "local" }],
+            "rules": [{ "domain": "a.com", "server": ["local", "remote"] }]
+        },
+        "inbounds": [
+            { "type": "mixed", "listen_port": "1080" },
+            { "type": "mixed", "tag": "a", "listen_port": 1080 },
+            { "type": "socks", "tag": "b", "listen_port": 1080 }
+        ],
+        "route": {
+            "rules": [
+                { "domain": 1, "outbound": "gone" },
+                { "domain": "b.com", "outbound": "lost" }
+            ]
+        }
+    });
+
+    // 旧版数组写法原样保留，路由到第一个
+    let (parsed, skipped) = Config::from_value_lenient(value.clone()).unwrap();
+    let rules = parsed.dns.as_ref().unwrap().rules.as_ref().unwrap();
+    assert_eq!(rules[0].server_tag(), Some("local"));
+    assert_eq!(
+        parsed.to_value().unwrap()["dns"]["rules"][0]["server"],
+        json!(["local", "remote"])
+    );
+    assert_eq!(
+        skipped.iter().map(|field| field.path.as_str()).collect::<Vec<_>>(),
+        ["/inbounds/0/listen_port", "/route/rules/0"]
+    );
+
+    let issues = Preflight::new().run_value(value, "en").unwrap();
+    assert_eq!(
+        paths(&issues),
+        [
+            "/inbounds/0/listen_port",
+            "/route/rules/0",
+            "/inbounds/1/listen_port",
+            "/route/rules/1/outbound",
+            "/dns/rules/0/server/1"
+        ]
+    );
+    assert!(issues[0].message.starts_with("Field could not be parsed and was skipped: "));
+    assert!(Preflight::new().run_value(json!([]), "en").is_err());
+}
@@ -0,0 +1,60 @@
+use sing_config_lib::recent::{RecentFiles, FILE_NAME, MAX_ENTRIES};
+
+#[test]
+fn adds_to_front_without_duplicates() {
+    let dir = tempfile::tempdir().unwrap();
+    let a = dir.path().join("a.json");
+    let b = dir.path().join("b.json");
+    std::fs::write(&a, "{}").unwrap();
+    std::fs::write(&b, "{}").unwrap();
+
+    let mut recent = RecentFiles::default();
+    recent.add(&a);
+    recent.add(&b);
+    recent.add(&a);
+    let a = std::fs::canonicalize(&a).unwrap();
+    let b = std::fs::canonicalize(&b).unwrap();
+    assert_eq!(recent.files(), &[a.clone(), b]);
+    assert_eq!(recent.get(0), Some(a.as_path()));
+    assert_eq!(recent.get(2), None);
+}
+
+#[test]
+fn keeps_at_most_max_entries() {
+    let mut recent = RecentFiles::default();
+    for i in 0..MAX_ENTRIES + 5 {
+        recent.add(format!("/nonexistent/{}.json", i));
+    }
+    assert_eq!(recent.files().len(), MAX_ENTRIES);
+    assert!(recent.files()[0].ends_with(format!("{}.json", MAX_ENTRIES + 4)));
+}
+
+#[test]
+fn prunes_missing_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let kept = dir.path().join("kept.json");
+    std::fs::write(&kept, "{}").unwrap();
+
+    let mut recent = RecentFiles::default();
+    recent.add(dir.path().join("gone.json"));
+    recent.add(&kept);
+    assert!(recent.prune());
+    assert_eq!(recent.files().len(), 1);
+    assert!(!recent.prune());
+}
+
+#[test]
+fn persists_and_clears() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested").join(FILE_NAME);
+    assert_eq!(RecentFiles::load(&path), RecentFiles::default());
+
+    let mut recent = RecentFiles::default();
+    recent.add("/nonexistent/config.json");
+    recent.save(&path).unwrap();
+    assert_eq!(RecentFiles::load(&path), recent);
+
+    recent.clear();
+    recent.save(&path).unwrap();
+    assert!(RecentFiles::load(&path).files().is_empty());
+}
@@ -0,0 +1,111 @@
+use std::fs;
+
+use sing_config_lib::save::{backup_path, save_config, write_atomic};
+
+const VALID: &str = r#"{ "log": { "level": "info" } }"#;
+
+#[test]
+fn rejects_invalid_config_without_touching_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    fs::write(&path, VALID).unwrap();
+
+    let error = save_config(&path, r#"{ "log": { "level": "loud" } }"#, 3).unwrap_err();
+    assert!(
+        error.starts_with("config is not saved: 1 validation error(s), first at /log/level"),
+        "{error}"
+    );
+    let error = save_config(&path, "{", 3).unwrap_err();
+    assert!(error.contains("first at /:"), "{error}");
+    assert_eq!(fs::read_to_string(&path).unwrap(), VALID);
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn rotates_backups() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+
+    // 新文件没有备份
+    let result = save_config(&path, r#"{ "log": {} }"#, 2).unwrap();
+    assert_eq!(result.backup, None);
+    for level in ["debug", "info", "warn"] {
+        let text = format!(r#"{{ "log": {{ "level": "{level}" }} }}"#);
+        let result = save_config(&path, &text, 2).unwrap();
+        assert_eq!(result.backup.as_deref(), backup_path(&path, 1).to_str());
+    }
+
+    assert!(fs::read_to_string(&path).unwrap().contains("warn"));
+    assert!(fs::read_to_string(backup_path(&path, 1))
+        .unwrap()
+        .contains("info"));
+    assert!(fs::read_to_string(backup_path(&path, 2))
+        .unwrap()
+        .contains("debug"));
+    assert!(!backup_path(&path, 3).exists());
+    // 没有遗留临时文件
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+
+    let result = write_atomic(&path, b"{}", 0).unwrap();
+    assert_eq!(result.backup, None);
+    assert!(fs::read_to_string(backup_path(&path, 1))
+        .unwrap()
+        .contains("info"));
+}
+
+#[cfg(unix)]
+#[test]
+fn keeps_mode_and_writes_through_symlink() {
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("real.json");
+    fs::write(&target, VALID).unwrap();
+    fs::set_permissions(&target, fs::Permissions::from_mode(0o640)).unwrap();
+    let link = dir.path().join("config.json");
+    symlink(&target, &link).unwrap();
+
+    let result = save_config(&link, r#"{ "log": { "disabled": true } }"#, 1).unwrap();
+    assert_eq!(
+        fs::canonicalize(&result.path).unwrap(),
+        fs::canonicalize(&target).unwrap()
+    );
+    assert!(result.warnings.is_empty());
+    assert!(fs::symlink_metadata(&link)
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert!(fs::read_to_string(&link).unwrap().contains("disabled"));
+    let mode = fs::metadata(&target).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+    assert_eq!(
+        fs::read_to_string(backup_path(&fs::canonicalize(&target).unwrap(), 1)).unwrap(),
+        VALID
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn keeps_private_mode_and_ignores_stale_temp_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    fs::write(&path, VALID).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+    // 预先放置的同名临时文件，权限宽松且有内容
+    let temp = dir
+        .path()
+        .join(format!(".config.json.{}.tmp", std::process::id()));
+    fs::write(&temp, "planted content that is longer than the config").unwrap();
+    fs::set_permissions(&temp, fs::Permissions::from_mode(0o666)).unwrap();
+
+    save_config(&path, r#"{ "log": { "disabled": true } }"#, 0).unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        r#"{ "log": { "disabled": true } }"#
+    );
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    assert!(!temp.exists());
+}
@@ -0,0 +1,34 @@
+use sing_config_lib::settings::{normalize_locale, AppSettings, FILE_NAME};
+
+#[test]
+fn normalizes_locale_codes() {
+    assert_eq!(normalize_locale("zh-CN"), "zh");
+    assert_eq!(normalize_locale("en_US.UTF-8"), "en");
+    assert_eq!(normalize_locale("ZH"), "zh");
+    assert_eq!(normalize_locale("xx"), "en");
+}
+
+#[test]
+fn persists_locale_and_keeps_unknown_fields() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested").join(FILE_NAME);
+    assert_eq!(AppSettings::load(&path), AppSettings::default());
+
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, r#"{ "theme": "dark" }"#).unwrap();
+    let mut settings = AppSettings::load(&path);
+    settings.locale = Some("en".to_string());
+    settings.save(&path).unwrap();
+
+    let loaded = AppSettings::load(&path);
+    assert_eq!(loaded.locale(), "en");
+    assert_eq!(loaded.extra["theme"], "dark");
+}
+
+#[test]
+fn falls_back_to_defaults_for_broken_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(FILE_NAME);
+    std::fs::write(&path, "{ not json").unwrap();
+    assert_eq!(AppSettings::load(&path).locale, None);
+}
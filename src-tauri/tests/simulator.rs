@@ -0,0 +1,445 @@
+use serde_json::{json, Value};
+use sing_config_lib::config::{Config, DnsAction, RouteAction};
+use sing_config_lib::simulator::dns::{self, AnswerSource, DnsDecision, DnsQuery};
+use sing_config_lib::simulator::route::{simulate, RouteDecision};
+use sing_config_lib::simulator::Connection;
+
+fn route(config: Value, connection: Value) -> RouteDecision {
+    let config = Config::from_value(config).unwrap();
+    let connection: Connection = serde_json::from_value(connection).unwrap();
+    simulate(&config, &connection, None)
+}
+
+fn matched(decision: &RouteDecision) -> Vec<usize> {
+    decision
+        .trace
+        .iter()
+        .filter(|trace| trace.matched)
+        .map(|trace| trace.index)
+        .collect()
+}
+
+#[test]
+fn routes_by_domain_and_falls_back_to_final() {
+    let config = json!({
+        "outbounds": [{ "type": "direct", "tag": "direct" }, { "type": "socks", "tag": "proxy" }],
+        "route": {
+            "rules": [
+                { "domain_suffix": "example.com", "port": 443, "outbound": "proxy" },
+                { "domain_keyword": "ads", "action": "reject", "method": "drop" }
+            ],
+            "final": "direct"
+        }
+    });
+
+    let decision = route(
+        config.clone(),
+        json!({ "domain": "WWW.Example.com", "port": 443 }),
+    );
+    assert_eq!(decision.action, RouteAction::Route);
+    assert_eq!(decision.outbound.as_deref(), Some("proxy"));
+    assert_eq!(decision.rule, Some(0));
+    assert_eq!(decision.trace.len(), 1);
+
+    // ".example.com" 之外的同名后缀不匹配，端口不符也不匹配
+    let decision = route(
+        config.clone(),
+        json!({ "domain": "badexample.com", "port": 443 }),
+    );
+    assert_eq!(decision.rule, None);
+    assert_eq!(decision.outbound.as_deref(), Some("direct"));
+    let decision = route(
+        config.clone(),
+        json!({ "domain": "example.com", "port": 80 }),
+    );
+    assert_eq!(decision.outbound.as_deref(), Some("direct"));
+
+    let decision = route(config, json!({ "domain": "ads.example.org" }));
+    assert_eq!(decision.action, RouteAction::Reject);
+    assert_eq!(decision.method.as_deref(), Some("drop"));
+    assert_eq!(decision.outbound, None);
+    assert_eq!(matched(&decision), [1]);
+
+    // 没有 final 时使用第一个出站
+    let decision = route(
+        json!({ "outbounds": [{ "type": "socks", "tag": "proxy" }] }),
+        json!({ "domain": "example.com" }),
+    );
+    assert_eq!(decision.outbound.as_deref(), Some("proxy"));
+}
+
+#[test]
+fn sniff_and_resolve_reveal_domain_and_ip() {
+    let config = json!({
+        "route": {
+            "rules": [
+                { "ip_cidr": "10.0.0.0/8", "outbound": "lan" },
+                { "action": "sniff", "sniffer": ["tls", "http"] },
+                { "protocol": "tls", "domain": "example.com", "outbound": "proxy" },
+                { "action": "resolve", "strategy": "ipv4_only" },
+                { "ip_is_private": true, "outbound": "lan" }
+            ],
+            "final": "direct"
+        }
+    });
+
+    // tun 入站收到 IP 连接，嗅探后得到域名
+    let decision = route(
+        config.clone(),
+        json!({ "ip": "93.184.216.34", "sniff_domain": true, "domain": "example.com", "protocol": "tls" }),
+    );
+    assert_eq!(decision.outbound.as_deref(), Some("proxy"));
+    assert_eq!(
+        decision.trace[1].note.as_deref(),
+        Some("sniffed tls, domain example.com")
+    );
+    assert_eq!(decision.protocol.as_deref(), Some("tls"));
+
+    // 域名目标在 resolve 之前没有 IP，第一条 ip_cidr 规则不匹配
+    let decision = route(
+        config.clone(),
+        json!({ "domain": "nas.lan", "ip": "10.0.0.2", "protocol": "quic" }),
+    );
+    assert_eq!(matched(&decision), [1, 3, 4]);
+    assert_eq!(
+        decision.trace[1].note.as_deref(),
+        Some("quic is not enabled in sniffer")
+    );
+    assert_eq!(
+        decision.trace[3].note.as_deref(),
+        Some("resolved nas.lan to 10.0.0.2")
+    );
+    assert_eq!(decision.outbound.as_deref(), Some("lan"));
+    assert_eq!(
+        decision.addresses,
+        ["10.0.0.2".parse::<std::net::IpAddr>().unwrap()]
+    );
+
+    let decision = route(config, json!({ "domain": "nas.lan", "ip": "fd00::2" }));
+    assert_eq!(
+        decision.trace[3].note.as_deref(),
+        Some("fd00::2 is excluded by strategy ipv4_only")
+    );
+    assert_eq!(decision.outbound.as_deref(), Some("direct"));
+}
+
+#[test]
+fn evaluates_logical_rules() {
+    let config = json!({
+        "route": {
+            "rules": [
+                {
+                    "type": "logical",
+                    "mode": "and",
+                    "rules": [
+                        { "network": "udp" },
+                        { "port": 443 },
+                        { "domain_suffix": "google.com", "invert": true }
+                    ],
+                    "action": "reject"
+                },
+                {
+                    "type": "logical",
+                    "mode": "or",
+                    "rules": [{ "process_name": "curl" }, { "port_range": "8000:9000" }],
+                    "invert": true,
+                    "outbound": "direct"
+                }
+            ],
+            "final": "proxy"
+        }
+    });
+
+    let decision = route(
+        config.clone(),
+        json!({ "network": "udp", "port": 443, "domain": "example.com" }),
+    );
+    assert_eq!(decision.action, RouteAction::Reject);
+
+    let decision = route(
+        config.clone(),
+        json!({ "network": "udp", "port": 443, "domain": "www.google.com" }),
+    );
+    assert_eq!(decision.outbound.as_deref(), Some("direct"));
+
+    let decision = route(config.clone(), json!({ "network": "tcp", "port": 8080 }));
+    assert_eq!(decision.outbound.as_deref(), Some("proxy"));
+    let decision = route(config, json!({ "process_name": "curl" }));
+    assert_eq!(decision.outbound.as_deref(), Some("proxy"));
+}
+
+#[test]
+fn merges_rule_set_items_with_rule() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("ads.json"),
+        json!({ "version": 3, "rules": [{ "domain_regex": "^ads?\\." }] }).to_string(),
+    )
+    .unwrap();
+    let config = Config::from_value(json!({
+        "route": {
+            "rules": [
+                { "action": "resolve" },
+                { "rule_set": "ads", "action": "reject" },
+                { "domain_suffix": "example.com", "rule_set": "cn-ip", "outbound": "cn" },
+                { "rule_set": "geoip-us", "outbound": "us" },
+                { "source_ip_cidr": "192.168.1.0/24", "rule_set": "cn-ip", "rule_set_ip_cidr_match_source": true, "outbound": "lan" }
+            ],
+            "rule_set": [
+                { "type": "local", "tag": "ads", "format": "source", "path": "ads.json" },
+                { "type": "inline", "tag": "cn-ip", "rules": [{ "ip_cidr": ["1.0.1.0/24", "192.168.0.0/16"] }] },
+                { "type": "remote", "tag": "geoip-us", "url": "https://example.com/geoip-us.srs" }
+            ],
+            "final": "proxy"
+        }
+    }))
+    .unwrap();
+    let simulate = |connection: Value| {
+        let connection: Connection = serde_json::from_value(connection).unwrap();
+        simulate(&config, &connection, Some(dir.path()))
+    };
+
+    assert_eq!(
+        simulate(json!({ "domain": "ad.example.org" })).action,
+        RouteAction::Reject
+    );
+    // 规则中的 domain_suffix 与规则集中的 ip_cidr 属于同一组，任一匹配即可
+    for connection in [
+        json!({ "domain": "www.example.com", "ip": "8.8.8.8" }),
+        json!({ "domain": "www.example.net", "ip": "1.0.1.1" }),
+    ] {
+        assert_eq!(simulate(connection).outbound.as_deref(), Some("cn"));
+    }
+
+    let decision = simulate(
+        json!({ "domain": "www.example.net", "ip": "8.8.8.8", "source_ip": "192.168.1.10" }),
+    );
+    assert_eq!(decision.outbound.as_deref(), Some("lan"));
+    assert_eq!(
+        decision.warnings,
+        ["rule-set geoip-us is treated as not matching: remote rule-sets are not downloaded"]
+    );
+}
+
+#[test]
+fn applies_route_options_and_legacy_sniff() {
+    let config = json!({
+        "inbounds": [{ "type": "mixed", "tag": "mixed-in", "sniff": true }],
+        "route": {
+            "rules": [
+                { "port": 53, "action": "route-options", "override_address": "1.1.1.1" },
+                { "protocol": "dns", "action": "hijack-dns" },
+                { "geosite": "cn", "outbound": "direct" }
+            ]
+        }
+    });
+
+    let decision = route(
+        config.clone(),
+        json!({ "inbound": "mixed-in", "ip": "8.8.8.8", "port": 53, "protocol": "dns" }),
+    );
+    assert_eq!(decision.action, RouteAction::HijackDns);
+    assert_eq!(decision.rule, Some(1));
+    assert_eq!(decision.override_address.as_deref(), Some("1.1.1.1"));
+    assert_eq!(
+        decision.warnings,
+        ["inbound sniff option is deprecated, sniffed dns before routing"]
+    );
+
+    // 没有嗅探时协议未知；geosite 已移除，视为不匹配；没有出站时使用 direct
+    let decision = route(
+        config,
+        json!({ "ip": "8.8.8.8", "port": 53, "protocol": "dns" }),
+    );
+    assert_eq!(decision.rule, None);
+    assert_eq!(decision.outbound.as_deref(), Some("direct"));
+    assert_eq!(
+        decision.warnings,
+        ["geosite is removed in sing-box 1.12 and is treated as not matching"]
+    );
+}
+
+fn resolve(config: Value, query: Value) -> DnsDecision {
+    let config = Config::from_value(config).unwrap();
+    let query: DnsQuery = serde_json::from_value(query).unwrap();
+    dns::simulate(&config, &query, None)
+}
+
+#[test]
+fn selects_dns_server_by_rules_and_final() {
+    let config = json!({
+        "dns": {
+            "servers": [
+                { "type": "local", "tag": "local" },
+                { "type": "tls", "tag": "google", "server": "8.8.8.8", "detour": "proxy" },
+                { "type": "fakeip", "tag": "fakeip", "inet4_range": "198.18.0.0/15" }
+            ],
+            "rules": [
+                { "domain_suffix": "lan", "action": "route-options", "disable_cache": true },
+                { "domain_suffix": "lan", "server": "local" },
+                { "domain_keyword": "ads", "action": "reject" },
+                { "domain": "router.test", "action": "predefined", "answer": "router.test. IN A 192.168.1.1" },
+                { "query_type": ["A", "AAAA"], "domain_suffix": "example.com", "server": "fakeip" }
+            ],
+            "final": "google"
+        }
+    });
+
+    let decision = resolve(config.clone(), json!({ "domain": "nas.lan." }));
+    assert_eq!(decision.action, DnsAction::Route);
+    assert_eq!(decision.rule, Some(1));
+    assert_eq!(decision.server.unwrap().tag, "local");
+    assert_eq!(decision.disable_cache, Some(true));
+
+    let decision = resolve(config.clone(), json!({ "domain": "ads.example.org" }));
+    assert_eq!(decision.action, DnsAction::Reject);
+    assert_eq!(decision.source, AnswerSource::Rejected);
+    assert_eq!(decision.rcode.as_deref(), Some("REFUSED"));
+
+    let decision = resolve(config.clone(), json!({ "domain": "router.test" }));
+    assert_eq!(decision.action, DnsAction::Predefined);
+    assert_eq!(decision.rcode.as_deref(), Some("NOERROR"));
+    assert_eq!(decision.answers, ["router.test. IN A 192.168.1.1"]);
+
+    let decision = resolve(config.clone(), json!({ "domain": "www.example.com" }));
+    assert_eq!(decision.source, AnswerSource::FakeIp);
+    assert_eq!(decision.answers, ["198.18.0.1"]);
+    assert_eq!(decision.fakeip_range.as_deref(), Some("198.18.0.0/15"));
+    // 没有 inet6_range 时 AAAA 查询得到空应答
+    let decision = resolve(
+        config.clone(),
+        json!({ "domain": "www.example.com", "query_type": "AAAA" }),
+    );
+    assert!(decision.answers.is_empty());
+    assert_eq!(
+        decision.warnings,
+        ["fakeip server fakeip has no inet6_range"]
+    );
+
+    // MX 查询不匹配 query_type，使用 dns.final
+    let decision = resolve(
+        config,
+        json!({ "domain": "www.example.com", "query_type": "MX", "answers": ["1.1.1.1"] }),
+    );
+    assert_eq!(decision.rule, None);
+    let server = decision.server.unwrap();
+    assert_eq!(server.tag, "google");
+    assert_eq!(server.kind, "tls");
+    assert_eq!(server.address.as_deref(), Some("8.8.8.8"));
+    assert_eq!(server.detour.as_deref(), Some("proxy"));
+    assert_eq!(decision.answers, ["1.1.1.1"]);
+}
+
+#[test]
+fn rechecks_address_filter_with_response() {
+    let config = json!({
+        "dns": {
+            "servers": [
+                { "type": "udp", "tag": "domestic", "server": "223.5.5.5" },
+                { "type": "https", "tag": "remote", "server": "1.1.1.1" }
+            ],
+            "rules": [
+                { "rule_set": "cn-ip", "server": "domestic" },
+                { "ip_accept_any": true, "server": "domestic", "strategy": "ipv4_only" }
+            ],
+            "final": "remote"
+        },
+        "route": {
+            "rule_set": [
+                { "type": "inline", "tag": "cn-ip", "rules": [{ "ip_cidr": "1.0.1.0/24" }] }
+            ]
+        }
+    });
+
+    let decision = resolve(
+        config.clone(),
+        json!({ "domain": "www.example.cn", "answers": ["1.0.1.1"] }),
+    );
+    assert_eq!(decision.rule, Some(0));
+    assert_eq!(decision.server.unwrap().tag, "domestic");
+
+    // 应答不在 cn-ip 中，跳过第一条规则；第二条接受任意非空应答
+    let decision = resolve(
+        config.clone(),
+        json!({ "domain": "www.example.com", "answers": ["93.184.216.34"] }),
+    );
+    assert_eq!(
+        decision.trace[0].note.as_deref(),
+        Some("response [93.184.216.34] from domestic does not match the address filter")
+    );
+    assert_eq!(decision.rule, Some(1));
+    assert_eq!(decision.strategy.as_deref(), Some("ipv4_only"));
+
+    // ipv4_only 下 AAAA 查询没有应答，两条规则都跳过，使用 dns.final
+    let decision = resolve(
+        config,
+        json!({ "domain": "www.example.com", "query_type": "AAAA", "answers": ["2606:2800::1"] }),
+    );
+    assert_eq!(decision.rule, None);
+    assert_eq!(decision.server.unwrap().tag, "remote");
+    assert_eq!(decision.answers, ["2606:2800::1"]);
+}
+
+#[test]
+fn answers_from_hosts_and_legacy_servers() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("hosts"),
+        "# local names\n10.0.0.5 nas.home nas\n",
+    )
+    .unwrap();
+    let config = Config::from_value(json!({
+        "dns": {
+            "servers": [
+                { "tag": "remote", "address": "tls://8.8.8.8", "strategy": "prefer_ipv4" },
+                { "tag": "block", "address": "rcode://refused" },
+                { "tag": "fakeip", "address": "fakeip" },
+                {
+                    "type": "hosts",
+                    "tag": "hosts",
+                    "path": "hosts",
+                    "predefined": { "router.home": ["192.168.1.1", "fd00::1"] }
+                }
+            ],
+            "rules": [
+                { "domain_suffix": "home", "server": "hosts" },
+                { "geosite": "category-ads", "server": "block" },
+                { "domain_keyword": "block", "server": "block" },
+                { "outbound": "any", "server": "fakeip" }
+            ],
+            "fakeip": { "enabled": true, "inet4_range": "198.18.0.0/15", "inet6_range": "fc00::/18" }
+        }
+    }))
+    .unwrap();
+    let resolve = |query: Value| {
+        let query: DnsQuery = serde_json::from_value(query).unwrap();
+        dns::simulate(&config, &query, Some(dir.path()))
+    };
+
+    let decision = resolve(json!({ "domain": "router.home", "query_type": "AAAA" }));
+    assert_eq!(decision.source, AnswerSource::Hosts);
+    assert_eq!(decision.answers, ["fd00::1"]);
+    assert_eq!(
+        resolve(json!({ "domain": "NAS.home" })).answers,
+        ["10.0.0.5"]
+    );
+
+    let decision = resolve(json!({ "domain": "block.example.com" }));
+    assert_eq!(decision.server.unwrap().kind, "rcode");
+    assert_eq!(decision.rcode.as_deref(), Some("refused"));
+    assert_eq!(
+        decision.warnings,
+        ["geosite is removed in sing-box 1.12 and is treated as not matching"]
+    );
+
+    // 旧版 outbound 规则项匹配发起查询的出站，地址范围取自 dns.fakeip
+    let decision =
+        resolve(json!({ "domain": "example.com", "query_type": 28, "outbound": "proxy" }));
+    assert_eq!(decision.source, AnswerSource::FakeIp);
+    assert_eq!(decision.answers, ["fc00::1"]);
+
+    // 没有规则命中且没有 dns.final 时使用第一个服务器及其策略
+    let decision = resolve(json!({ "domain": "example.com" }));
+    assert_eq!(decision.server.unwrap().kind, "tls");
+    assert_eq!(decision.strategy.as_deref(), Some("prefer_ipv4"));
+}
@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+use sing_config_lib::preflight::IssueLevel;
+use sing_config_lib::singbox::{
+    check, locate, parse_check_output, parse_version_output, CoreInfo, BINARY_NAME,
+};
+
+/// 写一个代替 sing-box 的 shell 脚本，`check` 把 check.stderr 中的 CONFIG 替换为配置文件路径后输出
+#[cfg(unix)]
+fn stub_core(dir: &Path, check_stderr: &str, check_status: i32) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join(BINARY_NAME);
+    let script = format!(
+        r#"#!/bin/sh
+if [ "$1" = "version" ]; then
+  echo "sing-box version 1.12.0"
+  echo
+  echo "Environment: go1.24.2 linux/amd64"
+  echo "Tags: with_gvisor,with_quic"
+  exit 0
+fi
+if [ "$1" = "check" ]; then
+  for arg; do config="$arg"; done
+  grep -q '"outbounds"' "$config" || exit 3
+  sed "s|CONFIG|$config|g" "$(dirname "$0")/check.stderr" >&2
+  exit {}
+fi
+exit 1
+"#,
+        check_status
+    );
+    std::fs::write(dir.join("check.stderr"), check_stderr).unwrap();
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn parses_version_output() {
+    let text = "sing-box version 1.11.4\n\nEnvironment: go1.23.1 darwin/arm64\nTags: with_gvisor,with_quic,with_utls\nRevision: abc\nCGO: disabled\n";
+    let (version, tags) = parse_version_output(text).unwrap();
+    assert_eq!(version, "1.11.4");
+    assert_eq!(tags, ["with_gvisor", "with_quic", "with_utls"]);
+    assert!(parse_version_output("clash 1.0").is_none());
+}
+
+#[test]
+fn anchors_diagnostics_to_config_paths() {
+    let stderr = "\
+FATAL[0000] decode config at config.json: outbounds[1].tls.server_name: json: cannot unmarshal number into Go value of type string
+FATAL[0000] create service: initialize outbound[2]: missing server address
++0800 2025-01-01 00:00:00 ERROR initialize DNS rule[0]: server not found: local
+\x1b[33mWARN\x1b[0m[0000] legacy special outbounds is deprecated in sing-box 1.11.0, checkout documentation for migration: https://sing-box.sagernet.org/migration/
+initialize rule[3]: rule-set not found: geoip-cn
+";
+    let issues = parse_check_output(stderr);
+    assert_eq!(issues.len(), 5);
+    assert_eq!(issues[0].path, "/outbounds/1/tls/server_name");
+    assert_eq!(issues[0].level, IssueLevel::Error);
+    assert!(issues[0].message.starts_with("decode config at config.json"));
+    assert_eq!(issues[1].path, "/outbounds/2");
+    assert_eq!(issues[2].path, "/dns/rules/0");
+    assert_eq!(issues[3].level, IssueLevel::Warning);
+    assert_eq!(issues[3].path, "");
+    assert_eq!(issues[4].path, "/route/rules/3");
+}
+
+#[cfg(unix)]
+#[test]
+fn locates_core_in_order() {
+    let configured = tempfile::tempdir().unwrap();
+    let on_path = tempfile::tempdir().unwrap();
+    let data = tempfile::tempdir().unwrap();
+    let empty = tempfile::tempdir().unwrap();
+    for dir in [&configured, &on_path, &data] {
+        stub_core(dir.path(), "", 0);
+    }
+    let path_var = std::env::join_paths([empty.path(), on_path.path()]).unwrap();
+
+    let found = locate(Some(configured.path()), Some(&path_var), Some(data.path()));
+    assert_eq!(found.unwrap(), configured.path().join(BINARY_NAME));
+    let found = locate(None, Some(&path_var), Some(data.path()));
+    assert_eq!(found.unwrap(), on_path.path().join(BINARY_NAME));
+    let found = locate(Some(&empty.path().join(BINARY_NAME)), None, Some(data.path()));
+    assert_eq!(found.unwrap(), data.path().join(BINARY_NAME));
+    assert!(locate(None, None, Some(empty.path())).is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn detects_version_of_stub_core() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary = stub_core(dir.path(), "", 0);
+    let info = CoreInfo::detect(&binary).unwrap();
+    assert_eq!(info.version, "1.12.0");
+    assert_eq!(info.tags, ["with_gvisor", "with_quic"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_check_with_stub_core() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = json!({ "outbounds": [{ "type": "direct", "tag": "direct" }] });
+
+    let binary = stub_core(dir.path(), "", 0);
+    let result = check(&binary, &config, Some(dir.path()), "config.json").unwrap();
+    assert!(result.valid);
+    assert!(result.diagnostics.is_empty());
+
+    let binary = stub_core(
+        dir.path(),
+        "FATAL[0000] decode config at CONFIG: outbounds[0]: unknown field\n",
+        1,
+    );
+    let result = check(&binary, &config, None, "my.json").unwrap();
+    assert!(!result.valid);
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].path, "/outbounds/0");
+    assert_eq!(
+        result.diagnostics[0].message,
+        "decode config at my.json: outbounds[0]: unknown field"
+    );
+
+    // 没有输出但退出码非 0
+    let result = check(&binary, &json!({}), None, "config.json").unwrap();
+    assert!(!result.valid);
+    assert_eq!(result.diagnostics[0].level, IssueLevel::Error);
+}
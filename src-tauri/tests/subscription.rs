@@ -0,0 +1,253 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::json;
+use sing_config_lib::config::{Config, OutboundType};
+use sing_config_lib::subscription::{
+    parse_content, GroupKind, Scheduler, Subscription, Subscriptions,
+};
+
+/// 启动本地订阅服务器，按路径返回预设内容
+fn fixture_server(routes: Vec<(&'static str, u16, String)>) -> String {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", server.server_addr().to_ip().unwrap());
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let response = match routes.iter().find(|(path, _, _)| *path == request.url()) {
+                Some((_, status, body)) => {
+                    tiny_http::Response::from_string(body.clone()).with_status_code(*status)
+                }
+                None => tiny_http::Response::from_string("not found").with_status_code(404),
+            };
+            let _ = request.respond(response);
+        }
+    });
+    base
+}
+
+const CLASH_YAML: &str = r#"
+proxies:
+  - name: "jp 01"
+    type: vmess
+    server: jp.example.com
+    port: 443
+    uuid: b831381d-6324-4d53-ad4f-8cda48b30811
+    alterId: 0
+    cipher: auto
+    tls: true
+    servername: jp.example.com
+    network: ws
+    ws-opts:
+      path: /ray
+      headers:
+        Host: cdn.example.com
+  - name: "jp 02"
+    type: hysteria2
+    server: jp2.example.com
+    port: 443
+    ports: 20000-30000
+    password: pw
+    obfs: salamander
+    obfs-password: cry
+  - name: "wg"
+    type: wireguard
+    server: wg.example.com
+    port: 51820
+proxy-groups:
+  - name: auto
+    type: url-test
+    proxies: ["jp 01", "jp 02"]
+"#;
+
+fn sing_box_json() -> String {
+    json!({
+        "outbounds": [
+            { "type": "selector", "tag": "proxy", "outbounds": ["us 01"] },
+            { "type": "trojan", "tag": "us 01", "server": "us.example.com", "server_port": 443, "password": "pw" },
+            { "type": "direct", "tag": "direct" }
+        ]
+    })
+    .to_string()
+}
+
+#[test]
+fn parses_each_subscription_format() {
+    let links = STANDARD.encode("trojan://pw@a.com:443#a\nfoo://bar\nss://YWVzLTI1Ni1nY206cw@b.com:1#b\n");
+    let parsed = parse_content(&links).unwrap();
+    assert_eq!(parsed.outbounds.len(), 2);
+    assert_eq!(parsed.skipped, ["line 2: unsupported scheme: foo"]);
+
+    let parsed = parse_content(&sing_box_json()).unwrap();
+    let tags: Vec<_> = parsed.outbounds.iter().map(|o| o.tag.as_deref().unwrap()).collect();
+    assert_eq!(tags, ["us 01"]);
+
+    let parsed = parse_content(CLASH_YAML).unwrap();
+    assert_eq!(parsed.outbounds.len(), 2);
+    assert_eq!(parsed.skipped, ["proxies[2]: proxy type wireguard is not supported"]);
+    let value = serde_json::to_value(&parsed.outbounds[1]).unwrap();
+    assert_eq!(value["server_ports"], "20000:30000");
+    assert_eq!(value["obfs"], json!({ "type": "salamander", "password": "cry" }));
+    let value = serde_json::to_value(&parsed.outbounds[0]).unwrap();
+    assert_eq!(
+        value["transport"],
+        json!({ "type": "ws", "path": "/ray", "headers": { "Host": "cdn.example.com" } })
+    );
+
+    assert!(parse_content("   ").is_err());
+    assert!(parse_content("just some text").is_err());
+}
+
+#[test]
+fn refreshes_and_merges_into_managed_group() {
+    let base = fixture_server(vec![
+        ("/links", 200, STANDARD.encode("trojan://pw@a.com:443#hk\nhy2://pw@b.com:443#hk\n")),
+        ("/clash", 200, CLASH_YAML.to_string()),
+        ("/gone", 500, "boom".to_string()),
+    ]);
+    let mut store = Subscriptions::default();
+    store.upsert(Subscription::new("links", format!("{base}/links"))).unwrap();
+    let mut clash = Subscription::new("clash", format!("{base}/clash"));
+    clash.prefix = Some("C/".to_string());
+    store.upsert(clash).unwrap();
+    store.upsert(Subscription::new("gone", format!("{base}/gone"))).unwrap();
+    assert!(store.upsert(Subscription::new("bad", "ftp://x")).is_err());
+
+    let results = store.refresh();
+    assert_eq!(results[0].count, 2);
+    assert_eq!(results[1].count, 2);
+    assert_eq!(results[1].skipped.len(), 1);
+    assert!(results[2].error.as_deref().unwrap().contains("500"));
+    assert!(store.get("gone").unwrap().error.is_some());
+    assert!(store.get("links").unwrap().updated_at.is_some());
+
+    let mut config: Config = serde_json::from_value(json!({
+        "outbounds": [
+            { "type": "direct", "tag": "direct" },
+            { "type": "selector", "tag": "subscriptions", "outbounds": ["[links] old"] },
+            { "type": "trojan", "tag": "[links] old", "server": "old.com", "server_port": 1, "password": "x" },
+            { "type": "trojan", "tag": "mine", "server": "m.com", "server_port": 1, "password": "x" }
+        ]
+    }))
+    .unwrap();
+    store.apply(&mut config);
+    let tags: Vec<_> = config.outbounds().iter().map(|o| o.tag.as_deref().unwrap()).collect();
+    assert_eq!(
+        tags,
+        ["direct", "subscriptions", "mine", "[links] hk", "[links] hk-2", "C/jp 01", "C/jp 02"]
+    );
+    assert_eq!(
+        config.outbound("subscriptions").unwrap().members(),
+        ["[links] hk", "[links] hk-2", "C/jp 01", "C/jp 02"]
+    );
+
+    // 重复合并结果不变；切换为 urltest 后分组原位重新生成
+    let before = config.clone();
+    store.apply(&mut config);
+    assert_eq!(config, before);
+    store.group.kind = GroupKind::UrlTest;
+    store.group.interval = Some("5m".to_string());
+    store.subscriptions[1].enabled = false;
+    store.apply(&mut config);
+    let group = config.outbound("subscriptions").unwrap();
+    assert_eq!(group.kind, OutboundType::UrlTest);
+    assert_eq!(group.interval.as_deref(), Some("5m"));
+    assert_eq!(group.members(), ["[links] hk", "[links] hk-2"]);
+    assert!(config.outbound("C/jp 01").is_none());
+
+    // 没有节点时移除分组
+    store.subscriptions.clear();
+    store.apply(&mut config);
+    let tags: Vec<_> = config.outbounds().iter().map(|o| o.tag.as_deref().unwrap()).collect();
+    assert_eq!(tags, ["direct", "mine"]);
+}
+
+#[test]
+fn keeps_user_outbounds_in_managed_group() {
+    let mut store = Subscriptions::default();
+    let mut sub = Subscription::new("a", "https://example.com/sub");
+    sub.outbounds = serde_json::from_value(json!([
+        { "type": "trojan", "tag": "[a] hk", "server": "a.com", "server_port": 443, "password": "x" }
+    ]))
+    .unwrap();
+    store.upsert(sub).unwrap();
+
+    let mut config: Config = serde_json::from_value(json!({
+        "outbounds": [
+            { "type": "selector", "tag": "subscriptions", "outbounds": ["direct", "[a] old", "mine"] },
+            { "type": "direct", "tag": "direct" },
+            { "type": "trojan", "tag": "mine", "server": "m.com", "server_port": 1, "password": "x" }
+        ]
+    }))
+    .unwrap();
+    store.apply(&mut config);
+    let tags: Vec<_> = config.outbounds().iter().map(|o| o.tag.as_deref().unwrap()).collect();
+    assert_eq!(tags, ["subscriptions", "direct", "mine", "[a] hk"]);
+    assert_eq!(config.outbound("subscriptions").unwrap().members(), ["[a] hk", "direct", "mine"]);
+    assert_eq!(store.applied, ["[a] hk"]);
+
+    // 删除订阅后按记录移除它的节点，用户的出站保留在分组中
+    store.remove("a");
+    store.apply(&mut config);
+    let tags: Vec<_> = config.outbounds().iter().map(|o| o.tag.as_deref().unwrap()).collect();
+    assert_eq!(tags, ["subscriptions", "direct", "mine"]);
+    assert_eq!(config.outbound("subscriptions").unwrap().members(), ["direct", "mine"]);
+    assert!(store.applied.is_empty());
+}
+
+#[test]
+fn saves_and_reports_due_subscriptions() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested").join("subscriptions.json");
+    assert_eq!(Subscriptions::load(&path), Subscriptions::default());
+
+    let mut store = Subscriptions::default();
+    let mut sub = Subscription::new("a", "https://example.com/sub");
+    sub.updated_at = Some(1_000);
+    store.upsert(sub).unwrap();
+    store.interval_minutes = 10;
+    assert!(!store.is_due(1_000 + 599));
+    assert!(store.is_due(1_000 + 600));
+    store.interval_minutes = 0;
+    assert!(!store.is_due(u64::MAX));
+
+    store.save(&path).unwrap();
+    assert_eq!(Subscriptions::load(&path), store);
+
+    // 刷新期间列表被修改：删除的订阅不会被写回，改了地址的保留当前设置
+    let mut snapshot = store.clone();
+    snapshot.subscriptions[0].updated_at = Some(2_000);
+    snapshot.subscriptions[0].error = Some("timeout".to_string());
+    let mut stale = snapshot.subscriptions[0].clone();
+    stale.name = "removed".to_string();
+    snapshot.subscriptions.push(stale);
+    let mut current = store.clone();
+    current.merge_refreshed(snapshot.subscriptions.clone());
+    assert_eq!(current.subscriptions.len(), 1);
+    assert_eq!(current.subscriptions[0].updated_at, Some(2_000));
+    assert_eq!(current.subscriptions[0].error.as_deref(), Some("timeout"));
+    current.subscriptions[0].url = "https://example.com/new".to_string();
+    current.subscriptions[0].updated_at = None;
+    current.merge_refreshed(snapshot.subscriptions);
+    assert_eq!(current.subscriptions[0].updated_at, None);
+    assert!(store.remove("a"));
+    assert!(!store.remove("a"));
+}
+
+#[test]
+fn scheduler_ticks_until_stopped() {
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let counter = ticks.clone();
+    let mut scheduler = Scheduler::start(Duration::from_millis(20), move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    thread::sleep(Duration::from_millis(150));
+    scheduler.stop();
+    let count = ticks.load(Ordering::SeqCst);
+    assert!(count >= 2, "ticked {count} times");
+    thread::sleep(Duration::from_millis(60));
+    assert_eq!(ticks.load(Ordering::SeqCst), count);
+}
@@ -0,0 +1,150 @@
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use sing_config_lib::singbox::supervisor::{
+    Backoff, CoreStatus, EventSink, Launch, LogStream, Supervisor, SupervisorEvent,
+};
+
+/// 写一个代替 `sing-box run` 的 shell 脚本
+fn stub_core(dir: &Path, body: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join("sing-box");
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+fn launch(binary: PathBuf) -> Launch {
+    Launch {
+        binary,
+        config: PathBuf::from("config.json"),
+        work_dir: None,
+    }
+}
+
+fn recorder() -> (EventSink, Arc<Mutex<Vec<SupervisorEvent>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink_events = events.clone();
+    let sink: EventSink = Arc::new(move |event| sink_events.lock().unwrap().push(event));
+    (sink, events)
+}
+
+fn wait_until(mut condition: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() {
+        assert!(Instant::now() < deadline, "timed out");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn fast_backoff(max_retries: u32) -> Backoff {
+    Backoff {
+        initial: Duration::from_millis(10),
+        max: Duration::from_millis(40),
+        max_retries,
+        reset_after: Duration::from_secs(60),
+    }
+}
+
+#[test]
+fn backoff_doubles_up_to_max() {
+    let backoff = Backoff::default();
+    assert_eq!(backoff.delay(1), Duration::from_secs(1));
+    assert_eq!(backoff.delay(3), Duration::from_secs(4));
+    assert_eq!(backoff.delay(10), Duration::from_secs(30));
+}
+
+#[test]
+fn streams_logs_and_stops() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary = stub_core(
+        dir.path(),
+        r#"echo "started $1 $4"; echo "warning" >&2; exec sleep 30"#,
+    );
+    let supervisor = Supervisor::new(fast_backoff(3));
+    let (sink, events) = recorder();
+    supervisor.start(launch(binary), sink).unwrap();
+    assert!(matches!(supervisor.status(), CoreStatus::Running { .. }));
+
+    wait_until(|| events.lock().unwrap().len() >= 3);
+    let logs: Vec<_> = events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|event| match event {
+            SupervisorEvent::Log(line) => Some(line.clone()),
+            _ => None,
+        })
+        .collect();
+    assert!(logs
+        .iter()
+        .any(|l| l.stream == LogStream::Stdout && l.line == "started run config.json"));
+    assert!(logs
+        .iter()
+        .any(|l| l.stream == LogStream::Stderr && l.line == "warning"));
+
+    supervisor.stop();
+    assert_eq!(supervisor.status(), CoreStatus::Stopped);
+    assert_eq!(
+        events.lock().unwrap().last(),
+        Some(&SupervisorEvent::Status(CoreStatus::Stopped))
+    );
+}
+
+#[test]
+fn restarts_crashed_core_until_retries_run_out() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary = stub_core(dir.path(), "exit 1");
+    let supervisor = Supervisor::new(fast_backoff(2));
+    let (sink, events) = recorder();
+    supervisor.start(launch(binary), sink).unwrap();
+
+    wait_until(|| matches!(supervisor.status(), CoreStatus::Failed { .. }));
+    let attempts: Vec<_> = events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|event| match event {
+            SupervisorEvent::Status(CoreStatus::Restarting { attempt, .. }) => Some(*attempt),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(attempts, [1, 2]);
+}
+
+#[test]
+fn restart_reuses_last_launch() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary = stub_core(dir.path(), "exec sleep 30");
+    let supervisor = Supervisor::new(fast_backoff(0));
+    assert!(supervisor.restart().is_err());
+
+    let (sink, _events) = recorder();
+    supervisor.start(launch(binary.clone()), sink).unwrap();
+    let CoreStatus::Running { pid: first } = supervisor.status() else {
+        panic!("not running");
+    };
+    supervisor.restart().unwrap();
+    let CoreStatus::Running { pid: second } = supervisor.status() else {
+        panic!("not running");
+    };
+    assert_ne!(first, second);
+    assert_eq!(supervisor.launch().unwrap().binary, binary);
+    supervisor.stop();
+}
+
+#[test]
+fn reports_missing_binary() {
+    let dir = tempfile::tempdir().unwrap();
+    let supervisor = Supervisor::default();
+    let (sink, events) = recorder();
+    assert!(supervisor
+        .start(launch(dir.path().join("missing")), sink)
+        .is_err());
+    assert!(matches!(supervisor.status(), CoreStatus::Failed { .. }));
+    assert_eq!(events.lock().unwrap().len(), 1);
+}
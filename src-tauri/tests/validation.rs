@@ -0,0 +1,46 @@
+use serde_json::json;
+use sing_config_lib::validation::{validate, validate_text};
+
+#[test]
+fn minimal_config_is_valid() {
+    let result = validate(&json!({
+        "inbounds": [{ "type": "mixed", "tag": "mixed-in", "listen_port": 2080 }],
+        "outbounds": [{ "type": "direct", "tag": "direct", "connect_timeout": "5s" }]
+    }));
+    assert!(result.valid, "{:?}", result.errors);
+}
+
+#[test]
+fn errors_point_at_the_offending_field() {
+    let result = validate(&json!({
+        "inbounds": [{ "type": "mixed", "listen_port": "2080" }],
+        "unknown_section": {}
+    }));
+    assert!(!result.valid);
+    let paths: Vec<&str> = result.errors.iter().map(|error| error.path.as_str()).collect();
+    assert!(paths.contains(&"/inbounds/0/listen_port"), "{paths:?}");
+    assert!(paths.contains(&""), "{paths:?}");
+
+    // 前端按 kind 和 params 本地化
+    let port = result
+        .errors
+        .iter()
+        .find(|error| error.path == "/inbounds/0/listen_port")
+        .unwrap();
+    assert_eq!(port.kind, "type");
+    assert_eq!(port.params["expected"], "integer");
+    assert_eq!(port.params["received"], "string");
+    let root = result.errors.iter().find(|error| error.path.is_empty()).unwrap();
+    assert_eq!(root.kind, "additionalProperties");
+    assert_eq!(root.params["properties"], json!(["unknown_section"]));
+}
+
+#[test]
+fn syntax_errors_are_reported() {
+    let result = validate_text("{ \"log\": ");
+    assert!(!result.valid);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].path, "");
+    assert_eq!(result.errors[0].kind, "syntax");
+    assert_eq!(result.errors[0].params["line"], 1);
+}
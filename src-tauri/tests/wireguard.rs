@@ -0,0 +1,84 @@
+use serde_json::json;
+use sing_config_lib::wireguard::import_conf;
+
+const CONF: &str = "
+[Interface]
+PrivateKey = yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=
+Address = 10.64.0.2/32, fc00:bbbb::2
+ListenPort = 51820
+MTU = 1380
+DNS = 10.64.0.1, corp.example
+PostUp = iptables -A FORWARD -i %i -j ACCEPT
+
+# 第一个对端
+[Peer]
+PublicKey = xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=
+PresharedKey = /UwcSPg38hW/D9Y3tcS1FOV0K1wuURMbS0sesJEP5ak=
+AllowedIPs = 0.0.0.0/0
+AllowedIPs = ::/0
+Endpoint = [2001:db8::1]:51820
+PersistentKeepalive = 25
+
+[Peer]
+PublicKey = TrMvSoP4jYQlY6RIzBgbssQqY3vxI2Pi+y71lOWWXX0=
+AllowedIPs = 192.168.1.0/24
+Endpoint = vpn.example.com:443
+PersistentKeepalive = off
+";
+
+#[test]
+fn imports_multi_peer_conf() {
+    let result = import_conf(CONF, "mullvad", &["mullvad".to_string()]).unwrap();
+    let endpoint = serde_json::to_value(&result.endpoint).unwrap();
+    assert_eq!(
+        endpoint,
+        json!({
+            "type": "wireguard",
+            "tag": "mullvad-2",
+            "mtu": 1380,
+            "address": ["10.64.0.2/32", "fc00:bbbb::2/128"],
+            "private_key": "yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=",
+            "listen_port": 51820,
+            "peers": [
+                {
+                    "address": "2001:db8::1",
+                    "port": 51820,
+                    "public_key": "xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=",
+                    "pre_shared_key": "/UwcSPg38hW/D9Y3tcS1FOV0K1wuURMbS0sesJEP5ak=",
+                    "allowed_ips": ["0.0.0.0/0", "::/0"],
+                    "persistent_keepalive_interval": 25
+                },
+                {
+                    "address": "vpn.example.com",
+                    "port": 443,
+                    "public_key": "TrMvSoP4jYQlY6RIzBgbssQqY3vxI2Pi+y71lOWWXX0=",
+                    "allowed_ips": ["192.168.1.0/24"]
+                }
+            ]
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(&result.dns_servers).unwrap(),
+        json!([{ "type": "udp", "tag": "mullvad-2-dns", "server": "10.64.0.1", "detour": "mullvad-2" }])
+    );
+    let lines: Vec<usize> = result.warnings.iter().map(|warning| warning.line).collect();
+    assert_eq!(lines, [7, 8]);
+}
+
+#[test]
+fn rejects_incomplete_conf() {
+    assert!(import_conf("[Peer]\nPublicKey = a\nAllowedIPs = 0.0.0.0/0\n", "wg", &[]).is_err());
+    assert!(import_conf(
+        "[Interface]\nPrivateKey = a\nAddress = 10.0.0.2\n",
+        "wg",
+        &[]
+    )
+    .is_err());
+    let err = import_conf(
+        "[Interface]\nPrivateKey = a\nAddress = 10.0.0.2\n[Peer]\nPublicKey = b\nAllowedIPs = 0.0.0.0/0\nEndpoint = host\n",
+        "wg",
+        &[],
+    )
+    .unwrap_err();
+    assert!(err.starts_with("line 7:"), "{err}");
+}
@@ -1,4 +1,5 @@
 import { ref, computed } from 'vue';
+import { invoke, isTauri } from '@tauri-apps/api/core';
 import zh from './locales/zh';
 import en from './locales/en';
 
@@ -18,6 +19,12 @@ export function useI18n() {
     if (typeof localStorage !== 'undefined') {
       localStorage.setItem('locale', loc);
     }
+    // 桌面端同步菜单、托盘语言并保存到应用配置目录
+    if (isTauri()) {
+      invoke('update_menu_locale', { locale: loc }).catch((e) => {
+        console.error('Failed to update menu locale:', e);
+      });
+    }
   }
   
   return { t, setLocale, currentLocale: locale };
@@ -40,3 +47,14 @@ if (typeof window !== 'undefined') {
   }
 }
 
+// 桌面端以后端保存的语言为准，保证与菜单、托盘一致
+if (typeof window !== 'undefined' && isTauri()) {
+  invoke<string>('get_current_locale')
+    .then((loc) => {
+      if ((loc === 'zh' || loc === 'en') && loc !== currentLocale.value) {
+        currentLocale.value = loc;
+        localStorage.setItem('locale', loc);
+      }
+    })
+    .catch(() => {});
+}
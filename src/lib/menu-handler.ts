@@ -10,7 +10,7 @@ import { invoke } from '@tauri-apps/api/core';
 type MenuEvent = string;
 
 // Topbar 组件引用（通过全局变量或事件总线传递）
-let topbarRef: { onNew?: () => Promise<void>; onOpen?: () => Promise<void>; onSave?: () => Promise<void>; onSaveAs?: () => Promise<void>; onLoadExample?: () => Promise<void>; showWizard?: () => void; showTemplates?: () => void; isOpening?: () => boolean } | null = null;
+let topbarRef: { onNew?: () => Promise<void>; onOpen?: () => Promise<void>; openPath?: (path: string) => Promise<void>; onSave?: () => Promise<void>; onSaveAs?: () => Promise<void>; onLoadExample?: () => Promise<void>; importWireGuard?: () => Promise<void>; showWizard?: () => void; showTemplates?: () => void; isOpening?: () => boolean } | null = null;
 
 // 正在处理的事件集合，防止同一事件被并发处理
 const processingEvents = new Set<string>();
@@ -59,14 +59,10 @@ async function handleMenuEvent(menuId: string) {
         await topbarRef.onOpen?.();
         break;
 
-      case 'file_open_recent':
-        // TODO: 实现打开最近文件功能
-        console.log('Open recent');
-        break;
+      // 最近文件菜单项与“清除列表”由后端处理，打开文件通过 open-file 事件通知
 
-      case 'file_clear_recent':
-        // TODO: 实现清除最近文件列表功能
-        console.log('Clear recent');
+      case 'file_import_wireguard':
+        await topbarRef?.importWireGuard?.();
         break;
 
       case 'file_save':
@@ -134,16 +130,12 @@ async function handleMenuEvent(menuId: string) {
         // 触发语言切换事件
         const zhEvent = new CustomEvent('change-locale', { detail: 'zh' });
         window.dispatchEvent(zhEvent);
-        // 更新菜单语言
-        await invoke('update_menu_locale', { locale: 'zh' });
         break;
 
       case 'view_language_en':
         // 触发语言切换事件
         const enEvent = new CustomEvent('change-locale', { detail: 'en' });
         window.dispatchEvent(enEvent);
-        // 更新菜单语言
-        await invoke('update_menu_locale', { locale: 'en' });
         break;
 
       // 工具菜单
@@ -187,6 +179,11 @@ async function handleMenuEvent(menuId: string) {
         break;
 
       default:
+        if (menuId.startsWith('view_language_')) {
+          // 界面目前只有中英文，其他语言只切换菜单和托盘
+          await invoke('update_menu_locale', { locale: menuId.slice('view_language_'.length) });
+          break;
+        }
         console.warn('Unknown menu event:', menuId);
     }
   } finally {
@@ -204,6 +201,7 @@ async function handleMenuEvent(menuId: string) {
 
 // 存储 unlisten 函数，避免重复注册监听器
 let unlistenMenuEvent: (() => void) | null = null;
+let unlistenOpenFile: (() => void) | null = null;
 // 追踪最后处理的事件和时间戳，用于防重复
 let lastProcessedEvent: { menuId: string; timestamp: number } | null = null;
 // 追踪正在接收的事件（在事件监听器层面）
@@ -241,6 +239,10 @@ export async function setupMenuHandlers() {
       unlistenMenuEvent();
       unlistenMenuEvent = null;
     }
+    if (unlistenOpenFile) {
+      unlistenOpenFile();
+      unlistenOpenFile = null;
+    }
     
     // 清理相关状态
     receivingEvents.clear();
@@ -280,6 +282,11 @@ export async function setupMenuHandlers() {
       });
     });
 
+    // 监听“打开最近”菜单选中的文件
+    unlistenOpenFile = await listen<string>('open-file', (event) => {
+      topbarRef?.openPath?.(event.payload);
+    });
+
     isInitialized = true;
   } catch (error) {
     console.error('Failed to setup menu handlers:', error);
@@ -294,6 +301,10 @@ export async function setupMenuHandlers() {
  * 清理菜单事件监听
  */
 export function cleanupMenuHandlers() {
+  if (unlistenOpenFile) {
+    unlistenOpenFile();
+    unlistenOpenFile = null;
+  }
   if (unlistenMenuEvent) {
     unlistenMenuEvent();
     unlistenMenuEvent = null;
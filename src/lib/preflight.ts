@@ -1,4 +1,5 @@
-import { currentConfig } from '../stores/config';
+import { invoke, isTauri } from '@tauri-apps/api/core';
+import { currentConfig, lastSavedPath, lastOpenedPath } from '../stores/config';
 
 export interface PreflightIssue {
   level: 'error' | 'warning' | 'info';
@@ -284,6 +285,18 @@ export function checkTlsConfig(): PreflightIssue[] {
 }
 
 export async function runPreflightCheck(): Promise<PreflightIssue[]> {
+  // 桌面端使用后端的 Rust 预检引擎
+  if (isTauri()) {
+    try {
+      return await invoke<PreflightIssue[]>('run_preflight', {
+        config: currentConfig.value,
+        locale: getCurrentLocale(),
+      });
+    } catch (e) {
+      console.warn('Native preflight failed, falling back to webview checks:', e);
+    }
+  }
+
   const issues: PreflightIssue[] = [];
   
   issues.push(...checkPortConflicts());
@@ -294,3 +307,29 @@ export async function runPreflightCheck(): Promise<PreflightIssue[]> {
   return issues;
 }
 
+
+/**
+ * 用本地 sing-box 内核运行 `sing-box check`（仅桌面端）
+ * 未找到内核或运行失败时返回一条提示
+ */
+export async function runCoreCheck(): Promise<PreflightIssue[]> {
+  if (!isTauri()) {
+    return [];
+  }
+  try {
+    const result = await invoke<{ valid: boolean; diagnostics: PreflightIssue[] }>('run_core_check', {
+      config: currentConfig.value,
+      path: lastSavedPath.value ?? lastOpenedPath.value,
+    });
+    return result.diagnostics;
+  } catch (e) {
+    const locale = getCurrentLocale();
+    return [{
+      level: 'info',
+      path: '',
+      message: locale === 'zh'
+        ? `未能运行 sing-box check：${e}`
+        : `Could not run sing-box check: ${e}`,
+    }];
+  }
+}
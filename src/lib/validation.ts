@@ -1,7 +1,8 @@
 import { Draft07 } from 'json-schema-library';
+import { invoke, isTauri } from '@tauri-apps/api/core';
 import { loadSchema } from './schema';
 import type { JsonError } from 'json-schema-library';
-import { createFormatError } from './codemirror-json-schema';
+import { createFormatError, getCurrentLocale, localizeErrorMessage } from './codemirror-json-schema';
 
 let validatorInstance: Draft07 | null = null;
 
@@ -39,7 +40,86 @@ function formatError(err: JsonError): { path: string; message: string } {
   };
 }
 
+// 后端返回的错误：message 为英文，kind 为 Schema 关键字，params 为生成文本所需的参数
+type NativeValidationError = {
+  path: string;
+  message: string;
+  kind: string;
+  params?: Record<string, any>;
+};
+
+type NativeValidationResult = {
+  valid: boolean;
+  errors: NativeValidationError[];
+};
+
+function quoteList(values: unknown[]): string {
+  return values.map((value) => JSON.stringify(value)).join(', ');
+}
+
+// 按 kind 和 params 生成当前语言的错误信息，未覆盖的类型沿用英文消息的本地化
+function formatNativeError(err: NativeValidationError): { path: string; message: string } {
+  const zh = getCurrentLocale() === 'zh';
+  const params = err.params || {};
+  let message: string;
+  switch (err.kind) {
+    case 'type':
+      message = createFormatError()({ message: err.message, data: params });
+      break;
+    case 'required':
+      message = zh
+        ? `缺少必需属性 ${JSON.stringify(params.property)}`
+        : `Missing required property ${JSON.stringify(params.property)}`;
+      break;
+    case 'additionalProperties':
+      message = zh
+        ? `不允许有额外属性 ${quoteList(params.properties || [])}`
+        : `Additional properties are not allowed: ${quoteList(params.properties || [])}`;
+      break;
+    case 'const':
+      message = zh
+        ? `期望值应为 ${JSON.stringify(params.expected)}`
+        : `Expected value to be ${JSON.stringify(params.expected)}`;
+      break;
+    case 'enum':
+      message = zh
+        ? `必须是以下值之一：${quoteList(params.options || [])}`
+        : `Must be one of: ${quoteList(params.options || [])}`;
+      break;
+    case 'minimum':
+      message = zh ? `不能小于 ${params.limit}` : `Must not be less than ${params.limit}`;
+      break;
+    case 'exclusiveMinimum':
+      message = zh ? `必须大于 ${params.limit}` : `Must be greater than ${params.limit}`;
+      break;
+    case 'maximum':
+      message = zh ? `不能大于 ${params.limit}` : `Must not be greater than ${params.limit}`;
+      break;
+    case 'exclusiveMaximum':
+      message = zh ? `必须小于 ${params.limit}` : `Must be less than ${params.limit}`;
+      break;
+    case 'syntax':
+      message = zh
+        ? `JSON 语法错误（第 ${params.line} 行，第 ${params.column} 列）`
+        : `JSON syntax error at line ${params.line}, column ${params.column}`;
+      break;
+    default:
+      message = localizeErrorMessage(err.message);
+  }
+  return { path: err.path, message };
+}
+
 export async function validateConfig(config: unknown): Promise<ValidationResult> {
+  // 桌面端使用后端的 Rust 校验器，与保存流程和命令行共用同一套规则
+  if (isTauri()) {
+    try {
+      const result = await invoke<NativeValidationResult>('validate_config', { config });
+      return { valid: result.valid, errors: result.errors.map(formatNativeError) };
+    } catch (e) {
+      console.warn('Native validation failed, falling back to webview validator:', e);
+    }
+  }
+
   try {
     const validator = await getValidator();
     
@@ -0,0 +1,109 @@
+import { ref } from 'vue';
+import { invoke, isTauri } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+/**
+ * 运行中 sing-box 的 Clash API（仅桌面端，需要配置 experimental.clash_api）
+ */
+export interface ClashProxy {
+  name: string;
+  type: string;
+  now?: string;
+  all: string[];
+  history: { time: string; delay: number }[];
+  udp: boolean;
+}
+
+export interface ClashConnection {
+  id: string;
+  metadata: Record<string, unknown>;
+  upload: number;
+  download: number;
+  start: string;
+  chains: string[];
+  rule: string;
+  rulePayload: string;
+}
+
+export interface ClashConnections {
+  downloadTotal: number;
+  uploadTotal: number;
+  connections: ClashConnection[];
+  memory?: number;
+}
+
+export const traffic = ref<{ up: number; down: number } | null>(null);
+export const memory = ref<{ inuse: number; oslimit: number } | null>(null);
+
+let unlisteners: Array<() => void> = [];
+
+/**
+ * 订阅 traffic / memory 推送
+ */
+export async function subscribeClash() {
+  if (!isTauri()) {
+    return;
+  }
+  if (unlisteners.length === 0) {
+    unlisteners = await Promise.all([
+      listen<{ up: number; down: number }>('clash-traffic', (event) => {
+        traffic.value = event.payload;
+      }),
+      listen<{ inuse: number; oslimit: number }>('clash-memory', (event) => {
+        memory.value = event.payload;
+      }),
+    ]);
+  }
+  await invoke('clash_subscribe');
+}
+
+export async function unsubscribeClash() {
+  unlisteners.forEach((unlisten) => unlisten());
+  unlisteners = [];
+  traffic.value = null;
+  memory.value = null;
+  if (isTauri()) {
+    await invoke('clash_unsubscribe');
+  }
+}
+
+export function getProxies() {
+  return invoke<ClashProxy[]>('clash_proxies');
+}
+
+export function selectProxy(selector: string, member: string) {
+  return invoke<void>('clash_select', { selector, member });
+}
+
+export function testProxyDelay(name: string, url?: string, timeout?: number) {
+  return invoke<number>('clash_proxy_delay', { name, url, timeout });
+}
+
+export function testGroupDelay(group: string, url?: string, timeout?: number) {
+  return invoke<Record<string, number>>('clash_group_delay', { group, url, timeout });
+}
+
+export function getConnections() {
+  return invoke<ClashConnections>('clash_connections');
+}
+
+/**
+ * 关闭指定连接，不传 id 时关闭全部连接
+ */
+export function closeConnections(id?: string) {
+  return invoke<void>('clash_close_connections', { id });
+}
+
+/**
+ * 格式化字节数，如 1.5 MB
+ */
+export function formatBytes(bytes: number): string {
+  const units = ['B', 'KB', 'MB', 'GB'];
+  let value = bytes;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit++;
+  }
+  return `${value.toFixed(unit === 0 ? 0 : 1)} ${units[unit]}`;
+}
@@ -0,0 +1,91 @@
+import { ref } from 'vue';
+import { invoke, isTauri } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+/**
+ * 本地 sing-box 内核的运行状态与日志（仅桌面端）
+ */
+export type CoreStatus =
+  | { state: 'stopped' }
+  | { state: 'running'; pid: number }
+  | { state: 'restarting'; attempt: number; delay_ms: number }
+  | { state: 'failed'; message: string };
+
+export interface CoreLogLine {
+  stream: 'stdout' | 'stderr';
+  line: string;
+}
+
+// 最多保留的日志行数
+const MAX_LOG_LINES = 1000;
+
+export const coreStatus = ref<CoreStatus>({ state: 'stopped' });
+export const coreLogs = ref<CoreLogLine[]>([]);
+export const coreError = ref<string | null>(null);
+
+let unlisteners: Array<() => void> = [];
+
+function appendLog(line: CoreLogLine) {
+  const logs = coreLogs.value;
+  logs.push(line);
+  if (logs.length > MAX_LOG_LINES) {
+    logs.splice(0, logs.length - MAX_LOG_LINES);
+  }
+}
+
+/**
+ * 开始接收 core-status / core-log / core-error 事件
+ */
+export async function setupCoreListeners() {
+  if (!isTauri() || unlisteners.length > 0) {
+    return;
+  }
+  unlisteners = await Promise.all([
+    listen<CoreStatus>('core-status', (event) => {
+      coreStatus.value = event.payload;
+    }),
+    listen<CoreLogLine>('core-log', (event) => {
+      appendLog(event.payload);
+    }),
+    // 托盘菜单操作失败
+    listen<string>('core-error', (event) => {
+      coreError.value = event.payload;
+    }),
+  ]);
+  coreStatus.value = await invoke<CoreStatus>('get_core_status');
+}
+
+export function cleanupCoreListeners() {
+  unlisteners.forEach((unlisten) => unlisten());
+  unlisteners = [];
+}
+
+async function control(command: string, args?: Record<string, unknown>) {
+  coreError.value = null;
+  try {
+    coreStatus.value = await invoke<CoreStatus>(command, args);
+  } catch (e) {
+    coreError.value = String(e);
+  }
+}
+
+/**
+ * 用已保存的配置文件启动 sing-box
+ */
+export function startCore(path: string) {
+  coreLogs.value = [];
+  return control('start_core', { path });
+}
+
+export function stopCore() {
+  return control('stop_core');
+}
+
+export function restartCore() {
+  coreLogs.value = [];
+  return control('restart_core');
+}
+
+export function clearCoreLogs() {
+  coreLogs.value = [];
+}
@@ -29,6 +29,7 @@ export interface EditorSettings {
   autoFormatOnLoad: boolean; // 加载文件时自动格式化
   autoFormatOnSave: boolean; // 保存文件时自动格式化
   autoFormatOnModeSwitch: boolean; // 切换模式时自动格式化
+  backupCount: number; // 保存时保留的 .bak 备份数
   
   // === 编辑辅助 ===
   
@@ -80,6 +81,7 @@ export const defaultSettings: EditorSettings = {
   autoFormatOnLoad: false,
   autoFormatOnSave: false,
   autoFormatOnModeSwitch: false,
+  backupCount: 3,
   
   // 编辑辅助
   autoCloseBrackets: true,
@@ -0,0 +1,94 @@
+import { ref } from 'vue';
+import { invoke, isTauri } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { currentConfig, setConfig } from './config';
+
+/**
+ * 订阅列表与刷新结果（仅桌面端）
+ */
+export interface Subscription {
+  name: string;
+  url: string;
+  prefix?: string;
+  enabled: boolean;
+  updated_at?: number;
+  error?: string;
+  outbounds?: Record<string, unknown>[];
+}
+
+export interface ManagedGroup {
+  tag: string;
+  type: 'selector' | 'urltest';
+  url?: string;
+  interval?: string;
+}
+
+export interface Subscriptions {
+  subscriptions: Subscription[];
+  group: ManagedGroup;
+  interval_minutes: number;
+}
+
+export interface RefreshResult {
+  name: string;
+  count: number;
+  skipped: string[];
+  error?: string;
+}
+
+export const subscriptions = ref<Subscriptions | null>(null);
+export const refreshResults = ref<RefreshResult[]>([]);
+
+let unlisten: (() => void) | null = null;
+
+/**
+ * 把订阅节点合并到当前配置
+ */
+export async function applySubscriptions() {
+  const config = await invoke<Record<string, unknown>>('apply_subscriptions', { config: currentConfig.value });
+  await setConfig(config);
+}
+
+/**
+ * 定时或托盘刷新完成后自动合并到当前配置
+ */
+export async function setupSubscriptionListener() {
+  if (!isTauri() || unlisten) {
+    return;
+  }
+  unlisten = await listen<RefreshResult[]>('subscriptions-updated', async (event) => {
+    refreshResults.value = event.payload;
+    await loadSubscriptions();
+    await applySubscriptions();
+  });
+}
+
+export function cleanupSubscriptionListener() {
+  unlisten?.();
+  unlisten = null;
+}
+
+export async function loadSubscriptions() {
+  subscriptions.value = await invoke<Subscriptions>('list_subscriptions');
+}
+
+export async function saveSubscription(subscription: Subscription) {
+  subscriptions.value = await invoke<Subscriptions>('save_subscription', { subscription });
+}
+
+export async function removeSubscription(name: string) {
+  subscriptions.value = await invoke<Subscriptions>('remove_subscription', { name });
+  await applySubscriptions();
+}
+
+export async function setSubscriptionOptions(group: ManagedGroup, intervalMinutes: number) {
+  subscriptions.value = await invoke<Subscriptions>('set_subscription_options', { group, intervalMinutes });
+  await applySubscriptions();
+}
+
+/**
+ * 立即刷新；合并由 subscriptions-updated 事件完成
+ */
+export async function refreshSubscriptions() {
+  refreshResults.value = await invoke<RefreshResult[]>('refresh_subscriptions');
+}